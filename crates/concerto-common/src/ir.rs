@@ -1,7 +1,12 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::ir_opcodes::Opcode;
 
+/// IR format version emitted by the compiler and accepted by the runtime.
+pub const IR_VERSION: &str = "0.1.0";
+
 /// Top-level IR module, the output of compilation.
 /// Serialized as the `.conc-ir` JSON file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +92,9 @@ pub struct IrFunction {
     pub params: Vec<IrParam>,
     pub return_type: serde_json::Value,
     pub is_async: bool,
+    /// Function decorators (`@trace` is the one the VM acts on).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorators: Vec<IrDecorator>,
     pub locals: Vec<String>,
     pub instructions: Vec<IrInstruction>,
 }
@@ -96,6 +104,9 @@ pub struct IrParam {
     pub name: String,
     #[serde(rename = "type")]
     pub param_type: serde_json::Value,
+    /// Constant value bound when a call omits this (trailing) parameter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
 }
 
 /// A single IR instruction.
@@ -169,6 +180,15 @@ pub struct IrModelConfig {
     pub system_prompt: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u32>,
+    /// Cap on `execute_with_schema` self-correction attempts (default 3).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_retries: Option<u32>,
+    /// Per-model endpoint, overriding the connection's `base_url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Sampling seed sent to providers that support it (OpenAI-style).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -186,6 +206,9 @@ pub struct IrTool {
     pub methods: Vec<IrFunction>,
     #[serde(default)]
     pub tool_schemas: Vec<ToolSchemaEntry>,
+    /// Initial `self` state from the tool's non-`description` fields.
+    #[serde(default)]
+    pub state: serde_json::Map<String, serde_json::Value>,
 }
 
 /// A tool schema entry for LLM function calling.
@@ -203,6 +226,9 @@ pub struct IrSchema {
     pub json_schema: serde_json::Value,
     #[serde(default = "default_validation_mode")]
     pub validation_mode: String,
+    /// Alternate top-level keys from `@alias`, mapped to the field they fill.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
 }
 
 fn default_validation_mode() -> String {
@@ -294,6 +320,9 @@ pub struct IrPipelineStage {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub decorators: Vec<IrDecorator>,
     pub instructions: Vec<IrInstruction>,
+    /// Pipeline run by a `stage name = Other;` stage (instructions are empty).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<String>,
 }
 
 /// A listen expression definition (bidirectional agent streaming).
@@ -309,6 +338,9 @@ pub struct IrListen {
 pub struct IrListenHandler {
     pub message_type: String,
     pub param: IrParam,
+    /// Schema a non-nil handler return value must match before it is sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<String>,
     pub instructions: Vec<IrInstruction>,
 }
 
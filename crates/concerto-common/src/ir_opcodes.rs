@@ -44,6 +44,20 @@ pub enum Opcode {
     /// Pop one, push negation.
     Neg,
 
+    // === Bitwise (Int only) ===
+    /// Pop two, push bitwise AND.
+    BitAnd,
+    /// Pop two, push bitwise OR.
+    BitOr,
+    /// Pop two, push bitwise XOR.
+    BitXor,
+    /// Pop two, push left shift (shift amount must be in 0..64).
+    Shl,
+    /// Pop two, push arithmetic right shift (shift amount must be in 0..64).
+    Shr,
+    /// Pop one, push bitwise complement.
+    BitNot,
+
     // === Comparison ===
     /// Pop two, push equality result (Bool).
     Eq,
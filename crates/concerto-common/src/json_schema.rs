@@ -0,0 +1,103 @@
+//! Translation of the Concerto type names in IR schemas (`String`, `Int`,
+//! `Array<T>`, ...) to standard JSON Schema. Shared by the runtime's schema
+//! validator and `concertoc --emit-schemas`.
+
+use serde_json::Value;
+
+/// Normalize Concerto type names to standard JSON Schema type names.
+/// Concerto uses `String`, `Int`, `Float`, `Bool`, `Array<T>`, `Map<K,V>`;
+/// JSON Schema uses `string`, `integer`, `number`, `boolean`, `array`, `object`.
+/// `Option<T>` accepts `T` or `null`; whether the field may also be absent
+/// is decided by `required`.
+pub fn normalize_schema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(obj) => {
+            let mut normalized = serde_json::Map::new();
+            for (key, val) in obj {
+                if key == "type" {
+                    if let Some(t) = val.as_str() {
+                        normalized.extend(normalize_type(t));
+                    } else {
+                        normalized.insert(key.clone(), normalize_schema(val));
+                    }
+                } else if key == "oneOf" {
+                    // A union value may match any alternative; under
+                    // `oneOf`, `Int | Float` would reject integers
+                    // (they are numbers too).
+                    normalized.insert("anyOf".to_string(), normalize_schema(val));
+                } else {
+                    normalized.insert(key.clone(), normalize_schema(val));
+                }
+            }
+            Value::Object(normalized)
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(normalize_schema).collect()),
+        other => other.clone(),
+    }
+}
+
+/// The JSON Schema keywords (`type`, plus `items` for arrays or `anyOf`
+/// for options) for one Concerto type name.
+fn normalize_type(t: &str) -> serde_json::Map<String, Value> {
+    let mut keywords = serde_json::Map::new();
+    if let Some(inner) = t.strip_prefix("Option<").and_then(|s| s.strip_suffix('>')) {
+        let alternatives = vec![
+            Value::Object(normalize_type(inner)),
+            serde_json::json!({ "type": "null" }),
+        ];
+        keywords.insert("anyOf".to_string(), Value::Array(alternatives));
+    } else if let Some(inner) = t.strip_prefix("Array<").and_then(|s| s.strip_suffix('>')) {
+        keywords.insert("type".to_string(), "array".into());
+        keywords.insert("items".to_string(), Value::Object(normalize_type(inner)));
+    } else if t.starts_with("Map<") {
+        keywords.insert("type".to_string(), "object".into());
+    } else {
+        keywords.insert("type".to_string(), normalize_type_name(t).into());
+    }
+    keywords
+}
+
+/// Map a Concerto type name to its JSON Schema equivalent.
+fn normalize_type_name(t: &str) -> &str {
+    match t {
+        "String" => "string",
+        "Int" => "integer",
+        "Float" => "number",
+        "Bool" => "boolean",
+        "Nil" => "null",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn concerto_types_become_json_schema_types() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "score": { "type": "Int" },
+                "tags": { "type": "Array<String>" },
+                "note": { "type": "Option<String>" },
+                "meta": { "type": "Map<String, Int>" },
+                "either": { "oneOf": [{ "type": "Int" }, { "type": "Float" }] },
+            },
+        });
+        assert_eq!(
+            normalize_schema(&schema),
+            json!({
+                "type": "object",
+                "properties": {
+                    "score": { "type": "integer" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                    "meta": { "type": "object" },
+                    "either": { "anyOf": [{ "type": "integer" }, { "type": "number" }] },
+                },
+            })
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -14,6 +14,14 @@ pub struct ConcertoManifest {
     pub root_dir: PathBuf,
 }
 
+impl ConcertoManifest {
+    /// Path of the `[project].entry` source file, resolved against the
+    /// manifest's directory.
+    pub fn entry_path(&self) -> PathBuf {
+        self.root_dir.join(&self.project.entry)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProjectSection {
     pub name: String,
@@ -36,6 +44,12 @@ pub struct ConnectionConfig {
     pub organization: Option<String>,
     #[serde(default)]
     pub project: Option<String>,
+    /// Extra HTTP headers sent with every request on this connection.
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
+    /// Request/response mapping for `provider = "http"` connections.
+    #[serde(default)]
+    pub http: Option<HttpProviderConfig>,
     #[serde(default)]
     pub retry: Option<RetryConfig>,
     #[serde(default)]
@@ -44,6 +58,22 @@ pub struct ConnectionConfig {
     pub models: Option<HashMap<String, String>>,
 }
 
+/// Request template and response paths for a generic HTTP connection.
+///
+/// Populated from `[connections.<name>.http]` in Concerto.toml.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpProviderConfig {
+    /// JSON body sent to `base_url`; `{{prompt}}`-style placeholders are
+    /// substituted per request.
+    pub request_template: serde_json::Value,
+    /// JSONPath of the response text, e.g. `$.output[0].text`.
+    pub text_path: String,
+    #[serde(default)]
+    pub tokens_in_path: Option<String>,
+    #[serde(default)]
+    pub tokens_out_path: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RetryConfig {
     #[serde(default = "default_max_attempts")]
@@ -138,6 +168,10 @@ pub enum ManifestError {
         "invalid Concerto.toml: connection '{0}' has provider '{1}' which requires 'api_key_env'"
     )]
     MissingApiKeyEnv(String, String),
+    #[error(
+        "invalid Concerto.toml: connection '{0}' has provider 'http' which requires 'base_url' and a [connections.{0}.http] table"
+    )]
+    HttpMissingConfig(String),
     #[error("invalid Concerto.toml: [mcp.{0}] transport 'stdio' requires 'command' field")]
     McpMissingCommand(String),
     #[error("invalid Concerto.toml: [mcp.{0}] transport 'sse' requires 'url' field")]
@@ -214,6 +248,9 @@ fn validate_connection(name: &str, conn: &ConnectionConfig) -> Result<(), Manife
             conn.provider.clone(),
         ));
     }
+    if conn.provider == "http" && (conn.base_url.is_none() || conn.http.is_none()) {
+        return Err(ManifestError::HttpMissingConfig(name.to_string()));
+    }
     Ok(())
 }
 
@@ -274,10 +311,57 @@ impl ConnectionConfig {
         if let Some(ref models) = self.models {
             let models_obj: serde_json::Map<String, serde_json::Value> = models
                 .iter()
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
                 .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
                 .collect();
             config.insert("models".to_string(), serde_json::Value::Object(models_obj));
         }
+        if let Some(ref org) = self.organization {
+            config.insert(
+                "organization".to_string(),
+                serde_json::Value::String(org.clone()),
+            );
+        }
+        if let Some(ref project) = self.project {
+            config.insert(
+                "project".to_string(),
+                serde_json::Value::String(project.clone()),
+            );
+        }
+        if let Some(ref headers) = self.headers {
+            let headers_obj: serde_json::Map<String, serde_json::Value> = headers
+                .iter()
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                .collect();
+            config.insert("headers".to_string(), serde_json::Value::Object(headers_obj));
+        }
+        if let Some(ref http) = self.http {
+            let mut http_obj = serde_json::Map::new();
+            http_obj.insert(
+                "request_template".to_string(),
+                http.request_template.clone(),
+            );
+            http_obj.insert(
+                "text_path".to_string(),
+                serde_json::Value::String(http.text_path.clone()),
+            );
+            if let Some(ref path) = http.tokens_in_path {
+                http_obj.insert(
+                    "tokens_in_path".to_string(),
+                    serde_json::Value::String(path.clone()),
+                );
+            }
+            if let Some(ref path) = http.tokens_out_path {
+                http_obj.insert(
+                    "tokens_out_path".to_string(),
+                    serde_json::Value::String(path.clone()),
+                );
+            }
+            config.insert("http".to_string(), serde_json::Value::Object(http_obj));
+        }
         if let Some(ref retry) = self.retry {
             let mut retry_obj = serde_json::Map::new();
             retry_obj.insert(
@@ -345,6 +429,21 @@ entry = "src/main.conc"
         assert!(manifest.mcp.is_empty());
     }
 
+    #[test]
+    fn entry_path_resolves_against_root_dir() {
+        let toml = r#"
+[project]
+name = "test-project"
+version = "0.1.0"
+entry = "src/main.conc"
+"#;
+        let manifest = parse_manifest(toml, PathBuf::from("/work/app")).unwrap();
+        assert_eq!(
+            manifest.entry_path(),
+            PathBuf::from("/work/app/src/main.conc")
+        );
+    }
+
     #[test]
     fn parse_full_manifest() {
         let toml = r#"
@@ -538,6 +637,8 @@ transport = "websocket"
             timeout: Some(60),
             organization: None,
             project: None,
+            headers: None,
+            http: None,
             retry: None,
             rate_limit: None,
             models: None,
@@ -550,6 +651,74 @@ transport = "websocket"
         assert_eq!(obj["timeout"], 60);
     }
 
+    #[test]
+    fn connection_headers_to_ir_config() {
+        let toml = r#"
+[project]
+name = "test"
+version = "0.1.0"
+entry = "src/main.conc"
+
+[connections.openai]
+provider = "openai"
+api_key_env = "OPENAI_API_KEY"
+organization = "org-123"
+project = "proj-456"
+
+[connections.openai.headers]
+X-Gateway-Key = "abc"
+"#;
+        let manifest = parse_manifest(toml, PathBuf::from(".")).unwrap();
+        let config = manifest.connections["openai"].to_ir_config();
+        assert_eq!(config["organization"], "org-123");
+        assert_eq!(config["project"], "proj-456");
+        assert_eq!(config["headers"]["X-Gateway-Key"], "abc");
+    }
+
+    #[test]
+    fn http_connection_to_ir_config() {
+        let toml = r#"
+[project]
+name = "test"
+version = "0.1.0"
+entry = "src/main.conc"
+
+[connections.local]
+provider = "http"
+base_url = "http://localhost:8080/generate"
+
+[connections.local.http]
+request_template = { inputs = "{{prompt}}", parameters = { max_new_tokens = "{{max_tokens}}" } }
+text_path = "$.output[0].generated"
+tokens_out_path = "$.stats.tokens"
+"#;
+        let manifest = parse_manifest(toml, PathBuf::from(".")).unwrap();
+        let config = manifest.connections["local"].to_ir_config();
+        assert_eq!(config["provider"], "http");
+        assert_eq!(config["http"]["request_template"]["inputs"], "{{prompt}}");
+        assert_eq!(config["http"]["text_path"], "$.output[0].generated");
+        assert_eq!(config["http"]["tokens_out_path"], "$.stats.tokens");
+        assert!(config["http"].get("tokens_in_path").is_none());
+    }
+
+    #[test]
+    fn http_connection_requires_template() {
+        let toml = r#"
+[project]
+name = "test"
+version = "0.1.0"
+entry = "src/main.conc"
+
+[connections.local]
+provider = "http"
+base_url = "http://localhost:8080/generate"
+"#;
+        let err = parse_manifest(toml, PathBuf::from("."))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("[connections.local.http]"), "got: {}", err);
+    }
+
     #[test]
     fn agent_params_deserialized() {
         let toml = r#"
@@ -44,6 +44,32 @@ pub enum DecoratorArg {
     },
 }
 
+impl Decorator {
+    /// The integer literal given as the sole positional argument, as in
+    /// `@max_iters(1000)`.
+    pub fn int_arg(&self) -> Option<i64> {
+        match self.args.as_slice() {
+            [DecoratorArg::Positional(Expr {
+                kind: ExprKind::Literal(Literal::Int(n)),
+                ..
+            })] => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The string literal given as the sole positional argument, as in
+    /// `@alias("firstName")`.
+    pub fn string_arg(&self) -> Option<&str> {
+        match self.args.as_slice() {
+            [DecoratorArg::Positional(Expr {
+                kind: ExprKind::Literal(Literal::String(s)),
+                ..
+            })] => Some(s),
+            _ => None,
+        }
+    }
+}
+
 // ============================================================================
 // Declarations
 // ============================================================================
@@ -84,6 +110,8 @@ pub enum Declaration {
 #[derive(Debug, Clone)]
 pub struct FunctionDecl {
     pub name: String,
+    /// Text of the `///` doc comment preceding the declaration, if any.
+    pub doc: Option<String>,
     pub decorators: Vec<Decorator>,
     pub self_param: SelfParam,
     pub params: Vec<Param>,
@@ -126,6 +154,7 @@ pub struct Param {
 #[derive(Debug, Clone)]
 pub struct ModelDecl {
     pub name: String,
+    pub doc: Option<String>,
     pub decorators: Vec<Decorator>,
     pub fields: Vec<ConfigField>,
     pub span: Span,
@@ -145,6 +174,7 @@ pub struct ModelDecl {
 #[derive(Debug, Clone)]
 pub struct ToolDecl {
     pub name: String,
+    pub doc: Option<String>,
     pub fields: Vec<ConfigField>,
     pub methods: Vec<FunctionDecl>,
     pub span: Span,
@@ -163,6 +193,7 @@ pub struct ToolDecl {
 #[derive(Debug, Clone)]
 pub struct SchemaDecl {
     pub name: String,
+    pub doc: Option<String>,
     pub decorators: Vec<Decorator>,
     pub fields: Vec<FieldDecl>,
     pub span: Span,
@@ -176,6 +207,7 @@ pub struct SchemaDecl {
 /// pipeline Name {
 ///     stage step1(input: String) -> Int { ... }
 ///     stage step2(n: Int) -> String { ... }
+///     stage step3 = OtherPipeline;
 /// }
 /// ```
 #[derive(Debug, Clone)]
@@ -197,6 +229,9 @@ pub struct StageDecl {
     pub params: Vec<Param>,
     pub return_type: Option<TypeAnnotation>,
     pub body: Block,
+    /// `stage name = OtherPipeline;` -- the stage runs another pipeline
+    /// (no params, return type or body of its own).
+    pub pipeline: Option<String>,
     pub span: Span,
 }
 
@@ -396,6 +431,7 @@ pub struct McpDecl {
 #[derive(Debug, Clone)]
 pub struct AgentDecl {
     pub name: String,
+    pub doc: Option<String>,
     pub decorators: Vec<Decorator>,
     pub fields: Vec<ConfigField>,
     pub span: Span,
@@ -413,11 +449,15 @@ pub struct ConfigField {
     pub span: Span,
 }
 
-/// A typed field: `[pub] name[?]: Type [= default]` (used in struct, schema).
+/// A typed field: `[pub] name[?]: Type ["description"] [= default]` (used in struct, schema).
 #[derive(Debug, Clone)]
 pub struct FieldDecl {
     pub name: String,
     pub type_ann: TypeAnnotation,
+    /// Trailing string after the type; becomes the JSON Schema `description`.
+    pub description: Option<String>,
+    /// Decorators between the name and the type: `first_name @alias("firstName"): String`.
+    pub decorators: Vec<Decorator>,
     pub default: Option<Expr>,
     pub is_public: bool,
     pub is_optional: bool,
@@ -504,11 +544,13 @@ pub struct MatchArm {
 }
 
 /// A handler arm within a listen expression.
-/// `"message_type" => |param: Type| { body }`
+/// `"message_type" => |param: Type| [-> Schema] { body }`
 #[derive(Debug, Clone)]
 pub struct ListenHandler {
     pub message_type: String,
     pub param: Param,
+    /// Schema the handler's response is validated against before it is sent.
+    pub return_type: Option<TypeAnnotation>,
     pub body: Block,
     pub span: Span,
 }
@@ -644,8 +686,13 @@ pub enum ExprKind {
     /// Unary operation: `-x`, `!flag`
     Unary { op: UnaryOp, operand: Box<Expr> },
 
-    /// Function or method call: `foo(a, b)`, `emit("ch", val)`
-    Call { callee: Box<Expr>, args: Vec<Expr> },
+    /// Function or method call: `foo(a, b)`, `emit("ch", val)`.
+    /// `type_args` is only parsed for `emit<Schema>(...)`.
+    Call {
+        callee: Box<Expr>,
+        type_args: Vec<super::types::TypeAnnotation>,
+        args: Vec<Expr>,
+    },
 
     /// `if condition { then } else { else }`
     If {
@@ -710,8 +757,12 @@ pub enum ExprKind {
     /// While loop: `while condition { body }`
     While { condition: Box<Expr>, body: Block },
 
-    /// Infinite loop: `loop { body }`
-    Loop { body: Block },
+    /// Infinite loop: `loop { body }`, optionally capped with
+    /// `@max_iters(N) loop { body }`.
+    Loop {
+        body: Block,
+        decorators: Vec<Decorator>,
+    },
 
     /// Closure: `|params| expr` or `|params| -> Type { block }`
     Closure {
@@ -799,6 +850,12 @@ pub enum BinaryOp {
     // Logical
     And,
     Or,
+    // Bitwise (Int only)
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
 }
 
 /// Unary operators.
@@ -806,6 +863,7 @@ pub enum BinaryOp {
 pub enum UnaryOp {
     Neg,
     Not,
+    BitNot,
 }
 
 /// Assignment operators.
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use concerto_common::Span;
 
 /// A type annotation in the source code (e.g., `Int`, `Array<String>`, `Result<T, E>`).
@@ -40,3 +42,28 @@ pub enum TypeKind {
     /// Inferred type (no annotation given).
     Inferred,
 }
+
+/// Renders the annotation as written in source (`Array<String>`, `fn(Int) -> Bool`).
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn join(types: &[TypeAnnotation], sep: &str) -> String {
+            types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(sep)
+        }
+        match &self.kind {
+            TypeKind::Named(name) => write!(f, "{}", name),
+            TypeKind::Generic { name, args } => write!(f, "{}<{}>", name, join(args, ", ")),
+            TypeKind::Tuple(elems) => write!(f, "({})", join(elems, ", ")),
+            TypeKind::Function {
+                params,
+                return_type,
+            } => write!(f, "fn({}) -> {}", join(params, ", "), return_type),
+            TypeKind::Union(variants) => write!(f, "{}", join(variants, " | ")),
+            TypeKind::StringLiteral(s) => write!(f, "\"{}\"", s),
+            TypeKind::Inferred => write!(f, "any"),
+        }
+    }
+}
@@ -236,156 +236,162 @@ pub trait Visitor {
     }
 
     fn visit_expr(&mut self, expr: &Expr) {
-        match &expr.kind {
-            ExprKind::Literal(_) => {}
-            ExprKind::Identifier(_) => {}
-            ExprKind::Binary { left, right, .. } => {
-                self.visit_expr(left);
-                self.visit_expr(right);
-            }
-            ExprKind::Unary { operand, .. } => {
-                self.visit_expr(operand);
-            }
-            ExprKind::Call { callee, args } => {
-                self.visit_expr(callee);
-                for arg in args {
-                    self.visit_expr(arg);
-                }
-            }
-            ExprKind::If {
-                condition,
-                then_branch,
-                else_branch,
-            } => {
-                self.visit_expr(condition);
-                self.visit_block(then_branch);
-                if let Some(ref eb) = else_branch {
-                    match eb {
-                        ElseBranch::Block(b) => self.visit_block(b),
-                        ElseBranch::ElseIf(e) => self.visit_expr(e),
-                    }
-                }
-            }
-            ExprKind::Block(block) => self.visit_block(block),
-            ExprKind::Assign { target, value, .. } => {
-                self.visit_expr(target);
-                self.visit_expr(value);
-            }
-            ExprKind::FieldAccess { object, .. } => {
-                self.visit_expr(object);
+        walk_expr(self, expr);
+    }
+}
+
+/// Visit the children of `expr` — the default behavior of
+/// [`Visitor::visit_expr`]. Overrides call this to keep walking.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.kind {
+        ExprKind::Literal(_) => {}
+        ExprKind::Identifier(_) => {}
+        ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Unary { operand, .. } => {
+            visitor.visit_expr(operand);
+        }
+        ExprKind::Call { callee, args, .. } => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
             }
-            ExprKind::MethodCall { object, args, .. } => {
-                self.visit_expr(object);
-                for arg in args {
-                    self.visit_expr(arg);
+        }
+        ExprKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(then_branch);
+            if let Some(ref eb) = else_branch {
+                match eb {
+                    ElseBranch::Block(b) => visitor.visit_block(b),
+                    ElseBranch::ElseIf(e) => visitor.visit_expr(e),
                 }
             }
-            ExprKind::Index { object, index } => {
-                self.visit_expr(object);
-                self.visit_expr(index);
-            }
-            ExprKind::Array(elems) => {
-                for elem in elems {
-                    self.visit_expr(elem);
-                }
+        }
+        ExprKind::Block(block) => visitor.visit_block(block),
+        ExprKind::Assign { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        ExprKind::FieldAccess { object, .. } => {
+            visitor.visit_expr(object);
+        }
+        ExprKind::MethodCall { object, args, .. } => {
+            visitor.visit_expr(object);
+            for arg in args {
+                visitor.visit_expr(arg);
             }
-            ExprKind::Map(entries) => {
-                for (key, val) in entries {
-                    self.visit_expr(key);
-                    self.visit_expr(val);
-                }
+        }
+        ExprKind::Index { object, index } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        }
+        ExprKind::Array(elems) => {
+            for elem in elems {
+                visitor.visit_expr(elem);
             }
-            ExprKind::Grouping(inner) => self.visit_expr(inner),
-
-            // New expression variants (Step 9)
-            ExprKind::Match { scrutinee, arms } => {
-                self.visit_expr(scrutinee);
-                for arm in arms {
-                    self.visit_pattern(&arm.pattern);
-                    if let Some(ref guard) = arm.guard {
-                        self.visit_expr(guard);
-                    }
-                    self.visit_expr(&arm.body);
-                }
+        }
+        ExprKind::Map(entries) => {
+            for (key, val) in entries {
+                visitor.visit_expr(key);
+                visitor.visit_expr(val);
             }
-            ExprKind::TryCatch { body, catches } => {
-                self.visit_block(body);
-                for catch in catches {
-                    self.visit_block(&catch.body);
+        }
+        ExprKind::Grouping(inner) => visitor.visit_expr(inner),
+
+        // New expression variants (Step 9)
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_pattern(&arm.pattern);
+                if let Some(ref guard) = arm.guard {
+                    visitor.visit_expr(guard);
                 }
+                visitor.visit_expr(&arm.body);
             }
-            ExprKind::For {
-                pattern,
-                iterable,
-                body,
-            } => {
-                self.visit_pattern(pattern);
-                self.visit_expr(iterable);
-                self.visit_block(body);
-            }
-            ExprKind::While { condition, body } => {
-                self.visit_expr(condition);
-                self.visit_block(body);
-            }
-            ExprKind::Loop { body } => {
-                self.visit_block(body);
-            }
-            ExprKind::Closure { body, .. } => {
-                self.visit_expr(body);
-            }
-            ExprKind::Pipe { left, right } => {
-                self.visit_expr(left);
-                self.visit_expr(right);
-            }
-            ExprKind::Propagate(inner) => {
-                self.visit_expr(inner);
-            }
-            ExprKind::NilCoalesce { left, right } => {
-                self.visit_expr(left);
-                self.visit_expr(right);
-            }
-            ExprKind::Range { start, end, .. } => {
-                if let Some(ref s) = start {
-                    self.visit_expr(s);
-                }
-                if let Some(ref e) = end {
-                    self.visit_expr(e);
-                }
+        }
+        ExprKind::TryCatch { body, catches } => {
+            visitor.visit_block(body);
+            for catch in catches {
+                visitor.visit_block(&catch.body);
             }
-            ExprKind::Cast { expr, .. } => {
-                self.visit_expr(expr);
+        }
+        ExprKind::For {
+            pattern,
+            iterable,
+            body,
+        } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expr(iterable);
+            visitor.visit_block(body);
+        }
+        ExprKind::While { condition, body } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(body);
+        }
+        ExprKind::Loop { body, .. } => {
+            visitor.visit_block(body);
+        }
+        ExprKind::Closure { body, .. } => {
+            visitor.visit_expr(body);
+        }
+        ExprKind::Pipe { left, right } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Propagate(inner) => {
+            visitor.visit_expr(inner);
+        }
+        ExprKind::NilCoalesce { left, right } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Range { start, end, .. } => {
+            if let Some(ref s) = start {
+                visitor.visit_expr(s);
             }
-            ExprKind::Path(_) => {}
-            ExprKind::Await(inner) => {
-                self.visit_expr(inner);
+            if let Some(ref e) = end {
+                visitor.visit_expr(e);
             }
-            ExprKind::Tuple(elems) => {
-                for elem in elems {
-                    self.visit_expr(elem);
-                }
+        }
+        ExprKind::Cast { expr, .. } => {
+            visitor.visit_expr(expr);
+        }
+        ExprKind::Path(_) => {}
+        ExprKind::Await(inner) => {
+            visitor.visit_expr(inner);
+        }
+        ExprKind::Tuple(elems) => {
+            for elem in elems {
+                visitor.visit_expr(elem);
             }
-            ExprKind::StructLiteral { fields, .. } => {
-                for f in fields {
-                    self.visit_expr(&f.value);
-                }
+        }
+        ExprKind::StructLiteral { fields, .. } => {
+            for f in fields {
+                visitor.visit_expr(&f.value);
             }
-            ExprKind::StringInterpolation(parts) => {
-                for part in parts {
-                    if let StringPart::Expr(ref e) = part {
-                        self.visit_expr(e);
-                    }
+        }
+        ExprKind::StringInterpolation(parts) => {
+            for part in parts {
+                if let StringPart::Expr(ref e) = part {
+                    visitor.visit_expr(e);
                 }
             }
-            ExprKind::Return(value) => {
-                if let Some(val) = value {
-                    self.visit_expr(val);
-                }
+        }
+        ExprKind::Return(value) => {
+            if let Some(val) = value {
+                visitor.visit_expr(val);
             }
-            ExprKind::Listen { call, handlers } => {
-                self.visit_expr(call);
-                for handler in handlers {
-                    self.visit_block(&handler.body);
-                }
+        }
+        ExprKind::Listen { call, handlers } => {
+            visitor.visit_expr(call);
+            for handler in handlers {
+                visitor.visit_block(&handler.body);
             }
         }
     }
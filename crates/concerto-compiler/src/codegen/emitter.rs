@@ -2,9 +2,13 @@ use concerto_common::ir::*;
 use concerto_common::ir_opcodes::Opcode;
 
 use crate::ast::*;
+use crate::semantic::const_eval::{fold_expr, ConstEvaluator};
 
 use super::constant_pool::ConstantPool;
 
+/// Channel used by single-argument `emit(payload)`.
+const DEFAULT_EMIT_CHANNEL: &str = "default";
+
 // ============================================================================
 // Code Generator
 // ============================================================================
@@ -31,6 +35,8 @@ pub struct CodeGenerator {
     listen_counter: usize,
     /// `use` import aliases: short name → full qualified path (e.g. "parse" → "std::json::parse")
     use_aliases: std::collections::HashMap<String, String>,
+    /// Folded `const` values, inlined as literals at every reference.
+    const_values: std::collections::HashMap<String, Literal>,
 }
 
 impl CodeGenerator {
@@ -55,6 +61,7 @@ impl CodeGenerator {
             closure_counter: 0,
             listen_counter: 0,
             use_aliases: std::collections::HashMap::new(),
+            const_values: std::collections::HashMap::new(),
         }
     }
 
@@ -87,12 +94,16 @@ impl CodeGenerator {
 
     /// Generate IR from a parsed program.
     pub fn generate(mut self, program: &Program) -> IrModule {
+        // Semantic analysis has already rejected consts that do not fold.
+        let (const_values, _) = ConstEvaluator::new(program).evaluate();
+        self.const_values = const_values;
+
         for decl in &program.declarations {
             self.generate_declaration(decl);
         }
 
         IrModule {
-            version: "0.1.0".to_string(),
+            version: IR_VERSION.to_string(),
             module: self.module_name.clone(),
             source_file: self.source_file.clone(),
             constants: self.pool.into_constants(),
@@ -141,7 +152,8 @@ impl CodeGenerator {
             Declaration::Enum(e) => self.generate_enum_decl(e),
             Declaration::Impl(i) => self.generate_impl(i),
             Declaration::Trait(t) => self.generate_trait_decl(t),
-            Declaration::Const(c) => self.generate_const(c),
+            // Consts are folded at compile time and inlined at each use.
+            Declaration::Const(_) => {}
             Declaration::HashMap(d) => self.generate_hashmap(d),
             Declaration::Ledger(l) => self.generate_ledger(l),
             Declaration::Memory(m) => self.generate_memory(m),
@@ -180,6 +192,16 @@ impl CodeGenerator {
         let body = func.body.as_ref()?;
         let mut ctx = FunctionCtx::new();
 
+        // Methods receive `self` as an implicit leading parameter.
+        let mut params = Vec::new();
+        if func.self_param != SelfParam::None {
+            ctx.add_local("self");
+            params.push(IrParam {
+                name: "self".to_string(),
+                param_type: serde_json::Value::String("any".to_string()),
+                default: None,
+            });
+        }
         for param in &func.params {
             ctx.add_local(&param.name);
         }
@@ -209,10 +231,9 @@ impl CodeGenerator {
             name: name.to_string(),
             module: self.module_name.clone(),
             visibility: visibility.to_string(),
-            params: func
-                .params
-                .iter()
-                .map(|p| IrParam {
+            params: params
+                .into_iter()
+                .chain(func.params.iter().map(|p| IrParam {
                     name: p.name.clone(),
                     param_type: serde_json::Value::String(
                         p.type_ann
@@ -220,7 +241,13 @@ impl CodeGenerator {
                             .map(format_type)
                             .unwrap_or_else(|| "any".to_string()),
                     ),
-                })
+                    // Semantic analysis rejects defaults that do not fold.
+                    default: p
+                        .default
+                        .as_ref()
+                        .and_then(|d| fold_expr(d, &self.const_values).ok())
+                        .map(|lit| literal_to_json(&lit)),
+                }))
                 .collect(),
             return_type: func
                 .return_type
@@ -228,6 +255,7 @@ impl CodeGenerator {
                 .map(|t| serde_json::Value::String(format_type(t)))
                 .unwrap_or(serde_json::Value::String("nil".to_string())),
             is_async: func.is_async,
+            decorators: func.decorators.iter().map(lower_decorator).collect(),
             locals: ctx.locals,
             instructions: ctx.instructions,
         })
@@ -382,20 +410,14 @@ impl CodeGenerator {
     fn generate_expr(&mut self, expr: &Expr, ctx: &mut FunctionCtx) {
         let span = Some([expr.span.start.line, expr.span.start.column]);
         match &expr.kind {
-            ExprKind::Literal(lit) => {
-                let idx = match lit {
-                    Literal::Int(v) => self.pool.add_int(*v),
-                    Literal::Float(v) => self.pool.add_float(*v),
-                    Literal::String(v) => self.pool.add_string(v),
-                    Literal::Bool(v) => self.pool.add_bool(*v),
-                    Literal::Nil => self.pool.add_nil(),
-                };
-                ctx.emit(IrInstruction {
-                    op: Opcode::LoadConst,
-                    arg: Some(serde_json::Value::Number(idx.into())),
-                    span,
-                    ..default_instruction()
-                });
+            ExprKind::Literal(lit) => self.generate_literal(lit, ctx, span),
+
+            // A const reference (not shadowed by a local) inlines its value
+            ExprKind::Identifier(name)
+                if self.const_values.contains_key(name) && !ctx.locals.contains(name) =>
+            {
+                let lit = self.const_values[name].clone();
+                self.generate_literal(&lit, ctx, span);
             }
 
             ExprKind::Identifier(name) => {
@@ -462,6 +484,11 @@ impl CodeGenerator {
                     BinaryOp::Gt => Opcode::Gt,
                     BinaryOp::Lte => Opcode::Lte,
                     BinaryOp::Gte => Opcode::Gte,
+                    BinaryOp::BitAnd => Opcode::BitAnd,
+                    BinaryOp::BitOr => Opcode::BitOr,
+                    BinaryOp::BitXor => Opcode::BitXor,
+                    BinaryOp::Shl => Opcode::Shl,
+                    BinaryOp::Shr => Opcode::Shr,
                     BinaryOp::And | BinaryOp::Or => unreachable!(),
                 };
                 ctx.emit(IrInstruction {
@@ -476,6 +503,7 @@ impl CodeGenerator {
                 let opcode = match op {
                     UnaryOp::Neg => Opcode::Neg,
                     UnaryOp::Not => Opcode::Not,
+                    UnaryOp::BitNot => Opcode::BitNot,
                 };
                 ctx.emit(IrInstruction {
                     op: opcode,
@@ -484,14 +512,37 @@ impl CodeGenerator {
                 });
             }
 
-            ExprKind::Call { callee, args } => {
-                // emit() special form
+            ExprKind::Call {
+                callee,
+                type_args,
+                args,
+            } => {
+                // emit() special form: emit(channel, payload) or emit(payload);
+                // emit<Schema>(...) also validates the payload at runtime.
                 if let ExprKind::Identifier(name) = &callee.kind {
-                    if name == "emit" && args.len() == 2 {
-                        self.generate_expr(&args[0], ctx);
-                        self.generate_expr(&args[1], ctx);
+                    if name == "emit" && (args.len() == 1 || args.len() == 2) {
+                        if args.len() == 1 {
+                            let idx = self.pool.add_string(DEFAULT_EMIT_CHANNEL);
+                            ctx.emit(IrInstruction {
+                                op: Opcode::LoadConst,
+                                arg: Some(serde_json::Value::Number(idx.into())),
+                                span,
+                                ..default_instruction()
+                            });
+                        }
+                        for arg in args {
+                            self.generate_expr(arg, ctx);
+                        }
+                        let schema = type_args.first().and_then(|ta| {
+                            if let crate::ast::types::TypeKind::Named(name) = &ta.kind {
+                                Some(name.clone())
+                            } else {
+                                None
+                            }
+                        });
                         ctx.emit(IrInstruction {
                             op: Opcode::Emit,
+                            schema,
                             span,
                             ..default_instruction()
                         });
@@ -670,8 +721,12 @@ impl CodeGenerator {
                 self.generate_while(condition, body, ctx, span);
             }
 
-            ExprKind::Loop { body } => {
-                self.generate_loop(body, ctx, span);
+            ExprKind::Loop { body, decorators } => {
+                let max_iters = decorators
+                    .iter()
+                    .find(|d| d.name == "max_iters")
+                    .and_then(Decorator::int_arg);
+                self.generate_loop(body, max_iters, ctx, span);
             }
 
             ExprKind::For {
@@ -873,6 +928,72 @@ impl CodeGenerator {
     // Assignment
     // ========================================================================
 
+    /// Load the object a field/index assignment mutates, keeping every
+    /// enclosing object of a `a.b.c` chain on the stack beneath it so that
+    /// [`generate_place_store`](Self::generate_place_store) can write the
+    /// updated value back.
+    fn generate_place_load(
+        &mut self,
+        place: &Expr,
+        ctx: &mut FunctionCtx,
+        span: Option<[u32; 2]>,
+    ) {
+        match &place.kind {
+            ExprKind::FieldAccess { object, field } => {
+                self.generate_place_load(object, ctx, span);
+                ctx.emit(IrInstruction {
+                    op: Opcode::Dup,
+                    span,
+                    ..default_instruction()
+                });
+                ctx.emit(IrInstruction {
+                    op: Opcode::FieldGet,
+                    name: Some(field.clone()),
+                    span,
+                    ..default_instruction()
+                });
+            }
+            _ => self.generate_expr(place, ctx),
+        }
+    }
+
+    /// Write an updated object (on top of the stack) back into the place it
+    /// was loaded from by [`generate_place_load`](Self::generate_place_load).
+    /// Places that are not a variable or field chain are simply discarded.
+    fn generate_place_store(
+        &mut self,
+        place: &Expr,
+        ctx: &mut FunctionCtx,
+        span: Option<[u32; 2]>,
+    ) {
+        match &place.kind {
+            ExprKind::Identifier(name) => {
+                ctx.emit(IrInstruction {
+                    op: Opcode::StoreLocal,
+                    name: Some(name.clone()),
+                    span,
+                    ..default_instruction()
+                });
+            }
+            ExprKind::FieldAccess { object, field } => {
+                ctx.emit(IrInstruction {
+                    op: Opcode::FieldSet,
+                    name: Some(field.clone()),
+                    span,
+                    ..default_instruction()
+                });
+                self.generate_place_store(object, ctx, span);
+            }
+            _ => {
+                ctx.emit(IrInstruction {
+                    op: Opcode::Pop,
+                    span,
+                    ..default_instruction()
+                });
+            }
+        }
+    }
+
     fn generate_assign(
         &mut self,
         target: &Expr,
@@ -918,7 +1039,7 @@ impl CodeGenerator {
             }
 
             ExprKind::FieldAccess { object, field } => {
-                self.generate_expr(object, ctx);
+                self.generate_place_load(object, ctx, span);
                 if op != AssignOp::Assign {
                     ctx.emit(IrInstruction {
                         op: Opcode::Dup,
@@ -946,6 +1067,7 @@ impl CodeGenerator {
                     span,
                     ..default_instruction()
                 });
+                self.generate_place_store(object, ctx, span);
                 // Assignment expression yields nil
                 let idx = self.pool.add_nil();
                 ctx.emit(IrInstruction {
@@ -958,7 +1080,7 @@ impl CodeGenerator {
 
             ExprKind::Index { object, index } => {
                 if op == AssignOp::Assign {
-                    self.generate_expr(object, ctx);
+                    self.generate_place_load(object, ctx, span);
                     self.generate_expr(index, ctx);
                     self.generate_expr(value, ctx);
                 } else {
@@ -966,7 +1088,7 @@ impl CodeGenerator {
                     let tmp_obj = ctx.fresh_local("$obj");
                     let tmp_idx = ctx.fresh_local("$idx");
 
-                    self.generate_expr(object, ctx);
+                    self.generate_place_load(object, ctx, span);
                     ctx.emit(IrInstruction {
                         op: Opcode::StoreLocal,
                         name: Some(tmp_obj.clone()),
@@ -1038,6 +1160,7 @@ impl CodeGenerator {
                     span,
                     ..default_instruction()
                 });
+                self.generate_place_store(object, ctx, span);
                 let idx = self.pool.add_nil();
                 ctx.emit(IrInstruction {
                     op: Opcode::LoadConst,
@@ -1124,9 +1247,16 @@ impl CodeGenerator {
         });
     }
 
-    fn generate_loop(&mut self, body: &Block, ctx: &mut FunctionCtx, span: Option<[u32; 2]>) {
+    fn generate_loop(
+        &mut self,
+        body: &Block,
+        max_iters: Option<i64>,
+        ctx: &mut FunctionCtx,
+        span: Option<[u32; 2]>,
+    ) {
         let result_var = ctx.fresh_local("$loop");
         let nil_idx = self.pool.add_nil();
+        let iters_var = max_iters.map(|_| ctx.fresh_local("$iters"));
 
         ctx.emit(IrInstruction {
             op: Opcode::LoadConst,
@@ -1141,9 +1271,30 @@ impl CodeGenerator {
             ..default_instruction()
         });
 
+        if let Some(iters_var) = &iters_var {
+            let zero_idx = self.pool.add_int(0);
+            ctx.emit(IrInstruction {
+                op: Opcode::LoadConst,
+                arg: Some(serde_json::Value::Number(zero_idx.into())),
+                span,
+                ..default_instruction()
+            });
+            ctx.emit(IrInstruction {
+                op: Opcode::StoreLocal,
+                name: Some(iters_var.clone()),
+                span,
+                ..default_instruction()
+            });
+        }
+
         let loop_start = ctx.current_ip();
         ctx.push_loop(result_var.clone());
 
+        // @max_iters: count this iteration and throw once past the cap.
+        if let (Some(iters_var), Some(cap)) = (&iters_var, max_iters) {
+            self.emit_iteration_cap_check(iters_var, cap, ctx, span);
+        }
+
         self.generate_block_void(body, ctx);
 
         let loop_ctx = ctx.pop_loop();
@@ -1171,6 +1322,76 @@ impl CodeGenerator {
         });
     }
 
+    /// `$iters += 1; if $iters > cap { throw "..." }`
+    fn emit_iteration_cap_check(
+        &mut self,
+        iters_var: &str,
+        cap: i64,
+        ctx: &mut FunctionCtx,
+        span: Option<[u32; 2]>,
+    ) {
+        let one_idx = self.pool.add_int(1);
+        let cap_idx = self.pool.add_int(cap);
+        let msg_idx = self
+            .pool
+            .add_string(&format!("loop exceeded @max_iters({})", cap));
+
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(iters_var.to_string()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(one_idx.into())),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::Add,
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::StoreLocal,
+            name: Some(iters_var.to_string()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(iters_var.to_string()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(cap_idx.into())),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::Gt,
+            span,
+            ..default_instruction()
+        });
+        let within_cap = ctx.emit_placeholder(Opcode::JumpIfFalse, span);
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(msg_idx.into())),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::Throw,
+            span,
+            ..default_instruction()
+        });
+        let after = ctx.current_ip();
+        ctx.instructions[within_cap].offset = Some(after as i32);
+    }
+
     fn generate_for(
         &mut self,
         pattern: &Pattern,
@@ -1207,6 +1428,55 @@ impl CodeGenerator {
             ..default_instruction()
         });
 
+        // Maps iterate as `[key, value]` entries; remember that for the
+        // whole loop so a `(a, b)` pattern destructures them.
+        let pairs_var = ctx.fresh_local("$pairs");
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(coll_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::CheckType,
+            type_name: Some("Map".to_string()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::StoreLocal,
+            name: Some(pairs_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(pairs_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        let jump_not_map = ctx.emit_placeholder(Opcode::JumpIfFalse, span);
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(coll_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::CallMethod,
+            name: Some("entries".to_string()),
+            argc: Some(0),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::StoreLocal,
+            name: Some(coll_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        ctx.patch_jump(jump_not_map);
+
         // Init index = 0
         let zero_idx = self.pool.add_int(0);
         ctx.emit(IrInstruction {
@@ -1272,7 +1542,22 @@ impl CodeGenerator {
         });
 
         // Bind element to pattern
-        self.emit_pattern_bind(pattern, ctx, span);
+        match &pattern.kind {
+            PatternKind::Tuple(parts) if parts.len() == 2 && !is_enumerate_call(iterable) => {
+                self.emit_indexed_pattern_bind(parts, &idx_var, &pairs_var, ctx, span)
+            }
+            PatternKind::Tuple(parts) => {
+                let elem_var = ctx.fresh_local("$elem");
+                ctx.emit(IrInstruction {
+                    op: Opcode::StoreLocal,
+                    name: Some(elem_var.clone()),
+                    span,
+                    ..default_instruction()
+                });
+                self.emit_loop_destructure(parts, &elem_var, ctx, span);
+            }
+            _ => self.emit_pattern_bind(pattern, ctx, span),
+        }
 
         // Body
         self.generate_block_void(body, ctx);
@@ -1283,53 +1568,195 @@ impl CodeGenerator {
         let one_idx = self.pool.add_int(1);
         ctx.emit(IrInstruction {
             op: Opcode::LoadLocal,
-            name: Some(idx_var.clone()),
+            name: Some(idx_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(one_idx.into())),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::Add,
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::StoreLocal,
+            name: Some(idx_var),
+            span,
+            ..default_instruction()
+        });
+
+        let loop_ctx = ctx.pop_loop();
+        // continue -> increment step
+        for patch in &loop_ctx.continue_patches {
+            ctx.instructions[*patch].offset = Some(increment_ip as i32);
+        }
+
+        ctx.emit(IrInstruction {
+            op: Opcode::Jump,
+            offset: Some(loop_start as i32),
+            span,
+            ..default_instruction()
+        });
+
+        let loop_end = ctx.current_ip();
+        ctx.patch_jump(jump_end);
+        for patch in loop_ctx.break_patches {
+            ctx.instructions[patch].offset = Some(loop_end as i32);
+        }
+
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(result_var),
+            span,
+            ..default_instruction()
+        });
+    }
+
+    /// Bind a `(a, b)` for-loop pattern against the current element (on the
+    /// stack). Over a map (`pairs_var` true) the `[key, value]` entry is
+    /// destructured; over an array `a` gets the loop index and `b` the
+    /// element, so `for (i, x) in [10, 20]` yields `(0, 10)`, `(1, 20)`.
+    fn emit_indexed_pattern_bind(
+        &mut self,
+        parts: &[Pattern],
+        idx_var: &str,
+        pairs_var: &str,
+        ctx: &mut FunctionCtx,
+        span: Option<[u32; 2]>,
+    ) {
+        let elem_var = ctx.fresh_local("$elem");
+        ctx.emit(IrInstruction {
+            op: Opcode::StoreLocal,
+            name: Some(elem_var.clone()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(pairs_var.to_string()),
+            span,
+            ..default_instruction()
+        });
+        let jump_indexed = ctx.emit_placeholder(Opcode::JumpIfFalse, span);
+
+        // Map entry: destructure it
+        self.emit_loop_destructure(parts, &elem_var, ctx, span);
+        let jump_done = ctx.emit_placeholder(Opcode::Jump, span);
+
+        // Array element: synthesize (index, element)
+        ctx.patch_jump(jump_indexed);
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(idx_var.to_string()),
+            span,
+            ..default_instruction()
+        });
+        self.emit_pattern_bind(&parts[0], ctx, span);
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(elem_var),
+            span,
+            ..default_instruction()
+        });
+        self.emit_pattern_bind(&parts[1], ctx, span);
+
+        ctx.patch_jump(jump_done);
+    }
+
+    /// Destructure the element in `elem_var` into a tuple loop pattern.
+    /// Throws unless the element is an array of exactly `parts.len()`
+    /// items, so a mismatched element is never silently truncated.
+    fn emit_loop_destructure(
+        &mut self,
+        parts: &[Pattern],
+        elem_var: &str,
+        ctx: &mut FunctionCtx,
+        span: Option<[u32; 2]>,
+    ) {
+        let arity_idx = self.pool.add_int(parts.len() as i64);
+        let msg_idx = self.pool.add_string(&format!(
+            "for loop pattern expects a tuple of {} elements",
+            parts.len()
+        ));
+
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(elem_var.to_string()),
+            span,
+            ..default_instruction()
+        });
+        ctx.emit(IrInstruction {
+            op: Opcode::CheckType,
+            type_name: Some("Array".to_string()),
+            span,
+            ..default_instruction()
+        });
+        let jump_not_array = ctx.emit_placeholder(Opcode::JumpIfFalse, span);
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadLocal,
+            name: Some(elem_var.to_string()),
             span,
             ..default_instruction()
         });
         ctx.emit(IrInstruction {
-            op: Opcode::LoadConst,
-            arg: Some(serde_json::Value::Number(one_idx.into())),
+            op: Opcode::CallMethod,
+            name: Some("len".to_string()),
+            argc: Some(0),
             span,
             ..default_instruction()
         });
         ctx.emit(IrInstruction {
-            op: Opcode::Add,
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(arity_idx.into())),
             span,
             ..default_instruction()
         });
         ctx.emit(IrInstruction {
-            op: Opcode::StoreLocal,
-            name: Some(idx_var),
+            op: Opcode::Eq,
             span,
             ..default_instruction()
         });
-
-        let loop_ctx = ctx.pop_loop();
-        // continue -> increment step
-        for patch in &loop_ctx.continue_patches {
-            ctx.instructions[*patch].offset = Some(increment_ip as i32);
-        }
-
+        let jump_ok = ctx.emit_placeholder(Opcode::JumpIfTrue, span);
+        ctx.patch_jump(jump_not_array);
         ctx.emit(IrInstruction {
-            op: Opcode::Jump,
-            offset: Some(loop_start as i32),
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(msg_idx.into())),
             span,
             ..default_instruction()
         });
-
-        let loop_end = ctx.current_ip();
-        ctx.patch_jump(jump_end);
-        for patch in loop_ctx.break_patches {
-            ctx.instructions[patch].offset = Some(loop_end as i32);
-        }
-
         ctx.emit(IrInstruction {
-            op: Opcode::LoadLocal,
-            name: Some(result_var),
+            op: Opcode::Throw,
             span,
             ..default_instruction()
         });
+        ctx.patch_jump(jump_ok);
+
+        for (i, pat) in parts.iter().enumerate() {
+            ctx.emit(IrInstruction {
+                op: Opcode::LoadLocal,
+                name: Some(elem_var.to_string()),
+                span,
+                ..default_instruction()
+            });
+            let idx = self.pool.add_int(i as i64);
+            ctx.emit(IrInstruction {
+                op: Opcode::LoadConst,
+                arg: Some(serde_json::Value::Number(idx.into())),
+                span,
+                ..default_instruction()
+            });
+            ctx.emit(IrInstruction {
+                op: Opcode::IndexGet,
+                span,
+                ..default_instruction()
+            });
+            self.emit_pattern_bind(pat, ctx, span);
+        }
     }
 
     // ========================================================================
@@ -1573,7 +2000,7 @@ impl CodeGenerator {
                 self.emit_pattern_check(pattern, ctx, span);
             }
 
-            PatternKind::Enum { path, .. } => {
+            PatternKind::Enum { path, fields } => {
                 // Handle core tagged unions used pervasively in Concerto.
                 // This prevents `Ok(...)` from matching `Err(...)` (and vice-versa).
                 let variant = path.last().map(|s| s.as_str()).unwrap_or("");
@@ -1615,7 +2042,7 @@ impl CodeGenerator {
                     // Variant predicate check (`is_ok`/`is_err`/`is_some`/`is_none`).
                     ctx.emit(IrInstruction {
                         op: Opcode::LoadLocal,
-                        name: Some(tmp),
+                        name: Some(tmp.clone()),
                         span,
                         ..default_instruction()
                     });
@@ -1626,11 +2053,49 @@ impl CodeGenerator {
                         span,
                         ..default_instruction()
                     });
+
+                    // Nested payload patterns (`Ok(Some(x))`, `Err("timeout")`)
+                    // must match too; plain bindings always do.
+                    let mut fail_patches = vec![type_mismatch];
+                    if !fields.iter().all(pattern_is_irrefutable) {
+                        fail_patches.push(ctx.emit_placeholder(Opcode::JumpIfFalse, span));
+                        for (i, pat) in fields.iter().enumerate() {
+                            ctx.emit(IrInstruction {
+                                op: Opcode::LoadLocal,
+                                name: Some(tmp.clone()),
+                                span,
+                                ..default_instruction()
+                            });
+                            let idx = self.pool.add_int(i as i64);
+                            ctx.emit(IrInstruction {
+                                op: Opcode::LoadConst,
+                                arg: Some(serde_json::Value::Number(idx.into())),
+                                span,
+                                ..default_instruction()
+                            });
+                            ctx.emit(IrInstruction {
+                                op: Opcode::IndexGet,
+                                span,
+                                ..default_instruction()
+                            });
+                            self.emit_pattern_check(pat, ctx, span);
+                            fail_patches.push(ctx.emit_placeholder(Opcode::JumpIfFalse, span));
+                        }
+                        let true_idx = self.pool.add_bool(true);
+                        ctx.emit(IrInstruction {
+                            op: Opcode::LoadConst,
+                            arg: Some(serde_json::Value::Number(true_idx.into())),
+                            span,
+                            ..default_instruction()
+                        });
+                    }
                     let done = ctx.emit_placeholder(Opcode::Jump, span);
 
-                    // Type mismatch => no match.
+                    // Type or payload mismatch => no match.
                     let mismatch_ip = ctx.current_ip();
-                    ctx.instructions[type_mismatch].offset = Some(mismatch_ip as i32);
+                    for patch in fail_patches {
+                        ctx.instructions[patch].offset = Some(mismatch_ip as i32);
+                    }
                     ctx.emit(IrInstruction {
                         op: Opcode::LoadConst,
                         arg: Some(serde_json::Value::Number(false_idx.into())),
@@ -2112,15 +2577,24 @@ impl CodeGenerator {
         // Patch TRY_BEGIN to catch start
         ctx.patch_jump(try_begin);
 
-        // Catch blocks
+        // Catch blocks. A typed CATCH carries the offset of the next clause,
+        // where the VM resumes when the error type doesn't match.
         let mut catch_exits = Vec::new();
+        let mut pending_mismatch: Option<usize> = None;
         for catch in catches {
+            if let Some(prev) = pending_mismatch.take() {
+                ctx.patch_jump(prev);
+            }
+            let catch_idx = ctx.current_ip();
             ctx.emit(IrInstruction {
                 op: Opcode::Catch,
                 type_name: catch.error_type.as_ref().map(format_type),
                 span: Some([catch.span.start.line, catch.span.start.column]),
                 ..default_instruction()
             });
+            if catch.error_type.is_some() {
+                pending_mismatch = Some(catch_idx);
+            }
 
             // Bind error variable if present
             if let Some(ref binding) = catch.binding {
@@ -2155,6 +2629,21 @@ impl CodeGenerator {
             catch_exits.push(catch_exit);
         }
 
+        // No clause matched: re-throw to the next outer handler
+        if let Some(prev) = pending_mismatch {
+            ctx.patch_jump(prev);
+            ctx.emit(IrInstruction {
+                op: Opcode::Catch,
+                span,
+                ..default_instruction()
+            });
+            ctx.emit(IrInstruction {
+                op: Opcode::Throw,
+                span,
+                ..default_instruction()
+            });
+        }
+
         // Patch all exit jumps (catch body exits + try-success exit) to here
         for exit in catch_exits {
             ctx.patch_jump(exit);
@@ -2236,7 +2725,9 @@ impl CodeGenerator {
                     param: IrParam {
                         name: h.param.name.clone(),
                         param_type,
+                        default: None,
                     },
+                    response_schema: h.return_type.as_ref().map(format_type),
                     instructions: handler_ctx.instructions,
                 }
             })
@@ -2263,6 +2754,22 @@ impl CodeGenerator {
         });
     }
 
+    fn generate_literal(&mut self, lit: &Literal, ctx: &mut FunctionCtx, span: Option<[u32; 2]>) {
+        let idx = match lit {
+            Literal::Int(v) => self.pool.add_int(*v),
+            Literal::Float(v) => self.pool.add_float(*v),
+            Literal::String(v) => self.pool.add_string(v),
+            Literal::Bool(v) => self.pool.add_bool(*v),
+            Literal::Nil => self.pool.add_nil(),
+        };
+        ctx.emit(IrInstruction {
+            op: Opcode::LoadConst,
+            arg: Some(serde_json::Value::Number(idx.into())),
+            span,
+            ..default_instruction()
+        });
+    }
+
     // ========================================================================
     // Closures
     // ========================================================================
@@ -2324,6 +2831,7 @@ impl CodeGenerator {
                             .map(format_type)
                             .unwrap_or_else(|| "any".to_string()),
                     ),
+                    default: None,
                 })
                 .collect(),
             return_type: return_type
@@ -2331,15 +2839,15 @@ impl CodeGenerator {
                 .map(|t| serde_json::Value::String(format_type(t)))
                 .unwrap_or(serde_json::Value::String("any".to_string())),
             is_async: false,
+            decorators: vec![],
             locals: closure_ctx.locals,
             instructions: closure_ctx.instructions,
         });
 
-        // Push closure reference
-        let idx = self.pool.add_string(&closure_name);
+        // Push closure reference (resolves to a function value at runtime)
         ctx.emit(IrInstruction {
-            op: Opcode::LoadConst,
-            arg: Some(serde_json::Value::Number(idx.into())),
+            op: Opcode::LoadGlobal,
+            name: Some(closure_name),
             span,
             ..default_instruction()
         });
@@ -2360,7 +2868,7 @@ impl CodeGenerator {
         // If right is a call `f(args...)`, becomes `f(left, args...)`
         // If right is an identifier/path, becomes `right(left)`
         match &right.kind {
-            ExprKind::Call { callee, args } => {
+            ExprKind::Call { callee, args, .. } => {
                 self.generate_expr(left, ctx);
                 for arg in args {
                     self.generate_expr(arg, ctx);
@@ -2527,6 +3035,9 @@ impl CodeGenerator {
         let mut max_tokens = None;
         let mut system_prompt = None;
         let mut timeout = None;
+        let mut schema_retries = None;
+        let mut base_url = None;
+        let mut seed = None;
         let mut tools = Vec::new();
         let mut memory = None;
 
@@ -2562,6 +3073,21 @@ impl CodeGenerator {
                         timeout = Some(*n as u32);
                     }
                 }
+                "schema_retries" => {
+                    if let ExprKind::Literal(Literal::Int(n)) = &field.value.kind {
+                        schema_retries = u32::try_from(*n).ok();
+                    }
+                }
+                "base_url" => {
+                    if let ExprKind::Literal(Literal::String(s)) = &field.value.kind {
+                        base_url = Some(s.clone());
+                    }
+                }
+                "seed" => {
+                    if let ExprKind::Literal(Literal::Int(n)) = &field.value.kind {
+                        seed = Some(*n);
+                    }
+                }
                 "tools" => {
                     if let ExprKind::Array(elems) = &field.value.kind {
                         for elem in elems {
@@ -2590,6 +3116,9 @@ impl CodeGenerator {
                 max_tokens,
                 system_prompt,
                 timeout,
+                schema_retries,
+                base_url,
+                seed,
             },
             tools,
             memory,
@@ -2608,11 +3137,20 @@ impl CodeGenerator {
         // Generate tool schemas from @describe/@param decorators
         let tool_schemas = self.generate_tool_schemas(&tool.name, &tool.methods);
 
+        // Every field other than `description` seeds the tool's `self` state
+        let mut state = serde_json::Map::new();
+        for field in &tool.fields {
+            if field.name != "description" {
+                state.insert(field.name.clone(), expr_to_json(&field.value));
+            }
+        }
+
         self.tools.push(IrTool {
             name: tool.name.clone(),
             module: self.module_name.clone(),
             methods,
             tool_schemas,
+            state,
         });
     }
 
@@ -2731,25 +3269,26 @@ impl CodeGenerator {
         use super::super::ast::types::TypeKind;
         let mut properties = serde_json::Map::new();
         let mut required = Vec::new();
+        let mut aliases = std::collections::BTreeMap::new();
 
         for field in &schema.fields {
-            let prop = match &field.type_ann.kind {
+            for alias in field
+                .decorators
+                .iter()
+                .filter(|d| d.name == "alias")
+                .filter_map(|d| d.string_arg())
+            {
+                aliases.insert(alias.to_string(), field.name.clone());
+            }
+            let mut prop = match &field.type_ann.kind {
                 TypeKind::Union(variants) => {
-                    // String literal union -> JSON schema enum
-                    let enum_vals: Vec<serde_json::Value> = variants
-                        .iter()
-                        .filter_map(|v| {
-                            if let TypeKind::StringLiteral(s) = &v.kind {
-                                Some(serde_json::Value::String(s.clone()))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-                    serde_json::json!({ "type": "string", "enum": enum_vals })
+                    union_json_schema(variants, |v| serde_json::json!({ "type": format_type(v) }))
                 }
                 _ => serde_json::json!({ "type": format_type(&field.type_ann) }),
             };
+            if let Some(description) = &field.description {
+                prop["description"] = serde_json::Value::String(description.clone());
+            }
             properties.insert(field.name.clone(), prop);
             if !field.is_optional {
                 required.push(serde_json::Value::String(field.name.clone()));
@@ -2766,6 +3305,7 @@ impl CodeGenerator {
             name: schema.name.clone(),
             json_schema,
             validation_mode: "strict".to_string(),
+            aliases,
         });
     }
 
@@ -2774,6 +3314,17 @@ impl CodeGenerator {
             .stages
             .iter()
             .map(|stage| {
+                if let Some(ref sub) = stage.pipeline {
+                    return IrPipelineStage {
+                        name: stage.name.clone(),
+                        params: Vec::new(),
+                        input_type: serde_json::Value::String("any".to_string()),
+                        output_type: serde_json::Value::String("any".to_string()),
+                        decorators: stage.decorators.iter().map(lower_decorator).collect(),
+                        instructions: Vec::new(),
+                        pipeline: Some(sub.clone()),
+                    };
+                }
                 let mut ctx = FunctionCtx::new();
                 for param in &stage.params {
                     ctx.add_local(&param.name);
@@ -2808,6 +3359,7 @@ impl CodeGenerator {
                                 .as_ref()
                                 .map(|t| serde_json::Value::String(format_type(t)))
                                 .unwrap_or(serde_json::Value::String("any".to_string())),
+                            default: None,
                         })
                         .collect(),
                     input_type: stage
@@ -2823,6 +3375,7 @@ impl CodeGenerator {
                         .unwrap_or(serde_json::Value::String("any".to_string())),
                     decorators: stage.decorators.iter().map(lower_decorator).collect(),
                     instructions: ctx.instructions,
+                    pipeline: None,
                 }
             })
             .collect();
@@ -2919,44 +3472,6 @@ impl CodeGenerator {
         }
     }
 
-    fn generate_const(&mut self, c: &ConstDecl) {
-        // Constants are compiled as global-level store instructions.
-        // For the IR, we emit them as a special init function.
-        let mut ctx = FunctionCtx::new();
-        let span = Some([c.span.start.line, c.span.start.column]);
-
-        self.generate_expr(&c.value, &mut ctx);
-        ctx.emit(IrInstruction {
-            op: Opcode::StoreGlobal,
-            name: Some(c.name.clone()),
-            span,
-            ..default_instruction()
-        });
-        let idx = self.pool.add_nil();
-        ctx.emit(IrInstruction {
-            op: Opcode::LoadConst,
-            arg: Some(serde_json::Value::Number(idx.into())),
-            span,
-            ..default_instruction()
-        });
-        ctx.emit(IrInstruction {
-            op: Opcode::Return,
-            span,
-            ..default_instruction()
-        });
-
-        self.functions.push(IrFunction {
-            name: format!("$const_{}", c.name),
-            module: self.module_name.clone(),
-            visibility: "private".to_string(),
-            params: Vec::new(),
-            return_type: serde_json::Value::String("nil".to_string()),
-            is_async: false,
-            locals: ctx.locals,
-            instructions: ctx.instructions,
-        });
-    }
-
     fn generate_hashmap(&mut self, hm: &HashMapDecl) {
         use super::super::ast::types::TypeKind;
         let (key_type, value_type) = match &hm.type_ann.kind {
@@ -3307,35 +3822,7 @@ fn compound_assign_opcode(op: AssignOp) -> Opcode {
 }
 
 fn format_type(ty: &super::super::ast::types::TypeAnnotation) -> String {
-    use super::super::ast::types::TypeKind;
-    match &ty.kind {
-        TypeKind::Named(name) => name.clone(),
-        TypeKind::Generic { name, args } => {
-            let arg_strs: Vec<String> = args.iter().map(format_type).collect();
-            format!("{}<{}>", name, arg_strs.join(", "))
-        }
-        TypeKind::Tuple(elems) => {
-            let elem_strs: Vec<String> = elems.iter().map(format_type).collect();
-            format!("({})", elem_strs.join(", "))
-        }
-        TypeKind::Function {
-            params,
-            return_type,
-        } => {
-            let param_strs: Vec<String> = params.iter().map(format_type).collect();
-            format!(
-                "fn({}) -> {}",
-                param_strs.join(", "),
-                format_type(return_type)
-            )
-        }
-        TypeKind::Union(variants) => {
-            let variant_strs: Vec<String> = variants.iter().map(format_type).collect();
-            variant_strs.join(" | ")
-        }
-        TypeKind::StringLiteral(s) => format!("\"{}\"", s),
-        TypeKind::Inferred => "any".to_string(),
-    }
+    ty.to_string()
 }
 
 /// Convert a Concerto type annotation to a JSON Schema value.
@@ -3368,41 +3855,58 @@ fn concerto_type_to_json_schema(
             }
             _ => serde_json::json!({ "type": "object" }),
         },
-        TypeKind::Union(variants) => {
-            let enum_vals: Vec<serde_json::Value> = variants
-                .iter()
-                .filter_map(|v| {
-                    if let TypeKind::StringLiteral(s) = &v.kind {
-                        Some(serde_json::Value::String(s.clone()))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            if !enum_vals.is_empty() {
-                serde_json::json!({ "type": "string", "enum": enum_vals })
-            } else {
-                serde_json::json!({ "type": "string" })
-            }
-        }
+        TypeKind::Union(variants) => union_json_schema(variants, concerto_type_to_json_schema),
         _ => serde_json::json!({ "type": "string" }),
     }
 }
 
+/// JSON Schema for a union type. String literal variants become one
+/// `"enum"`; a union of other types becomes `"oneOf"` with `variant_schema`
+/// applied to each, e.g. `String | Array<String>`.
+fn union_json_schema(
+    variants: &[super::super::ast::types::TypeAnnotation],
+    variant_schema: impl Fn(&super::super::ast::types::TypeAnnotation) -> serde_json::Value,
+) -> serde_json::Value {
+    use super::super::ast::types::TypeKind;
+    let (literals, types): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .partition(|v| matches!(v.kind, TypeKind::StringLiteral(_)));
+    let enum_vals: Vec<serde_json::Value> = literals
+        .iter()
+        .filter_map(|v| match &v.kind {
+            TypeKind::StringLiteral(s) => Some(serde_json::Value::String(s.clone())),
+            _ => None,
+        })
+        .collect();
+    let enum_schema = serde_json::json!({ "type": "string", "enum": enum_vals });
+    if types.is_empty() {
+        return enum_schema;
+    }
+    let mut alternatives: Vec<serde_json::Value> = types.into_iter().map(variant_schema).collect();
+    if !enum_vals.is_empty() {
+        alternatives.push(enum_schema);
+    }
+    serde_json::json!({ "oneOf": alternatives })
+}
+
 fn is_option_type_annotation(ty: &super::super::ast::types::TypeAnnotation) -> bool {
     use super::super::ast::types::TypeKind;
     matches!(&ty.kind, TypeKind::Generic { name, .. } if name == "Option")
 }
 
+fn literal_to_json(lit: &Literal) -> serde_json::Value {
+    match lit {
+        Literal::Int(v) => serde_json::json!(*v),
+        Literal::Float(v) => serde_json::json!(*v),
+        Literal::String(v) => serde_json::json!(v),
+        Literal::Bool(v) => serde_json::json!(*v),
+        Literal::Nil => serde_json::Value::Null,
+    }
+}
+
 fn expr_to_json(expr: &Expr) -> serde_json::Value {
     match &expr.kind {
-        ExprKind::Literal(lit) => match lit {
-            Literal::Int(v) => serde_json::json!(*v),
-            Literal::Float(v) => serde_json::json!(*v),
-            Literal::String(v) => serde_json::json!(v),
-            Literal::Bool(v) => serde_json::json!(*v),
-            Literal::Nil => serde_json::Value::Null,
-        },
+        ExprKind::Literal(lit) => literal_to_json(lit),
         ExprKind::Identifier(name) => serde_json::json!(name),
         ExprKind::Array(elems) => {
             let vals: Vec<serde_json::Value> = elems.iter().map(expr_to_json).collect();
@@ -3429,6 +3933,21 @@ fn expr_to_u32(expr: &Expr) -> Option<u32> {
     }
 }
 
+/// True when a pattern matches every value (so it only binds).
+fn pattern_is_irrefutable(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Wildcard | PatternKind::Identifier(_) | PatternKind::Rest => true,
+        PatternKind::Binding { pattern, .. } => pattern_is_irrefutable(pattern),
+        _ => false,
+    }
+}
+
+/// True when a `for` iterable is an `.enumerate()` call, whose `[index,
+/// element]` pairs a `(a, b)` loop pattern destructures directly.
+fn is_enumerate_call(iterable: &Expr) -> bool {
+    matches!(&iterable.kind, ExprKind::MethodCall { method, .. } if method == "enumerate")
+}
+
 fn lower_decorator(d: &Decorator) -> IrDecorator {
     let args = if d.args.is_empty() {
         None
@@ -3538,6 +4057,22 @@ mod tests {
         assert!(ops.contains(&Opcode::Emit));
     }
 
+    #[test]
+    fn single_arg_emit_uses_default_channel() {
+        let ir = compile(r#"fn main() { emit(42); }"#);
+        let main = &ir.functions[0];
+        let emit_pos = main
+            .instructions
+            .iter()
+            .position(|i| i.op == Opcode::Emit)
+            .expect("expected EMIT");
+        // Channel constant is loaded first, then the payload
+        let channel = &main.instructions[emit_pos - 2];
+        assert_eq!(channel.op, Opcode::LoadConst);
+        let idx = channel.arg.as_ref().unwrap().as_u64().unwrap() as usize;
+        assert_eq!(ir.constants[idx].value, serde_json::json!("default"));
+    }
+
     #[test]
     fn function_with_return() {
         let ir = compile("fn add(a: Int, b: Int) -> Int { return a + b; }");
@@ -3704,6 +4239,36 @@ mod tests {
         // Should have main + closure function
         assert!(ir.functions.len() >= 2);
         assert!(ir.functions.iter().any(|f| f.name.starts_with("$closure_")));
+        // The closure value is a reference to that function
+        let main = ir.functions.iter().find(|f| f.name == "main").unwrap();
+        assert!(main.instructions.iter().any(|i| i.op == Opcode::LoadGlobal
+            && i.name
+                .as_deref()
+                .is_some_and(|n| n.starts_with("$closure_"))));
+    }
+
+    #[test]
+    fn const_references_are_inlined() {
+        let ir = compile(
+            r#"
+            const BASE: Int = 20;
+            const LIMIT: Int = BASE + 1;
+            fn main() {
+                let x = LIMIT;
+            }
+        "#,
+        );
+        // No init function; the use site loads the folded literal
+        assert!(!ir.functions.iter().any(|f| f.name.starts_with("$const_")));
+        let main = ir.functions.iter().find(|f| f.name == "main").unwrap();
+        assert!(!main
+            .instructions
+            .iter()
+            .any(|i| i.name.as_deref() == Some("LIMIT") && i.op == Opcode::LoadLocal));
+        assert!(ir
+            .constants
+            .iter()
+            .any(|c| c.const_type == "int" && c.value == serde_json::json!(21)));
     }
 
     #[test]
@@ -3815,6 +4380,9 @@ mod tests {
             model MyAgent {
                 provider: openai,
                 base: "gpt-4o",
+                schema_retries: 1,
+                base_url: "http://localhost:8080",
+                seed: 7,
             }
             fn main() {}
         "#,
@@ -3823,6 +4391,12 @@ mod tests {
         assert_eq!(ir.models[0].name, "MyAgent");
         assert_eq!(ir.models[0].connection, "openai");
         assert_eq!(ir.models[0].config.base, Some("gpt-4o".to_string()));
+        assert_eq!(ir.models[0].config.schema_retries, Some(1));
+        assert_eq!(
+            ir.models[0].config.base_url,
+            Some("http://localhost:8080".to_string())
+        );
+        assert_eq!(ir.models[0].config.seed, Some(7));
     }
 
     #[test]
@@ -3866,6 +4440,76 @@ mod tests {
         assert!(!required.contains(&serde_json::json!("age")));
     }
 
+    #[test]
+    fn schema_field_descriptions_in_json_schema() {
+        let ir = compile(
+            r#"
+            schema Verdict {
+                label: "yes" | "no" "the final answer",
+                reason?: String "why, in one sentence",
+                score: Int,
+            }
+            fn main() {}
+        "#,
+        );
+        let props = &ir.schemas[0].json_schema["properties"];
+        assert_eq!(
+            props["label"],
+            serde_json::json!({ "type": "string", "enum": ["yes", "no"], "description": "the final answer" })
+        );
+        assert_eq!(
+            props["reason"],
+            serde_json::json!({ "type": "String", "description": "why, in one sentence" })
+        );
+        assert!(props["score"].get("description").is_none());
+    }
+
+    #[test]
+    fn schema_union_of_types_lowers_to_one_of() {
+        let ir = compile(
+            r#"
+            schema Reply {
+                content: String | Array<String>,
+                status: Int | "pending" | "failed",
+            }
+            fn main() {}
+        "#,
+        );
+        let props = &ir.schemas[0].json_schema["properties"];
+        assert_eq!(
+            props["content"],
+            serde_json::json!({ "oneOf": [{ "type": "String" }, { "type": "Array<String>" }] })
+        );
+        assert_eq!(
+            props["status"],
+            serde_json::json!({ "oneOf": [
+                { "type": "Int" },
+                { "type": "string", "enum": ["pending", "failed"] },
+            ] })
+        );
+    }
+
+    #[test]
+    fn schema_field_aliases_lower_to_ir() {
+        let ir = compile(
+            r#"
+            schema Person {
+                first_name @alias("firstName"): String,
+                age: Int,
+            }
+            fn main() {}
+        "#,
+        );
+        let schema = &ir.schemas[0];
+        assert_eq!(
+            schema.aliases.get("firstName").map(String::as_str),
+            Some("first_name")
+        );
+        assert_eq!(schema.aliases.len(), 1);
+        // The model is still asked for the declared name.
+        assert!(schema.json_schema["properties"].get("first_name").is_some());
+    }
+
     #[test]
     fn pipeline_generates_stages() {
         let ir = compile(
@@ -3888,6 +4532,26 @@ mod tests {
         assert_eq!(ir.pipelines[0].stages[1].name, "format");
     }
 
+    #[test]
+    fn pipeline_stage_referencing_pipeline_lowers_to_name() {
+        let ir = compile(
+            r#"
+            pipeline Inner {
+                stage only(x: Int) -> Int { x }
+            }
+            pipeline Outer {
+                @retry(max: 2)
+                stage nested = Inner;
+            }
+            fn main() {}
+        "#,
+        );
+        let stage = &ir.pipelines[1].stages[0];
+        assert_eq!(stage.pipeline.as_deref(), Some("Inner"));
+        assert!(stage.instructions.is_empty());
+        assert_eq!(stage.decorators.len(), 1);
+    }
+
     #[test]
     fn impl_generates_qualified_functions() {
         let ir = compile(
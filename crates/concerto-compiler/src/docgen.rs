@@ -0,0 +1,220 @@
+//! Markdown reference generation from declarations and `///` doc comments.
+//!
+//! Used by `concerto doc`. Functions (except `@test` functions), agents,
+//! models, schemas and tools are listed in source order within each section,
+//! with their signature or fields followed by the doc comment text.
+
+use crate::ast::*;
+
+/// Render a markdown reference for `program`, headed by `title`.
+pub fn render_markdown(program: &Program, title: &str) -> String {
+    let mut functions = Vec::new();
+    let mut agents = Vec::new();
+    let mut models = Vec::new();
+    let mut schemas = Vec::new();
+    let mut tools = Vec::new();
+    for decl in &program.declarations {
+        match decl {
+            Declaration::Function(f) if !f.decorators.iter().any(|d| d.name == "test") => {
+                functions.push(f)
+            }
+            Declaration::Agent(a) => agents.push(a),
+            Declaration::Model(m) => models.push(m),
+            Declaration::Schema(s) => schemas.push(s),
+            Declaration::Tool(t) => tools.push(t),
+            _ => {}
+        }
+    }
+
+    let mut out = format!("# {}\n", title);
+
+    if !functions.is_empty() {
+        out.push_str("\n## Functions\n");
+        for f in functions {
+            out.push_str(&format!("\n### `{}`\n\n", f.name));
+            push_signature(&mut out, f);
+            push_doc(&mut out, &f.doc);
+        }
+    }
+
+    if !agents.is_empty() {
+        out.push_str("\n## Agents\n");
+        for a in agents {
+            out.push_str(&format!("\n### `{}`\n", a.name));
+            push_doc(&mut out, &a.doc);
+            push_config_fields(&mut out, &a.fields);
+        }
+    }
+
+    if !models.is_empty() {
+        out.push_str("\n## Models\n");
+        for m in models {
+            out.push_str(&format!("\n### `{}`\n", m.name));
+            push_doc(&mut out, &m.doc);
+            push_config_fields(&mut out, &m.fields);
+        }
+    }
+
+    if !schemas.is_empty() {
+        out.push_str("\n## Schemas\n");
+        for s in schemas {
+            out.push_str(&format!("\n### `{}`\n", s.name));
+            push_doc(&mut out, &s.doc);
+            if !s.fields.is_empty() {
+                out.push_str("\n| Field | Type |\n| --- | --- |\n");
+                for field in &s.fields {
+                    let optional = if field.is_optional { "?" } else { "" };
+                    out.push_str(&format!(
+                        "| `{}{}` | `{}` |\n",
+                        field.name,
+                        optional,
+                        escape_cell(&field.type_ann.to_string())
+                    ));
+                }
+            }
+        }
+    }
+
+    if !tools.is_empty() {
+        out.push_str("\n## Tools\n");
+        for t in tools {
+            out.push_str(&format!("\n### `{}`\n", t.name));
+            push_doc(&mut out, &t.doc);
+            push_config_fields(&mut out, &t.fields);
+            for method in &t.methods {
+                out.push_str(&format!("\n#### `{}::{}`\n\n", t.name, method.name));
+                push_signature(&mut out, method);
+                push_doc(&mut out, &method.doc);
+            }
+        }
+    }
+
+    out
+}
+
+/// Append a fenced `fn` signature, e.g. `pub async fn fetch(self, url: String) -> String`.
+fn push_signature(out: &mut String, f: &FunctionDecl) {
+    let mut params = Vec::new();
+    match f.self_param {
+        SelfParam::None => {}
+        SelfParam::Immutable => params.push("self".to_string()),
+        SelfParam::Mutable => params.push("mut self".to_string()),
+    }
+    for p in &f.params {
+        let mut param = match &p.type_ann {
+            Some(ty) => format!("{}: {}", p.name, ty),
+            None => p.name.clone(),
+        };
+        if let Some(default) = &p.default {
+            param.push_str(&format!(" = {}", expr_text(default)));
+        }
+        params.push(param);
+    }
+
+    out.push_str("```concerto\n");
+    if f.is_public {
+        out.push_str("pub ");
+    }
+    if f.is_async {
+        out.push_str("async ");
+    }
+    out.push_str(&format!("fn {}({})", f.name, params.join(", ")));
+    if let Some(ret) = &f.return_type {
+        out.push_str(&format!(" -> {}", ret));
+    }
+    out.push_str("\n```\n");
+}
+
+fn push_doc(out: &mut String, doc: &Option<String>) {
+    if let Some(doc) = doc {
+        out.push('\n');
+        out.push_str(doc);
+        out.push('\n');
+    }
+}
+
+/// Append a `Field | Value` table for model/agent/tool config fields.
+fn push_config_fields(out: &mut String, fields: &[ConfigField]) {
+    if fields.is_empty() {
+        return;
+    }
+    out.push_str("\n| Field | Value |\n| --- | --- |\n");
+    for field in fields {
+        out.push_str(&format!(
+            "| `{}` | `{}` |\n",
+            field.name,
+            escape_cell(&expr_text(&field.value))
+        ));
+    }
+}
+
+/// Source-like text for simple expressions; anything else renders as `...`.
+fn expr_text(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Literal(Literal::Int(n)) => n.to_string(),
+        ExprKind::Literal(Literal::Float(n)) => n.to_string(),
+        ExprKind::Literal(Literal::String(s)) => format!("{:?}", s),
+        ExprKind::Literal(Literal::Bool(b)) => b.to_string(),
+        ExprKind::Literal(Literal::Nil) => "nil".to_string(),
+        ExprKind::Identifier(name) => name.clone(),
+        ExprKind::Path(segments) => segments.join("::"),
+        ExprKind::Array(items) => format!(
+            "[{}]",
+            items.iter().map(expr_text).collect::<Vec<_>>().join(", ")
+        ),
+        _ => "...".to_string(),
+    }
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn render(source: &str) -> String {
+        let (tokens, _) = Lexer::new(source, "test.conc").tokenize();
+        let (program, diags) = Parser::new(tokens).parse();
+        assert!(!diags.has_errors(), "{:?}", diags.diagnostics());
+        render_markdown(&program, "demo")
+    }
+
+    #[test]
+    fn documents_functions_schemas_and_tools() {
+        let md = render(
+            r#"
+            /// Adds `n` to `x`.
+            pub fn add(x: Int, n: Int = 1) -> Int { return x + n; }
+
+            @test
+            fn add_works() {}
+
+            /// A verdict.
+            schema Verdict {
+                label: "yes" | "no",
+                reason?: String,
+            }
+
+            tool Calc {
+                description: "math",
+                /// Doubles a number.
+                pub fn double(self, x: Int) -> Result<Int, ToolError> { return Ok(x * 2); }
+            }
+        "#,
+        );
+        assert!(md.starts_with("# demo\n"));
+        assert!(md.contains(
+            "### `add`\n\n```concerto\npub fn add(x: Int, n: Int = 1) -> Int\n```\n\nAdds `n` to `x`.\n"
+        ));
+        assert!(!md.contains("add_works"));
+        assert!(md.contains("| `label` | `\"yes\" \\| \"no\"` |\n| `reason?` | `String` |"));
+        assert!(md.contains("| `description` | `\"math\"` |"));
+        assert!(md.contains(
+            "#### `Calc::double`\n\n```concerto\npub fn double(self, x: Int) -> Result<Int, ToolError>\n```\n\nDoubles a number.\n"
+        ));
+    }
+}
@@ -58,7 +58,9 @@ impl<'src> Lexer<'src> {
 
     /// Scan the next token.
     fn next_token(&mut self) -> Token {
-        self.skip_whitespace_and_comments();
+        if let Some(doc) = self.skip_whitespace_and_comments() {
+            return doc;
+        }
 
         if self.cursor.is_eof() {
             let pos = self.cursor.position();
@@ -180,6 +182,8 @@ impl<'src> Lexer<'src> {
             '<' => {
                 if self.cursor.eat('=') {
                     self.make_token(TokenKind::LessEqual, start)
+                } else if self.cursor.eat('<') {
+                    self.make_token(TokenKind::LessLess, start)
                 } else {
                     self.make_token(TokenKind::Less, start)
                 }
@@ -187,6 +191,8 @@ impl<'src> Lexer<'src> {
             '>' => {
                 if self.cursor.eat('=') {
                     self.make_token(TokenKind::GreaterEqual, start)
+                } else if self.cursor.eat('>') {
+                    self.make_token(TokenKind::GreaterGreater, start)
                 } else {
                     self.make_token(TokenKind::Greater, start)
                 }
@@ -195,12 +201,11 @@ impl<'src> Lexer<'src> {
                 if self.cursor.eat('&') {
                     self.make_token(TokenKind::AmpAmp, start)
                 } else {
-                    let span = self.cursor.span_from(start);
-                    self.diagnostics
-                        .error("unexpected character '&'; did you mean '&&'?", span.clone());
-                    self.make_token_with_span(TokenKind::AmpAmp, start, span)
+                    self.make_token(TokenKind::Amp, start)
                 }
             }
+            '^' => self.make_token(TokenKind::Caret, start),
+            '~' => self.make_token(TokenKind::Tilde, start),
             '|' => {
                 if self.cursor.eat('|') {
                     self.make_token(TokenKind::PipePipe, start)
@@ -254,8 +259,9 @@ impl<'src> Lexer<'src> {
     // Whitespace & comments
     // ---------------------------------------------------------------
 
-    /// Skip whitespace and comments.
-    fn skip_whitespace_and_comments(&mut self) {
+    /// Skip whitespace and comments. A doc comment (`///`, but not `////`)
+    /// stops the scan and is returned as a `DocComment` token.
+    fn skip_whitespace_and_comments(&mut self) -> Option<Token> {
         loop {
             // Skip whitespace
             self.cursor.eat_while(|c| c.is_ascii_whitespace());
@@ -264,14 +270,15 @@ impl<'src> Lexer<'src> {
             if self.cursor.peek() == Some('/') {
                 match self.cursor.peek_second() {
                     Some('/') => {
+                        let start = self.cursor.position();
                         self.cursor.advance(); // first /
                         self.cursor.advance(); // second /
 
-                        // Doc comment (///) — skip for now, emit as DocComment later
+                        // Doc comment (///)
                         if self.cursor.peek() == Some('/') && self.cursor.peek_second() != Some('/')
                         {
                             self.cursor.eat_while(|c| c != '\n');
-                            continue;
+                            return Some(self.make_token(TokenKind::DocComment, start));
                         }
 
                         // Regular line comment
@@ -289,7 +296,7 @@ impl<'src> Lexer<'src> {
                 }
             }
 
-            break;
+            return None;
         }
     }
 
@@ -658,16 +665,6 @@ impl<'src> Lexer<'src> {
         let span = self.cursor.span_from(start);
         Token::new(kind, lexeme, span)
     }
-
-    fn make_token_with_span(
-        &self,
-        kind: TokenKind,
-        start: Position,
-        span: concerto_common::Span,
-    ) -> Token {
-        let lexeme = self.cursor.slice_from(start.offset);
-        Token::new(kind, lexeme, span)
-    }
 }
 
 fn is_ident_start(c: char) -> bool {
@@ -756,6 +753,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bitwise_operators() {
+        let kinds = lex_kinds("& | ^ ~ << >> &&");
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Amp,
+                TokenKind::Pipe,
+                TokenKind::Caret,
+                TokenKind::Tilde,
+                TokenKind::LessLess,
+                TokenKind::GreaterGreater,
+                TokenKind::AmpAmp,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn keywords() {
         let kinds = lex_kinds("fn let mut if else return emit true false nil");
@@ -813,6 +828,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn doc_comment_token() {
+        let tokens = lex("/// Adds one.\n//// banner\nfn");
+        assert_eq!(tokens[0].kind, TokenKind::DocComment);
+        assert_eq!(tokens[0].lexeme, "/// Adds one.");
+        assert_eq!(tokens[1].kind, TokenKind::Fn);
+    }
+
     #[test]
     fn block_comment_skipped() {
         let kinds = lex_kinds("x /* block */ y");
@@ -111,6 +111,11 @@ pub enum TokenKind {
     AmpAmp,           // &&
     PipePipe,         // ||
     Bang,             // !
+    Amp,              // &
+    Caret,            // ^
+    Tilde,            // ~
+    LessLess,         // <<
+    GreaterGreater,   // >>
     Equal,            // =
     PlusEqual,        // +=
     MinusEqual,       // -=
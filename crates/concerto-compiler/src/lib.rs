@@ -1,5 +1,6 @@
 pub mod ast;
 pub mod codegen;
+pub mod docgen;
 pub mod lexer;
 pub mod parser;
 pub mod semantic;
@@ -6,6 +6,20 @@ use super::Parser;
 impl Parser {
     /// Parse a top-level declaration.
     pub(super) fn parse_declaration(&mut self) -> Option<Declaration> {
+        let doc = self.doc_comment();
+        let mut decl = self.parse_undocumented_declaration()?;
+        match &mut decl {
+            Declaration::Function(d) => d.doc = doc,
+            Declaration::Model(d) => d.doc = doc,
+            Declaration::Tool(d) => d.doc = doc,
+            Declaration::Schema(d) => d.doc = doc,
+            Declaration::Agent(d) => d.doc = doc,
+            _ => {}
+        }
+        Some(decl)
+    }
+
+    fn parse_undocumented_declaration(&mut self) -> Option<Declaration> {
         // Collect any leading decorators
         let decorators = self.parse_decorators();
 
@@ -56,7 +70,7 @@ impl Parser {
     // ========================================================================
 
     /// Parse zero or more decorators.
-    fn parse_decorators(&mut self) -> Vec<Decorator> {
+    pub(super) fn parse_decorators(&mut self) -> Vec<Decorator> {
         let mut decorators = Vec::new();
         while self.peek() == TokenKind::At {
             if let Some(dec) = self.parse_decorator() {
@@ -151,6 +165,7 @@ impl Parser {
 
         Some(Declaration::Function(FunctionDecl {
             name,
+            doc: None,
             decorators,
             self_param,
             params,
@@ -206,6 +221,7 @@ impl Parser {
         let span = start.merge(&self.previous_span());
         Some(FunctionDecl {
             name,
+            doc: None,
             decorators,
             self_param,
             params,
@@ -343,7 +359,7 @@ impl Parser {
                     break;
                 }
             }
-            self.expect(TokenKind::Greater)?;
+            self.expect_closing_angle()?;
             let span = start.merge(&self.previous_span());
             Some(TypeAnnotation {
                 kind: types::TypeKind::Generic { name, args },
@@ -403,7 +419,7 @@ impl Parser {
         Some(fields)
     }
 
-    /// Parse a single typed field: `[pub] name[?]: Type [= default]`
+    /// Parse a single typed field: `[pub] name[?]: Type ["description"] [= default]`
     fn parse_field_decl(&mut self) -> Option<FieldDecl> {
         let start = self.current_span();
         let is_public = self.eat(TokenKind::Pub);
@@ -414,8 +430,32 @@ impl Parser {
         // Optional `?` for schema optional fields
         let is_optional = self.eat(TokenKind::Question);
 
+        // Field decorators: `first_name @alias("firstName"): String`
+        let decorators = self.parse_decorators();
+
         self.expect(TokenKind::Colon)?;
-        let type_ann = self.parse_type_annotation()?;
+        let mut type_ann = self.parse_type_annotation()?;
+
+        // In a field, `|` can only continue a union, so named types may lead
+        // one too: `content: String | Array<String>`
+        if self.peek() == TokenKind::Pipe {
+            let start = type_ann.span.clone();
+            let mut variants = vec![type_ann];
+            while self.eat(TokenKind::Pipe) {
+                variants.push(self.parse_single_type_annotation()?);
+            }
+            type_ann = TypeAnnotation {
+                kind: types::TypeKind::Union(variants),
+                span: start.merge(&self.previous_span()),
+            };
+        }
+
+        // Optional description string: `reason: String "why the label applies"`
+        let description = if self.peek() == TokenKind::StringLiteral {
+            Some(self.advance().lexeme.clone())
+        } else {
+            None
+        };
 
         // Optional default value
         let default = if self.eat(TokenKind::Equal) {
@@ -428,6 +468,8 @@ impl Parser {
         Some(FieldDecl {
             name,
             type_ann,
+            description,
+            decorators,
             default,
             is_public,
             is_optional,
@@ -456,6 +498,7 @@ impl Parser {
 
         Some(Declaration::Model(ModelDecl {
             name,
+            doc: None,
             decorators,
             fields,
             span,
@@ -486,8 +529,10 @@ impl Parser {
                 || self.peek() == TokenKind::Fn
                 || self.peek() == TokenKind::Async
             {
+                let doc = self.doc_comment();
                 let method_decorators = self.parse_decorators();
-                let method = self.parse_method(method_decorators, true)?;
+                let mut method = self.parse_method(method_decorators, true)?;
+                method.doc = doc;
                 methods.push(method);
             } else {
                 // Config field
@@ -502,6 +547,7 @@ impl Parser {
 
         Some(Declaration::Tool(ToolDecl {
             name,
+            doc: None,
             fields,
             methods,
             span,
@@ -529,6 +575,7 @@ impl Parser {
 
         Some(Declaration::Schema(SchemaDecl {
             name,
+            doc: None,
             decorators,
             fields,
             span,
@@ -584,6 +631,7 @@ impl Parser {
     }
 
     /// Parse `[decorators] stage name(params) [-> Type] { body }`
+    /// or `[decorators] stage name = Pipeline;`
     fn parse_stage(&mut self, decorators: Vec<Decorator>) -> Option<StageDecl> {
         let start = if let Some(first) = decorators.first() {
             first.span.clone()
@@ -595,6 +643,25 @@ impl Parser {
         let name_token = self.expect(TokenKind::Identifier)?;
         let name = name_token.lexeme.clone();
 
+        if self.eat(TokenKind::Equal) {
+            let pipeline = self.expect(TokenKind::Identifier)?.lexeme.clone();
+            self.expect(TokenKind::Semicolon)?;
+            let span = start.merge(&self.previous_span());
+            return Some(StageDecl {
+                name,
+                decorators,
+                params: Vec::new(),
+                return_type: None,
+                body: Block {
+                    stmts: Vec::new(),
+                    tail_expr: None,
+                    span: span.clone(),
+                },
+                pipeline: Some(pipeline),
+                span,
+            });
+        }
+
         self.expect(TokenKind::LeftParen)?;
         let params = self.parse_param_list()?;
         self.expect(TokenKind::RightParen)?;
@@ -614,6 +681,7 @@ impl Parser {
             params,
             return_type,
             body,
+            pipeline: None,
             span,
         })
     }
@@ -1052,6 +1120,7 @@ impl Parser {
 
         Some(Declaration::Agent(AgentDecl {
             name,
+            doc: None,
             decorators,
             fields,
             span,
@@ -1215,6 +1284,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_doc_comments_attach_to_declarations() {
+        let prog = parse(
+            r#"
+            /// Classification result.
+            schema Verdict {
+                label: String,
+            }
+
+            // not a doc comment
+            fn undocumented() {}
+
+            /// Adds one.
+            /// Never fails.
+            @test
+            fn add_one(x: Int) -> Int {
+                /// ignored inside a body
+                return x + 1;
+            }
+
+            tool Calc {
+                description: "math",
+                /// Doubles a number.
+                pub fn double(self, x: Int) -> Result<Int, ToolError> { return Ok(x * 2); }
+            }
+        "#,
+        );
+        let docs: Vec<Option<&str>> = prog
+            .declarations
+            .iter()
+            .map(|d| match d {
+                Declaration::Schema(s) => s.doc.as_deref(),
+                Declaration::Function(f) => f.doc.as_deref(),
+                Declaration::Tool(t) => t.methods[0].doc.as_deref(),
+                _ => panic!("unexpected declaration"),
+            })
+            .collect();
+        assert_eq!(
+            docs,
+            vec![
+                Some("Classification result."),
+                None,
+                Some("Adds one.\nNever fails."),
+                Some("Doubles a number."),
+            ]
+        );
+    }
+
     #[test]
     fn parse_schema_optional_field() {
         let prog = parse(
@@ -1234,6 +1351,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_schema_field_descriptions() {
+        let prog = parse(
+            r#"
+            schema Verdict {
+                label: "yes" | "no" "the final answer",
+                reason?: String "why, in one sentence",
+                score: Int,
+            }
+        "#,
+        );
+        match &prog.declarations[0] {
+            Declaration::Schema(s) => {
+                assert!(
+                    matches!(&s.fields[0].type_ann.kind, crate::ast::types::TypeKind::Union(v) if v.len() == 2)
+                );
+                assert_eq!(s.fields[0].description.as_deref(), Some("the final answer"));
+                assert_eq!(
+                    s.fields[1].description.as_deref(),
+                    Some("why, in one sentence")
+                );
+                assert!(s.fields[1].is_optional);
+                assert_eq!(s.fields[2].description, None);
+            }
+            other => panic!("expected Schema, got {:?}", std::mem::discriminant(other)),
+        }
+    }
+
+    #[test]
+    fn parse_schema_field_alias() {
+        let prog = parse(
+            r#"
+            schema Person {
+                first_name @alias("firstName"): String,
+                nick? @alias("nickName"): String "what friends call them",
+            }
+        "#,
+        );
+        match &prog.declarations[0] {
+            Declaration::Schema(s) => {
+                assert_eq!(s.fields[0].decorators[0].name, "alias");
+                assert_eq!(s.fields[0].decorators[0].string_arg(), Some("firstName"));
+                assert!(s.fields[1].is_optional);
+                assert_eq!(s.fields[1].decorators[0].string_arg(), Some("nickName"));
+                assert_eq!(
+                    s.fields[1].description.as_deref(),
+                    Some("what friends call them")
+                );
+            }
+            other => panic!("expected Schema, got {:?}", std::mem::discriminant(other)),
+        }
+    }
+
     #[test]
     fn parse_schema_with_defaults() {
         let prog = parse(
@@ -1310,6 +1480,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_pipeline_stage_referencing_pipeline() {
+        let prog = parse(
+            r#"
+            pipeline Outer {
+                stage load(path: String) -> String {
+                    path
+                }
+                @retry(max: 2)
+                stage clean = Cleaner;
+            }
+        "#,
+        );
+        match &prog.declarations[0] {
+            Declaration::Pipeline(p) => {
+                assert_eq!(p.stages.len(), 2);
+                assert_eq!(p.stages[0].pipeline, None);
+                assert_eq!(p.stages[1].name, "clean");
+                assert_eq!(p.stages[1].pipeline.as_deref(), Some("Cleaner"));
+                assert_eq!(p.stages[1].decorators.len(), 1);
+                assert!(p.stages[1].params.is_empty());
+            }
+            other => panic!("expected Pipeline, got {:?}", std::mem::discriminant(other)),
+        }
+    }
+
     #[test]
     fn parse_pipeline_without_signature() {
         // Backwards compatibility: no signature
@@ -19,10 +19,14 @@ use super::Parser;
 //  Spec 8:  Equality    ==, !=          (13, 14) left-assoc
 //  Spec 7:  Comparison  <, >, <=, >=    (15, 16) left-assoc
 //  Spec 6:  Range       .., ..=         (17, 18)
-//  Spec 5:  Additive    +, -            (19, 20) left-assoc
-//  Spec 4:  Multiplicative *, /, %      (21, 22) left-assoc
-//  Spec 3:  Cast        as              (23, 24) left-assoc
-//  Spec 2:  Prefix      !, - (unary)    25
+//           Bitwise OR  |               (19, 20) left-assoc
+//           Bitwise XOR ^               (21, 22) left-assoc
+//           Bitwise AND &               (23, 24) left-assoc
+//           Shift       <<, >>          (25, 26) left-assoc
+//  Spec 5:  Additive    +, -            (27, 28) left-assoc
+//  Spec 4:  Multiplicative *, /, %      (29, 30) left-assoc
+//  Spec 3:  Cast        as              (31, 32) left-assoc
+//  Spec 2:  Prefix      !, -, ~ (unary) 33
 //  Spec 1:  Postfix     (), [], ., ::   handled in parse_postfix
 //  Spec 13: Propagate   ? (postfix)     bp = 3 (above assignment, below pipe)
 
@@ -59,14 +63,22 @@ fn infix_binding_power(kind: TokenKind) -> Option<(u8, u8)> {
         // Range
         TokenKind::DotDot | TokenKind::DotDotEqual => Some((17, 18)),
 
+        // Bitwise OR / XOR / AND (left-associative)
+        TokenKind::Pipe => Some((19, 20)),
+        TokenKind::Caret => Some((21, 22)),
+        TokenKind::Amp => Some((23, 24)),
+
+        // Shifts (left-associative)
+        TokenKind::LessLess | TokenKind::GreaterGreater => Some((25, 26)),
+
         // Addition / Subtraction (left-associative)
-        TokenKind::Plus | TokenKind::Minus => Some((19, 20)),
+        TokenKind::Plus | TokenKind::Minus => Some((27, 28)),
 
         // Multiplication / Division / Modulo (left-associative)
-        TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Some((21, 22)),
+        TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Some((29, 30)),
 
         // Type cast (left-associative)
-        TokenKind::As => Some((23, 24)),
+        TokenKind::As => Some((31, 32)),
 
         _ => None,
     }
@@ -75,7 +87,7 @@ fn infix_binding_power(kind: TokenKind) -> Option<(u8, u8)> {
 /// Prefix binding power for unary operators.
 fn prefix_binding_power(kind: TokenKind) -> Option<u8> {
     match kind {
-        TokenKind::Minus | TokenKind::Bang => Some(25),
+        TokenKind::Minus | TokenKind::Bang | TokenKind::Tilde => Some(33),
         _ => None,
     }
 }
@@ -231,6 +243,7 @@ impl Parser {
             let op = match kind {
                 TokenKind::Minus => UnaryOp::Neg,
                 TokenKind::Bang => UnaryOp::Not,
+                TokenKind::Tilde => UnaryOp::BitNot,
                 _ => unreachable!(),
             };
             let operand = self.parse_expr_bp(bp)?;
@@ -427,13 +440,16 @@ impl Parser {
             // Loop expression
             TokenKind::Loop => self.parse_loop_expr(),
 
+            // Decorated loop: `@max_iters(N) loop { ... }`
+            TokenKind::At => self.parse_decorated_loop_expr(),
+
             // Try/catch expression
             TokenKind::Try => self.parse_try_catch_expr(),
 
             // Prefix await: `await expr`
             TokenKind::Await => {
                 self.advance(); // consume 'await'
-                let operand = self.parse_expr_bp(25)?; // tight binding (same as prefix unary)
+                let operand = self.parse_expr_bp(33)?; // tight binding (same as prefix unary)
                 let span = start.merge(&operand.span);
                 Some(Expr::new(ExprKind::Await(Box::new(operand)), span))
             }
@@ -494,6 +510,35 @@ impl Parser {
                     expr = Expr::new(
                         ExprKind::Call {
                             callee: Box::new(expr),
+                            type_args: vec![],
+                            args,
+                        },
+                        span,
+                    );
+                }
+
+                // Schema-checked emit: emit<Schema>(channel, payload)
+                TokenKind::Less
+                    if matches!(&expr.kind, ExprKind::Identifier(name) if name == "emit")
+                        && self.is_generic_method_call() =>
+                {
+                    self.advance(); // consume '<'
+                    let type_args = self.parse_type_arg_list();
+                    if self.expect(TokenKind::Greater).is_none() {
+                        return expr;
+                    }
+                    if self.expect(TokenKind::LeftParen).is_none() {
+                        return expr;
+                    }
+                    let args = self.parse_arg_list();
+                    if self.expect(TokenKind::RightParen).is_none() {
+                        return expr;
+                    }
+                    let span = expr.span.merge(&self.previous_span());
+                    expr = Expr::new(
+                        ExprKind::Call {
+                            callee: Box::new(expr),
+                            type_args,
                             args,
                         },
                         span,
@@ -512,7 +557,9 @@ impl Parser {
                         continue;
                     }
 
-                    if self.peek() != TokenKind::Identifier {
+                    // `mock` is a keyword, but also the inline-mock
+                    // builder method: `Model.mock("text").execute(p)`
+                    if !matches!(self.peek(), TokenKind::Identifier | TokenKind::Mock) {
                         let span = self.current_span();
                         self.diagnostics
                             .error("expected identifier after '.'", span);
@@ -811,7 +858,41 @@ impl Parser {
         let body = self.parse_block()?;
 
         let span = start.merge(&self.previous_span());
-        Some(Expr::new(ExprKind::Loop { body }, span))
+        Some(Expr::new(
+            ExprKind::Loop {
+                body,
+                decorators: Vec::new(),
+            },
+            span,
+        ))
+    }
+
+    /// Parse a decorated loop: `@max_iters(N) loop { body }`. Decorators in
+    /// expression position may only precede `loop`.
+    fn parse_decorated_loop_expr(&mut self) -> Option<Expr> {
+        let start = self.current_span();
+        let decorators = self.parse_decorators();
+        if self.peek() != TokenKind::Loop {
+            let span = self.current_span();
+            self.diagnostics.error(
+                format!(
+                    "decorators in expression position must precede `loop`, found {:?}",
+                    self.peek()
+                ),
+                span,
+            );
+            return None;
+        }
+        let mut expr = self.parse_loop_expr()?;
+        if let ExprKind::Loop {
+            decorators: ref mut decs,
+            ..
+        } = expr.kind
+        {
+            *decs = decorators;
+        }
+        expr.span = start.merge(&expr.span);
+        Some(expr)
     }
 
     /// Parse a try/catch expression:
@@ -890,7 +971,7 @@ impl Parser {
         ))
     }
 
-    /// Parse a single listen handler: `"message_type" => |param: Type| { body }`
+    /// Parse a single listen handler: `"message_type" => |param: Type| [-> Schema] { body }`
     fn parse_listen_handler(&mut self) -> Option<ListenHandler> {
         let start = self.current_span();
 
@@ -910,6 +991,13 @@ impl Parser {
 
         self.expect(TokenKind::Pipe)?; // closing |
 
+        // Optional response schema: -> Schema
+        let return_type = if self.eat(TokenKind::Arrow) {
+            Some(self.parse_type_annotation()?)
+        } else {
+            None
+        };
+
         // Parse handler body block
         let body = self.parse_block()?;
 
@@ -917,6 +1005,7 @@ impl Parser {
         Some(ListenHandler {
             message_type,
             param,
+            return_type,
             body,
             span,
         })
@@ -1189,12 +1278,19 @@ impl Parser {
     // ========================================================================
 
     /// Heuristic: check if the upcoming `{` starts a map literal.
-    /// A map starts with `{ string_key : ...`.
+    /// A map starts with `{ literal_key : ...` where the key is a string,
+    /// integer or boolean literal.
     fn is_map_literal(&self) -> bool {
         if self.peek() == TokenKind::LeftBrace {
             let after_brace = self.pos + 1;
             if let Some(token) = self.tokens.get(after_brace) {
-                if token.kind == TokenKind::StringLiteral {
+                if matches!(
+                    token.kind,
+                    TokenKind::StringLiteral
+                        | TokenKind::IntLiteral
+                        | TokenKind::True
+                        | TokenKind::False
+                ) {
                     if let Some(colon) = self.tokens.get(after_brace + 1) {
                         return colon.kind == TokenKind::Colon;
                     }
@@ -1267,13 +1363,16 @@ impl Parser {
             // Identifier, path, enum variant, or struct destructure
             TokenKind::Identifier => self.parse_identifier_pattern(),
 
-            // Literal patterns
-            TokenKind::IntLiteral
-            | TokenKind::FloatLiteral
-            | TokenKind::StringLiteral
-            | TokenKind::True
-            | TokenKind::False
-            | TokenKind::Nil => self.parse_literal_pattern(),
+            // Numeric literal or range pattern: 42, 1..=5
+            TokenKind::IntLiteral | TokenKind::FloatLiteral => {
+                let pat = self.parse_literal_pattern()?;
+                self.parse_range_pattern_tail(pat)
+            }
+
+            // Other literal patterns
+            TokenKind::StringLiteral | TokenKind::True | TokenKind::False | TokenKind::Nil => {
+                self.parse_literal_pattern()
+            }
 
             // Negative literal pattern: -42
             TokenKind::Minus => {
@@ -1290,19 +1389,22 @@ impl Parser {
                     }
                 }
                 pat.span = start.merge(&pat.span);
-                Some(pat)
+                self.parse_range_pattern_tail(pat)
             }
 
-            // Tuple pattern: (a, b, c)
+            // Tuple pattern: (a, b, c); a single pattern without a trailing
+            // comma is just grouped: `n @ (1 | 2)`
             TokenKind::LeftParen => {
                 self.advance(); // consume '('
                 let mut patterns = Vec::new();
+                let mut saw_comma = false;
                 if self.peek() != TokenKind::RightParen {
                     loop {
                         patterns.push(self.parse_pattern()?);
                         if !self.eat(TokenKind::Comma) {
                             break;
                         }
+                        saw_comma = true;
                         if self.peek() == TokenKind::RightParen {
                             break;
                         }
@@ -1310,6 +1412,11 @@ impl Parser {
                 }
                 self.expect(TokenKind::RightParen)?;
                 let span = start.merge(&self.previous_span());
+                if patterns.len() == 1 && !saw_comma {
+                    let mut inner = patterns.pop().unwrap();
+                    inner.span = span;
+                    return Some(inner);
+                }
                 Some(Pattern {
                     kind: PatternKind::Tuple(patterns),
                     span,
@@ -1359,6 +1466,53 @@ impl Parser {
         }
     }
 
+    /// Extend a numeric literal pattern into a range pattern (`1..=5`, `0..10`)
+    /// when it is followed by `..` or `..=`. The end must be a numeric literal.
+    fn parse_range_pattern_tail(&mut self, start_pat: Pattern) -> Option<Pattern> {
+        let inclusive = match self.peek() {
+            TokenKind::DotDotEqual => true,
+            TokenKind::DotDot => false,
+            _ => return Some(start_pat),
+        };
+        let start_span = start_pat.span.clone();
+        let PatternKind::Literal(start_lit) = start_pat.kind else {
+            return Some(start_pat);
+        };
+        self.advance(); // consume '..' or '..='
+
+        let end_start = self.current_span();
+        let negative = self.eat(TokenKind::Minus);
+        let end_lit = match self.peek() {
+            TokenKind::IntLiteral | TokenKind::FloatLiteral => {
+                match self.parse_literal_pattern()?.kind {
+                    PatternKind::Literal(Literal::Int(v)) if negative => Literal::Int(-v),
+                    PatternKind::Literal(Literal::Float(v)) if negative => Literal::Float(-v),
+                    PatternKind::Literal(lit) => lit,
+                    _ => return None,
+                }
+            }
+            _ => {
+                self.diagnostics.error(
+                    format!(
+                        "expected numeric literal as range pattern end, found {:?}",
+                        self.peek()
+                    ),
+                    end_start,
+                );
+                return None;
+            }
+        };
+        let end_span = end_start.merge(&self.previous_span());
+        Some(Pattern {
+            span: start_span.merge(&end_span),
+            kind: PatternKind::Range {
+                start: Box::new(Expr::new(ExprKind::Literal(start_lit), start_span)),
+                end: Box::new(Expr::new(ExprKind::Literal(end_lit), end_span)),
+                inclusive,
+            },
+        })
+    }
+
     /// Parse an identifier pattern, potentially with path, enum constructor, or struct destructure.
     fn parse_identifier_pattern(&mut self) -> Option<Pattern> {
         let start = self.current_span();
@@ -1564,6 +1718,11 @@ fn token_to_binary_op(kind: TokenKind) -> Option<BinaryOp> {
         TokenKind::GreaterEqual => Some(BinaryOp::Gte),
         TokenKind::AmpAmp => Some(BinaryOp::And),
         TokenKind::PipePipe => Some(BinaryOp::Or),
+        TokenKind::Amp => Some(BinaryOp::BitAnd),
+        TokenKind::Pipe => Some(BinaryOp::BitOr),
+        TokenKind::Caret => Some(BinaryOp::BitXor),
+        TokenKind::LessLess => Some(BinaryOp::Shl),
+        TokenKind::GreaterGreater => Some(BinaryOp::Shr),
         _ => None,
     }
 }
@@ -1606,6 +1765,7 @@ fn can_start_expression(kind: TokenKind) -> bool {
             | TokenKind::PipePipe
             | TokenKind::Minus
             | TokenKind::Bang
+            | TokenKind::Tilde
             | TokenKind::Await
             | TokenKind::Return
             | TokenKind::Listen
@@ -1711,6 +1871,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_bitwise_precedence() {
+        // `|` binds looser than `^`, which binds looser than `&`, then shifts,
+        // then `+`: parses as `a | (b ^ (c & (d << (e + 1))))`.
+        let prog = parse("fn main() { let z = a | b ^ c & d << e + 1; }");
+        let f = get_fn(&prog);
+        let init = match &body(f).stmts[0] {
+            Stmt::Let(s) => s.initializer.as_ref().unwrap(),
+            _ => panic!("expected let statement"),
+        };
+        let mut expr = init;
+        for expected in [BinaryOp::BitOr, BinaryOp::BitXor, BinaryOp::BitAnd, BinaryOp::Shl] {
+            match &expr.kind {
+                ExprKind::Binary { op, right, .. } => {
+                    assert_eq!(*op, expected);
+                    expr = right;
+                }
+                other => panic!("expected {:?}, got {:?}", expected, other),
+            }
+        }
+        assert!(matches!(
+            &expr.kind,
+            ExprKind::Binary {
+                op: BinaryOp::Add,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_shift_right_and_nested_generics() {
+        let prog = parse("fn main() { let m: Map<String, Array<Int>> = x >> 2; let n = ~m; }");
+        let f = get_fn(&prog);
+        match &body(f).stmts[0] {
+            Stmt::Let(s) => {
+                assert!(s.type_ann.is_some());
+                assert!(matches!(
+                    &s.initializer.as_ref().unwrap().kind,
+                    ExprKind::Binary {
+                        op: BinaryOp::Shr,
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("expected let statement"),
+        }
+        match &body(f).stmts[1] {
+            Stmt::Let(s) => assert!(matches!(
+                &s.initializer.as_ref().unwrap().kind,
+                ExprKind::Unary {
+                    op: UnaryOp::BitNot,
+                    ..
+                }
+            )),
+            _ => panic!("expected let statement"),
+        }
+    }
+
     #[test]
     fn parse_if_expression() {
         let prog = parse("fn main() { if x > 0 { 1; } else { 2; } }");
@@ -1729,7 +1947,7 @@ mod tests {
         assert_eq!(b.stmts.len(), 1);
         match &b.stmts[0] {
             Stmt::Expr(s) => match &s.expr.kind {
-                ExprKind::Call { callee, args } => {
+                ExprKind::Call { callee, args, .. } => {
                     matches!(&callee.kind, ExprKind::Identifier(name) if name == "emit");
                     assert_eq!(args.len(), 2);
                 }
@@ -1785,6 +2003,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_schema_checked_emit() {
+        let prog = parse("fn main() { emit<Score>(\"result\", x); }");
+        let f = get_fn(&prog);
+        let b = body(f);
+        match &b.stmts[0] {
+            Stmt::Expr(s) => match &s.expr.kind {
+                ExprKind::Call {
+                    callee,
+                    type_args,
+                    args,
+                } => {
+                    assert!(matches!(&callee.kind, ExprKind::Identifier(name) if name == "emit"));
+                    assert_eq!(type_args.len(), 1);
+                    assert_eq!(args.len(), 2);
+                }
+                _ => panic!("expected call, got {:?}", s.expr.kind),
+            },
+            _ => panic!("expected expression statement"),
+        }
+    }
+
     #[test]
     fn parse_generic_method_call_multiple_type_args() {
         let prog = parse("fn main() { obj.method<A, B>(x); }");
@@ -2163,6 +2403,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_match_binding_range_and_group_patterns() {
+        let prog = parse(
+            r#"
+            fn main() {
+                match x {
+                    n @ 1..=10 => n,
+                    m @ (20 | 30) => m,
+                    -5..0 => 0,
+                    (a, b) => a,
+                    _ => 0,
+                }
+            }
+        "#,
+        );
+        let f = get_fn(&prog);
+        let b = body(f);
+        let arms = match &b.tail_expr.as_ref().unwrap().kind {
+            ExprKind::Match { arms, .. } => arms,
+            _ => panic!("expected match"),
+        };
+        match &arms[0].pattern.kind {
+            PatternKind::Binding { name, pattern } => {
+                assert_eq!(name, "n");
+                match &pattern.kind {
+                    PatternKind::Range {
+                        start,
+                        end,
+                        inclusive,
+                    } => {
+                        assert!(matches!(start.kind, ExprKind::Literal(Literal::Int(1))));
+                        assert!(matches!(end.kind, ExprKind::Literal(Literal::Int(10))));
+                        assert!(*inclusive);
+                    }
+                    _ => panic!("expected range pattern"),
+                }
+            }
+            _ => panic!("expected binding pattern"),
+        }
+        match &arms[1].pattern.kind {
+            PatternKind::Binding { pattern, .. } => {
+                assert!(matches!(&pattern.kind, PatternKind::Or(alts) if alts.len() == 2));
+            }
+            _ => panic!("expected binding pattern"),
+        }
+        match &arms[2].pattern.kind {
+            PatternKind::Range {
+                start, inclusive, ..
+            } => {
+                assert!(matches!(start.kind, ExprKind::Literal(Literal::Int(-5))));
+                assert!(!*inclusive);
+            }
+            _ => panic!("expected range pattern"),
+        }
+        assert!(matches!(&arms[3].pattern.kind, PatternKind::Tuple(pats) if pats.len() == 2));
+    }
+
     // =====================================================================
     // New tests: For/While/Loop
     // =====================================================================
@@ -2253,6 +2550,29 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_loop_with_max_iters() {
+        let prog = parse(
+            r#"
+            fn main() {
+                @max_iters(1000) loop {
+                    break;
+                }
+            }
+        "#,
+        );
+        let f = get_fn(&prog);
+        let b = body(f);
+        match &b.tail_expr.as_ref().unwrap().kind {
+            ExprKind::Loop { decorators, .. } => {
+                assert_eq!(decorators.len(), 1);
+                assert_eq!(decorators[0].name, "max_iters");
+                assert_eq!(decorators[0].int_arg(), Some(1000));
+            }
+            _ => panic!("expected loop"),
+        }
+    }
+
     // =====================================================================
     // New tests: Break / Continue / Throw
     // =====================================================================
@@ -2272,7 +2592,7 @@ mod tests {
         let b = body(f);
         // The loop is a tail expression
         match &b.tail_expr.as_ref().unwrap().kind {
-            ExprKind::Loop { body } => {
+            ExprKind::Loop { body, .. } => {
                 assert_eq!(body.stmts.len(), 1);
                 assert!(matches!(body.stmts[0], Stmt::Break(_)));
             }
@@ -2294,7 +2614,7 @@ mod tests {
         let f = get_fn(&prog);
         let b = body(f);
         match &b.tail_expr.as_ref().unwrap().kind {
-            ExprKind::Loop { body } => match &body.stmts[0] {
+            ExprKind::Loop { body, .. } => match &body.stmts[0] {
                 Stmt::Break(s) => assert!(s.value.is_some()),
                 _ => panic!("expected break"),
             },
@@ -2582,7 +2902,7 @@ mod tests {
         let b = body(f);
         match &b.stmts[0] {
             Stmt::Let(s) => match &s.initializer.as_ref().unwrap().kind {
-                ExprKind::Call { callee, args } => {
+                ExprKind::Call { callee, args, .. } => {
                     match &callee.kind {
                         ExprKind::Path(segments) => {
                             assert_eq!(segments, &vec!["std", "json", "parse"]);
@@ -2986,6 +3306,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_listen_handler_response_schema() {
+        let prog = parse(
+            r#"
+            fn main() {
+                let result = listen Agent.execute("prompt") {
+                    "question" => |q| -> Answer {
+                        { "approved": true }
+                    },
+                    "progress" => |p| { emit("p", p); },
+                };
+            }
+            "#,
+        );
+        let f = get_fn(&prog);
+        let b = body(f);
+        match &b.stmts[0] {
+            Stmt::Let(l) => match &l.initializer.as_ref().unwrap().kind {
+                ExprKind::Listen { handlers, .. } => {
+                    let ret = handlers[0].return_type.as_ref().expect("response schema");
+                    assert_eq!(ret.to_string(), "Answer");
+                    assert!(handlers[1].return_type.is_none());
+                }
+                _ => panic!("expected Listen expression"),
+            },
+            _ => panic!("expected let statement"),
+        }
+    }
+
     #[test]
     fn parse_listen_no_trailing_comma() {
         let prog = parse(
@@ -2,6 +2,8 @@ mod declarations;
 mod expressions;
 mod statements;
 
+use std::collections::HashMap;
+
 use concerto_common::{DiagnosticBag, Span};
 
 use crate::ast::*;
@@ -14,14 +16,39 @@ pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
     diagnostics: DiagnosticBag,
+    /// `///` doc comment text keyed by the byte offset of the token it precedes.
+    docs: HashMap<u32, String>,
 }
 
 impl Parser {
+    /// Doc comment tokens are pulled out of the stream here, so the grammar
+    /// never sees them; declarations look theirs up via `doc_comment()`.
     pub fn new(tokens: Vec<Token>) -> Self {
+        let mut docs = HashMap::new();
+        let mut pending: Vec<String> = Vec::new();
+        let mut kept = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if token.kind == TokenKind::DocComment {
+                let text = token.lexeme.trim_start_matches('/');
+                pending.push(
+                    text.strip_prefix(' ')
+                        .unwrap_or(text)
+                        .trim_end()
+                        .to_string(),
+                );
+                continue;
+            }
+            if !pending.is_empty() {
+                docs.insert(token.span.start.offset, pending.join("\n"));
+                pending.clear();
+            }
+            kept.push(token);
+        }
         Self {
-            tokens,
+            tokens: kept,
             pos: 0,
             diagnostics: DiagnosticBag::new(),
+            docs,
         }
     }
 
@@ -74,6 +101,11 @@ impl Parser {
             .unwrap_or(TokenKind::Eof)
     }
 
+    /// The doc comment immediately preceding the current token, if any.
+    fn doc_comment(&self) -> Option<String> {
+        self.docs.get(&self.current().span.start.offset).cloned()
+    }
+
     /// Get the current token.
     fn current(&self) -> &Token {
         self.tokens
@@ -109,6 +141,24 @@ impl Parser {
         }
     }
 
+    /// Consume the `>` closing a generic argument list. A `>>` token (lexed
+    /// as a right shift, as in `Array<Array<Int>>`) is split into two `>`
+    /// tokens first, so the second one closes the outer list.
+    fn expect_closing_angle(&mut self) -> Option<()> {
+        if self.peek() == TokenKind::GreaterGreater {
+            let token = &mut self.tokens[self.pos];
+            let mut second = token.span.clone();
+            second.start.column += 1;
+            second.start.offset += 1;
+            token.kind = TokenKind::Greater;
+            token.lexeme = ">".to_string();
+            token.span.end = second.start;
+            self.tokens
+                .insert(self.pos + 1, Token::new(TokenKind::Greater, ">", second));
+        }
+        self.expect(TokenKind::Greater).map(|_| ())
+    }
+
     /// Consume if the current token matches, otherwise do nothing.
     fn eat(&mut self, kind: TokenKind) -> bool {
         if self.peek() == kind {
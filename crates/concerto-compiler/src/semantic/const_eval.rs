@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+
+use concerto_common::{Diagnostic, DiagnosticBag, Span};
+
+use crate::ast::nodes::*;
+use crate::semantic::type_checker::binary_op_symbol;
+
+/// Compile-time evaluation of `const` declarations.
+///
+/// A const initializer may use literals, other consts (in any declaration
+/// order), parentheses, and unary/binary operators. Anything else — function
+/// calls, method calls, runtime values — is reported as an error. Arithmetic
+/// mirrors the runtime: Int wraps, Int/Float mix promotes to Float, and `+`
+/// concatenates two Strings.
+///
+/// Codegen uses the folded values to inline every const reference as a
+/// literal. Default values of function parameters must fold the same way,
+/// since the IR carries them as constants.
+pub struct ConstEvaluator<'a> {
+    decls: HashMap<&'a str, &'a ConstDecl>,
+    functions: Vec<&'a FunctionDecl>,
+    values: HashMap<String, Literal>,
+    /// Consts that failed to evaluate (already reported).
+    failed: HashSet<String>,
+    /// Consts currently being evaluated, for cycle detection.
+    in_progress: Vec<String>,
+    diagnostics: DiagnosticBag,
+}
+
+/// Why an expression could not be folded. `Reported` means the cause was
+/// already diagnosed on another const, so the dependent stays quiet.
+enum EvalError {
+    At(String, Span),
+    Reported,
+}
+
+type EvalResult = Result<Literal, EvalError>;
+
+impl<'a> ConstEvaluator<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        let decls = program
+            .declarations
+            .iter()
+            .filter_map(|d| match d {
+                Declaration::Const(c) => Some((c.name.as_str(), c)),
+                _ => None,
+            })
+            .collect();
+        let functions = program
+            .declarations
+            .iter()
+            .filter_map(|d| match d {
+                Declaration::Function(f) => Some(f),
+                _ => None,
+            })
+            .collect();
+        Self {
+            decls,
+            functions,
+            values: HashMap::new(),
+            failed: HashSet::new(),
+            in_progress: Vec::new(),
+            diagnostics: DiagnosticBag::new(),
+        }
+    }
+
+    /// Evaluate every const. Returns the folded values by name plus one
+    /// error per const initializer or parameter default that is not
+    /// compile-time evaluable.
+    pub fn evaluate(mut self) -> (HashMap<String, Literal>, DiagnosticBag) {
+        let mut names: Vec<&str> = self.decls.keys().copied().collect();
+        names.sort_by_key(|name| self.decls[name].span.start.offset);
+        for name in names {
+            // Errors are recorded inside `eval_const`.
+            let _ = self.eval_const(name);
+        }
+        for func in std::mem::take(&mut self.functions) {
+            for param in &func.params {
+                if let Some(default) = &param.default {
+                    self.check_default(&param.name, default);
+                }
+            }
+        }
+        (self.values, self.diagnostics)
+    }
+
+    fn check_default(&mut self, param: &str, default: &Expr) {
+        match self.eval_expr(default) {
+            Ok(_) | Err(EvalError::Reported) => {}
+            Err(EvalError::At(message, span)) => self.diagnostics.report(
+                Diagnostic::error(format!(
+                    "default for parameter `{}` cannot be evaluated at compile time: {}",
+                    param, message
+                ))
+                .with_span(span),
+            ),
+        }
+    }
+
+    fn eval_const(&mut self, name: &str) -> EvalResult {
+        if let Some(value) = self.values.get(name) {
+            return Ok(value.clone());
+        }
+        if self.failed.contains(name) {
+            return Err(EvalError::Reported);
+        }
+        let decl = self.decls[name];
+        if self.in_progress.iter().any(|n| n == name) {
+            return Err(EvalError::At(
+                format!("const `{}` is defined in terms of itself", name),
+                decl.span.clone(),
+            ));
+        }
+
+        self.in_progress.push(name.to_string());
+        let result = self.eval_expr(&decl.value);
+        self.in_progress.pop();
+
+        match result {
+            Ok(value) => {
+                self.values.insert(name.to_string(), value.clone());
+                Ok(value)
+            }
+            Err(EvalError::At(message, span)) => {
+                self.failed.insert(name.to_string());
+                self.diagnostics.report(
+                    Diagnostic::error(format!(
+                        "const `{}` cannot be evaluated at compile time: {}",
+                        name, message
+                    ))
+                    .with_span(span)
+                    .with_suggestion("use a `let` binding inside a function for runtime values"),
+                );
+                Err(EvalError::Reported)
+            }
+            Err(EvalError::Reported) => {
+                self.failed.insert(name.to_string());
+                Err(EvalError::Reported)
+            }
+        }
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> EvalResult {
+        match &expr.kind {
+            ExprKind::Literal(lit) => Ok(lit.clone()),
+            ExprKind::Grouping(inner) => self.eval_expr(inner),
+            ExprKind::Identifier(name) if self.decls.contains_key(name.as_str()) => {
+                self.eval_const(name)
+            }
+            ExprKind::Identifier(name) => Err(EvalError::At(
+                format!("`{}` is not a constant", name),
+                expr.span.clone(),
+            )),
+            ExprKind::Unary { op, operand } => {
+                let value = self.eval_expr(operand)?;
+                fold_unary(*op, value).map_err(|m| EvalError::At(m, expr.span.clone()))
+            }
+            ExprKind::Binary { left, op, right } => {
+                let left = self.eval_expr(left)?;
+                let right = self.eval_expr(right)?;
+                fold_binary(*op, left, right).map_err(|m| EvalError::At(m, expr.span.clone()))
+            }
+            ExprKind::Call { .. } | ExprKind::MethodCall { .. } => Err(EvalError::At(
+                "function calls are not allowed".to_string(),
+                expr.span.clone(),
+            )),
+            _ => Err(EvalError::At(
+                "only literals, other consts, and operators are allowed".to_string(),
+                expr.span.clone(),
+            )),
+        }
+    }
+}
+
+/// Fold `expr` against already-evaluated const values. Used for values
+/// that must be known at compile time outside a `const` declaration, such
+/// as parameter defaults.
+pub fn fold_expr(expr: &Expr, consts: &HashMap<String, Literal>) -> Result<Literal, String> {
+    match &expr.kind {
+        ExprKind::Literal(lit) => Ok(lit.clone()),
+        ExprKind::Grouping(inner) => fold_expr(inner, consts),
+        ExprKind::Identifier(name) => consts
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("`{}` is not a constant", name)),
+        ExprKind::Unary { op, operand } => fold_unary(*op, fold_expr(operand, consts)?),
+        ExprKind::Binary { left, op, right } => {
+            fold_binary(*op, fold_expr(left, consts)?, fold_expr(right, consts)?)
+        }
+        _ => Err("only literals, consts, and operators are allowed".to_string()),
+    }
+}
+
+fn type_name(lit: &Literal) -> &'static str {
+    match lit {
+        Literal::Int(_) => "Int",
+        Literal::Float(_) => "Float",
+        Literal::String(_) => "String",
+        Literal::Bool(_) => "Bool",
+        Literal::Nil => "Nil",
+    }
+}
+
+fn fold_unary(op: UnaryOp, value: Literal) -> Result<Literal, String> {
+    match (op, value) {
+        (UnaryOp::Neg, Literal::Int(v)) => Ok(Literal::Int(v.wrapping_neg())),
+        (UnaryOp::Neg, Literal::Float(v)) => Ok(Literal::Float(-v)),
+        (UnaryOp::Not, Literal::Bool(v)) => Ok(Literal::Bool(!v)),
+        (UnaryOp::BitNot, Literal::Int(v)) => Ok(Literal::Int(!v)),
+        (op, value) => {
+            let symbol = match op {
+                UnaryOp::Neg => "-",
+                UnaryOp::Not => "!",
+                UnaryOp::BitNot => "~",
+            };
+            Err(format!("cannot apply {} to {}", symbol, type_name(&value)))
+        }
+    }
+}
+
+fn fold_binary(op: BinaryOp, left: Literal, right: Literal) -> Result<Literal, String> {
+    use Literal::*;
+
+    let mismatch = |l: &Literal, r: &Literal| {
+        format!(
+            "cannot apply {} to {} and {}",
+            binary_op_symbol(op),
+            type_name(l),
+            type_name(r)
+        )
+    };
+
+    match op {
+        BinaryOp::Add => match (&left, &right) {
+            (String(a), String(b)) => Ok(String(format!("{}{}", a, b))),
+            _ => numeric(&left, &right, i64::wrapping_add, |a, b| a + b)
+                .ok_or_else(|| mismatch(&left, &right)),
+        },
+        BinaryOp::Sub => numeric(&left, &right, i64::wrapping_sub, |a, b| a - b)
+            .ok_or_else(|| mismatch(&left, &right)),
+        BinaryOp::Mul => numeric(&left, &right, i64::wrapping_mul, |a, b| a * b)
+            .ok_or_else(|| mismatch(&left, &right)),
+        BinaryOp::Div | BinaryOp::Mod => {
+            if matches!(right, Int(0)) && matches!(left, Int(_)) {
+                return Err("division by zero".to_string());
+            }
+            let result = if op == BinaryOp::Div {
+                numeric(&left, &right, i64::wrapping_div, |a, b| a / b)
+            } else {
+                numeric(&left, &right, i64::wrapping_rem, |a, b| a % b)
+            };
+            result.ok_or_else(|| mismatch(&left, &right))
+        }
+        BinaryOp::Eq | BinaryOp::Neq => {
+            let equal = match (&left, &right) {
+                (Int(a), Int(b)) => a == b,
+                (String(a), String(b)) => a == b,
+                (Bool(a), Bool(b)) => a == b,
+                (Nil, Nil) => true,
+                _ => match (as_f64(&left), as_f64(&right)) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => false,
+                },
+            };
+            Ok(Bool(equal == (op == BinaryOp::Eq)))
+        }
+        BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Lte | BinaryOp::Gte => {
+            let ordering = match (&left, &right) {
+                (String(a), String(b)) => a.partial_cmp(b),
+                _ => match (as_f64(&left), as_f64(&right)) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b),
+                    _ => None,
+                },
+            }
+            .ok_or_else(|| mismatch(&left, &right))?;
+            Ok(Bool(match op {
+                BinaryOp::Lt => ordering.is_lt(),
+                BinaryOp::Gt => ordering.is_gt(),
+                BinaryOp::Lte => ordering.is_le(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        BinaryOp::And | BinaryOp::Or => match (&left, &right) {
+            (Bool(a), Bool(b)) => Ok(Bool(if op == BinaryOp::And {
+                *a && *b
+            } else {
+                *a || *b
+            })),
+            _ => Err(mismatch(&left, &right)),
+        },
+        BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::Shl | BinaryOp::Shr => {
+            let (Int(a), Int(b)) = (&left, &right) else {
+                return Err(mismatch(&left, &right));
+            };
+            let (a, b) = (*a, *b);
+            match op {
+                BinaryOp::BitAnd => Ok(Int(a & b)),
+                BinaryOp::BitOr => Ok(Int(a | b)),
+                BinaryOp::BitXor => Ok(Int(a ^ b)),
+                _ if !(0..64).contains(&b) => Err(format!("shift amount {} is out of range", b)),
+                BinaryOp::Shl => Ok(Int(a.wrapping_shl(b as u32))),
+                _ => Ok(Int(a.wrapping_shr(b as u32))),
+            }
+        }
+    }
+}
+
+fn as_f64(lit: &Literal) -> Option<f64> {
+    match lit {
+        Literal::Int(v) => Some(*v as f64),
+        Literal::Float(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Apply an arithmetic operator with the runtime's Int/Float promotion.
+fn numeric(
+    left: &Literal,
+    right: &Literal,
+    int_op: fn(i64, i64) -> i64,
+    float_op: fn(f64, f64) -> f64,
+) -> Option<Literal> {
+    match (left, right) {
+        (Literal::Int(a), Literal::Int(b)) => Some(Literal::Int(int_op(*a, *b))),
+        _ => Some(Literal::Float(float_op(as_f64(left)?, as_f64(right)?))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser;
+
+    fn evaluate(source: &str) -> (HashMap<String, Literal>, Vec<String>) {
+        let (tokens, lex_diags) = Lexer::new(source, "test.conc").tokenize();
+        assert!(!lex_diags.has_errors(), "lexer errors: {:?}", lex_diags);
+        let (program, parse_diags) = parser::Parser::new(tokens).parse();
+        assert!(
+            !parse_diags.has_errors(),
+            "parser errors: {:?}",
+            parse_diags
+        );
+        let (values, diags) = ConstEvaluator::new(&program).evaluate();
+        let errors = diags
+            .into_diagnostics()
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        (values, errors)
+    }
+
+    fn int(values: &HashMap<String, Literal>, name: &str) -> i64 {
+        match values.get(name) {
+            Some(Literal::Int(v)) => *v,
+            other => panic!("expected Int for {}, got {:?}", name, other),
+        }
+    }
+
+    #[test]
+    fn const_referencing_other_consts() {
+        let (values, errors) = evaluate(
+            r#"
+            const LIMIT: Int = BASE * 2 + OFFSET;
+            const BASE: Int = 10;
+            const OFFSET: Int = -(1 << 2);
+            const LABEL: String = "limit" + SUFFIX;
+            const SUFFIX: String = "=16";
+            const RATIO: Float = LIMIT / 4.0;
+            const BIG: Bool = LIMIT > BASE && true;
+            "#,
+        );
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        assert_eq!(int(&values, "LIMIT"), 16);
+        assert_eq!(int(&values, "OFFSET"), -4);
+        assert!(matches!(&values["LABEL"], Literal::String(s) if s == "limit=16"));
+        assert!(matches!(values["RATIO"], Literal::Float(f) if f == 4.0));
+        assert!(matches!(values["BIG"], Literal::Bool(true)));
+    }
+
+    #[test]
+    fn const_with_function_call_is_rejected() {
+        let (values, errors) = evaluate(
+            r#"
+            fn compute() -> Int { return 3; }
+            const BAD: Int = compute() + 1;
+            const DERIVED: Int = BAD * 2;
+            "#,
+        );
+        assert!(values.is_empty());
+        // Reported once, on the const that contains the call
+        assert_eq!(errors.len(), 1, "errors: {:?}", errors);
+        assert!(errors[0].contains("const `BAD` cannot be evaluated at compile time"));
+        assert!(errors[0].contains("function calls"));
+    }
+
+    #[test]
+    fn const_cycle_and_type_errors_are_rejected() {
+        let (_, errors) = evaluate(
+            r#"
+            const A: Int = B + 1;
+            const B: Int = A + 1;
+            const C: Int = 1 / 0;
+            const D: Int = "x" - 1;
+            "#,
+        );
+        assert_eq!(errors.len(), 3, "errors: {:?}", errors);
+        assert!(errors[0].contains("defined in terms of itself"));
+        assert!(errors[1].contains("division by zero"));
+        assert!(errors[2].contains("cannot apply - to String and Int"));
+    }
+
+    #[test]
+    fn parameter_defaults_must_fold() {
+        let (_, errors) = evaluate(
+            r#"
+            const STEP: Int = 2;
+            fn ok(a: Int, b: Int = STEP * 3, c: String = "x") -> Int { return a; }
+            fn bad(a: Int, b: Int = a + 1, c: Int = compute()) -> Int { return a; }
+            fn compute() -> Int { return 3; }
+            "#,
+        );
+        assert_eq!(errors.len(), 2, "errors: {:?}", errors);
+        assert!(errors[0].contains("default for parameter `b`"));
+        assert!(errors[0].contains("`a` is not a constant"));
+        assert!(errors[1].contains("default for parameter `c`"));
+        assert!(errors[1].contains("function calls"));
+    }
+}
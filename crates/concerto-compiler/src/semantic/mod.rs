@@ -1,3 +1,4 @@
+pub mod const_eval;
 pub mod resolver;
 pub mod scope;
 pub mod type_checker;
@@ -17,6 +18,7 @@ use crate::ast::nodes::Program;
 ///  3. Control-flow validation (break/continue in loops, ? in Result fns, etc.)
 ///  4. Declaration-level validation (agent/tool/schema field rules)
 ///  5. Unused variable warnings
+///  6. Compile-time evaluation of `const` initializers and parameter defaults
 pub fn analyze(program: &Program) -> DiagnosticBag {
     analyze_with_connections(program, &[])
 }
@@ -42,5 +44,11 @@ pub fn analyze_with_connections(program: &Program, connection_names: &[String])
         all_diagnostics.report(diag);
     }
 
+    // Pass 5: const initializers and parameter defaults must fold at compile time.
+    let (_, const_diags) = const_eval::ConstEvaluator::new(program).evaluate();
+    for diag in const_diags.into_diagnostics() {
+        all_diagnostics.report(diag);
+    }
+
     all_diagnostics
 }
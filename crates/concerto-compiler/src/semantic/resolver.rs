@@ -1,6 +1,9 @@
+use std::collections::{HashMap, HashSet};
+
 use concerto_common::{Diagnostic, DiagnosticBag, Span};
 
 use crate::ast::nodes::*;
+use crate::ast::types::TypeKind;
 
 use super::scope::{ScopeKind, ScopeStack, Symbol, SymbolKind};
 use super::type_checker;
@@ -20,6 +23,16 @@ pub struct Resolver {
     in_async: bool,
     /// Whether we are inside a `@test` function.
     in_test: bool,
+    /// Declared tool/MCP method arities keyed by `Owner::method`, as
+    /// (required, total) parameter counts (`self` excluded).
+    method_arities: HashMap<String, (usize, usize)>,
+    /// Top-level function arities as (required, total) parameter counts;
+    /// parameters with defaults are optional.
+    function_arities: HashMap<String, (usize, usize)>,
+    /// Names registered by `register_builtins`, for shadowing warnings.
+    builtin_names: HashSet<String>,
+    /// Short names brought in by `use std::...`, mapped to their full path.
+    stdlib_aliases: HashMap<String, String>,
 }
 
 impl Default for Resolver {
@@ -36,6 +49,10 @@ impl Resolver {
             current_function_return: None,
             in_async: false,
             in_test: false,
+            method_arities: HashMap::new(),
+            function_arities: HashMap::new(),
+            builtin_names: HashSet::new(),
+            stdlib_aliases: HashMap::new(),
         };
         r.register_builtins();
         r
@@ -95,6 +112,14 @@ impl Resolver {
                     return_type: Box::new(Type::Nil),
                 },
             ),
+            (
+                "pprint",
+                SymbolKind::Function,
+                Type::Function {
+                    params: vec![Type::Any],
+                    return_type: Box::new(Type::Nil),
+                },
+            ),
             // Option / Result constructors
             (
                 "Some",
@@ -216,6 +241,7 @@ impl Resolver {
         ];
 
         for (name, kind, ty) in builtins {
+            self.builtin_names.insert(name.to_string());
             let _ = self.scopes.define(Symbol {
                 name: name.to_string(),
                 kind,
@@ -269,6 +295,7 @@ impl Resolver {
                         false,
                         t.span.clone(),
                     );
+                    self.declare_method_arities(&t.name, &t.methods);
                 }
                 Declaration::Schema(s) => {
                     self.define_symbol(
@@ -380,6 +407,7 @@ impl Resolver {
                         false,
                         m.span.clone(),
                     );
+                    self.declare_method_arities(&m.name, &m.methods);
                 }
                 Declaration::Agent(h) => {
                     self.define_symbol(
@@ -418,10 +446,55 @@ impl Resolver {
                             false,
                             u.span.clone(),
                         );
+                        if u.path.first().is_some_and(|p| p == "std") {
+                            self.stdlib_aliases.insert(local_name, u.path.join("::"));
+                        }
                     }
                 }
             }
         }
+        // Every declaration is known now, so aliases of user declarations
+        // can take on their target's kind and type.
+        for decl in &program.declarations {
+            if let Declaration::Use(u) = decl {
+                self.link_declaration_alias(u);
+            }
+        }
+    }
+
+    /// Resolve a `use` of a user declaration (`use greet as hello;`): the
+    /// target must be a top-level declaration, and the alias gets its kind
+    /// and type so calls through it are checked like calls to the target.
+    /// Declarations in other modules cannot be imported yet.
+    fn link_declaration_alias(&mut self, u: &UseDecl) {
+        let (Some(alias), [target]) = (&u.alias, u.path.as_slice()) else {
+            if u.path.len() > 1 && u.path[0] != "std" {
+                self.diagnostics.report(
+                    Diagnostic::error(format!(
+                        "cannot resolve import `{}`: declarations from other modules cannot be imported yet",
+                        u.path.join("::")
+                    ))
+                    .with_span(u.span.clone())
+                    .with_suggestion("declare it in this file and alias it with `use name as alias;`"),
+                );
+            }
+            return;
+        };
+        let Some(symbol) = self.scopes.lookup(target).cloned() else {
+            self.diagnostics.report(
+                Diagnostic::error(format!("cannot resolve import `{}`", target))
+                    .with_span(u.span.clone())
+                    .with_suggestion("`use` takes a `std::` path or a top-level declaration"),
+            );
+            return;
+        };
+        if let Some(target_sym) = self.scopes.lookup_mut(target) {
+            target_sym.used = true;
+        }
+        if let Some(alias_sym) = self.scopes.lookup_mut(alias) {
+            alias_sym.kind = symbol.kind;
+            alias_sym.ty = symbol.ty;
+        }
     }
 
     fn declare_function_symbol(&mut self, f: &FunctionDecl) {
@@ -444,6 +517,9 @@ impl Resolver {
             params,
             return_type: Box::new(return_type),
         };
+        let required = f.params.iter().filter(|p| p.default.is_none()).count();
+        self.function_arities
+            .insert(f.name.clone(), (required, f.params.len()));
         self.define_symbol(
             &f.name,
             SymbolKind::Function,
@@ -454,6 +530,18 @@ impl Resolver {
         );
     }
 
+    /// Record the parameter counts of tool/MCP methods so that
+    /// `Owner::method(...)` calls can be arity-checked in pass 2.
+    fn declare_method_arities(&mut self, owner: &str, methods: &[FunctionDecl]) {
+        for method in methods {
+            let required = method.params.iter().filter(|p| p.default.is_none()).count();
+            self.method_arities.insert(
+                format!("{}::{}", owner, method.name),
+                (required, method.params.len()),
+            );
+        }
+    }
+
     // ====================================================================
     // Pass 2: resolve bodies
     // ====================================================================
@@ -548,11 +636,12 @@ impl Resolver {
             return;
         };
 
-        let return_type = func
-            .return_type
-            .as_ref()
-            .map(Type::from_annotation)
-            .unwrap_or(Type::Nil);
+        let return_type = self.resolve_type(
+            func.return_type
+                .as_ref()
+                .map(Type::from_annotation)
+                .unwrap_or(Type::Nil),
+        );
 
         let is_test = func.decorators.iter().any(|d| d.name == "test");
 
@@ -611,11 +700,12 @@ impl Resolver {
             return;
         };
 
-        let return_type = func
-            .return_type
-            .as_ref()
-            .map(Type::from_annotation)
-            .unwrap_or(Type::Nil);
+        let return_type = self.resolve_type(
+            func.return_type
+                .as_ref()
+                .map(Type::from_annotation)
+                .unwrap_or(Type::Nil),
+        );
 
         let prev_return = self.current_function_return.take();
         let prev_async = self.in_async;
@@ -668,6 +758,28 @@ impl Resolver {
     }
 
     fn resolve_stage(&mut self, stage: &StageDecl) {
+        // `stage name = Other;` must name a declared pipeline
+        if let Some(ref pipeline) = stage.pipeline {
+            match self.scopes.lookup_mut(pipeline) {
+                Some(sym) if sym.kind == SymbolKind::Pipeline => sym.used = true,
+                Some(_) => self.diagnostics.error(
+                    format!(
+                        "stage `{}` must reference a pipeline, but `{}` is not one",
+                        stage.name, pipeline
+                    ),
+                    stage.span.clone(),
+                ),
+                None => self.diagnostics.error(
+                    format!(
+                        "undefined pipeline `{}` in stage `{}`",
+                        pipeline, stage.name
+                    ),
+                    stage.span.clone(),
+                ),
+            }
+            return;
+        }
+
         // Stages implicitly return Result<T, Error> where T is the declared return type.
         // This allows ? operator usage inside stages.
         let return_type = stage
@@ -675,7 +787,7 @@ impl Resolver {
             .as_ref()
             .map(|ann| {
                 Type::Result(
-                    Box::new(Type::from_annotation(ann)),
+                    Box::new(self.resolve_type(Type::from_annotation(ann))),
                     Box::new(Type::Named("Error".to_string())),
                 )
             })
@@ -763,7 +875,10 @@ impl Resolver {
     fn resolve_stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Let(s) => self.resolve_let(s),
-            Stmt::Expr(s) => self.resolve_expr(&s.expr),
+            Stmt::Expr(s) => {
+                self.resolve_expr(&s.expr);
+                self.check_discarded_result(&s.expr);
+            }
             Stmt::Return(s) => self.resolve_return(s),
             Stmt::Break(s) => self.resolve_break(s),
             Stmt::Continue(s) => self.resolve_continue(s),
@@ -774,16 +889,15 @@ impl Resolver {
 
     /// Resolve a type by looking up type aliases in scope.
     /// If the type is `Named("Foo")` and `Foo` is a type alias for `String`,
-    /// returns `String`.  All other types pass through unchanged.
+    /// returns `String`; aliases nested in `Array<Foo>`, `Result<Foo, E>`, etc.
+    /// are expanded too.
     fn resolve_type(&self, ty: Type) -> Type {
-        if let Type::Named(ref name) = ty {
-            if let Some(sym) = self.scopes.lookup(name) {
-                if sym.kind == SymbolKind::TypeAlias {
-                    return sym.ty.clone();
-                }
-            }
-        }
-        ty
+        ty.expand_aliases(&|name| {
+            self.scopes
+                .lookup(name)
+                .filter(|sym| sym.kind == SymbolKind::TypeAlias)
+                .map(|sym| sym.ty.clone())
+        })
     }
 
     fn resolve_let(&mut self, stmt: &LetStmt) {
@@ -925,11 +1039,35 @@ impl Resolver {
                 }
             }
 
-            ExprKind::Call { callee, args } => {
+            ExprKind::Call {
+                callee,
+                type_args,
+                args,
+            } => {
                 self.resolve_expr(callee);
                 for arg in args {
                     self.resolve_expr(arg);
                 }
+                // emit<Schema>(...) needs exactly one schema type argument
+                if !type_args.is_empty() {
+                    let schema = match type_args.as_slice() {
+                        [ty] => match &ty.kind {
+                            TypeKind::Named(name) => self
+                                .scopes
+                                .lookup(name)
+                                .filter(|sym| sym.kind == SymbolKind::Schema)
+                                .map(|_| name),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if schema.is_none() {
+                        self.diagnostics.error(
+                            "`emit<...>` takes a single schema type argument",
+                            type_args[0].span.clone(),
+                        );
+                    }
+                }
                 // Prevent calling @test functions from non-test code
                 if !self.in_test {
                     if let ExprKind::Identifier(name) = &callee.kind {
@@ -953,22 +1091,42 @@ impl Resolver {
                         let is_user_defined = !sym.defined_at.file.is_empty();
                         if is_user_defined {
                             if let Type::Function { ref params, .. } = sym.ty {
-                                if args.len() != params.len() {
-                                    self.diagnostics.error(
-                                        format!(
-                                            "function `{}` expects {} argument{}, got {}",
-                                            name,
-                                            params.len(),
-                                            if params.len() == 1 { "" } else { "s" },
-                                            args.len()
-                                        ),
-                                        expr.span.clone(),
-                                    );
-                                }
+                                let (required, total) = match sym.kind {
+                                    SymbolKind::Function => self
+                                        .function_arities
+                                        .get(name)
+                                        .copied()
+                                        .unwrap_or((params.len(), params.len())),
+                                    _ => (params.len(), params.len()),
+                                };
+                                self.check_call_arity(
+                                    "function",
+                                    name,
+                                    required,
+                                    total,
+                                    args.len(),
+                                    expr.span.clone(),
+                                );
                             }
                         }
                     }
                 }
+                // Check call arity for tool/MCP methods: `Tool::method(...)`
+                if let ExprKind::Path(segments) = &callee.kind {
+                    if segments.len() == 2 {
+                        let qualified = segments.join("::");
+                        if let Some(&(required, total)) = self.method_arities.get(&qualified) {
+                            self.check_call_arity(
+                                "method",
+                                &qualified,
+                                required,
+                                total,
+                                args.len(),
+                                expr.span.clone(),
+                            );
+                        }
+                    }
+                }
             }
 
             ExprKind::If {
@@ -1035,11 +1193,26 @@ impl Resolver {
                 self.resolve_expr(object);
             }
 
-            ExprKind::MethodCall { object, args, .. } => {
+            ExprKind::MethodCall {
+                object,
+                method,
+                args,
+                ..
+            } => {
                 self.resolve_expr(object);
                 for arg in args {
                     self.resolve_expr(arg);
                 }
+                self.check_method_call_arity(object, method, args.len(), expr.span.clone());
+                if matches!(method.as_str(), "mock" | "mock_error")
+                    && !self.in_test
+                    && self.is_model_or_agent_chain(object)
+                {
+                    self.diagnostics.error(
+                        format!("`{}()` can only be used inside `@test` functions", method),
+                        expr.span.clone(),
+                    );
+                }
             }
 
             ExprKind::Index { object, index } => {
@@ -1180,7 +1353,7 @@ impl Resolver {
                 self.emit_unused_warnings(idx);
             }
 
-            ExprKind::Loop { body } => {
+            ExprKind::Loop { body, .. } => {
                 self.scopes.push(ScopeKind::Loop);
                 self.resolve_block(body);
                 let idx = self.scopes.pop();
@@ -1331,6 +1504,21 @@ impl Resolver {
                         false,
                         handler.param.span.clone(),
                     );
+                    if let Some(ret) = &handler.return_type {
+                        let is_schema = match &ret.kind {
+                            TypeKind::Named(name) => self
+                                .scopes
+                                .lookup(name)
+                                .is_some_and(|sym| sym.kind == SymbolKind::Schema),
+                            _ => false,
+                        };
+                        if !is_schema {
+                            self.diagnostics.error(
+                                format!("listen handler response type `{}` must be a schema", ret),
+                                ret.span.clone(),
+                            );
+                        }
+                    }
                     self.resolve_block(&handler.body);
                     let idx = self.scopes.pop();
                     self.emit_unused_warnings(idx);
@@ -1552,9 +1740,30 @@ impl Resolver {
                     }
                 }
             }
-            // Field access / index: allowed (the object itself controls mutability,
-            // which would need full type info to check).
-            ExprKind::FieldAccess { .. } | ExprKind::Index { .. } => {}
+            // Field access / index: mutating through an immutable local is an
+            // error; other roots (parameters, call results) are not tracked.
+            ExprKind::FieldAccess { .. } | ExprKind::Index { .. } => {
+                let mut root = target;
+                while let ExprKind::FieldAccess { object, .. } | ExprKind::Index { object, .. } =
+                    &root.kind
+                {
+                    root = object;
+                }
+                if let ExprKind::Identifier(name) = &root.kind {
+                    if let Some(sym) = self.scopes.lookup(name) {
+                        if !sym.mutable && sym.kind == SymbolKind::Variable {
+                            self.diagnostics.report(
+                                Diagnostic::error(format!(
+                                    "cannot mutate immutable variable `{}`",
+                                    name
+                                ))
+                                .with_span(target.span.clone())
+                                .with_suggestion("make the binding mutable with 'let mut'"),
+                            );
+                        }
+                    }
+                }
+            }
             _ => {
                 self.diagnostics
                     .error("invalid assignment target", target.span.clone());
@@ -1562,6 +1771,97 @@ impl Resolver {
         }
     }
 
+    // ====================================================================
+    // Method arity checking
+    // ====================================================================
+
+    /// Check `Owner.method(...)` arity for tool/MCP methods and for the
+    /// built-in execution methods on models and agents.
+    fn check_method_call_arity(&mut self, object: &Expr, method: &str, argc: usize, span: Span) {
+        let ExprKind::Identifier(owner) = &object.kind else {
+            return;
+        };
+        let Some(kind) = self.scopes.lookup(owner).map(|s| s.kind.clone()) else {
+            return;
+        };
+        let qualified = format!("{}::{}", owner, method);
+        let arity = match kind {
+            SymbolKind::Tool | SymbolKind::Mcp => self.method_arities.get(&qualified).copied(),
+            SymbolKind::Model | SymbolKind::Agent => builtin_method_arity(method),
+            _ => None,
+        };
+        if let Some((required, total)) = arity {
+            self.check_call_arity("method", &qualified, required, total, argc, span);
+        }
+    }
+
+    /// Warn when an expression statement drops a `Result`: a model/agent
+    /// `execute` (directly or at the end of a builder chain) or a call to a
+    /// function declared to return `Result`. `let _ = ...` opts out. A `try`
+    /// body does not exempt the call: a returned `Err` is not thrown, so the
+    /// `catch` never sees it.
+    fn check_discarded_result(&mut self, expr: &Expr) {
+        let discarded = match &expr.kind {
+            ExprKind::MethodCall { object, method, .. } => {
+                matches!(method.as_str(), "execute" | "execute_with_schema")
+                    && self.is_model_or_agent_chain(object)
+            }
+            ExprKind::Call { .. } => matches!(self.infer_expr_type(expr), Type::Result(..)),
+            _ => false,
+        };
+        if discarded {
+            self.diagnostics.report(
+                Diagnostic::warning("unused `Result` that must be handled")
+                    .with_span(expr.span.clone())
+                    .with_suggestion(
+                        "handle the error with `?` or `match`, or discard it explicitly with `let _ = ...`",
+                    ),
+            );
+        }
+    }
+
+    /// Whether `expr` is a model/agent name, or a builder chain rooted at one
+    /// (`Model.with_memory(m)`).
+    fn is_model_or_agent_chain(&self, expr: &Expr) -> bool {
+        let mut root = expr;
+        while let ExprKind::MethodCall { object, .. } = &root.kind {
+            root = object;
+        }
+        let ExprKind::Identifier(name) = &root.kind else {
+            return false;
+        };
+        self.scopes
+            .lookup(name)
+            .is_some_and(|sym| matches!(sym.kind, SymbolKind::Model | SymbolKind::Agent))
+    }
+
+    fn check_call_arity(
+        &mut self,
+        kind: &str,
+        name: &str,
+        required: usize,
+        total: usize,
+        argc: usize,
+        span: Span,
+    ) {
+        if argc >= required && argc <= total {
+            return;
+        }
+        let expected = if required == total {
+            format!(
+                "{} argument{}",
+                total,
+                if total == 1 { "" } else { "s" }
+            )
+        } else {
+            format!("{} to {} arguments", required, total)
+        };
+        self.diagnostics.error(
+            format!("{} `{}` expects {}, got {}", kind, name, expected, argc),
+            span,
+        );
+    }
+
     // ====================================================================
     // Helpers
     // ====================================================================
@@ -1584,6 +1884,31 @@ impl Resolver {
             used: false,
             is_public,
         };
+        if span != Span::dummy() {
+            if self.builtin_names.contains(name) {
+                self.diagnostics.report(
+                    Diagnostic::warning(format!("`{}` shadows a built-in", name))
+                        .with_span(span.clone())
+                        .with_suggestion("rename it; the built-in is unreachable in this scope"),
+                );
+                // A top-level declaration replaces the built-in instead of
+                // colliding with it; a second user definition still collides.
+                match self.scopes.define(symbol.clone()) {
+                    Ok(()) => return,
+                    Err(prev_span) if prev_span == Span::dummy() => {
+                        self.scopes.redefine(symbol);
+                        return;
+                    }
+                    Err(_) => {}
+                }
+            } else if let Some(path) = self.stdlib_aliases.get(name) {
+                self.diagnostics.report(
+                    Diagnostic::warning(format!("`{}` shadows the import of `{}`", name, path))
+                        .with_span(span.clone())
+                        .with_suggestion("rename it or import with `use ... as other_name`"),
+                );
+            }
+        }
         if let Err(prev_span) = self.scopes.define(symbol) {
             self.diagnostics.report(
                 concerto_common::Diagnostic::error(format!("duplicate definition of `{}`", name))
@@ -1616,6 +1941,26 @@ impl Resolver {
     }
 }
 
+/// Arity of the built-in execution methods shared by models and agents,
+/// as (required, total) argument counts.
+fn builtin_method_arity(method: &str) -> Option<(usize, usize)> {
+    match method {
+        "execute"
+        | "execute_with_schema"
+        | "with_tools"
+        | "with_context"
+        | "with_retry"
+        | "with_timeout"
+        | "with_seed"
+        | "mock"
+        | "mock_error" => Some((1, 1)),
+        // `with_memory(memory, auto: false)` takes an optional auto-append flag
+        "with_memory" => Some((1, 2)),
+        "without_tools" => Some((0, 0)),
+        _ => None,
+    }
+}
+
 // ========================================================================
 // Tests
 // ========================================================================
@@ -1660,6 +2005,76 @@ mod tests {
             .collect()
     }
 
+    // -- Discarded results --
+
+    #[test]
+    fn discarded_model_execute_warns() {
+        let src = r#"
+            model Bot { provider: openai, base: "gpt-4o" }
+            fn main() {
+                Bot.execute("hi");
+                Bot.with_context("ctx").execute("hi");
+            }
+        "#;
+        let warns = warnings(src);
+        let count = warns
+            .iter()
+            .filter(|w| w.contains("unused `Result`"))
+            .count();
+        assert_eq!(count, 2, "warnings: {:?}", warns);
+    }
+
+    #[test]
+    fn handled_or_explicitly_discarded_result_is_quiet() {
+        let src = r#"
+            model Bot { provider: openai, base: "gpt-4o" }
+            fn load() -> Result<Int, String> { Ok(1) }
+            fn run() -> Result<Int, String> {
+                let _ = Bot.execute("hi");
+                Bot.execute("hi")?;
+                let n = load()?;
+                try {
+                    load()?;
+                } catch {
+                    emit("failed", "load");
+                }
+                Ok(n)
+            }
+            fn main() {
+                let _ = run();
+            }
+        "#;
+        let warns = warnings(src);
+        assert!(
+            !warns.iter().any(|w| w.contains("unused `Result`")),
+            "warnings: {:?}",
+            warns
+        );
+    }
+
+    #[test]
+    fn discarded_function_result_warns() {
+        let warns = warnings("fn load() -> Result<Int, String> { Ok(1) }\nfn main() { load(); }");
+        assert!(
+            warns.iter().any(|w| w.contains("unused `Result`")),
+            "warnings: {:?}",
+            warns
+        );
+    }
+
+    #[test]
+    fn discarded_function_result_in_try_still_warns() {
+        let warns = warnings(
+            "fn load() -> Result<Int, String> { Err(\"x\") }\n\
+             fn main() { try { load(); } catch { emit(\"caught\", true); } }",
+        );
+        assert!(
+            warns.iter().any(|w| w.contains("unused `Result`")),
+            "warnings: {:?}",
+            warns
+        );
+    }
+
     // -- Name resolution --
 
     #[test]
@@ -1797,6 +2212,53 @@ mod tests {
         assert!(errs.is_empty(), "unexpected errors: {:?}", errs);
     }
 
+    #[test]
+    fn assign_field_of_immutable_struct() {
+        let errs = errors(
+            r#"
+            struct Point { x: Int, y: Int }
+            fn main() {
+                let p = Point { x: 1, y: 2 };
+                p.x = 10;
+            }
+            "#,
+        );
+        assert!(errs
+            .iter()
+            .any(|e| e.contains("cannot mutate immutable variable `p`")));
+    }
+
+    #[test]
+    fn assign_field_and_index_of_mutable_ok() {
+        let errs = errors(
+            r#"
+            struct Point { x: Int, y: Int }
+            fn main() {
+                let mut p = Point { x: 1, y: 2 };
+                p.x = 10;
+                let mut items = [1, 2, 3];
+                items[0] = 5;
+            }
+            "#,
+        );
+        assert!(errs.is_empty(), "unexpected errors: {:?}", errs);
+    }
+
+    #[test]
+    fn assign_index_of_immutable_array() {
+        let errs = errors(
+            r#"
+            fn main() {
+                let items = [1, 2, 3];
+                items[0] = 5;
+            }
+            "#,
+        );
+        assert!(errs
+            .iter()
+            .any(|e| e.contains("cannot mutate immutable variable `items`")));
+    }
+
     // -- Async / Await --
 
     #[test]
@@ -2138,6 +2600,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inline_builder_mock_outside_test_error() {
+        let errs = errors(
+            r#"
+            model MyAgent {
+                provider: "openai",
+                base: "gpt-4o",
+            }
+
+            fn main() {
+                let _ = MyAgent.mock("hello").execute("hi");
+            }
+
+            @test
+            fn mocked() {
+                let _ = MyAgent.mock_error("down").execute("hi");
+            }
+        "#,
+        );
+        assert_eq!(
+            errs,
+            vec!["`mock()` can only be used inside `@test` functions".to_string()]
+        );
+    }
+
     #[test]
     fn for_loop_non_iterable_error() {
         let errs = errors("fn main() { for n in 42 { } }");
@@ -2198,6 +2685,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn return_type_resolves_aliases() {
+        let errs = errors(
+            r#"
+            type Doc = String;
+            fn load() -> Doc { return "text"; }
+            pipeline P {
+                stage read(path: String) -> Doc { return path; }
+            }
+            fn main() { }
+            "#,
+        );
+        assert!(errs.is_empty(), "unexpected errors: {:?}", errs);
+    }
+
     #[test]
     fn assign_type_mismatch_error() {
         let errs = errors(r#"fn main() { let mut x: Int = 1; x = "oops"; }"#);
@@ -2249,6 +2751,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn call_arity_allows_defaults() {
+        let errs = errors(
+            r#"
+            fn scale(x: Int, by: Int = 2) -> Int { x * by }
+            fn main() {
+                let a = scale(1);
+                let b = scale(1, 3);
+                let c = scale();
+            }
+            "#,
+        );
+        let arity_errs: Vec<_> = errs.iter().filter(|e| e.contains("expects")).collect();
+        assert_eq!(arity_errs.len(), 1, "got: {:?}", errs);
+        assert!(arity_errs[0].contains("function `scale` expects 1 to 2 arguments, got 0"));
+    }
+
+    #[test]
+    fn tool_method_arity_too_few_args() {
+        let errs = errors(
+            r#"
+            tool Calc {
+                pub fn add(self, a: Int, b: Int) -> Int { a + b }
+            }
+            fn main() { Calc::add(1); }
+            "#,
+        );
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("method `Calc::add` expects 2 arguments, got 1")),
+            "expected tool method arity error, got: {:?}",
+            errs
+        );
+    }
+
+    #[test]
+    fn tool_method_arity_too_many_args() {
+        let errs = errors(
+            r#"
+            tool Calc {
+                pub fn add(self, a: Int, b: Int) -> Int { a + b }
+            }
+            fn main() { Calc::add(1, 2, 3); }
+            "#,
+        );
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("method `Calc::add` expects 2 arguments, got 3")),
+            "expected tool method arity error, got: {:?}",
+            errs
+        );
+    }
+
+    #[test]
+    fn mcp_method_arity_allows_defaults() {
+        let errs = errors(
+            r#"
+            mcp Search {
+                transport: "stdio",
+                command: "search-server",
+                fn search(query: String, limit: Int = 10) -> Result<String, ToolError>;
+            }
+            fn main() {
+                let a = Search::search("q");
+                let b = Search::search("q", 5);
+                let c = Search::search();
+            }
+            "#,
+        );
+        let arity_errs: Vec<_> = errs.iter().filter(|e| e.contains("expects")).collect();
+        assert_eq!(arity_errs.len(), 1, "got: {:?}", errs);
+        assert!(arity_errs[0].contains("expects 1 to 2 arguments, got 0"));
+    }
+
+    #[test]
+    fn agent_execute_arity_error() {
+        let errs = errors(
+            r#"
+            agent Coder {
+                connector: "coder",
+            }
+            fn main() {
+                let r = Coder.execute("a", "b");
+                let s = Coder.without_tools(1);
+            }
+            "#,
+        );
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("method `Coder::execute` expects 1 argument, got 2")),
+            "expected agent execute arity error, got: {:?}",
+            errs
+        );
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("method `Coder::without_tools` expects 0 arguments, got 1")),
+            "expected without_tools arity error, got: {:?}",
+            errs
+        );
+    }
+
     #[test]
     fn use_import_alias_resolves() {
         // `use std::json::parse` should make `parse` a valid identifier (no error)
@@ -2270,4 +2873,160 @@ mod tests {
             errs
         );
     }
+
+    #[test]
+    fn use_aliases_a_local_declaration() {
+        let source = r#"
+            use greet as hello;
+            fn main() { let s: String = hello("bob"); emit("s", s); }
+            fn greet(name: String) -> String { return "hi " + name; }
+        "#;
+        let errs = errors(source);
+        assert!(errs.is_empty(), "unexpected errors: {:?}", errs);
+
+        let errs =
+            errors("use greet as hello; fn main() { hello(\"a\", \"b\"); } fn greet(n: String) {}");
+        assert!(
+            errs.iter().any(|e| e.contains("argument")),
+            "expected arity error through alias, got: {:?}",
+            errs
+        );
+    }
+
+    #[test]
+    fn use_of_missing_declaration_is_an_error() {
+        let errs = errors("use nothere as hello; fn main() { hello(); }");
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("cannot resolve import `nothere`")),
+            "got: {:?}",
+            errs
+        );
+
+        let errs = errors("use other::Helper as H; fn main() { H(); }");
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("cannot resolve import `other::Helper`")),
+            "got: {:?}",
+            errs
+        );
+    }
+
+    // -- Built-in shadowing --
+
+    #[test]
+    fn function_named_print_warns_shadowing() {
+        let source = r#"
+            fn print(msg: String) { emit("log", msg); }
+            fn main() { print("hi"); }
+        "#;
+        let warns = warnings(source);
+        assert!(
+            warns.iter().any(|w| w == "`print` shadows a built-in"),
+            "expected shadowing warning, got: {:?}",
+            warns
+        );
+        // The declaration replaces the built-in rather than colliding with it.
+        let errs = errors(source);
+        assert!(errs.is_empty(), "unexpected errors: {:?}", errs);
+    }
+
+    #[test]
+    fn local_binding_shadowing_builtin_warns() {
+        let warns = warnings(r#"fn main() { let len = 3; emit("n", len); }"#);
+        assert!(warns.iter().any(|w| w == "`len` shadows a built-in"));
+    }
+
+    #[test]
+    fn binding_shadowing_stdlib_import_warns() {
+        let warns = warnings(
+            r#"
+            use std::json::parse;
+            fn main() {
+                let parse = "raw";
+                emit("p", parse);
+            }
+            "#,
+        );
+        assert!(
+            warns
+                .iter()
+                .any(|w| w == "`parse` shadows the import of `std::json::parse`"),
+            "expected import shadowing warning, got: {:?}",
+            warns
+        );
+    }
+
+    #[test]
+    fn duplicate_user_function_over_builtin_still_errors() {
+        let errs = errors(
+            r#"
+            fn print(msg: String) { emit("a", msg); }
+            fn print(msg: String) { emit("b", msg); }
+            fn main() { }
+            "#,
+        );
+        assert!(errs
+            .iter()
+            .any(|e| e.contains("duplicate definition of `print`")));
+    }
+
+    #[test]
+    fn listen_handler_response_type_must_be_schema() {
+        let src = r#"
+            agent Worker { connector: "worker" }
+            schema Answer { approved: Bool }
+            fn main() {
+                let _ = listen Worker.execute("go") {
+                    "question" => |q| -> Answer { { "approved": true } },
+                    "review" => |r| -> String { "ok" },
+                };
+            }
+        "#;
+        let errs = errors(src);
+        assert_eq!(
+            errs,
+            vec!["listen handler response type `String` must be a schema".to_string()]
+        );
+    }
+
+    #[test]
+    fn pipeline_stage_must_reference_a_pipeline() {
+        let src = r#"
+            pipeline Inner {
+                stage only(x: Int) -> Int { x }
+            }
+            pipeline Outer {
+                stage a = Inner;
+                stage b = Missing;
+                stage c = helper;
+            }
+            fn helper(x: Int) -> Int { x }
+            fn main() {}
+        "#;
+        let errs = errors(src);
+        assert_eq!(
+            errs,
+            vec![
+                "undefined pipeline `Missing` in stage `b`".to_string(),
+                "stage `c` must reference a pipeline, but `helper` is not one".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn emit_type_argument_must_be_schema() {
+        let src = r#"
+            schema Score { label: String }
+            fn main() {
+                emit<Score>("result", { "label": "ok" });
+                emit<String>("result", "plain");
+            }
+        "#;
+        let errs = errors(src);
+        assert_eq!(
+            errs,
+            vec!["`emit<...>` takes a single schema type argument".to_string()]
+        );
+    }
 }
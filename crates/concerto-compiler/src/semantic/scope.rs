@@ -122,6 +122,14 @@ impl ScopeStack {
         Ok(())
     }
 
+    /// Define a symbol in the current scope, replacing any existing entry.
+    /// Used when a user declaration takes over a built-in name.
+    pub fn redefine(&mut self, symbol: Symbol) {
+        self.scopes[self.current]
+            .symbols
+            .insert(symbol.name.clone(), symbol);
+    }
+
     /// Look up a symbol by name, walking up the scope chain.
     pub fn lookup(&self, name: &str) -> Option<&Symbol> {
         let mut idx = self.current;
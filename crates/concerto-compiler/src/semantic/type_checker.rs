@@ -81,6 +81,17 @@ pub fn check_binary_op(left: &Type, op: BinaryOp, right: &Type) -> Result<Type,
                 right.display_name()
             )),
         },
+        BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::Shl | BinaryOp::Shr => {
+            match (left, right) {
+                (Type::Int, Type::Int) => Ok(Type::Int),
+                _ => Err(format!(
+                    "operator '{}' requires Int operands, got {} and {}",
+                    binary_op_symbol(op),
+                    left.display_name(),
+                    right.display_name()
+                )),
+            }
+        }
     }
 }
 
@@ -105,6 +116,13 @@ pub fn check_unary_op(op: UnaryOp, operand: &Type) -> Result<Type, String> {
                 operand.display_name()
             )),
         },
+        UnaryOp::BitNot => match operand {
+            Type::Int => Ok(Type::Int),
+            _ => Err(format!(
+                "operator '~' requires Int operand, got {}",
+                operand.display_name()
+            )),
+        },
     }
 }
 
@@ -125,14 +143,22 @@ fn types_comparable(left: &Type, right: &Type) -> bool {
 
 /// Check if two types support ordering comparison (`<`, `>`, `<=`, `>=`).
 fn types_ordered(left: &Type, right: &Type) -> bool {
-    matches!(
-        (left, right),
-        (Type::Int, Type::Int)
-            | (Type::Float, Type::Float)
-            | (Type::Int, Type::Float)
-            | (Type::Float, Type::Int)
-            | (Type::String, Type::String)
-    )
+    match (left, right) {
+        // Arrays compare element-wise; unknown element types are checked at runtime.
+        (Type::Array(l), Type::Array(r)) => {
+            matches!(**l, Type::Unknown | Type::Any | Type::Error)
+                || matches!(**r, Type::Unknown | Type::Any | Type::Error)
+                || types_ordered(l, r)
+        }
+        _ => matches!(
+            (left, right),
+            (Type::Int, Type::Int)
+                | (Type::Float, Type::Float)
+                | (Type::Int, Type::Float)
+                | (Type::Float, Type::Int)
+                | (Type::String, Type::String)
+        ),
+    }
 }
 
 /// Check if a value of type `from` can be assigned to a target of type `to`.
@@ -181,7 +207,7 @@ pub fn types_assignable(from: &Type, to: &Type) -> bool {
     false
 }
 
-fn binary_op_symbol(op: BinaryOp) -> &'static str {
+pub(crate) fn binary_op_symbol(op: BinaryOp) -> &'static str {
     match op {
         BinaryOp::Add => "+",
         BinaryOp::Sub => "-",
@@ -196,6 +222,11 @@ fn binary_op_symbol(op: BinaryOp) -> &'static str {
         BinaryOp::Gte => ">=",
         BinaryOp::And => "&&",
         BinaryOp::Or => "||",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::Shl => "<<",
+        BinaryOp::Shr => ">>",
     }
 }
 
@@ -215,6 +246,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bitwise_requires_int() {
+        assert_eq!(
+            check_binary_op(&Type::Int, BinaryOp::Shl, &Type::Int),
+            Ok(Type::Int)
+        );
+        assert!(check_binary_op(&Type::Int, BinaryOp::BitAnd, &Type::Float).is_err());
+        assert!(check_binary_op(&Type::Bool, BinaryOp::BitOr, &Type::Bool).is_err());
+        assert_eq!(check_unary_op(UnaryOp::BitNot, &Type::Int), Ok(Type::Int));
+        assert!(check_unary_op(UnaryOp::BitNot, &Type::Bool).is_err());
+    }
+
     #[test]
     fn mixed_numeric() {
         assert_eq!(
@@ -248,6 +291,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comparison_on_strings_and_arrays() {
+        assert_eq!(
+            check_binary_op(&Type::String, BinaryOp::Lt, &Type::String),
+            Ok(Type::Bool)
+        );
+        let ints = Type::Array(Box::new(Type::Int));
+        assert_eq!(check_binary_op(&ints, BinaryOp::Gte, &ints), Ok(Type::Bool));
+        let strs = Type::Array(Box::new(Type::String));
+        assert!(check_binary_op(&ints, BinaryOp::Lt, &strs).is_err());
+        assert!(check_binary_op(&Type::String, BinaryOp::Lt, &Type::Int).is_err());
+    }
+
     #[test]
     fn logical_requires_bool() {
         assert_eq!(
@@ -87,9 +87,18 @@ impl Type {
                 params: params.iter().map(Type::from_annotation).collect(),
                 return_type: Box::new(Type::from_annotation(return_type)),
             },
-            TypeKind::Union(_) => {
-                // Union types (e.g., "legal" | "technical") are treated as String at runtime
-                Type::String
+            TypeKind::Union(variants) => {
+                // String literal unions (e.g., "legal" | "technical") are
+                // String at runtime; schema unions of other types
+                // (`String | Array<String>`) can hold any alternative.
+                if variants
+                    .iter()
+                    .all(|v| matches!(v.kind, TypeKind::StringLiteral(_)))
+                {
+                    Type::String
+                } else {
+                    Type::Any
+                }
             }
             TypeKind::StringLiteral(_) => {
                 // String literal types are String at runtime
@@ -157,6 +166,50 @@ impl Type {
         }
     }
 
+    /// Replace every `Named` type that `lookup` recognizes as an alias with its
+    /// target, recursing into container types. Chains of aliases are followed;
+    /// a cyclic alias is left as the name it cycles back to.
+    pub fn expand_aliases(&self, lookup: &dyn Fn(&str) -> Option<Type>) -> Type {
+        self.expand_aliases_guarded(lookup, &mut Vec::new())
+    }
+
+    fn expand_aliases_guarded(
+        &self,
+        lookup: &dyn Fn(&str) -> Option<Type>,
+        seen: &mut Vec<std::string::String>,
+    ) -> Type {
+        let mut expand = |t: &Type| Box::new(t.expand_aliases_guarded(lookup, seen));
+        match self {
+            Type::Named(name) => {
+                if seen.contains(name) {
+                    return self.clone();
+                }
+                match lookup(name) {
+                    Some(target) => {
+                        seen.push(name.clone());
+                        let resolved = target.expand_aliases_guarded(lookup, seen);
+                        seen.pop();
+                        resolved
+                    }
+                    None => self.clone(),
+                }
+            }
+            Type::Array(inner) => Type::Array(expand(inner)),
+            Type::Option(inner) => Type::Option(expand(inner)),
+            Type::Map(k, v) => Type::Map(expand(k), expand(v)),
+            Type::Result(ok, err) => Type::Result(expand(ok), expand(err)),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(|e| *expand(e)).collect()),
+            Type::Function {
+                params,
+                return_type,
+            } => Type::Function {
+                params: params.iter().map(|p| *expand(p)).collect(),
+                return_type: expand(return_type),
+            },
+            other => other.clone(),
+        }
+    }
+
     /// Check if `output` type is assignable to `input` type for pipeline stage adjacency.
     /// Automatically unwraps `Result<T, E>` to `T` (runtime does this between stages).
     pub fn is_pipeline_assignable(output: &Type, input: &Type) -> bool {
@@ -1,10 +1,15 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use concerto_common::{Diagnostic, DiagnosticBag};
+use concerto_common::{Diagnostic, DiagnosticBag, Span};
 
 use crate::ast::nodes::*;
+use crate::ast::types::{TypeAnnotation, TypeKind};
+use crate::ast::visitor::{walk_expr, Visitor};
 use crate::semantic::types::Type;
 
+/// How deep stage types are followed through nested pipelines.
+const MAX_PIPELINE_NESTING: usize = 32;
+
 /// Declaration-level validation pass.
 ///
 /// Checks structural constraints that don't depend on name resolution
@@ -13,9 +18,16 @@ use crate::semantic::types::Type;
 ///  - Agents must have a `provider` field.
 ///  - Tools must have a `description` field; public methods need `@describe`.
 ///  - Schemas/structs must not have duplicate fields.
-///  - Pipelines should have at least one stage.
+///  - Pipelines should have at least one stage, and must not include
+///    themselves through `stage name = Pipeline;` stages.
+///  - Match expressions should not repeat a literal pattern (the later arm is dead).
+///  - A `loop` should have a way out (`break`, `return`, `throw` or `?`).
 pub struct Validator {
     diagnostics: DiagnosticBag,
+    /// `type Name = T;` declarations, for comparing stage types by target.
+    type_aliases: HashMap<String, Type>,
+    /// Pipeline declarations, for typing stages that run another pipeline.
+    pipelines: HashMap<String, PipelineDecl>,
 }
 
 impl Default for Validator {
@@ -28,14 +40,29 @@ impl Validator {
     pub fn new() -> Self {
         Self {
             diagnostics: DiagnosticBag::new(),
+            type_aliases: HashMap::new(),
+            pipelines: HashMap::new(),
         }
     }
 
     /// Run validation on the entire program and return diagnostics.
     pub fn validate(mut self, program: &Program) -> DiagnosticBag {
+        for decl in &program.declarations {
+            match decl {
+                Declaration::TypeAlias(alias) => {
+                    self.type_aliases
+                        .insert(alias.name.clone(), Type::from_annotation(&alias.type_ann));
+                }
+                Declaration::Pipeline(p) => {
+                    self.pipelines.insert(p.name.clone(), p.clone());
+                }
+                _ => {}
+            }
+        }
         for decl in &program.declarations {
             self.validate_declaration(decl);
         }
+        self.visit_program(program);
         self.diagnostics
     }
 
@@ -103,6 +130,27 @@ impl Validator {
                 ),
             );
         }
+        for field in &model.fields {
+            let int_literal = match field.value.kind {
+                ExprKind::Literal(Literal::Int(n)) => Some(n),
+                _ => None,
+            };
+            match field.name.as_str() {
+                "schema_retries"
+                    if !int_literal.is_some_and(|n| n > 0 && u32::try_from(n).is_ok()) =>
+                {
+                    self.diagnostics.error(
+                        "`schema_retries` must be a positive integer literal",
+                        field.span.clone(),
+                    );
+                }
+                "seed" if int_literal.is_none() => {
+                    self.diagnostics
+                        .error("`seed` must be an integer literal", field.span.clone());
+                }
+                _ => {}
+            }
+        }
     }
 
     fn validate_tool(&mut self, tool: &ToolDecl) {
@@ -163,6 +211,38 @@ impl Validator {
                 );
             }
         }
+        self.check_field_aliases(schema);
+    }
+
+    /// Schema fields accept `@alias("otherName")`, a key the validator also
+    /// accepts for the field. An alias may not repeat another field's name
+    /// or alias.
+    fn check_field_aliases(&mut self, schema: &SchemaDecl) {
+        let mut taken: HashSet<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        for field in &schema.fields {
+            for dec in &field.decorators {
+                if dec.name != "alias" {
+                    self.diagnostics.error(
+                        format!("unknown field decorator `@{}`", dec.name),
+                        dec.span.clone(),
+                    );
+                    continue;
+                }
+                match dec.string_arg() {
+                    Some(alias) if !taken.insert(alias) => self.diagnostics.error(
+                        format!(
+                            "alias `{}` on field `{}` in schema `{}` is already a field or alias",
+                            alias, field.name, schema.name
+                        ),
+                        dec.span.clone(),
+                    ),
+                    Some(_) => {}
+                    None => self
+                        .diagnostics
+                        .error("`@alias` takes a single string literal", dec.span.clone()),
+                }
+            }
+        }
     }
 
     fn validate_struct(&mut self, s: &StructDecl) {
@@ -174,6 +254,29 @@ impl Validator {
                     field.span.clone(),
                 );
             }
+            if let TypeKind::Union(variants) = &field.type_ann.kind {
+                if !variants
+                    .iter()
+                    .all(|v| matches!(v.kind, TypeKind::StringLiteral(_)))
+                {
+                    self.diagnostics.error(
+                        format!(
+                            "field `{}` in struct `{}`: unions of types are only supported in schema fields",
+                            field.name, s.name
+                        ),
+                        field.type_ann.span.clone(),
+                    );
+                }
+            }
+            if let Some(dec) = field.decorators.first() {
+                self.diagnostics.error(
+                    format!(
+                        "field `{}` in struct `{}`: field decorators are only supported in schema fields",
+                        field.name, s.name
+                    ),
+                    dec.span.clone(),
+                );
+            }
         }
     }
 
@@ -187,7 +290,7 @@ impl Validator {
 
         // Required return type annotations (promoted from warning to error)
         for stage in &pipeline.stages {
-            if stage.return_type.is_none() {
+            if stage.pipeline.is_none() && stage.return_type.is_none() {
                 self.diagnostics.error(
                     format!(
                         "stage `{}` in pipeline `{}` must have a return type annotation",
@@ -198,24 +301,30 @@ impl Validator {
             }
         }
 
+        // A pipeline reached again through its own stages would recurse forever
+        for stage in &pipeline.stages {
+            if let Some(ref sub) = stage.pipeline {
+                if self.pipeline_reaches(sub, &pipeline.name, &mut HashSet::new()) {
+                    self.diagnostics.error(
+                        format!(
+                            "pipeline `{}` includes itself through stage `{}`",
+                            pipeline.name, stage.name
+                        ),
+                        stage.span.clone(),
+                    );
+                    return;
+                }
+            }
+        }
+
         // Stage adjacency type checking
         for i in 0..pipeline.stages.len().saturating_sub(1) {
             let current = &pipeline.stages[i];
             let next = &pipeline.stages[i + 1];
 
-            let output_type = current
-                .return_type
-                .as_ref()
-                .map(Type::from_annotation)
-                .unwrap_or(Type::Any);
-
-            if let Some(first_param) = next.params.first() {
-                let input_type = first_param
-                    .type_ann
-                    .as_ref()
-                    .map(Type::from_annotation)
-                    .unwrap_or(Type::Any);
+            let output_type = self.stage_output_type(current);
 
+            if let Some((input_type, input_span)) = self.stage_input_type(next) {
                 if !Type::is_pipeline_assignable(&output_type, &input_type) {
                     self.diagnostics.report(
                         Diagnostic::error(format!(
@@ -226,7 +335,7 @@ impl Validator {
                             next.name,
                             input_type.display_name()
                         ))
-                        .with_span(next.params[0].span.clone())
+                        .with_span(input_span)
                         .with_suggestion(
                             "align stage signatures or insert a conversion stage",
                         ),
@@ -238,17 +347,8 @@ impl Validator {
         // Pipeline-level signature validation
         if let Some(ref input_param) = pipeline.input_param {
             if let Some(first_stage) = pipeline.stages.first() {
-                if let Some(first_param) = first_stage.params.first() {
-                    let pipeline_input = input_param
-                        .type_ann
-                        .as_ref()
-                        .map(Type::from_annotation)
-                        .unwrap_or(Type::Any);
-                    let stage_input = first_param
-                        .type_ann
-                        .as_ref()
-                        .map(Type::from_annotation)
-                        .unwrap_or(Type::Any);
+                if let Some((stage_input, input_span)) = self.stage_input_type(first_stage) {
+                    let pipeline_input = self.annotated_type(input_param.type_ann.as_ref());
 
                     if !Type::is_pipeline_assignable(&pipeline_input, &stage_input) {
                         self.diagnostics.error(
@@ -259,7 +359,7 @@ impl Validator {
                                 first_stage.name,
                                 stage_input.display_name()
                             ),
-                            first_param.span.clone(),
+                            input_span,
                         );
                     }
                 }
@@ -267,13 +367,9 @@ impl Validator {
         }
 
         if let Some(ref ret_ann) = pipeline.return_type {
-            let pipeline_output = Type::from_annotation(ret_ann);
+            let pipeline_output = self.annotated_type(Some(ret_ann));
             if let Some(last_stage) = pipeline.stages.last() {
-                let stage_output = last_stage
-                    .return_type
-                    .as_ref()
-                    .map(Type::from_annotation)
-                    .unwrap_or(Type::Any);
+                let stage_output = self.stage_output_type(last_stage);
 
                 if !Type::is_pipeline_assignable(&stage_output, &pipeline_output) {
                     self.diagnostics.error(
@@ -290,6 +386,251 @@ impl Validator {
             }
         }
     }
+
+    /// The type a stage accepts and the span to report a mismatch at: its
+    /// first parameter, or the input of the pipeline it runs.
+    fn stage_input_type(&self, stage: &StageDecl) -> Option<(Type, Span)> {
+        match stage.pipeline {
+            Some(ref sub) => Some((self.pipeline_input_type(sub, 0), stage.span.clone())),
+            None => stage.params.first().map(|param| {
+                (
+                    self.annotated_type(param.type_ann.as_ref()),
+                    param.span.clone(),
+                )
+            }),
+        }
+    }
+
+    /// The type a stage produces: its return type, or the output of the
+    /// pipeline it runs.
+    fn stage_output_type(&self, stage: &StageDecl) -> Type {
+        match stage.pipeline {
+            Some(ref sub) => self.pipeline_output_type(sub, 0),
+            None => self.annotated_type(stage.return_type.as_ref()),
+        }
+    }
+
+    /// A pipeline's declared input type, else its first stage's. `depth`
+    /// bounds the walk through nested pipelines (cycles are reported
+    /// separately); unknown pipelines are `Any`.
+    fn pipeline_input_type(&self, name: &str, depth: usize) -> Type {
+        let Some(pipeline) = self
+            .pipelines
+            .get(name)
+            .filter(|_| depth < MAX_PIPELINE_NESTING)
+        else {
+            return Type::Any;
+        };
+        if let Some(ref param) = pipeline.input_param {
+            return self.annotated_type(param.type_ann.as_ref());
+        }
+        match pipeline.stages.first() {
+            Some(StageDecl {
+                pipeline: Some(sub),
+                ..
+            }) => self.pipeline_input_type(sub, depth + 1),
+            Some(stage) => stage
+                .params
+                .first()
+                .map(|p| self.annotated_type(p.type_ann.as_ref()))
+                .unwrap_or(Type::Any),
+            None => Type::Any,
+        }
+    }
+
+    /// A pipeline's declared output type, else its last stage's.
+    fn pipeline_output_type(&self, name: &str, depth: usize) -> Type {
+        let Some(pipeline) = self
+            .pipelines
+            .get(name)
+            .filter(|_| depth < MAX_PIPELINE_NESTING)
+        else {
+            return Type::Any;
+        };
+        if let Some(ref ret) = pipeline.return_type {
+            return self.annotated_type(Some(ret));
+        }
+        match pipeline.stages.last() {
+            Some(StageDecl {
+                pipeline: Some(sub),
+                ..
+            }) => self.pipeline_output_type(sub, depth + 1),
+            Some(stage) => self.annotated_type(stage.return_type.as_ref()),
+            None => Type::Any,
+        }
+    }
+
+    /// Whether running pipeline `from` can end up running pipeline `target`.
+    fn pipeline_reaches(&self, from: &str, target: &str, seen: &mut HashSet<String>) -> bool {
+        if from == target {
+            return true;
+        }
+        if !seen.insert(from.to_string()) {
+            return false;
+        }
+        self.pipelines.get(from).is_some_and(|p| {
+            p.stages
+                .iter()
+                .filter_map(|s| s.pipeline.as_deref())
+                .any(|sub| self.pipeline_reaches(sub, target, seen))
+        })
+    }
+
+    /// The checked type of an optional annotation, with aliases expanded;
+    /// a missing annotation is `Any`.
+    fn annotated_type(&self, ann: Option<&TypeAnnotation>) -> Type {
+        ann.map(Type::from_annotation)
+            .unwrap_or(Type::Any)
+            .expand_aliases(&|name| self.type_aliases.get(name).cloned())
+    }
+}
+
+impl Visitor for Validator {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Match { arms, .. } => self.check_duplicate_literal_arms(arms),
+            ExprKind::Loop { body, decorators } => {
+                self.check_loop_decorators(decorators);
+                // A capped loop ends by throwing once it hits the cap.
+                if decorators.is_empty() {
+                    self.check_loop_exits(body, expr);
+                }
+            }
+            _ => {}
+        }
+        // Keep walking so nested matches are checked too
+        walk_expr(self, expr);
+    }
+}
+
+impl Validator {
+    /// Warn on literal patterns already matched by an earlier unguarded arm.
+    fn check_duplicate_literal_arms(&mut self, arms: &[MatchArm]) {
+        let mut seen = HashSet::new();
+        for arm in arms {
+            let literals: Vec<&Pattern> = match &arm.pattern.kind {
+                PatternKind::Literal(_) => vec![&arm.pattern],
+                PatternKind::Or(alts) => alts
+                    .iter()
+                    .filter(|p| matches!(p.kind, PatternKind::Literal(_)))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for pattern in literals {
+                let PatternKind::Literal(lit) = &pattern.kind else {
+                    continue;
+                };
+                let key = literal_key(lit);
+                if seen.contains(&key) {
+                    self.diagnostics.report(
+                        Diagnostic::warning(format!(
+                            "unreachable match arm: pattern `{}` is already matched by an earlier arm",
+                            key
+                        ))
+                        .with_span(pattern.span.clone())
+                        .with_suggestion("remove the duplicate arm or change its pattern"),
+                    );
+                } else if arm.guard.is_none() {
+                    // A guarded arm may fall through, so it doesn't shadow later ones
+                    seen.insert(key);
+                }
+            }
+        }
+    }
+}
+
+impl Validator {
+    /// `loop` accepts only `@max_iters(N)` with a positive integer literal.
+    fn check_loop_decorators(&mut self, decorators: &[Decorator]) {
+        for dec in decorators {
+            if dec.name != "max_iters" {
+                self.diagnostics.error(
+                    format!("unknown loop decorator `@{}`", dec.name),
+                    dec.span.clone(),
+                );
+                continue;
+            }
+            match dec.int_arg() {
+                Some(n) if n > 0 => {}
+                _ => self.diagnostics.error(
+                    "`@max_iters` takes a single positive integer literal",
+                    dec.span.clone(),
+                ),
+            }
+        }
+        for dup in decorators.iter().filter(|d| d.name == "max_iters").skip(1) {
+            self.diagnostics
+                .error("duplicate `@max_iters` on loop", dup.span.clone());
+        }
+    }
+
+    /// Warn on a `loop` whose body can never leave it. Not an error, since a
+    /// server loop may be meant to run forever.
+    fn check_loop_exits(&mut self, body: &Block, expr: &Expr) {
+        let mut finder = LoopExitFinder::default();
+        finder.visit_block(body);
+        if !finder.found {
+            self.diagnostics.report(
+                Diagnostic::warning(
+                    "`loop` has no `break`, `return` or `throw` and will never end",
+                )
+                .with_span(expr.span.clone())
+                .with_suggestion("add a `break` for the exit condition, or ignore this if the loop is meant to run forever"),
+            );
+        }
+    }
+}
+
+/// Looks for a way out of a `loop` body: an unlabeled `break` outside any
+/// nested loop, a labeled `break`, or a `return`/`throw`/`?`. Closure and
+/// listen handler bodies are skipped, since exits there don't leave the loop.
+#[derive(Default)]
+struct LoopExitFinder {
+    nested_loops: usize,
+    found: bool,
+}
+
+impl Visitor for LoopExitFinder {
+    fn visit_break_stmt(&mut self, stmt: &BreakStmt) {
+        if self.nested_loops == 0 || stmt.label.is_some() {
+            self.found = true;
+        }
+    }
+
+    fn visit_return_stmt(&mut self, _stmt: &ReturnStmt) {
+        self.found = true;
+    }
+
+    fn visit_throw_stmt(&mut self, _stmt: &ThrowStmt) {
+        self.found = true;
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Return(_) | ExprKind::Propagate(_) => self.found = true,
+            ExprKind::Closure { .. } => return,
+            ExprKind::Listen { call, .. } => return self.visit_expr(call),
+            ExprKind::For { .. } | ExprKind::While { .. } | ExprKind::Loop { .. } => {
+                self.nested_loops += 1;
+                walk_expr(self, expr);
+                self.nested_loops -= 1;
+                return;
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Source-like rendering of a literal, used to compare patterns.
+fn literal_key(lit: &Literal) -> String {
+    match lit {
+        Literal::Int(n) => n.to_string(),
+        Literal::Float(f) => format!("{:?}", f),
+        Literal::String(s) => format!("{:?}", s),
+        Literal::Bool(b) => b.to_string(),
+        Literal::Nil => "nil".to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -370,6 +711,36 @@ mod tests {
         assert!(errs.is_empty(), "unexpected errors: {:?}", errs);
     }
 
+    #[test]
+    fn model_schema_retries_and_seed_must_be_int_literals() {
+        let errs = val_errors(
+            r#"
+            model Good {
+                provider: openai,
+                schema_retries: 2,
+                seed: 42,
+            }
+            model Bad {
+                provider: openai,
+                schema_retries: -1,
+                seed: "42",
+            }
+            model Zero {
+                provider: openai,
+                schema_retries: 0,
+            }
+            "#,
+        );
+        assert_eq!(
+            errs,
+            vec![
+                "`schema_retries` must be a positive integer literal",
+                "`seed` must be an integer literal",
+                "`schema_retries` must be a positive integer literal",
+            ]
+        );
+    }
+
     #[test]
     fn tool_missing_description() {
         let errs = val_errors(
@@ -502,6 +873,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pipeline_stage_struct_input_after_string_output() {
+        let errs = val_errors(
+            r#"
+            struct Report { title: String }
+            pipeline P {
+                stage fetch(url: String) -> String {
+                    return url;
+                }
+                stage summarize(report: Report) -> String {
+                    return report.title;
+                }
+            }
+            "#,
+        );
+        assert!(
+            errs.iter()
+                .any(|e| e.contains("`fetch` returns `String` but `summarize` expects `Report`")),
+            "expected adjacency type mismatch error, got: {:?}",
+            errs
+        );
+    }
+
+    #[test]
+    fn pipeline_stage_adjacency_expands_type_aliases() {
+        let errs = val_errors(
+            r#"
+            type Doc = String;
+            pipeline P(path: String) -> Doc {
+                stage read(p: String) -> Doc {
+                    return p;
+                }
+                stage clean(text: String) -> Doc {
+                    return text;
+                }
+                stage count(text: Doc) -> Int {
+                    return 1;
+                }
+            }
+            "#,
+        );
+        let mismatches: Vec<_> = errs.iter().filter(|e| e.contains("mismatch")).collect();
+        assert_eq!(mismatches.len(), 1, "got: {:?}", mismatches);
+        assert!(mismatches[0].contains("`count` returns `Int`"));
+    }
+
     #[test]
     fn pipeline_stage_adjacency_any_accepts_all() {
         let errs = val_errors(
@@ -574,6 +991,62 @@ mod tests {
         assert!(errs.is_empty(), "expected no errors, got: {:?}", errs);
     }
 
+    #[test]
+    fn pipeline_stage_types_flow_through_nested_pipeline() {
+        let errs = val_errors(
+            r#"
+            pipeline Inner {
+                stage parse(s: String) -> Int {
+                    return 1;
+                }
+            }
+            pipeline Good(input: String) -> String {
+                stage run = Inner;
+                stage show(n: Int) -> String {
+                    return "ok";
+                }
+            }
+            pipeline Bad {
+                stage start(x: Int) -> Int {
+                    return x;
+                }
+                stage run = Inner;
+            }
+            "#,
+        );
+        assert_eq!(
+            errs,
+            vec![
+                "pipeline `Bad` stage type mismatch: `start` returns `Int` but `run` expects `String`"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn pipeline_including_itself_is_error() {
+        let errs = val_errors(
+            r#"
+            pipeline A {
+                stage first(x: Int) -> Int {
+                    return x;
+                }
+                stage next = B;
+            }
+            pipeline B {
+                stage back = A;
+            }
+            "#,
+        );
+        assert_eq!(
+            errs,
+            vec![
+                "pipeline `A` includes itself through stage `next`".to_string(),
+                "pipeline `B` includes itself through stage `back`".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn pipeline_adjacency_compatible_types_ok() {
         let errs = val_errors(
@@ -617,4 +1090,196 @@ mod tests {
             diag.suggestion
         );
     }
+
+    #[test]
+    fn duplicate_literal_match_arm_warns_on_second() {
+        let diags = full_val_diagnostics(
+            r#"
+            fn route(status: String) -> Int {
+                match status {
+                    "active" => 1,
+                    "paused" => 2,
+                    "active" => 3,
+                    _ => 0,
+                }
+            }
+            "#,
+        );
+        let dupes: Vec<_> = diags
+            .iter()
+            .filter(|d| d.message.contains("unreachable match arm"))
+            .collect();
+        assert_eq!(dupes.len(), 1, "got: {:?}", diags);
+        assert_eq!(dupes[0].severity, Severity::Warning);
+        assert!(dupes[0].message.contains("\"active\""));
+        // Points at the second `"active"` arm (line 6 of the source)
+        assert_eq!(dupes[0].span.as_ref().unwrap().start.line, 6);
+    }
+
+    #[test]
+    fn duplicate_literal_in_or_pattern_and_nested_match() {
+        let warns = val_warnings(
+            r#"
+            fn f(x: Int) -> Int {
+                let y = match x {
+                    1 | 2 => 10,
+                    _ => match x {
+                        3 => 1,
+                        4 | 3 => 2,
+                        _ => 0,
+                    },
+                };
+                match y {
+                    2 => 0,
+                    _ => 1,
+                }
+            }
+            "#,
+        );
+        let dupes: Vec<_> = warns
+            .iter()
+            .filter(|w| w.contains("unreachable match arm"))
+            .collect();
+        assert_eq!(dupes.len(), 1, "got: {:?}", warns);
+        assert!(dupes[0].contains("`3`"));
+    }
+
+    #[test]
+    fn guarded_literal_arm_does_not_shadow() {
+        let warns = val_warnings(
+            r#"
+            fn f(x: Int, flag: Bool) -> Int {
+                match x {
+                    1 if flag => 10,
+                    1 => 20,
+                    _ => 0,
+                }
+            }
+            "#,
+        );
+        assert!(
+            !warns.iter().any(|w| w.contains("unreachable match arm")),
+            "got: {:?}",
+            warns
+        );
+    }
+
+    #[test]
+    fn loop_without_break_warns() {
+        let warns = val_warnings(
+            r#"
+            fn spin() {
+                let mut n = 0;
+                loop {
+                    n = n + 1;
+                    for i in [1, 2] {
+                        if i == n { break; }
+                    }
+                    let f = |x: Int| { return x; };
+                }
+            }
+            "#,
+        );
+        assert!(
+            warns.iter().any(|w| w.contains("`loop` has no `break`")),
+            "got: {:?}",
+            warns
+        );
+    }
+
+    #[test]
+    fn loop_with_conditional_break_ok() {
+        let warns = val_warnings(
+            r#"
+            fn count() {
+                let mut n = 0;
+                loop {
+                    n = n + 1;
+                    if n > 10 { break; }
+                }
+                loop {
+                    if n > 20 { return; }
+                    n = n + 1;
+                }
+            }
+            "#,
+        );
+        assert!(
+            !warns.iter().any(|w| w.contains("`loop`")),
+            "got: {:?}",
+            warns
+        );
+    }
+
+    #[test]
+    fn type_unions_only_in_schema_fields() {
+        let errors = val_errors(
+            r#"
+            schema Reply { content: String | Array<String> }
+            struct Note { body: String | Array<String>, tone: "calm" | "urgent" }
+            fn main() {}
+            "#,
+        );
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert!(errors[0].contains("field `body` in struct `Note`"));
+    }
+
+    #[test]
+    fn schema_field_aliases_checked() {
+        let errors = val_errors(
+            r#"
+            schema Person {
+                first_name @alias("firstName"): String,
+                last_name @alias("first_name"): String,
+                age @alias(3): Int,
+                email @rename("mail"): String,
+            }
+            struct Note { body @alias("text"): String }
+            fn main() {}
+            "#,
+        );
+        assert_eq!(errors.len(), 4, "got: {:?}", errors);
+        assert!(errors[0].contains("alias `first_name` on field `last_name`"));
+        assert!(errors[1].contains("`@alias` takes a single string literal"));
+        assert!(errors[2].contains("unknown field decorator `@rename`"));
+        assert!(errors[3].contains("field decorators are only supported in schema fields"));
+    }
+
+    #[test]
+    fn loop_max_iters_checked() {
+        let source = r#"
+            fn capped() {
+                @max_iters(100) loop { emit("tick", 1); }
+                @max_iters(0) loop { break; }
+                @unroll(2) loop { break; }
+            }
+        "#;
+        let errors = val_errors(source);
+        assert_eq!(errors.len(), 2, "got: {:?}", errors);
+        assert!(errors[0].contains("single positive integer literal"));
+        assert!(errors[1].contains("unknown loop decorator `@unroll`"));
+        // A capped loop ends by throwing, so it has no missing-exit warning.
+        assert!(!val_warnings(source).iter().any(|w| w.contains("`loop`")));
+    }
+
+    #[test]
+    fn duplicate_max_iters_points_at_the_repeat() {
+        let diags = full_val_diagnostics(
+            r#"
+            fn capped() {
+                @unroll(2)
+                @max_iters(5)
+                @max_iters(6)
+                loop { break; }
+            }
+            "#,
+        );
+        let dupes: Vec<_> = diags
+            .iter()
+            .filter(|d| d.message.contains("duplicate `@max_iters`"))
+            .collect();
+        assert_eq!(dupes.len(), 1, "got: {:?}", diags);
+        // Points at `@max_iters(6)` (line 5 of the source), not `decorators[1]`
+        assert_eq!(dupes[0].span.as_ref().unwrap().start.line, 5);
+    }
 }
@@ -40,6 +40,8 @@ pub struct AgentClient {
     stdout_reader: Option<BufReader<ChildStdout>>,
     /// Initialization params from [agents.<name>.params] in Concerto.toml.
     params: Option<serde_json::Value>,
+    /// Runtime values for `{param}` placeholders, taking precedence over `params`.
+    param_overrides: HashMap<String, String>,
 }
 
 impl std::fmt::Debug for AgentClient {
@@ -71,9 +73,40 @@ impl AgentClient {
             child: None,
             stdout_reader: None,
             params: ir_agent.params.clone(),
+            param_overrides: HashMap::new(),
         }
     }
 
+    /// Set a runtime value for `{key}` placeholders in `args` and `env`.
+    /// Takes effect the next time the process is spawned.
+    pub fn set_param(&mut self, key: &str, value: &str) {
+        self.param_overrides
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Values available to `{param}` placeholders: top-level manifest params
+    /// (strings as-is, other JSON compact) overlaid with runtime overrides.
+    fn template_values(&self) -> HashMap<String, String> {
+        let mut values: HashMap<String, String> = self
+            .params
+            .as_ref()
+            .and_then(|p| p.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .map(|(k, v)| {
+                        let text = match v {
+                            serde_json::Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        (k.clone(), text)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        values.extend(self.param_overrides.clone());
+        values
+    }
+
     /// Ensure the subprocess is running. Spawns if not connected.
     fn ensure_connected(&mut self) -> Result<()> {
         if let Some(ref mut child) = self.child {
@@ -97,10 +130,11 @@ impl AgentClient {
             )));
         }
 
+        let values = self.template_values();
         let mut cmd = Command::new(&self.command);
-        cmd.args(&self.args);
+        cmd.args(self.args.iter().map(|a| substitute_params(a, &values)));
         for (key, val) in &self.env {
-            cmd.env(key, val);
+            cmd.env(key, substitute_params(val, &values));
         }
         if let Some(ref dir) = self.working_dir {
             cmd.current_dir(dir);
@@ -201,6 +235,17 @@ impl AgentClient {
 
     /// Execute a prompt on the agent and return the response.
     pub fn execute(&mut self, prompt: &str, context: Option<&Value>) -> Result<String> {
+        self.execute_with_events(prompt, context, &mut |_| {})
+    }
+
+    /// Execute a prompt, passing intermediate `{"type":"token"}` and
+    /// `{"type":"progress"}` lines to `on_event` until the response line.
+    pub fn execute_with_events(
+        &mut self,
+        prompt: &str,
+        context: Option<&Value>,
+        on_event: &mut dyn FnMut(&serde_json::Value),
+    ) -> Result<String> {
         self.ensure_connected()?;
 
         let child = self.child.as_mut().unwrap();
@@ -226,23 +271,37 @@ impl AgentClient {
             }
         };
 
-        stdin.write_all(input.as_bytes()).map_err(|e| {
-            RuntimeError::CallError(format!("Failed to write to agent '{}': {}", self.name, e))
-        })?;
-        stdin.flush().map_err(|e| {
-            RuntimeError::CallError(format!("Failed to flush agent '{}' stdin: {}", self.name, e))
-        })?;
+        // As in listen sessions, an agent that ignores stdin may have exited
+        // already; its reply is still buffered on stdout.
+        if let Err(e) = stdin
+            .write_all(input.as_bytes())
+            .and_then(|_| stdin.flush())
+        {
+            if e.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(RuntimeError::CallError(format!(
+                    "Failed to write to agent '{}': {}",
+                    self.name, e
+                )));
+            }
+        }
 
-        // Read output (one line, with timeout)
-        let line = self.read_line_with_timeout()?;
+        // Read output (one line, with timeout), skipping past stream events
+        let line = loop {
+            let line = self.read_line_with_timeout()?;
 
-        if line.is_empty() {
-            self.child = None;
-            return Err(RuntimeError::CallError(format!(
-                "Agent '{}' process exited unexpectedly",
-                self.name
-            )));
-        }
+            if line.is_empty() {
+                self.child = None;
+                return Err(RuntimeError::CallError(format!(
+                    "Agent '{}' process exited unexpectedly",
+                    self.name
+                )));
+            }
+
+            match stream_event(&line) {
+                Some(event) => on_event(&event),
+                None => break line,
+            }
+        };
 
         let response = line.trim_end().to_string();
 
@@ -289,14 +348,17 @@ impl AgentClient {
             }
         };
 
-        stdin.write_all(input.as_bytes()).map_err(|e| {
-            RuntimeError::CallError(format!("Failed to write to agent '{}': {}", self.name, e))
-        })?;
-        stdin.flush().map_err(|e| {
-            RuntimeError::CallError(format!("Failed to flush agent '{}' stdin: {}", self.name, e))
-        })?;
-
-        Ok(())
+        // An agent that never reads stdin may already have exited; its
+        // output is still buffered on stdout, so let read_message surface it.
+        match stdin
+            .write_all(input.as_bytes())
+            .and_then(|_| stdin.flush())
+        {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(RuntimeError::CallError(
+                format!("Failed to write to agent '{}': {}", self.name, e),
+            )),
+            _ => Ok(()),
+        }
     }
 
     /// Read one NDJSON message from agent stdout.
@@ -427,6 +489,47 @@ impl Drop for AgentClient {
     }
 }
 
+/// Replace `{name}` placeholders whose name is a known param. Anything else in
+/// braces (unknown names, JSON, shell syntax) is left untouched.
+fn substitute_params(text: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let replaced = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            let is_ident =
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            values
+                .get(name)
+                .filter(|_| is_ident)
+                .map(|value| (value, close))
+        });
+        match replaced {
+            Some((value, close)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse an intermediate `token`/`progress` message sent before the response.
+fn stream_event(line: &str) -> Option<serde_json::Value> {
+    let json: serde_json::Value = serde_json::from_str(line.trim_end()).ok()?;
+    match json.get("type").and_then(|t| t.as_str()) {
+        Some("token" | "progress") => Some(json),
+        _ => None,
+    }
+}
+
 /// Registry that manages named agent clients.
 #[derive(Debug, Default)]
 pub struct AgentRegistry {
@@ -455,6 +558,21 @@ impl AgentRegistry {
         client.execute(prompt, context)
     }
 
+    /// Execute a prompt on a named agent, reporting stream events to `on_event`.
+    pub fn execute_with_events(
+        &mut self,
+        name: &str,
+        prompt: &str,
+        context: Option<&Value>,
+        on_event: &mut dyn FnMut(&serde_json::Value),
+    ) -> Result<String> {
+        let client = self
+            .clients
+            .get_mut(name)
+            .ok_or_else(|| RuntimeError::CallError(format!("Agent '{}' not registered", name)))?;
+        client.execute_with_events(prompt, context, on_event)
+    }
+
     /// Get a mutable reference to a named agent client.
     pub fn get_client_mut(&mut self, name: &str) -> Result<&mut AgentClient> {
         self.clients
@@ -462,6 +580,13 @@ impl AgentRegistry {
             .ok_or_else(|| RuntimeError::CallError(format!("Agent '{}' not registered", name)))
     }
 
+    /// Set a runtime value for an agent's `{key}` placeholders (see
+    /// [`AgentClient::set_param`]).
+    pub fn set_param(&mut self, name: &str, key: &str, value: &str) -> Result<()> {
+        self.get_client_mut(name)?.set_param(key, value);
+        Ok(())
+    }
+
     /// Check if an agent is registered.
     pub fn has_agent(&self, name: &str) -> bool {
         self.clients.contains_key(name)
@@ -588,6 +713,36 @@ mod tests {
         assert!(msg3.is_none());
     }
 
+    #[test]
+    fn agent_execute_reports_stream_events() {
+        let ir_agent = IrAgent {
+            name: "StreamAgent".to_string(),
+            connector: "test".to_string(),
+            input_format: "text".to_string(),
+            output_format: "json".to_string(),
+            timeout: Some(5),
+            decorators: vec![],
+            command: Some("bash".to_string()),
+            args: Some(vec![
+                "-c".to_string(),
+                r#"read prompt; echo '{"type":"progress","message":"Working"}'; echo '{"type":"token","text":"Do"}'; echo '{"type":"result","text":"Done"}'"#
+                    .to_string(),
+            ]),
+            env: None,
+            working_dir: None,
+            params: None,
+        };
+        let mut client = AgentClient::from_ir(&ir_agent);
+        let mut events = Vec::new();
+        let result = client
+            .execute_with_events("go", None, &mut |e| events.push(e.clone()))
+            .unwrap();
+        assert_eq!(result, "Done");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["type"], "progress");
+        assert_eq!(events[1]["type"], "token");
+    }
+
     #[test]
     fn agent_init_sends_params_and_receives_ack() {
         // Use bash to read init message, echo init_ack, then echo result for execute
@@ -619,6 +774,46 @@ mod tests {
         assert_eq!(exec_result.unwrap(), "response_text");
     }
 
+    #[test]
+    fn agent_args_and_env_substitute_params_at_spawn() {
+        let ir_agent = IrAgent {
+            name: "TemplatedAgent".to_string(),
+            connector: "test".to_string(),
+            input_format: "text".to_string(),
+            output_format: "text".to_string(),
+            timeout: Some(5),
+            decorators: vec![],
+            command: Some("bash".to_string()),
+            args: Some(vec![
+                "-c".to_string(),
+                r#"read init_line; echo '{"type":"init_ack"}'; read prompt; echo "$1 $AGENT_TEMP {unknown}""#
+                    .to_string(),
+                "agent".to_string(),
+                "--model={model}".to_string(),
+            ]),
+            env: Some(HashMap::from([(
+                "AGENT_TEMP".to_string(),
+                "t={temperature}".to_string(),
+            )])),
+            working_dir: None,
+            params: Some(serde_json::json!({"model": "gpt-4o", "temperature": 0.5})),
+        };
+        let mut registry = AgentRegistry::new();
+        registry.register(&ir_agent);
+        let result = registry.execute("TemplatedAgent", "hi", None).unwrap();
+        assert_eq!(result, "--model=gpt-4o t=0.5 {unknown}");
+
+        // Runtime values win over manifest params on the next spawn
+        let mut registry = AgentRegistry::new();
+        registry.register(&ir_agent);
+        registry
+            .set_param("TemplatedAgent", "model", "llama3")
+            .unwrap();
+        let result = registry.execute("TemplatedAgent", "hi", None).unwrap();
+        assert_eq!(result, "--model=llama3 t=0.5 {unknown}");
+        assert!(registry.set_param("Missing", "model", "x").is_err());
+    }
+
     #[test]
     fn agent_init_error_propagates() {
         // Agent responds with error instead of init_ack
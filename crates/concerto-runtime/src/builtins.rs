@@ -62,10 +62,16 @@ pub fn call_builtin(name: &str, args: Vec<Value>) -> Result<Value> {
             println!();
             Ok(Value::Nil)
         }
+        "$builtin_pprint" => {
+            for arg in &args {
+                println!("{}", arg.pretty_string());
+            }
+            Ok(Value::Nil)
+        }
         "$builtin_len" => {
             let val = args.into_iter().next().unwrap_or(Value::Nil);
             match &val {
-                Value::String(s) => Ok(Value::Int(s.len() as i64)),
+                Value::String(s) => Ok(Value::Int(s.chars().count() as i64)),
                 Value::Array(a) => Ok(Value::Int(a.len() as i64)),
                 Value::Map(m) => Ok(Value::Int(m.len() as i64)),
                 _ => Err(RuntimeError::TypeError(format!(
@@ -23,6 +23,9 @@ pub enum RuntimeError {
     #[error("division by zero")]
     DivisionByZero,
 
+    #[error("shift overflow: shift amount {0} is outside 0..64")]
+    ShiftOverflow(i64),
+
     #[error("field error: no field '{field}' on {type_name}")]
     FieldError { type_name: String, field: String },
 
@@ -38,6 +41,12 @@ pub enum RuntimeError {
     #[error("max call depth exceeded ({0})")]
     StackOverflow(usize),
 
+    #[error("timeout: run exceeded {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("timeout exceeded (no response within {0:?})")]
+    ProviderTimeout(std::time::Duration),
+
     #[error("schema validation error: {0}")]
     SchemaError(String),
 
@@ -48,9 +57,46 @@ pub enum RuntimeError {
     JsonError(#[from] serde_json::Error),
 }
 
+/// `io::Error` and `serde_json::Error` are not `Clone`, so those variants are
+/// rebuilt from their kind and message; every other variant is copied as is.
+impl Clone for RuntimeError {
+    fn clone(&self) -> Self {
+        match self {
+            RuntimeError::LoadError(s) => RuntimeError::LoadError(s.clone()),
+            RuntimeError::TypeError(s) => RuntimeError::TypeError(s.clone()),
+            RuntimeError::NameError(s) => RuntimeError::NameError(s.clone()),
+            RuntimeError::StackUnderflow => RuntimeError::StackUnderflow,
+            RuntimeError::CallError(s) => RuntimeError::CallError(s.clone()),
+            RuntimeError::DivisionByZero => RuntimeError::DivisionByZero,
+            RuntimeError::ShiftOverflow(n) => RuntimeError::ShiftOverflow(*n),
+            RuntimeError::FieldError { type_name, field } => RuntimeError::FieldError {
+                type_name: type_name.clone(),
+                field: field.clone(),
+            },
+            RuntimeError::IndexError { index, len } => RuntimeError::IndexError {
+                index: *index,
+                len: *len,
+            },
+            RuntimeError::UnhandledThrow(s) => RuntimeError::UnhandledThrow(s.clone()),
+            RuntimeError::Propagated(v) => RuntimeError::Propagated(v.clone()),
+            RuntimeError::StackOverflow(n) => RuntimeError::StackOverflow(*n),
+            RuntimeError::Timeout(d) => RuntimeError::Timeout(*d),
+            RuntimeError::ProviderTimeout(d) => RuntimeError::ProviderTimeout(*d),
+            RuntimeError::SchemaError(s) => RuntimeError::SchemaError(s.clone()),
+            RuntimeError::IoError(e) => {
+                RuntimeError::IoError(std::io::Error::new(e.kind(), e.to_string()))
+            }
+            RuntimeError::JsonError(e) => {
+                RuntimeError::JsonError(serde::de::Error::custom(e.to_string()))
+            }
+        }
+    }
+}
+
 /// Wrapper to carry a runtime Value through the error system.
 /// We can't use Value directly in RuntimeError because of circular dependency
 /// during definition, so we use a newtype wrapper.
+#[derive(Clone)]
 pub struct PropagatedValue {
     /// Display representation of the propagated error value.
     pub display: String,
@@ -0,0 +1,128 @@
+//! Remediation hints for runtime errors (`concerto run --explain-error`).
+//!
+//! The runtime counterpart of a compile-time diagnostic's `suggestion`: given
+//! a failed run's error and the names the program could have meant, produce
+//! one line of advice, or `None` when there is nothing useful to add.
+
+use crate::error::RuntimeError;
+use crate::stdlib;
+
+/// Suggest a fix for `err`. `known_names` are the functions and globals of
+/// the program, used for did-you-mean matching of undefined names.
+pub fn hint(err: &RuntimeError, known_names: &[&str]) -> Option<String> {
+    match err {
+        RuntimeError::NameError(name) => {
+            let name = name
+                .strip_prefix("unknown model: ")
+                .or_else(|| name.strip_prefix("unknown pipeline: "))
+                .unwrap_or(name);
+            closest(name, known_names.iter().copied()).map(|m| format!("did you mean `{}`?", m))
+        }
+        RuntimeError::CallError(msg) => {
+            if let Some(path) = msg.strip_prefix("unknown function: std::") {
+                let (module, function) = path.split_once("::")?;
+                let names = stdlib::function_names(module);
+                Some(match closest(function, names.iter().copied()) {
+                    Some(m) => format!("did you mean `std::{}::{}`?", module, m),
+                    None => format!("std::{} provides: {}", module, names.join(", ")),
+                })
+            } else if let Some(module) = msg.strip_prefix("unknown stdlib module: std::") {
+                closest(module, stdlib::MODULES.iter().copied())
+                    .map(|m| format!("did you mean `std::{}`?", m))
+            } else {
+                None
+            }
+        }
+        RuntimeError::UnhandledThrow(msg) if msg.starts_with("called unwrap() on Err") => Some(
+            "`unwrap()` fails on `Err`; handle both cases with `match`, or return the error to \
+             the caller with `?`"
+                .to_string(),
+        ),
+        RuntimeError::UnhandledThrow(msg) if msg.starts_with("called unwrap() on None") => Some(
+            "`unwrap()` fails on `None`; handle both cases with `match`, or supply a default \
+             with `??`"
+                .to_string(),
+        ),
+        RuntimeError::TypeError(msg) if msg.contains("Nil") => Some(
+            "a missing map key, field or return value is `nil`; check it first, or supply a \
+             default with `??`"
+                .to_string(),
+        ),
+        RuntimeError::IndexError { .. } => Some(
+            "check the index against `len()`, or use `.get(i)`, which returns `None` when out \
+             of range"
+                .to_string(),
+        ),
+        RuntimeError::DivisionByZero => {
+            Some("check that the divisor is non-zero before dividing".to_string())
+        }
+        RuntimeError::StackOverflow(depth) => Some(format!(
+            "recursion went deeper than {} calls; check the base case, or raise the limit \
+             with `--max-depth N`",
+            depth
+        )),
+        RuntimeError::Timeout(_) => {
+            Some("raise the limit with `--timeout-secs N` if the run needs longer".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The candidate closest to `name` by edit distance, if it is close enough
+/// to be a plausible typo (at most a third of the name's length, rounded up).
+fn closest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = name.chars().count().div_ceil(3);
+    candidates
+        .filter(|c| *c != name && !c.starts_with('$'))
+        .map(|c| (stdlib::string::levenshtein(name, c), c))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, c)| c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undefined_names_suggest_the_closest_known_name() {
+        let names = ["main", "greet", "println", "$closure_0"];
+        let err = RuntimeError::NameError("mian".into());
+        assert_eq!(hint(&err, &names).as_deref(), Some("did you mean `main`?"));
+        let err = RuntimeError::NameError("unknown model: Gret".into());
+        assert_eq!(hint(&err, &names).as_deref(), Some("did you mean `greet`?"));
+        let err = RuntimeError::NameError("completely_different".into());
+        assert_eq!(hint(&err, &names), None);
+    }
+
+    #[test]
+    fn unknown_std_functions_suggest_a_std_name() {
+        let err = RuntimeError::CallError("unknown function: std::string::to_uppr".into());
+        assert_eq!(
+            hint(&err, &[]).as_deref(),
+            Some("did you mean `std::string::to_upper`?")
+        );
+        let err = RuntimeError::CallError("unknown function: std::prompt::nothing_close".into());
+        assert_eq!(
+            hint(&err, &[]).as_deref(),
+            Some("std::prompt provides: template, from_file, count_tokens")
+        );
+        let err = RuntimeError::CallError("unknown stdlib module: std::strng".into());
+        assert_eq!(
+            hint(&err, &[]).as_deref(),
+            Some("did you mean `std::string`?")
+        );
+    }
+
+    #[test]
+    fn common_failures_get_advice() {
+        let err = RuntimeError::UnhandledThrow("called unwrap() on Err(bad)".into());
+        assert!(hint(&err, &[]).unwrap().contains("`?`"));
+        let err = RuntimeError::StackOverflow(1000);
+        assert!(hint(&err, &[]).unwrap().contains("--max-depth"));
+        assert_eq!(
+            hint(&RuntimeError::CallError("provider failed".into()), &[]),
+            None
+        );
+    }
+}
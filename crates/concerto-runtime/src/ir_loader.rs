@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use concerto_common::ir::*;
+use concerto_common::ir_opcodes::Opcode;
 
 use crate::error::{Result, RuntimeError};
 use crate::value::Value;
@@ -53,6 +54,10 @@ impl LoadedModule {
 
     /// Convert an IrModule into a LoadedModule with lookup tables.
     pub fn from_ir(module: IrModule) -> Result<Self> {
+        check_ir_version(&module.version)?;
+        validate_instructions(&module)?;
+        validate_pipeline_nesting(&module)?;
+
         // Convert constant pool
         let constants: Vec<Value> = module
             .constants
@@ -193,6 +198,10 @@ impl LoadedModule {
     ///
     /// Used for test-only files that may not have a `main` function.
     pub fn from_ir_permissive(module: IrModule) -> Result<Self> {
+        check_ir_version(&module.version)?;
+        validate_instructions(&module)?;
+        validate_pipeline_nesting(&module)?;
+
         // Convert constant pool
         let constants: Vec<Value> = module
             .constants
@@ -303,6 +312,172 @@ fn convert_constant(constant: &IrConstant) -> Result<Value> {
     }
 }
 
+/// A stage that runs another pipeline must name one in the module, and no
+/// pipeline may reach itself that way: the VM runs nested pipelines by native
+/// recursion, so a cycle would overflow the process stack.
+fn validate_pipeline_nesting(module: &IrModule) -> Result<()> {
+    let pipelines: HashMap<&str, &IrPipeline> = module
+        .pipelines
+        .iter()
+        .map(|p| (p.name.as_str(), p))
+        .collect();
+    for pipeline in &module.pipelines {
+        for stage in &pipeline.stages {
+            if let Some(ref sub) = stage.pipeline {
+                if !pipelines.contains_key(sub.as_str()) {
+                    return Err(RuntimeError::LoadError(format!(
+                        "{}::{}: runs unknown pipeline '{}'",
+                        pipeline.name, stage.name, sub
+                    )));
+                }
+            }
+        }
+    }
+
+    for root in &module.pipelines {
+        let mut pending: Vec<&str> = vec![root.name.as_str()];
+        let mut seen: HashSet<&str> = HashSet::new();
+        while let Some(name) = pending.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            for stage in &pipelines[name].stages {
+                let Some(ref sub) = stage.pipeline else {
+                    continue;
+                };
+                if sub == &root.name {
+                    return Err(RuntimeError::LoadError(format!(
+                        "pipeline '{}' includes itself through stage '{}::{}'",
+                        root.name, name, stage.name
+                    )));
+                }
+                pending.push(sub.as_str());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that an IR `version` is compatible with this runtime's `IR_VERSION`.
+///
+/// A major version mismatch is rejected (for `0.x` versions the minor
+/// version acts as the major). A minor mismatch warns but loads; patch
+/// differences are ignored.
+pub fn check_ir_version(version: &str) -> Result<()> {
+    let incompatible = || {
+        RuntimeError::LoadError(format!(
+            "IR version '{}' is incompatible with this runtime (expects {}); \
+             recompile your source with a matching compiler",
+            version, IR_VERSION
+        ))
+    };
+    let (major, minor) = parse_major_minor(version).ok_or_else(incompatible)?;
+    let (rt_major, rt_minor) =
+        parse_major_minor(IR_VERSION).expect("IR_VERSION is valid semver");
+
+    if major != rt_major || (major == 0 && minor != rt_minor) {
+        return Err(incompatible());
+    }
+    if minor != rt_minor {
+        eprintln!(
+            "[warning] IR version '{}' differs from runtime IR version {}; consider recompiling",
+            version, IR_VERSION
+        );
+    }
+    Ok(())
+}
+
+/// Reject instructions whose operands point outside their function or the
+/// constant pool, so a corrupt or hand-edited artifact fails at load rather
+/// than mid-execution.
+///
+/// Jump targets are absolute instruction indices; a target equal to the
+/// instruction count is allowed (it falls off the end, an implicit return).
+fn validate_instructions(module: &IrModule) -> Result<()> {
+    let mut bodies: Vec<(String, &[IrInstruction])> = Vec::new();
+    for func in &module.functions {
+        bodies.push((func.name.clone(), &func.instructions));
+    }
+    for tool in &module.tools {
+        for method in &tool.methods {
+            bodies.push((
+                format!("{}::{}", tool.name, method.name),
+                &method.instructions,
+            ));
+        }
+    }
+    for mdl in &module.models {
+        for method in &mdl.methods {
+            bodies.push((
+                format!("{}::{}", mdl.name, method.name),
+                &method.instructions,
+            ));
+        }
+    }
+    for pipeline in &module.pipelines {
+        for stage in &pipeline.stages {
+            bodies.push((
+                format!("{}::{}", pipeline.name, stage.name),
+                &stage.instructions,
+            ));
+        }
+    }
+    for listen in &module.listens {
+        for handler in &listen.handlers {
+            bodies.push((
+                format!("{}::{}", listen.name, handler.message_type),
+                &handler.instructions,
+            ));
+        }
+    }
+    for test in &module.tests {
+        bodies.push((format!("test:{}", test.description), &test.instructions));
+    }
+
+    let constant_count = module.constants.len();
+    for (owner, instructions) in bodies {
+        for (pc, inst) in instructions.iter().enumerate() {
+            if let Some(target) = inst.offset {
+                if target < 0 || target as usize > instructions.len() {
+                    return Err(RuntimeError::LoadError(format!(
+                        "{}: instruction {} ({:?}) jumps to {}, outside 0..={}",
+                        owner,
+                        pc,
+                        inst.op,
+                        target,
+                        instructions.len()
+                    )));
+                }
+            }
+            if inst.op == Opcode::LoadConst {
+                match inst.arg.as_ref().and_then(|v| v.as_u64()) {
+                    Some(idx) if (idx as usize) < constant_count => {}
+                    Some(idx) => {
+                        return Err(RuntimeError::LoadError(format!(
+                            "{}: instruction {} loads constant {}, but the pool has {}",
+                            owner, pc, idx, constant_count
+                        )))
+                    }
+                    None => {
+                        return Err(RuntimeError::LoadError(format!(
+                            "{}: instruction {} is LOAD_CONST without a constant index",
+                            owner, pc
+                        )))
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_major_minor(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -357,6 +532,58 @@ mod tests {
         assert!(module.functions.contains_key("main"));
     }
 
+    fn ir_with_version(version: &str) -> IrModule {
+        let json = format!(
+            r#"{{
+            "version": "{}",
+            "module": "test",
+            "source_file": "test.conc",
+            "functions": [
+                {{
+                    "name": "main",
+                    "module": "test",
+                    "visibility": "private",
+                    "params": [],
+                    "return_type": "nil",
+                    "is_async": false,
+                    "locals": [],
+                    "instructions": [{{"op": "RETURN"}}]
+                }}
+            ],
+            "metadata": {{
+                "compiler_version": "0.1.0",
+                "compiled_at": "",
+                "optimization_level": 0,
+                "debug_info": true,
+                "entry_point": "main"
+            }}
+        }}"#,
+            version
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn matching_ir_version_accepted() {
+        assert!(LoadedModule::from_ir(ir_with_version(IR_VERSION)).is_ok());
+        // Patch differences are compatible.
+        assert!(LoadedModule::from_ir(ir_with_version("0.1.7")).is_ok());
+    }
+
+    #[test]
+    fn old_ir_version_rejected() {
+        let err = LoadedModule::from_ir(ir_with_version("0.0.3")).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("incompatible"), "got: {}", msg);
+        assert!(msg.contains("recompile"), "got: {}", msg);
+    }
+
+    #[test]
+    fn malformed_ir_version_rejected() {
+        assert!(LoadedModule::from_ir(ir_with_version("latest")).is_err());
+        assert!(LoadedModule::from_ir_permissive(ir_with_version("2.0.0")).is_err());
+    }
+
     #[test]
     fn missing_entry_point() {
         let json = r#"{
@@ -378,4 +605,117 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("entry point"));
     }
+
+    fn ir_with_main(instructions: &str, constants: &str) -> IrModule {
+        let json = format!(
+            r#"{{
+            "version": "{}",
+            "module": "test",
+            "source_file": "test.conc",
+            "constants": [{}],
+            "functions": [
+                {{
+                    "name": "main",
+                    "module": "test",
+                    "visibility": "private",
+                    "params": [],
+                    "return_type": "nil",
+                    "is_async": false,
+                    "locals": [],
+                    "instructions": [{}]
+                }}
+            ],
+            "metadata": {{
+                "compiler_version": "0.1.0",
+                "compiled_at": "",
+                "optimization_level": 0,
+                "debug_info": true,
+                "entry_point": "main"
+            }}
+        }}"#,
+            IR_VERSION, constants, instructions
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn out_of_range_jump_rejected_at_load() {
+        let ir = ir_with_main(r#"{"op": "JUMP", "offset": 7}, {"op": "RETURN"}"#, "");
+        let err = LoadedModule::from_ir(ir).unwrap_err().to_string();
+        assert!(err.contains("main: instruction 0"), "{err}");
+        assert!(err.contains("jumps to 7"), "{err}");
+
+        let ir = ir_with_main(r#"{"op": "JUMP_IF_FALSE", "offset": -1}"#, "");
+        assert!(LoadedModule::from_ir_permissive(ir).is_err());
+
+        // Jumping to the end of the function is an implicit return.
+        let ir = ir_with_main(r#"{"op": "JUMP", "offset": 1}"#, "");
+        assert!(LoadedModule::from_ir(ir).is_ok());
+    }
+
+    #[test]
+    fn invalid_const_index_rejected_at_load() {
+        let ir = ir_with_main(
+            r#"{"op": "LOAD_CONST", "arg": 1}, {"op": "RETURN"}"#,
+            r#"{"index": 0, "type": "int", "value": 1}"#,
+        );
+        let err = LoadedModule::from_ir(ir).unwrap_err().to_string();
+        assert!(
+            err.contains("loads constant 1, but the pool has 1"),
+            "{err}"
+        );
+
+        let ir = ir_with_main(r#"{"op": "LOAD_CONST"}"#, "");
+        let err = LoadedModule::from_ir(ir).unwrap_err().to_string();
+        assert!(err.contains("without a constant index"), "{err}");
+    }
+
+    #[test]
+    fn stage_running_unknown_pipeline_rejected_at_load() {
+        let mut ir = ir_with_main(r#"{"op": "RETURN"}"#, "");
+        ir.pipelines = vec![concerto_common::ir::IrPipeline {
+            name: "Outer".to_string(),
+            stages: vec![concerto_common::ir::IrPipelineStage {
+                name: "nested".to_string(),
+                params: vec![],
+                input_type: serde_json::json!("any"),
+                output_type: serde_json::json!("any"),
+                decorators: vec![],
+                instructions: vec![],
+                pipeline: Some("Missing".to_string()),
+            }],
+            input_type: None,
+            output_type: None,
+        }];
+        let err = LoadedModule::from_ir(ir).unwrap_err().to_string();
+        assert!(
+            err.contains("Outer::nested: runs unknown pipeline 'Missing'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn pipeline_cycle_rejected_at_load() {
+        let pipeline = |name: &str, sub: &str| concerto_common::ir::IrPipeline {
+            name: name.to_string(),
+            stages: vec![concerto_common::ir::IrPipelineStage {
+                name: "nested".to_string(),
+                params: vec![],
+                input_type: serde_json::json!("any"),
+                output_type: serde_json::json!("any"),
+                decorators: vec![],
+                instructions: vec![],
+                pipeline: Some(sub.to_string()),
+            }],
+            input_type: None,
+            output_type: None,
+        };
+        let mut ir = ir_with_main(r#"{"op": "RETURN"}"#, "");
+        ir.pipelines = vec![pipeline("A", "B"), pipeline("B", "A")];
+        let err = LoadedModule::from_ir(ir).unwrap_err().to_string();
+        assert!(
+            err.contains("pipeline 'A' includes itself through stage 'B::nested'"),
+            "{err}"
+        );
+    }
 }
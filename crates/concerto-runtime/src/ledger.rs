@@ -41,6 +41,7 @@ impl LedgerEntry {
 /// In-memory storage for all ledgers.
 ///
 /// Keys are ledger names (or "name::scope" for scoped views).
+#[derive(Clone)]
 pub struct LedgerStore {
     entries: HashMap<String, Vec<LedgerEntry>>,
 }
@@ -77,6 +78,38 @@ impl LedgerStore {
         }
     }
 
+    /// Insert an entry unless one already has the same identifier, or the same
+    /// value and the same keys (case-insensitive, in any order). Unlike
+    /// `insert`, an existing entry is never changed. Returns true if inserted.
+    pub fn insert_unique(
+        &mut self,
+        ledger: &str,
+        identifier: String,
+        keys: Vec<String>,
+        value: String,
+    ) -> bool {
+        let entries = self.entries.entry(ledger.to_string()).or_default();
+        let key_set: HashSet<String> = keys.iter().map(|k| k.to_lowercase()).collect();
+        let duplicate = entries.iter().any(|e| {
+            e.identifier == identifier
+                || (e.value == value
+                    && e.keys
+                        .iter()
+                        .map(|k| k.to_lowercase())
+                        .collect::<HashSet<_>>()
+                        == key_set)
+        });
+        if duplicate {
+            return false;
+        }
+        entries.push(LedgerEntry {
+            identifier,
+            keys,
+            value,
+        });
+        true
+    }
+
     /// Delete an entry by exact identifier match. Returns true if found.
     pub fn delete(&mut self, ledger: &str, identifier: &str) -> bool {
         if let Some(entries) = self.entries.get_mut(ledger) {
@@ -296,6 +329,38 @@ mod tests {
         assert!(results[0].identifier.contains("Uniswap"));
     }
 
+    #[test]
+    fn insert_unique_skips_duplicates() {
+        let mut store = LedgerStore::new();
+        let keys = || vec!["Uniswap".to_string(), "Ethereum".to_string()];
+        let value = || "0x1f98...".to_string();
+        assert!(store.insert_unique("k", "Uniswap router".to_string(), keys(), value()));
+        // Same identifier
+        assert!(!store.insert_unique(
+            "k",
+            "Uniswap router".to_string(),
+            keys(),
+            "other".to_string()
+        ));
+        // Same value and keys under a reworded identifier, keys reordered/recased
+        assert!(!store.insert_unique(
+            "k",
+            "The Uniswap router".to_string(),
+            vec!["ethereum".to_string(), "UNISWAP".to_string()],
+            value(),
+        ));
+        assert_eq!(store.len("k"), 1);
+        assert_eq!(store.entries("k")[0].value, "0x1f98...");
+        // Same value but different keys is a new fact
+        assert!(store.insert_unique(
+            "k",
+            "Uniswap on Base".to_string(),
+            vec!["Base".to_string()],
+            value()
+        ));
+        assert_eq!(store.len("k"), 2);
+    }
+
     #[test]
     fn upsert_replaces_existing() {
         let mut store = setup_store();
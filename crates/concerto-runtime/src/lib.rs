@@ -2,10 +2,12 @@ pub mod builtins;
 pub mod decorator;
 pub mod error;
 pub mod agent;
+pub mod explain;
 pub mod ir_loader;
 pub mod ledger;
 pub mod mcp;
 pub mod memory;
+pub mod profile;
 pub mod provider;
 pub mod providers;
 pub mod schema;
@@ -17,7 +19,7 @@ pub mod vm;
 pub use error::RuntimeError;
 pub use ir_loader::LoadedModule;
 pub use value::Value;
-pub use vm::VM;
+pub use vm::{VmSnapshot, VM};
 
 /// Load and execute a .conc-ir file, returning the result value.
 pub fn run_file(path: &str) -> error::Result<Value> {
@@ -8,7 +8,7 @@ use crate::value::Value;
 ///
 /// Each memory is a named list of ChatMessages. Supports sliding window
 /// via optional max_messages limit.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct MemoryStore {
     /// Conversation histories keyed by memory name.
     memories: HashMap<String, MemoryInstance>,
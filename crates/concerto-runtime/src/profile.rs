@@ -0,0 +1,102 @@
+//! Per-function call counts and timings for `concerto run --profile`.
+//!
+//! The VM records a frame's wall-clock time when the frame is popped. Total
+//! time includes callees (and is counted once per active frame, so recursive
+//! functions overcount); self time excludes nested Concerto frames, so host
+//! work such as provider calls, tool I/O and stdlib calls is charged to the
+//! function that made it.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Accumulated timings for one function.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionProfile {
+    pub calls: u64,
+    pub total: Duration,
+    pub self_time: Duration,
+}
+
+/// Per-function timings, keyed by IR function name.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    functions: HashMap<String, FunctionProfile>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed call.
+    pub fn record(&mut self, function: &str, total: Duration, self_time: Duration) {
+        let entry = self.functions.entry(function.to_string()).or_default();
+        entry.calls += 1;
+        entry.total += total;
+        entry.self_time += self_time;
+    }
+
+    pub fn get(&self, function: &str) -> Option<&FunctionProfile> {
+        self.functions.get(function)
+    }
+
+    /// Functions sorted by total time, slowest first (ties by name).
+    pub fn sorted(&self) -> Vec<(&str, &FunctionProfile)> {
+        let mut rows: Vec<_> = self
+            .functions
+            .iter()
+            .map(|(name, profile)| (name.as_str(), profile))
+            .collect();
+        rows.sort_by(|a, b| b.1.total.cmp(&a.1.total).then_with(|| a.0.cmp(b.0)));
+        rows
+    }
+
+    /// Render the sorted table printed by `concerto run --profile`.
+    pub fn report(&self) -> String {
+        let rows = self.sorted();
+        let width = rows
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("function".len());
+        let mut out = format!(
+            "{:<width$}  {:>8}  {:>12}  {:>12}\n",
+            "function", "calls", "total (ms)", "self (ms)"
+        );
+        for (name, profile) in rows {
+            out.push_str(&format!(
+                "{:<width$}  {:>8}  {:>12.3}  {:>12.3}\n",
+                name,
+                profile.calls,
+                profile.total.as_secs_f64() * 1000.0,
+                profile.self_time.as_secs_f64() * 1000.0,
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_sorts_by_total_time() {
+        let mut profiler = Profiler::new();
+        profiler.record("helper", Duration::from_millis(2), Duration::from_millis(2));
+        profiler.record("helper", Duration::from_millis(3), Duration::from_millis(3));
+        profiler.record("main", Duration::from_millis(9), Duration::from_millis(4));
+
+        let helper = profiler.get("helper").unwrap();
+        assert_eq!(helper.calls, 2);
+        assert_eq!(helper.total, Duration::from_millis(5));
+
+        let report = profiler.report();
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[0].starts_with("function"));
+        assert!(lines[1].starts_with("main "), "{}", report);
+        assert!(lines[2].starts_with("helper "), "{}", report);
+        assert!(lines[2].contains("5.000"), "{}", report);
+    }
+}
@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 use concerto_common::ir::IrConnection;
 
@@ -9,7 +12,7 @@ use crate::error::{Result, RuntimeError};
 // ============================================================================
 
 /// A chat message in a conversation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
@@ -25,7 +28,7 @@ pub struct ToolCallRequest {
 }
 
 /// Tool schema sent to the LLM for function calling.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ToolSchema {
     pub name: String,
     pub description: String,
@@ -33,14 +36,14 @@ pub struct ToolSchema {
 }
 
 /// Response format specification (e.g., JSON Schema for structured output).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ResponseFormat {
     pub format_type: String,
     pub json_schema: Option<serde_json::Value>,
 }
 
 /// A request to an LLM provider.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ChatRequest {
     pub model: String,
     pub messages: Vec<ChatMessage>,
@@ -48,6 +51,14 @@ pub struct ChatRequest {
     pub max_tokens: Option<u32>,
     pub tools: Option<Vec<ToolSchema>>,
     pub response_format: Option<ResponseFormat>,
+    /// Extra HTTP headers from the connection config, sent as-is.
+    pub headers: Vec<(String, String)>,
+    /// Endpoint override from the model's `base_url`; replaces the
+    /// connection's base URL for this request only.
+    pub base_url: Option<String>,
+    /// Sampling seed for reproducible output; providers without seed
+    /// support ignore it.
+    pub seed: Option<i64>,
 }
 
 /// A response from an LLM provider.
@@ -68,6 +79,34 @@ pub trait LlmProvider: Send + Sync {
     fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse>;
 }
 
+/// Call `provider` with an optional deadline. Under a limit the request runs
+/// on its own thread; if no answer arrives in time the caller gets a timeout
+/// error straight away and the late result is dropped when it lands. A zero
+/// limit means the budget is already spent, so the provider is not called.
+pub fn chat_completion_within(
+    provider: Arc<dyn LlmProvider>,
+    request: ChatRequest,
+    limit: Option<Duration>,
+) -> Result<ChatResponse> {
+    let Some(limit) = limit else {
+        return provider.chat_completion(request);
+    };
+    if limit.is_zero() {
+        return Err(RuntimeError::ProviderTimeout(limit));
+    }
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(provider.chat_completion(request));
+    });
+    match rx.recv_timeout(limit) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(RuntimeError::ProviderTimeout(limit)),
+        Err(RecvTimeoutError::Disconnected) => Err(RuntimeError::CallError(
+            "provider call panicked".to_string(),
+        )),
+    }
+}
+
 // ============================================================================
 // Mock Provider (for testing and when no API key is set)
 // ============================================================================
@@ -83,22 +122,11 @@ impl LlmProvider for MockProvider {
             .last()
             .map(|m| m.content.clone())
             .unwrap_or_default();
-        let truncated = if prompt.len() > 50 {
-            &prompt[..50]
-        } else {
-            &prompt
-        };
-
-        // If response_format is json_schema, return mock JSON matching the schema
-        let text = if let Some(ref rf) = request.response_format {
-            if let Some(ref schema) = rf.json_schema {
-                mock_json_from_schema(schema)
-            } else {
-                format!("[mock response to: {}]", truncated)
-            }
-        } else {
-            format!("[mock response to: {}]", truncated)
-        };
+        let schema = request
+            .response_format
+            .as_ref()
+            .and_then(|rf| rf.json_schema.as_ref());
+        let text = mock_response_text(&prompt, schema);
 
         Ok(ChatResponse {
             text,
@@ -110,6 +138,17 @@ impl LlmProvider for MockProvider {
     }
 }
 
+/// The canned text the mock provider answers `prompt` with: mock JSON
+/// matching `json_schema` when one is given, otherwise a placeholder that
+/// echoes the start of the prompt.
+pub fn mock_response_text(prompt: &str, json_schema: Option<&serde_json::Value>) -> String {
+    if let Some(schema) = json_schema {
+        return mock_json_from_schema(schema);
+    }
+    let truncated: String = prompt.chars().take(50).collect();
+    format!("[mock response to: {}]", truncated)
+}
+
 /// Generate mock JSON from a JSON Schema.
 fn mock_json_from_schema(schema: &serde_json::Value) -> String {
     let mut result = serde_json::Map::new();
@@ -143,14 +182,115 @@ fn mock_json_from_schema(schema: &serde_json::Value) -> String {
     serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
 }
 
+// ============================================================================
+// Request coalescing
+// ============================================================================
+
+/// Wraps a provider so that concurrent identical requests share a single
+/// in-flight call. The first caller performs the request; callers arriving
+/// with the same request while it is running wait for and reuse its result.
+/// Nothing is cached once the call completes.
+pub struct CoalescingProvider {
+    inner: Box<dyn LlmProvider>,
+    in_flight: Mutex<HashMap<String, Arc<InFlight>>>,
+}
+
+/// Result slot shared between the caller performing a request and the
+/// callers waiting on it.
+#[derive(Default)]
+struct InFlight {
+    result: Mutex<Option<Result<ChatResponse>>>,
+    done: Condvar,
+}
+
+impl CoalescingProvider {
+    pub fn new(inner: Box<dyn LlmProvider>) -> Self {
+        CoalescingProvider {
+            inner,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Key identifying identical requests (every field, including headers),
+/// as the request serialized to JSON.
+fn request_key(request: &ChatRequest) -> String {
+    serde_json::to_string(request).expect("ChatRequest serializes to JSON")
+}
+
+/// Finishes the leader's flight when dropped, so waiters are released even
+/// if the inner provider panics: the key is removed and the slot is filled
+/// with the recorded result, or an error when there is none.
+struct FlightGuard<'a> {
+    in_flight: &'a Mutex<HashMap<String, Arc<InFlight>>>,
+    key: String,
+    flight: Arc<InFlight>,
+    result: Option<Result<ChatResponse>>,
+}
+
+impl Drop for FlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
+        let result = self.result.take().unwrap_or_else(|| {
+            Err(RuntimeError::CallError(
+                "provider call panicked".to_string(),
+            ))
+        });
+        *self.flight.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+        self.flight.done.notify_all();
+    }
+}
+
+impl LlmProvider for CoalescingProvider {
+    fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let key = request_key(&request);
+        let (flight, is_leader) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(flight) => (Arc::clone(flight), false),
+                None => {
+                    let flight = Arc::new(InFlight::default());
+                    in_flight.insert(key.clone(), Arc::clone(&flight));
+                    (flight, true)
+                }
+            }
+        };
+
+        if !is_leader {
+            let mut slot = flight.result.lock().unwrap_or_else(|e| e.into_inner());
+            while slot.is_none() {
+                slot = flight.done.wait(slot).unwrap();
+            }
+            return slot.clone().unwrap();
+        }
+
+        let mut guard = FlightGuard {
+            in_flight: &self.in_flight,
+            key,
+            flight,
+            result: None,
+        };
+        let result = self.inner.chat_completion(request);
+        guard.result = Some(result.clone());
+        result
+    }
+}
+
 // ============================================================================
 // Connection Manager
 // ============================================================================
 
 /// Manages LLM provider instances, one per connection name.
+/// Configured providers are wrapped in a `CoalescingProvider`.
 pub struct ConnectionManager {
-    providers: HashMap<String, Box<dyn LlmProvider>>,
-    fallback: MockProvider,
+    providers: HashMap<String, Arc<dyn LlmProvider>>,
+    headers: HashMap<String, Vec<(String, String)>>,
+    default_models: HashMap<String, String>,
+    fallback: Arc<dyn LlmProvider>,
+    mock_all: bool,
 }
 
 impl ConnectionManager {
@@ -158,12 +298,18 @@ impl ConnectionManager {
     /// For each connection, attempts to resolve API key from env and
     /// create the appropriate provider. Falls back to MockProvider.
     pub fn from_connections(connections: &HashMap<String, IrConnection>) -> Self {
-        let mut providers: HashMap<String, Box<dyn LlmProvider>> = HashMap::new();
+        let mut providers: HashMap<String, Arc<dyn LlmProvider>> = HashMap::new();
+        let mut headers = HashMap::new();
+        let mut default_models = HashMap::new();
 
         for (name, conn) in connections {
+            headers.insert(name.clone(), connection_headers(conn));
+            if let Some(model) = conn.config.get("default_model").and_then(|v| v.as_str()) {
+                default_models.insert(name.clone(), model.to_string());
+            }
             match create_provider(conn) {
                 Ok(provider) => {
-                    providers.insert(name.clone(), provider);
+                    providers.insert(name.clone(), Arc::new(CoalescingProvider::new(provider)));
                 }
                 Err(e) => {
                     eprintln!(
@@ -176,17 +322,45 @@ impl ConnectionManager {
 
         ConnectionManager {
             providers,
-            fallback: MockProvider,
+            headers,
+            default_models,
+            fallback: Arc::new(MockProvider),
+            mock_all: false,
         }
     }
 
+    /// Replace the provider for a connection name.
+    pub fn set_provider(&mut self, name: &str, provider: Box<dyn LlmProvider>) {
+        self.providers.insert(
+            name.to_string(),
+            Arc::new(CoalescingProvider::new(provider)),
+        );
+    }
+
+    /// Get the extra request headers configured for a connection name.
+    pub fn headers(&self, name: &str) -> &[(String, String)] {
+        self.headers.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Get the `default_model` configured for a connection name, used by
+    /// models that do not set `base:` themselves.
+    pub fn default_model(&self, name: &str) -> Option<&str> {
+        self.default_models.get(name).map(String::as_str)
+    }
+
+    /// Route every connection to the MockProvider, whatever is configured.
+    pub fn set_mock_all(&mut self, enabled: bool) {
+        self.mock_all = enabled;
+    }
+
     /// Get the provider for a connection name.
-    /// Returns the fallback MockProvider if no real provider is configured.
-    pub fn get_provider(&self, name: &str) -> &dyn LlmProvider {
-        self.providers
-            .get(name)
-            .map(|p| p.as_ref())
-            .unwrap_or(&self.fallback)
+    /// Returns the fallback MockProvider if no real provider is configured,
+    /// or for every name while mock-all is on.
+    pub fn get_provider(&self, name: &str) -> Arc<dyn LlmProvider> {
+        if self.mock_all {
+            return Arc::clone(&self.fallback);
+        }
+        Arc::clone(self.providers.get(name).unwrap_or(&self.fallback))
     }
 }
 
@@ -194,7 +368,10 @@ impl Default for ConnectionManager {
     fn default() -> Self {
         ConnectionManager {
             providers: HashMap::new(),
-            fallback: MockProvider,
+            headers: HashMap::new(),
+            default_models: HashMap::new(),
+            fallback: Arc::new(MockProvider),
+            mock_all: false,
         }
     }
 }
@@ -209,21 +386,7 @@ fn create_provider(conn: &IrConnection) -> Result<Box<dyn LlmProvider>> {
         .and_then(|v| v.as_str())
         .map(String::from);
 
-    // Determine provider type: explicit "provider" field (from Concerto.toml),
-    // or fall back to name/base_url heuristics (legacy connect blocks).
-    let provider_type = config
-        .get("provider")
-        .and_then(|v| v.as_str())
-        .map(String::from)
-        .unwrap_or_else(|| {
-            if conn.name == "anthropic"
-                || base_url.as_deref().is_some_and(|u| u.contains("anthropic"))
-            {
-                "anthropic".to_string()
-            } else {
-                "openai".to_string()
-            }
-        });
+    let provider_type = provider_type(conn);
 
     // Ollama and local providers don't need an API key
     if provider_type == "ollama" {
@@ -234,6 +397,19 @@ fn create_provider(conn: &IrConnection) -> Result<Box<dyn LlmProvider>> {
         )));
     }
 
+    // Generic HTTP endpoints: the API key is optional and sent as a bearer token
+    if provider_type == "http" {
+        let url = base_url
+            .ok_or_else(|| RuntimeError::CallError("http connection needs base_url".into()))?;
+        let http = config
+            .get("http")
+            .ok_or_else(|| RuntimeError::CallError("http connection needs an http table".into()))?;
+        let api_key = resolve_api_key(config).ok().filter(|k| !k.is_empty());
+        return Ok(Box::new(crate::providers::http::HttpProvider::from_config(
+            url, api_key, http,
+        )?));
+    }
+
     // Try to resolve API key (may be env("VAR"), direct string, or api_key_env)
     let api_key = resolve_api_key(config)?;
     if api_key.is_empty() {
@@ -253,6 +429,52 @@ fn create_provider(conn: &IrConnection) -> Result<Box<dyn LlmProvider>> {
     }
 }
 
+/// Determine a connection's provider type: the explicit "provider" field
+/// (from Concerto.toml), or name/base_url heuristics (legacy connect blocks).
+fn provider_type(conn: &IrConnection) -> String {
+    let config = &conn.config;
+    config
+        .get("provider")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| {
+            let base_url = config.get("base_url").and_then(|v| v.as_str());
+            if conn.name == "anthropic" || base_url.is_some_and(|u| u.contains("anthropic")) {
+                "anthropic".to_string()
+            } else {
+                "openai".to_string()
+            }
+        })
+}
+
+/// Collect the extra request headers for a connection.
+///
+/// On OpenAI connections, `organization` and `project` map to the
+/// `OpenAI-Organization` and `OpenAI-Project` headers; other providers
+/// ignore them. Entries in the `headers` table are added after them in
+/// name order.
+fn connection_headers(conn: &IrConnection) -> Vec<(String, String)> {
+    let config = &conn.config;
+    let mut headers = Vec::new();
+    if provider_type(conn) == "openai" {
+        if let Some(org) = config.get("organization").and_then(|v| v.as_str()) {
+            headers.push(("OpenAI-Organization".to_string(), org.to_string()));
+        }
+        if let Some(project) = config.get("project").and_then(|v| v.as_str()) {
+            headers.push(("OpenAI-Project".to_string(), project.to_string()));
+        }
+    }
+    if let Some(table) = config.get("headers").and_then(|v| v.as_object()) {
+        let mut custom: Vec<(String, String)> = table
+            .iter()
+            .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+            .collect();
+        custom.sort();
+        headers.extend(custom);
+    }
+    headers
+}
+
 /// Resolve an API key from connection config.
 /// Supports three formats:
 /// - `api_key: "sk-..."` — direct string (legacy connect blocks)
@@ -308,6 +530,9 @@ mod tests {
             max_tokens: None,
             tools: None,
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let response = provider.chat_completion(request).unwrap();
         assert!(response.text.contains("Hello world"));
@@ -315,6 +540,13 @@ mod tests {
         assert!(response.tokens_in > 0);
     }
 
+    #[test]
+    fn mock_response_truncates_on_char_boundaries() {
+        let prompt = format!("{}é and more", "a".repeat(49));
+        let text = mock_response_text(&prompt, None);
+        assert_eq!(text, format!("[mock response to: {}é]", "a".repeat(49)));
+    }
+
     #[test]
     fn mock_provider_json_schema_response() {
         let provider = MockProvider;
@@ -339,6 +571,9 @@ mod tests {
                 format_type: "json_schema".to_string(),
                 json_schema: Some(schema),
             }),
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let response = provider.chat_completion(request).unwrap();
         // Should be valid JSON
@@ -358,6 +593,9 @@ mod tests {
             max_tokens: None,
             tools: None,
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         // Should work (mock provider)
         let result = provider.chat_completion(request);
@@ -413,5 +651,204 @@ mod tests {
         };
         let result = create_provider(&conn);
         assert!(result.is_ok());
+
+        // Generic HTTP endpoints need no API key, only the template config
+        let conn = IrConnection {
+            name: "custom".to_string(),
+            config: serde_json::json!({
+                "provider": "http",
+                "base_url": "http://localhost:8080/generate",
+                "http": { "request_template": { "inputs": "{{prompt}}" }, "text_path": "$.text" },
+            }),
+        };
+        assert!(create_provider(&conn).is_ok());
+    }
+
+    #[test]
+    fn openai_headers_only_on_openai_connections() {
+        let conn = |name: &str, provider: Option<&str>| {
+            let mut config = serde_json::json!({
+                "organization": "org-1",
+                "project": "proj-1",
+                "headers": { "X-Team": "core" },
+            });
+            if let Some(provider) = provider {
+                config["provider"] = provider.into();
+            }
+            IrConnection {
+                name: name.to_string(),
+                config,
+            }
+        };
+        let names = |conn: &IrConnection| -> Vec<String> {
+            connection_headers(conn)
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect()
+        };
+
+        let openai = ["OpenAI-Organization", "OpenAI-Project", "X-Team"];
+        assert_eq!(names(&conn("main", Some("openai"))), openai);
+        assert_eq!(names(&conn("main", None)), openai);
+        for provider in ["anthropic", "ollama", "http"] {
+            assert_eq!(names(&conn("main", Some(provider))), ["X-Team"]);
+        }
+        assert_eq!(names(&conn("anthropic", None)), ["X-Team"]);
+    }
+
+    /// Counts calls and holds each one open long enough for a concurrent
+    /// duplicate to arrive.
+    struct SlowCountingProvider {
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl LlmProvider for SlowCountingProvider {
+        fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            MockProvider.chat_completion(request)
+        }
+    }
+
+    fn prompt_request(prompt: &str) -> ChatRequest {
+        ChatRequest {
+            model: "test".to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+                tool_call_id: None,
+            }],
+            temperature: Some(0.2),
+            max_tokens: None,
+            tools: None,
+            response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn coalescing_provider_shares_concurrent_identical_requests() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut manager = ConnectionManager::default();
+        manager.set_provider(
+            "llm",
+            Box::new(SlowCountingProvider {
+                calls: Arc::clone(&calls),
+            }),
+        );
+        let provider = manager.get_provider("llm");
+        let barrier = std::sync::Barrier::new(2);
+
+        let texts: Vec<String> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        provider
+                            .chat_completion(prompt_request("same"))
+                            .unwrap()
+                            .text
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(texts[0], texts[1]);
+
+        // Different or sequential requests are not coalesced
+        provider.chat_completion(prompt_request("same")).unwrap();
+        provider.chat_completion(prompt_request("other")).unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    /// Panics on its first call (after giving a duplicate time to join the
+    /// flight) and answers normally afterwards.
+    struct PanickingOnceProvider {
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl LlmProvider for PanickingOnceProvider {
+        fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+            if self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                panic!("provider blew up");
+            }
+            MockProvider.chat_completion(request)
+        }
+    }
+
+    #[test]
+    fn coalescing_provider_releases_waiters_when_the_call_panics() {
+        let provider = CoalescingProvider::new(Box::new(PanickingOnceProvider {
+            calls: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+        }));
+        let barrier = std::sync::Barrier::new(2);
+
+        let outcomes: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        provider.chat_completion(prompt_request("same"))
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join()).collect()
+        });
+
+        // The leader's thread panicked; the waiter got an error instead of
+        // blocking forever.
+        assert_eq!(outcomes.iter().filter(|o| o.is_err()).count(), 1);
+        let waiter = outcomes.into_iter().find_map(|o| o.ok()).unwrap();
+        assert!(waiter
+            .unwrap_err()
+            .to_string()
+            .contains("provider call panicked"));
+
+        // The key was released, so a later identical request runs.
+        assert!(provider.chat_completion(prompt_request("same")).is_ok());
+    }
+
+    /// Times out after giving a duplicate time to join the flight.
+    struct SlowTimeoutProvider;
+
+    impl LlmProvider for SlowTimeoutProvider {
+        fn chat_completion(&self, _request: ChatRequest) -> Result<ChatResponse> {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            Err(RuntimeError::ProviderTimeout(
+                std::time::Duration::from_secs(5),
+            ))
+        }
+    }
+
+    #[test]
+    fn coalescing_provider_gives_waiters_the_leaders_error() {
+        let provider = CoalescingProvider::new(Box::new(SlowTimeoutProvider));
+        let barrier = std::sync::Barrier::new(2);
+
+        let errors: Vec<RuntimeError> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        provider
+                            .chat_completion(prompt_request("same"))
+                            .unwrap_err()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for err in errors {
+            assert!(
+                matches!(err, RuntimeError::ProviderTimeout(d) if d.as_secs() == 5),
+                "{err:?}"
+            );
+        }
     }
 }
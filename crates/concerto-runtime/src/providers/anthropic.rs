@@ -150,15 +150,20 @@ impl AnthropicProvider {
 
 impl LlmProvider for AnthropicProvider {
     fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let url = format!("{}/v1/messages", self.base_url.trim_end_matches('/'));
+        let base_url = request.base_url.as_deref().unwrap_or(&self.base_url);
+        let url = format!("{}/v1/messages", base_url.trim_end_matches('/'));
         let body = Self::build_request_body(&request);
 
-        let response = self
+        let mut http_request = self
             .client
             .post(&url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
+            .header("Content-Type", "application/json");
+        for (name, value) in &request.headers {
+            http_request = http_request.header(name, value);
+        }
+        let response = http_request
             .json(&body)
             .send()
             .map_err(|e| RuntimeError::CallError(format!("Anthropic HTTP error: {}", e)))?;
@@ -211,6 +216,9 @@ mod tests {
             max_tokens: Some(200),
             tools: None,
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let body = AnthropicProvider::build_request_body(&request);
         assert_eq!(body["model"], "claude-sonnet-4-5-20250929");
@@ -239,6 +247,9 @@ mod tests {
                 parameters: serde_json::json!({"type": "object"}),
             }]),
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let body = AnthropicProvider::build_request_body(&request);
         assert_eq!(body["tools"][0]["name"], "get_weather");
@@ -254,6 +265,9 @@ mod tests {
             max_tokens: None,
             tools: None,
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let body = AnthropicProvider::build_request_body(&request);
         assert_eq!(body["max_tokens"], 4096);
@@ -308,4 +322,33 @@ mod tests {
         let result = AnthropicProvider::parse_response(&json);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn request_base_url_overrides_connection_url() {
+        let (base, received) = crate::providers::serve_once(
+            r#"{"content": [{"type": "text", "text": "pong"}], "model": "claude", "usage": {"input_tokens": 1, "output_tokens": 1}}"#,
+        );
+        // The connection URL is unroutable; only the override can answer.
+        let provider =
+            AnthropicProvider::new("key".to_string(), Some("http://127.0.0.1:9".to_string()));
+        let request = ChatRequest {
+            model: "claude".to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: "ping".to_string(),
+                tool_call_id: None,
+            }],
+            temperature: None,
+            max_tokens: None,
+            tools: None,
+            response_format: None,
+            headers: Vec::new(),
+            base_url: Some(base),
+            seed: None,
+        };
+        assert_eq!(provider.chat_completion(request).unwrap().text, "pong");
+        let received = received.recv().unwrap();
+        assert_eq!(received.path, "/v1/messages");
+        assert!(received.body.contains("ping"));
+    }
 }
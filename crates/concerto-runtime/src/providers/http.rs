@@ -0,0 +1,438 @@
+use reqwest::blocking::Client;
+
+use crate::error::{Result, RuntimeError};
+use crate::provider::{ChatRequest, ChatResponse, LlmProvider};
+
+/// Generic HTTP provider for endpoints that match neither the OpenAI nor the
+/// Anthropic API shape.
+///
+/// The request body is built from a JSON template whose string values may
+/// contain placeholders (`{{prompt}}`, `{{system}}`, `{{messages}}`,
+/// `{{model}}`, `{{temperature}}`, `{{max_tokens}}`). The response text and
+/// token counts are extracted with JSONPath expressions.
+pub struct HttpProvider {
+    client: Client,
+    api_key: Option<String>,
+    url: String,
+    request_template: serde_json::Value,
+    text_path: JsonPath,
+    tokens_in_path: Option<JsonPath>,
+    tokens_out_path: Option<JsonPath>,
+}
+
+impl HttpProvider {
+    /// Build a provider from the connection's `http` config table.
+    pub fn from_config(
+        url: String,
+        api_key: Option<String>,
+        http: &serde_json::Value,
+    ) -> Result<Self> {
+        let request_template = http
+            .get("request_template")
+            .cloned()
+            .ok_or_else(|| RuntimeError::CallError("http: missing request_template".into()))?;
+        let path = |key: &str| -> Result<Option<JsonPath>> {
+            http.get(key)
+                .and_then(|v| v.as_str())
+                .map(JsonPath::parse)
+                .transpose()
+        };
+        let text_path = path("text_path")?
+            .ok_or_else(|| RuntimeError::CallError("http: missing text_path".into()))?;
+        Ok(HttpProvider {
+            client: Client::new(),
+            api_key,
+            url,
+            request_template,
+            text_path,
+            tokens_in_path: path("tokens_in_path")?,
+            tokens_out_path: path("tokens_out_path")?,
+        })
+    }
+
+    /// Substitute the request into the template.
+    ///
+    /// A string that is exactly one placeholder is replaced by the typed JSON
+    /// value (so `"{{max_tokens}}"` becomes a number); placeholders inside
+    /// longer strings are replaced textually.
+    pub fn build_request_body(&self, request: &ChatRequest) -> serde_json::Value {
+        let prompt = request
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.role == "user")
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        let system = request
+            .messages
+            .iter()
+            .filter(|m| m.role == "system")
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let messages: Vec<serde_json::Value> = request
+            .messages
+            .iter()
+            .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+            .collect();
+        let vars = [
+            ("prompt", serde_json::json!(prompt)),
+            ("system", serde_json::json!(system)),
+            ("messages", serde_json::json!(messages)),
+            ("model", serde_json::json!(request.model)),
+            ("temperature", serde_json::json!(request.temperature)),
+            ("max_tokens", serde_json::json!(request.max_tokens)),
+        ];
+        substitute(&self.request_template, &vars)
+    }
+
+    /// Extract the text and token counts from a response body.
+    pub fn parse_response(&self, json: &serde_json::Value, model: &str) -> Result<ChatResponse> {
+        let text = match self.text_path.get(json) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(serde_json::Value::Null) | None => {
+                return Err(RuntimeError::CallError(format!(
+                    "HTTP provider: no value at '{}' in response",
+                    self.text_path.source
+                )))
+            }
+            Some(other) => other.to_string(),
+        };
+        let tokens = |path: &Option<JsonPath>| {
+            path.as_ref()
+                .and_then(|p| p.get(json))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0)
+        };
+        Ok(ChatResponse {
+            text,
+            tokens_in: tokens(&self.tokens_in_path),
+            tokens_out: tokens(&self.tokens_out_path),
+            model: model.to_string(),
+            tool_calls: Vec::new(),
+        })
+    }
+}
+
+impl LlmProvider for HttpProvider {
+    fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let body = self.build_request_body(&request);
+
+        let mut http_request = self
+            .client
+            .post(request.base_url.as_deref().unwrap_or(&self.url))
+            .header("Content-Type", "application/json");
+        if let Some(ref key) = self.api_key {
+            http_request = http_request.header("Authorization", format!("Bearer {}", key));
+        }
+        for (name, value) in &request.headers {
+            http_request = http_request.header(name, value);
+        }
+        let response = http_request
+            .json(&body)
+            .send()
+            .map_err(|e| RuntimeError::CallError(format!("HTTP provider error: {}", e)))?;
+
+        let status = response.status();
+        let response_text = response
+            .text()
+            .map_err(|e| RuntimeError::CallError(format!("HTTP provider read error: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(RuntimeError::CallError(format!(
+                "HTTP provider error ({}): {}",
+                status, response_text
+            )));
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&response_text).map_err(|e| {
+            RuntimeError::CallError(format!("HTTP provider JSON parse error: {}", e))
+        })?;
+
+        self.parse_response(&json, &request.model)
+    }
+}
+
+/// Recursively replace `{{name}}` placeholders in a JSON template.
+fn substitute(
+    template: &serde_json::Value,
+    vars: &[(&str, serde_json::Value)],
+) -> serde_json::Value {
+    match template {
+        serde_json::Value::String(s) => {
+            for (name, value) in vars {
+                if s == &format!("{{{{{}}}}}", name) {
+                    return value.clone();
+                }
+            }
+            serde_json::Value::String(substitute_text(s, vars))
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(|v| substitute(v, vars)).collect())
+        }
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), substitute(v, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Replace `{{name}}` placeholders inside a string in one pass over the
+/// template, so substituted text (such as a prompt containing `{{model}}`)
+/// is never expanded again.
+fn substitute_text(text: &str, vars: &[(&str, serde_json::Value)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let replaced = after.find("}}").and_then(|close| {
+            let name = &after[..close];
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (value, close))
+        });
+        match replaced {
+            Some((value, close)) => {
+                match value {
+                    serde_json::Value::String(v) => out.push_str(v),
+                    serde_json::Value::Null => {}
+                    other => out.push_str(&other.to_string()),
+                }
+                rest = &after[close + 2..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// One step of a JSONPath expression.
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A minimal JSONPath: `$` followed by `.key`, `[index]`, or `['key']` steps.
+#[derive(Debug, Clone)]
+struct JsonPath {
+    source: String,
+    segments: Vec<PathSegment>,
+}
+
+impl JsonPath {
+    fn parse(source: &str) -> Result<Self> {
+        let invalid = |why: &str| {
+            RuntimeError::CallError(format!("http: invalid JSONPath '{}': {}", source, why))
+        };
+        let rest = source
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with '$'"))?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut segments = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    let start = i + 1;
+                    i = start;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    if i == start {
+                        return Err(invalid("empty key"));
+                    }
+                    segments.push(PathSegment::Key(chars[start..i].iter().collect()));
+                }
+                '[' => {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|&c| c == ']')
+                        .map(|p| i + p)
+                        .ok_or_else(|| invalid("unclosed '['"))?;
+                    let inner: String = chars[i + 1..close].iter().collect();
+                    let quoted = inner.len() >= 2
+                        && ((inner.starts_with('\'') && inner.ends_with('\''))
+                            || (inner.starts_with('"') && inner.ends_with('"')));
+                    if quoted {
+                        segments.push(PathSegment::Key(inner[1..inner.len() - 1].to_string()));
+                    } else {
+                        let index = inner
+                            .trim()
+                            .parse::<usize>()
+                            .map_err(|_| invalid("index must be a non-negative integer"))?;
+                        segments.push(PathSegment::Index(index));
+                    }
+                    i = close + 1;
+                }
+                c => return Err(invalid(&format!("unexpected '{}'", c))),
+            }
+        }
+        Ok(JsonPath {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    fn get<'a>(&self, json: &'a serde_json::Value) -> Option<&'a serde_json::Value> {
+        self.segments
+            .iter()
+            .try_fold(json, |current, segment| match segment {
+                PathSegment::Key(key) => current.get(key.as_str()),
+                PathSegment::Index(index) => current.get(*index),
+            })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ChatMessage;
+    use crate::providers::serve_once;
+
+    fn request(prompt: &str) -> ChatRequest {
+        ChatRequest {
+            model: "local-7b".to_string(),
+            messages: vec![
+                ChatMessage {
+                    role: "system".to_string(),
+                    content: "Be brief.".to_string(),
+                    tool_call_id: None,
+                },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: prompt.to_string(),
+                    tool_call_id: None,
+                },
+            ],
+            temperature: None,
+            max_tokens: Some(64),
+            tools: None,
+            response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
+        }
+    }
+
+    fn provider(url: String, http: serde_json::Value) -> HttpProvider {
+        HttpProvider::from_config(url, None, &http).unwrap()
+    }
+
+    #[test]
+    fn json_path_parses_keys_and_indices() {
+        let path = JsonPath::parse("$.output[0]['generated text']").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("output".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Key("generated text".to_string()),
+            ]
+        );
+        let json = serde_json::json!({"output": [{"generated text": "hi"}]});
+        assert_eq!(path.get(&json), Some(&serde_json::json!("hi")));
+        assert!(JsonPath::parse("output.text").is_err());
+        assert!(JsonPath::parse("$.a[x]").is_err());
+    }
+
+    #[test]
+    fn build_request_substitutes_placeholders() {
+        let p = provider(
+            "http://unused".to_string(),
+            serde_json::json!({
+                "request_template": {
+                    "inputs": "{{system}}\n\n{{prompt}}",
+                    "parameters": { "max_new_tokens": "{{max_tokens}}", "temp": "{{temperature}}" },
+                    "model": "{{model}}",
+                },
+                "text_path": "$.text",
+            }),
+        );
+        let body = p.build_request_body(&request("Hello"));
+        assert_eq!(body["inputs"], "Be brief.\n\nHello");
+        assert_eq!(body["parameters"]["max_new_tokens"], 64);
+        assert!(body["parameters"]["temp"].is_null());
+        assert_eq!(body["model"], "local-7b");
+    }
+
+    #[test]
+    fn build_request_does_not_expand_placeholders_in_prompt() {
+        let p = provider(
+            "http://unused".to_string(),
+            serde_json::json!({
+                "request_template": { "inputs": "{{prompt}} ({{model}})" },
+                "text_path": "$.text",
+            }),
+        );
+        let body = p.build_request_body(&request("say {{system}} and {{model}}"));
+        assert_eq!(body["inputs"], "say {{system}} and {{model}} (local-7b)");
+    }
+
+    #[test]
+    fn parse_response_missing_text_errors() {
+        let p = provider(
+            "http://unused".to_string(),
+            serde_json::json!({ "request_template": {}, "text_path": "$.result.text" }),
+        );
+        let err = p
+            .parse_response(&serde_json::json!({"result": {}}), "m")
+            .unwrap_err();
+        assert!(err.to_string().contains("$.result.text"), "got: {}", err);
+    }
+
+    #[test]
+    fn chat_completion_against_custom_endpoint() {
+        let (base, received) = serve_once(
+            r#"{"output": [{"generated": "Paris"}], "stats": {"prompt": 12, "completion": 3}}"#,
+        );
+        let p = provider(
+            format!("{}/generate", base),
+            serde_json::json!({
+                "request_template": { "inputs": "{{prompt}}", "options": { "n": "{{max_tokens}}" } },
+                "text_path": "$.output[0].generated",
+                "tokens_in_path": "$.stats.prompt",
+                "tokens_out_path": "$.stats.completion",
+            }),
+        );
+        let response = p.chat_completion(request("Capital of France?")).unwrap();
+        assert_eq!(response.text, "Paris");
+        assert_eq!(response.tokens_in, 12);
+        assert_eq!(response.tokens_out, 3);
+        assert_eq!(response.model, "local-7b");
+
+        let received = received.recv().unwrap();
+        assert_eq!(received.path, "/generate");
+        let sent: serde_json::Value = serde_json::from_str(&received.body).unwrap();
+        assert_eq!(
+            sent,
+            serde_json::json!({ "inputs": "Capital of France?", "options": { "n": 64 } })
+        );
+    }
+
+    #[test]
+    fn request_base_url_overrides_connection_url() {
+        let (base, received) = serve_once(r#"{"text": "pong"}"#);
+        // The connection URL is unroutable; only the override can answer.
+        let p = provider(
+            "http://127.0.0.1:9/unused".to_string(),
+            serde_json::json!({ "request_template": { "inputs": "{{prompt}}" }, "text_path": "$.text" }),
+        );
+        let mut req = request("ping");
+        req.base_url = Some(format!("{}/generate", base));
+        let response = p.chat_completion(req).unwrap();
+        assert_eq!(response.text, "pong");
+        assert!(received.recv().unwrap().body.contains("ping"));
+    }
+}
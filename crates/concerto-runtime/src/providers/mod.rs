@@ -1,2 +1,82 @@
 pub mod anthropic;
+pub mod http;
 pub mod openai;
+
+/// A request received by [`serve`]: the request path, the request line and
+/// headers as sent, and the body.
+#[cfg(test)]
+pub(crate) struct ReceivedRequest {
+    pub path: String,
+    pub head: String,
+    pub body: String,
+}
+
+/// Answer one connection per `(status, body)` in `responses`, in order, on a
+/// local port. Returns the base URL (`http://127.0.0.1:<port>`) and a channel
+/// that yields each request before its response is written.
+#[cfg(test)]
+pub(crate) fn serve(
+    responses: Vec<(u16, String)>,
+) -> (String, std::sync::mpsc::Receiver<ReceivedRequest>) {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for (status, body) in responses {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 1024];
+            let header_end = loop {
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+                match stream.read(&mut chunk) {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+            };
+            let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+            let path = head
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or_default()
+                .to_string();
+            let content_length = head
+                .to_lowercase()
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            while buf.len() < header_end + content_length {
+                match stream.read(&mut chunk) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+            let request_body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+            let _ = tx.send(ReceivedRequest {
+                path,
+                head,
+                body: request_body,
+            });
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (format!("http://{}", addr), rx)
+}
+
+/// Serve a single canned JSON response with [`serve`].
+#[cfg(test)]
+pub(crate) fn serve_once(body: &str) -> (String, std::sync::mpsc::Receiver<ReceivedRequest>) {
+    serve(vec![(200, body.to_string())])
+}
@@ -49,6 +49,9 @@ impl OpenAiProvider {
         if let Some(max) = request.max_tokens {
             body["max_tokens"] = serde_json::json!(max);
         }
+        if let Some(seed) = request.seed {
+            body["seed"] = serde_json::json!(seed);
+        }
 
         if let Some(ref tools) = request.tools {
             let tool_defs: Vec<serde_json::Value> = tools
@@ -158,17 +161,19 @@ impl OpenAiProvider {
 
 impl LlmProvider for OpenAiProvider {
     fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let url = format!(
-            "{}/v1/chat/completions",
-            self.base_url.trim_end_matches('/')
-        );
+        let base_url = request.base_url.as_deref().unwrap_or(&self.base_url);
+        let url = format!("{}/v1/chat/completions", base_url.trim_end_matches('/'));
         let body = Self::build_request_body(&request);
 
-        let response = self
+        let mut http_request = self
             .client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
+            .header("Content-Type", "application/json");
+        for (name, value) in &request.headers {
+            http_request = http_request.header(name, value);
+        }
+        let response = http_request
             .json(&body)
             .send()
             .map_err(|e| RuntimeError::CallError(format!("OpenAI HTTP error: {}", e)))?;
@@ -214,6 +219,9 @@ mod tests {
             max_tokens: Some(100),
             tools: None,
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let body = OpenAiProvider::build_request_body(&request);
         assert_eq!(body["model"], "gpt-4");
@@ -222,6 +230,24 @@ mod tests {
         assert_eq!(body["temperature"], 0.7);
         assert_eq!(body["max_tokens"], 100);
         assert!(body.get("tools").is_none());
+        assert!(body.get("seed").is_none());
+    }
+
+    #[test]
+    fn build_request_sends_seed() {
+        let request = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![],
+            temperature: Some(0.0),
+            max_tokens: None,
+            tools: None,
+            response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: Some(1234),
+        };
+        let body = OpenAiProvider::build_request_body(&request);
+        assert_eq!(body["seed"], 1234);
     }
 
     #[test]
@@ -242,6 +268,9 @@ mod tests {
                 }),
             }]),
             response_format: None,
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let body = OpenAiProvider::build_request_body(&request);
         assert_eq!(body["tools"][0]["type"], "function");
@@ -262,6 +291,9 @@ mod tests {
                 format_type: "json_schema".to_string(),
                 json_schema: Some(schema.clone()),
             }),
+            headers: Vec::new(),
+            base_url: None,
+            seed: None,
         };
         let body = OpenAiProvider::build_request_body(&request);
         assert_eq!(body["response_format"]["type"], "json_schema");
@@ -324,4 +356,33 @@ mod tests {
         let result = OpenAiProvider::parse_response(&json);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn request_base_url_overrides_connection_url() {
+        let (base, received) = crate::providers::serve_once(
+            r#"{"choices": [{"message": {"content": "pong"}}], "model": "gpt-4o", "usage": {"prompt_tokens": 1, "completion_tokens": 1}}"#,
+        );
+        // The connection URL is unroutable; only the override can answer.
+        let provider =
+            OpenAiProvider::new("key".to_string(), Some("http://127.0.0.1:9".to_string()));
+        let request = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: "ping".to_string(),
+                tool_call_id: None,
+            }],
+            temperature: None,
+            max_tokens: None,
+            tools: None,
+            response_format: None,
+            headers: Vec::new(),
+            base_url: Some(base),
+            seed: None,
+        };
+        assert_eq!(provider.chat_completion(request).unwrap().text, "pong");
+        let received = received.recv().unwrap();
+        assert_eq!(received.path, "/v1/chat/completions");
+        assert!(received.body.contains("ping"));
+    }
 }
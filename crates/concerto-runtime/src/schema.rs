@@ -1,109 +1,258 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 
 use concerto_common::ir::IrSchema;
+use concerto_common::json_schema;
+use jsonschema::error::ValidationErrorKind;
 
-use crate::error::{Result, RuntimeError};
-use crate::value::Value;
+use crate::error::RuntimeError;
+use crate::value::{MapKey, Value};
 
 /// Maximum number of retry attempts for schema validation.
 const MAX_RETRIES: usize = 3;
 
+/// Why a single part of a response failed schema validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationReason {
+    /// The response was not parseable JSON at all.
+    InvalidJson,
+    /// A required field was absent.
+    Missing,
+    /// A field was present but had the wrong JSON type.
+    WrongType,
+    /// A field had the right type but broke a constraint (enum, range, length, ...).
+    Constraint,
+}
+
+impl ViolationReason {
+    /// Stable lowercase name exposed to Concerto programs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViolationReason::InvalidJson => "invalid_json",
+            ViolationReason::Missing => "missing",
+            ViolationReason::WrongType => "wrong_type",
+            ViolationReason::Constraint => "constraint",
+        }
+    }
+}
+
+/// One validation failure, pinned to the offending field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    /// Dotted path to the field (e.g. `count`, `items.0.name`); empty for the root.
+    pub field: String,
+    pub reason: ViolationReason,
+    pub message: String,
+}
+
+/// Structured error returned by [`SchemaValidator::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaValidationError {
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl SchemaValidationError {
+    fn single(field: &str, reason: ViolationReason, message: String) -> Self {
+        SchemaValidationError {
+            violations: vec![SchemaViolation {
+                field: field.to_string(),
+                reason,
+                message,
+            }],
+        }
+    }
+
+    /// Convert into a `SchemaError` struct value for Concerto programs.
+    ///
+    /// `message` is the full human-readable text; `field` and `reason` describe
+    /// the first violation; `violations` lists every violation as a
+    /// `SchemaViolation` struct with the same three fields.
+    pub fn to_value(&self, message: String) -> Value {
+        let violation_value = |v: &SchemaViolation| {
+            let mut fields = HashMap::new();
+            fields.insert("field".to_string(), Value::String(v.field.clone()));
+            fields.insert(
+                "reason".to_string(),
+                Value::String(v.reason.as_str().to_string()),
+            );
+            fields.insert("message".to_string(), Value::String(v.message.clone()));
+            Value::Struct {
+                type_name: "SchemaViolation".to_string(),
+                fields,
+            }
+        };
+
+        let mut fields = HashMap::new();
+        fields.insert("message".to_string(), Value::String(message));
+        let first = self.violations.first();
+        fields.insert(
+            "field".to_string(),
+            Value::String(first.map(|v| v.field.clone()).unwrap_or_default()),
+        );
+        fields.insert(
+            "reason".to_string(),
+            Value::String(
+                first
+                    .map(|v| v.reason.as_str().to_string())
+                    .unwrap_or_default(),
+            ),
+        );
+        fields.insert(
+            "violations".to_string(),
+            Value::Array(self.violations.iter().map(violation_value).collect()),
+        );
+        Value::Struct {
+            type_name: "SchemaError".to_string(),
+            fields,
+        }
+    }
+}
+
+impl fmt::Display for SchemaValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .violations
+            .iter()
+            .map(|v| {
+                if v.field.is_empty() {
+                    v.message.clone()
+                } else {
+                    format!("{} at {}", v.message, v.field)
+                }
+            })
+            .collect();
+        write!(f, "schema validation error: {}", parts.join("; "))
+    }
+}
+
+impl std::error::Error for SchemaValidationError {}
+
+impl From<SchemaValidationError> for RuntimeError {
+    fn from(e: SchemaValidationError) -> Self {
+        let text = e.to_string();
+        let detail = text
+            .strip_prefix("schema validation error: ")
+            .unwrap_or(&text)
+            .to_string();
+        RuntimeError::SchemaError(detail)
+    }
+}
+
 /// Validates JSON strings against Concerto schema definitions.
 pub struct SchemaValidator;
 
 impl SchemaValidator {
     /// Validate a JSON string against an IrSchema.
-    /// Returns a typed Value::Struct on success, or an error with details.
-    pub fn validate(json_str: &str, schema: &IrSchema) -> Result<Value> {
+    /// Returns a typed Value::Struct on success, or a structured error listing
+    /// each offending field and why it failed.
+    pub fn validate(
+        json_str: &str,
+        schema: &IrSchema,
+    ) -> Result<Value, SchemaValidationError> {
         // Parse the JSON string
-        let json: serde_json::Value = serde_json::from_str(json_str)
-            .map_err(|e| RuntimeError::SchemaError(format!("invalid JSON: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_str(json_str).map_err(|e| {
+            SchemaValidationError::single(
+                "",
+                ViolationReason::InvalidJson,
+                format!("invalid JSON: {}", e),
+            )
+        })?;
+
+        Self::validate_value(&json, schema)
+    }
+
+    /// Validate an already-parsed JSON value against an IrSchema.
+    /// Used by `validate` and by `std::json::validate` for in-program data.
+    pub fn validate_value(
+        json: &serde_json::Value,
+        schema: &IrSchema,
+    ) -> Result<Value, SchemaValidationError> {
+        let renamed;
+        let json = if schema.aliases.is_empty() {
+            json
+        } else {
+            renamed = Self::apply_aliases(json, &schema.aliases);
+            &renamed
+        };
 
         // Normalize Concerto types to JSON Schema types before validation
-        let normalized = Self::normalize_schema(&schema.json_schema);
+        let normalized = json_schema::normalize_schema(&schema.json_schema);
 
         // Validate against the normalized JSON Schema
-        Self::validate_json(&json, &normalized)?;
+        Self::validate_json(json, &normalized)?;
 
         // Convert to typed Value::Struct
-        Ok(Self::json_to_struct(&json, &schema.name))
+        Ok(Self::json_to_struct(json, &schema.name))
     }
 
-    /// Normalize Concerto type names to standard JSON Schema type names.
-    /// Concerto uses `String`, `Int`, `Float`, `Bool`, `Array<T>`, `Map<K,V>`;
-    /// JSON Schema uses `string`, `integer`, `number`, `boolean`, `array`, `object`.
-    fn normalize_schema(schema: &serde_json::Value) -> serde_json::Value {
-        match schema {
-            serde_json::Value::Object(obj) => {
-                let mut normalized = serde_json::Map::new();
-                for (key, val) in obj {
-                    if key == "type" {
-                        if let Some(t) = val.as_str() {
-                            // Check for compound types like Array<String>
-                            if let Some(inner) =
-                                t.strip_prefix("Array<").and_then(|s| s.strip_suffix('>'))
-                            {
-                                normalized.insert("type".to_string(), serde_json::json!("array"));
-                                normalized.insert(
-                                    "items".to_string(),
-                                    serde_json::json!({"type": Self::normalize_type_name(inner)}),
-                                );
-                            } else if t.starts_with("Map<") || t.starts_with("Option<") {
-                                // Map and Option are complex; treat as permissive object/any
-                                normalized.insert(
-                                    key.clone(),
-                                    serde_json::Value::String("object".to_string()),
-                                );
-                            } else {
-                                normalized.insert(
-                                    key.clone(),
-                                    serde_json::Value::String(
-                                        Self::normalize_type_name(t).to_string(),
-                                    ),
-                                );
-                            }
-                        } else {
-                            normalized.insert(key.clone(), Self::normalize_schema(val));
-                        }
-                    } else {
-                        normalized.insert(key.clone(), Self::normalize_schema(val));
-                    }
+    /// Move top-level keys given by `@alias` to the field they stand for. A
+    /// key under the field's own name wins over its alias.
+    fn apply_aliases(
+        json: &serde_json::Value,
+        aliases: &BTreeMap<String, String>,
+    ) -> serde_json::Value {
+        let mut json = json.clone();
+        if let Some(obj) = json.as_object_mut() {
+            for (alias, field) in aliases {
+                if obj.contains_key(field) {
+                    continue;
+                }
+                if let Some(value) = obj.shift_remove(alias) {
+                    obj.insert(field.clone(), value);
                 }
-                serde_json::Value::Object(normalized)
-            }
-            serde_json::Value::Array(arr) => {
-                serde_json::Value::Array(arr.iter().map(Self::normalize_schema).collect())
             }
-            other => other.clone(),
-        }
-    }
-
-    /// Map a Concerto type name to its JSON Schema equivalent.
-    fn normalize_type_name(t: &str) -> &str {
-        match t {
-            "String" => "string",
-            "Int" => "integer",
-            "Float" => "number",
-            "Bool" => "boolean",
-            "Nil" => "null",
-            other => other,
         }
+        json
     }
 
     /// Validate a serde_json::Value against a JSON Schema.
-    fn validate_json(json: &serde_json::Value, json_schema: &serde_json::Value) -> Result<()> {
-        let validator = jsonschema::validator_for(json_schema)
-            .map_err(|e| RuntimeError::SchemaError(format!("invalid schema: {}", e)))?;
+    fn validate_json(
+        json: &serde_json::Value,
+        json_schema: &serde_json::Value,
+    ) -> Result<(), SchemaValidationError> {
+        let validator = jsonschema::validator_for(json_schema).map_err(|e| {
+            SchemaValidationError::single(
+                "",
+                ViolationReason::Constraint,
+                format!("invalid schema: {}", e),
+            )
+        })?;
 
-        let errors: Vec<String> = validator
+        let violations: Vec<SchemaViolation> = validator
             .iter_errors(json)
-            .map(|e| format!("{} at {}", e, e.instance_path))
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                let mut field = path.trim_start_matches('/').replace('/', ".");
+                let reason = match &e.kind {
+                    ValidationErrorKind::Required { property } => {
+                        // The instance path points at the parent object; append
+                        // the missing property so `field` names what is absent.
+                        let name = property
+                            .as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| property.to_string());
+                        field = if field.is_empty() {
+                            name
+                        } else {
+                            format!("{}.{}", field, name)
+                        };
+                        ViolationReason::Missing
+                    }
+                    ValidationErrorKind::Type { .. } => ViolationReason::WrongType,
+                    _ => ViolationReason::Constraint,
+                };
+                SchemaViolation {
+                    field,
+                    reason,
+                    message: e.to_string(),
+                }
+            })
             .collect();
 
-        if errors.is_empty() {
+        if violations.is_empty() {
             Ok(())
         } else {
-            Err(RuntimeError::SchemaError(errors.join("; ")))
+            Err(SchemaValidationError { violations })
         }
     }
 
@@ -155,9 +304,9 @@ impl SchemaValidator {
                 Value::Array(arr.iter().map(Self::json_to_value).collect())
             }
             serde_json::Value::Object(obj) => {
-                let pairs: Vec<(String, Value)> = obj
+                let pairs: Vec<(MapKey, Value)> = obj
                     .iter()
-                    .map(|(k, v)| (k.clone(), Self::json_to_value(v)))
+                    .map(|(k, v)| (k.clone().into(), Self::json_to_value(v)))
                     .collect();
                 Value::Map(pairs)
             }
@@ -190,6 +339,7 @@ mod tests {
                 "required": ["message", "count"]
             }),
             validation_mode: "strict".to_string(),
+            aliases: Default::default(),
         }
     }
 
@@ -226,6 +376,55 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn validation_error_identifies_missing_field() {
+        let schema = test_schema();
+        let err = SchemaValidator::validate(r#"{"message": "hello"}"#, &schema).unwrap_err();
+        assert_eq!(err.violations.len(), 1);
+        assert_eq!(err.violations[0].field, "count");
+        assert_eq!(err.violations[0].reason, ViolationReason::Missing);
+    }
+
+    #[test]
+    fn validation_error_identifies_wrong_type_and_constraint() {
+        let schema = IrSchema {
+            name: "Rated".to_string(),
+            json_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "label": {"type": "String"},
+                    "score": {"type": "Int", "minimum": 0}
+                },
+                "required": ["label", "score"]
+            }),
+            validation_mode: "strict".to_string(),
+            aliases: Default::default(),
+        };
+        let err = SchemaValidator::validate(r#"{"label": 7, "score": -1}"#, &schema).unwrap_err();
+        let by_field: HashMap<&str, ViolationReason> = err
+            .violations
+            .iter()
+            .map(|v| (v.field.as_str(), v.reason))
+            .collect();
+        assert_eq!(by_field.get("label"), Some(&ViolationReason::WrongType));
+        assert_eq!(by_field.get("score"), Some(&ViolationReason::Constraint));
+    }
+
+    #[test]
+    fn validation_error_converts_to_struct_value() {
+        let schema = test_schema();
+        let err = SchemaValidator::validate(r#"{"message": "hello"}"#, &schema).unwrap_err();
+        match err.to_value("failed".to_string()) {
+            Value::Struct { type_name, fields } => {
+                assert_eq!(type_name, "SchemaError");
+                assert_eq!(fields.get("field"), Some(&Value::String("count".into())));
+                assert_eq!(fields.get("reason"), Some(&Value::String("missing".into())));
+                assert_eq!(fields.get("message"), Some(&Value::String("failed".into())));
+            }
+            other => panic!("expected Struct, got {:?}", other),
+        }
+    }
+
     #[test]
     fn validate_invalid_json_string() {
         let schema = test_schema();
@@ -251,7 +450,8 @@ mod tests {
             Value::Map(pairs) => {
                 assert_eq!(pairs.len(), 6);
                 // Check a few values
-                let map: HashMap<String, Value> = pairs.into_iter().collect();
+                let map: HashMap<String, Value> =
+                    pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
                 assert_eq!(map.get("name"), Some(&Value::String("test".into())));
                 assert_eq!(map.get("count"), Some(&Value::Int(42)));
                 assert_eq!(map.get("active"), Some(&Value::Bool(true)));
@@ -275,6 +475,7 @@ mod tests {
                 "required": ["message", "count"]
             }),
             validation_mode: "strict".to_string(),
+            aliases: Default::default(),
         };
         let json = r#"{"message": "hello", "count": 5}"#;
         let result = SchemaValidator::validate(json, &schema).unwrap();
@@ -288,6 +489,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn aliased_keys_fill_declared_fields() {
+        let schema = IrSchema {
+            name: "Person".to_string(),
+            json_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "first_name": {"type": "String"},
+                    "last_name": {"type": "String"}
+                },
+                "required": ["first_name", "last_name"]
+            }),
+            validation_mode: "strict".to_string(),
+            aliases: [
+                ("firstName".to_string(), "first_name".to_string()),
+                ("lastName".to_string(), "last_name".to_string()),
+            ]
+            .into(),
+        };
+        let fields = |json: &str| match SchemaValidator::validate(json, &schema) {
+            Ok(Value::Struct { fields, .. }) => fields,
+            other => panic!("expected Struct for {}, got {:?}", json, other),
+        };
+
+        let person = fields(r#"{"firstName": "Ada", "lastName": "Lovelace"}"#);
+        assert_eq!(person.get("first_name"), Some(&Value::String("Ada".into())));
+        assert_eq!(
+            person.get("last_name"),
+            Some(&Value::String("Lovelace".into()))
+        );
+        assert!(!person.contains_key("firstName"));
+
+        // The declared name wins when both are present.
+        let person = fields(r#"{"first_name": "Ada", "firstName": "X", "last_name": "L"}"#);
+        assert_eq!(person.get("first_name"), Some(&Value::String("Ada".into())));
+
+        // Without the alias, the camelCase key is still a missing field.
+        let plain = IrSchema {
+            aliases: Default::default(),
+            ..schema.clone()
+        };
+        let err = SchemaValidator::validate(r#"{"firstName": "Ada", "lastName": "L"}"#, &plain)
+            .unwrap_err();
+        assert_eq!(err.violations[0].field, "first_name");
+    }
+
+    #[test]
+    fn union_field_accepts_any_alternative() {
+        let schema = IrSchema {
+            name: "Reply".to_string(),
+            json_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "content": {"oneOf": [{"type": "String"}, {"type": "Array<String>"}]},
+                    "score": {"oneOf": [{"type": "Int"}, {"type": "Float"}]}
+                },
+                "required": ["content", "score"]
+            }),
+            validation_mode: "strict".to_string(),
+            aliases: Default::default(),
+        };
+        let field = |json: &str, name: &str| match SchemaValidator::validate(json, &schema) {
+            Ok(Value::Struct { fields, .. }) => fields.get(name).cloned(),
+            other => panic!("expected Struct for {}, got {:?}", json, other),
+        };
+
+        assert_eq!(
+            field(r#"{"content": "hi", "score": 1}"#, "content"),
+            Some(Value::String("hi".into()))
+        );
+        assert_eq!(
+            field(r#"{"content": ["a", "b"], "score": 0.5}"#, "content"),
+            Some(Value::Array(vec![
+                Value::String("a".into()),
+                Value::String("b".into())
+            ]))
+        );
+
+        let err = SchemaValidator::validate(r#"{"content": 3, "score": 1}"#, &schema).unwrap_err();
+        assert_eq!(err.violations[0].field, "content");
+    }
+
     #[test]
     fn retry_prompt_includes_context() {
         let schema = test_schema();
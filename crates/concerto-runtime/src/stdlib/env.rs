@@ -1,5 +1,5 @@
 use crate::error::{Result, RuntimeError};
-use crate::value::Value;
+use crate::value::{MapKey, Value};
 
 pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     match name {
@@ -55,8 +55,8 @@ fn stdlib_require(args: Vec<Value>) -> Result<Value> {
 }
 
 fn stdlib_all() -> Result<Value> {
-    let pairs: Vec<(String, Value)> = std::env::vars()
-        .map(|(k, v)| (k, Value::String(v)))
+    let pairs: Vec<(MapKey, Value)> = std::env::vars()
+        .map(|(k, v)| (k.into(), Value::String(v)))
         .collect();
     Ok(Value::Map(pairs))
 }
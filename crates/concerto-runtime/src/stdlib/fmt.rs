@@ -8,6 +8,10 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
         "pad_right" => stdlib_pad_right(args),
         "truncate" => stdlib_truncate(args),
         "indent" => stdlib_indent(args),
+        "pretty" => stdlib_pretty(args),
+        "json_lines" => stdlib_json_lines(args),
+        "currency" => stdlib_currency(args),
+        "percent" => stdlib_percent(args),
         _ => Err(RuntimeError::CallError(format!(
             "unknown function: std::fmt::{}",
             name
@@ -47,6 +51,94 @@ fn expect_int(args: &[Value], idx: usize, fn_name: &str) -> Result<i64> {
     }
 }
 
+fn expect_number(args: &[Value], idx: usize, fn_name: &str) -> Result<f64> {
+    match args.get(idx) {
+        Some(Value::Int(n)) => Ok(*n as f64),
+        Some(Value::Float(f)) => Ok(*f),
+        Some(other) => Err(RuntimeError::TypeError(format!(
+            "std::fmt::{} expected numeric at arg {}, got {}",
+            fn_name,
+            idx,
+            other.type_name()
+        ))),
+        None => Err(RuntimeError::TypeError(format!(
+            "std::fmt::{} missing argument {}",
+            fn_name, idx
+        ))),
+    }
+}
+
+fn expect_decimals(args: &[Value], idx: usize, fn_name: &str) -> Result<usize> {
+    let decimals = expect_int(args, idx, fn_name)?;
+    usize::try_from(decimals).map_err(|_| {
+        RuntimeError::TypeError(format!(
+            "std::fmt::{} decimals must be non-negative, got {}",
+            fn_name, decimals
+        ))
+    })
+}
+
+/// Fixed-point text for `x`, rounded with `std::math`'s `round_decimal`, so
+/// `1.005` gives `1.01`. Returns the sign separately; a value that rounds to
+/// zero is unsigned.
+fn fixed_point(x: f64, decimals: usize) -> (bool, String) {
+    if !x.is_finite() {
+        return (false, x.to_string());
+    }
+    let places = i64::try_from(decimals).unwrap_or(i64::MAX);
+    let rounded = super::math::round_decimal(x.abs(), places);
+    (
+        x < 0.0 && rounded != 0.0,
+        format!("{:.*}", decimals, rounded),
+    )
+}
+
+/// Insert `,` between each group of three digits in the integer part.
+fn group_thousands(digits: &str) -> String {
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (digits, None),
+    };
+    let mut grouped = String::new();
+    for (i, ch) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    if let Some(frac) = frac_part {
+        grouped.push('.');
+        grouped.push_str(frac);
+    }
+    grouped
+}
+
+/// `currency(x, symbol, decimals)`: `-$1,234.56` style.
+fn stdlib_currency(args: Vec<Value>) -> Result<Value> {
+    let x = expect_number(&args, 0, "currency")?;
+    let symbol = expect_string(&args, 1, "currency")?;
+    let decimals = expect_decimals(&args, 2, "currency")?;
+    let (negative, digits) = fixed_point(x, decimals);
+    let sign = if negative { "-" } else { "" };
+    Ok(Value::String(format!(
+        "{}{}{}",
+        sign,
+        symbol,
+        group_thousands(&digits)
+    )))
+}
+
+/// `percent(x, decimals)`: a ratio as a percentage, `0.4213` -> `42.1%`.
+fn stdlib_percent(args: Vec<Value>) -> Result<Value> {
+    let x = expect_number(&args, 0, "percent")?;
+    let decimals = expect_decimals(&args, 1, "percent")?;
+    // Scale on the decimal text so 0.07 becomes 7, not 7.000000000000001.
+    let scaled: f64 = format!("{}e2", x).parse().unwrap_or(x * 100.0);
+    let (negative, digits) = fixed_point(scaled, decimals);
+    let sign = if negative { "-" } else { "" };
+    Ok(Value::String(format!("{}{}%", sign, digits)))
+}
+
 fn stdlib_format(args: Vec<Value>) -> Result<Value> {
     let template = expect_string(&args, 0, "format")?;
     let format_args = match args.get(1) {
@@ -130,6 +222,35 @@ fn stdlib_indent(args: Vec<Value>) -> Result<Value> {
     Ok(Value::String(result))
 }
 
+fn stdlib_pretty(args: Vec<Value>) -> Result<Value> {
+    match args.first() {
+        Some(value) => Ok(Value::String(value.pretty_string())),
+        None => Err(RuntimeError::TypeError(
+            "std::fmt::pretty missing argument 0".to_string(),
+        )),
+    }
+}
+
+/// Serialize a value as one compact JSON line (no trailing newline).
+fn json_line(value: &Value) -> String {
+    value.to_json().to_string()
+}
+
+fn stdlib_json_lines(args: Vec<Value>) -> Result<Value> {
+    match args.first() {
+        Some(Value::Array(items)) => Ok(Value::String(
+            items.iter().map(json_line).collect::<Vec<_>>().join("\n"),
+        )),
+        Some(other) => Err(RuntimeError::TypeError(format!(
+            "std::fmt::json_lines expected Array at arg 0, got {}",
+            other.type_name()
+        ))),
+        None => Err(RuntimeError::TypeError(
+            "std::fmt::json_lines missing argument 0".to_string(),
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,8 +335,112 @@ mod tests {
         assert_eq!(result, Value::String("    line1\n    line2".into()));
     }
 
+    #[test]
+    fn pretty_nested_array() {
+        let value = Value::Array(vec![Value::Array(vec![Value::Int(1)])]);
+        let result = call("pretty", vec![value]).unwrap();
+        assert_eq!(result, Value::String("[\n  [\n    1,\n  ],\n]".into()));
+    }
+
+    fn currency(x: Value, decimals: i64) -> Value {
+        call(
+            "currency",
+            vec![x, Value::String("$".into()), Value::Int(decimals)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn currency_groups_thousands() {
+        assert_eq!(
+            currency(Value::Float(1234.56), 2),
+            Value::String("$1,234.56".into())
+        );
+        assert_eq!(
+            currency(Value::Int(1234567), 2),
+            Value::String("$1,234,567.00".into())
+        );
+        assert_eq!(currency(Value::Int(999), 0), Value::String("$999".into()));
+        assert_eq!(
+            currency(Value::Float(999.995), 2),
+            Value::String("$1,000.00".into())
+        );
+        assert_eq!(
+            currency(Value::Float(1.005), 2),
+            Value::String("$1.01".into())
+        );
+    }
+
+    #[test]
+    fn currency_negative_values() {
+        assert_eq!(
+            currency(Value::Float(-1234.5), 2),
+            Value::String("-$1,234.50".into())
+        );
+        assert_eq!(
+            currency(Value::Float(-0.001), 2),
+            Value::String("$0.00".into())
+        );
+        assert!(call(
+            "currency",
+            vec![Value::Int(1), Value::String("$".into()), Value::Int(-1)],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn percent_rounds_scaled_value() {
+        let percent = |x: Value, decimals: i64| call("percent", vec![x, Value::Int(decimals)]);
+        assert_eq!(
+            percent(Value::Float(0.42), 1).unwrap(),
+            Value::String("42.0%".into())
+        );
+        assert_eq!(
+            percent(Value::Float(0.12345), 2).unwrap(),
+            Value::String("12.35%".into())
+        );
+        assert_eq!(
+            percent(Value::Float(0.07), 0).unwrap(),
+            Value::String("7%".into())
+        );
+        assert_eq!(
+            percent(Value::Int(1), 0).unwrap(),
+            Value::String("100%".into())
+        );
+        assert_eq!(
+            percent(Value::Float(-0.256), 1).unwrap(),
+            Value::String("-25.6%".into())
+        );
+        assert!(percent(Value::String("1".into()), 0).is_err());
+    }
+
     #[test]
     fn unknown_function() {
         assert!(call("nonexistent", vec![]).is_err());
     }
+
+    #[test]
+    fn json_lines_one_compact_object_per_line() {
+        let event = |step: i64| {
+            Value::Map(vec![
+                ("step".into(), Value::Int(step)),
+                (
+                    "tags".into(),
+                    Value::Array(vec![Value::String("a b".into())]),
+                ),
+            ])
+        };
+        let result = call("json_lines", vec![Value::Array(vec![event(1), event(2)])]).unwrap();
+        assert_eq!(
+            result,
+            Value::String(
+                "{\"step\":1,\"tags\":[\"a b\"]}\n{\"step\":2,\"tags\":[\"a b\"]}".into()
+            )
+        );
+        assert_eq!(
+            call("json_lines", vec![Value::Array(vec![])]).unwrap(),
+            Value::String(String::new())
+        );
+        assert!(call("json_lines", vec![Value::Int(1)]).is_err());
+    }
 }
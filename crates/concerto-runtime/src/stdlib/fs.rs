@@ -1,7 +1,13 @@
 use crate::error::{Result, RuntimeError};
+use crate::schema::SchemaValidator;
 use crate::value::Value;
 
 use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Directory entries `glob` may visit before giving up, so a stray `**`
+/// cannot walk an entire disk.
+const GLOB_MAX_ENTRIES: usize = 100_000;
 
 pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     match name {
@@ -12,6 +18,9 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
         "list_dir" => stdlib_list_dir(args),
         "remove_file" => stdlib_remove_file(args),
         "file_size" => stdlib_file_size(args),
+        "read_json" => stdlib_read_json(args),
+        "write_json" => stdlib_write_json(args),
+        "glob" => stdlib_glob(args),
         _ => Err(RuntimeError::CallError(format!(
             "unknown function: std::fs::{}",
             name
@@ -120,6 +129,220 @@ fn stdlib_file_size(args: Vec<Value>) -> Result<Value> {
     })
 }
 
+/// Err payload prefixed with the failing step so callers can tell
+/// IO failures apart from (de)serialization failures.
+fn wrap_step_err(step: &str, e: impl std::fmt::Display) -> Value {
+    Value::Result {
+        is_ok: false,
+        value: Box::new(Value::String(format!("{} error: {}", step, e))),
+    }
+}
+
+fn stdlib_read_json(args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(&args, 0, "read_json")?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => return Ok(wrap_step_err("io", e)),
+    };
+    Ok(match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(json) => wrap_io_ok(SchemaValidator::json_to_value(&json)),
+        Err(e) => wrap_step_err("parse", e),
+    })
+}
+
+fn stdlib_write_json(args: Vec<Value>) -> Result<Value> {
+    let path = expect_string(&args, 0, "write_json")?;
+    let value = args.get(1).ok_or_else(|| {
+        RuntimeError::TypeError("std::fs::write_json missing argument 1".to_string())
+    })?;
+    let pretty = match args.get(2) {
+        None => false,
+        Some(Value::Bool(b)) => *b,
+        Some(other) => {
+            return Err(RuntimeError::TypeError(format!(
+                "std::fs::write_json expected Bool at arg 2, got {}",
+                other.type_name()
+            )))
+        }
+    };
+    let json = value.to_json();
+    let text = if pretty {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(e) => return Ok(wrap_step_err("serialize", e)),
+    };
+    Ok(match std::fs::write(&path, text) {
+        Ok(()) => wrap_io_ok(Value::Nil),
+        Err(e) => wrap_step_err("io", e),
+    })
+}
+
+/// `glob(pattern)`: sorted paths matching `*` (within a name), `?` (one
+/// character) and `**` (any number of directories). Names starting with `.`
+/// only match a segment that starts with `.` too, and `**` does not enter
+/// hidden directories or follow symlinks. No match is an empty array.
+fn stdlib_glob(args: Vec<Value>) -> Result<Value> {
+    let pattern = expect_string(&args, 0, "glob")?;
+    if pattern.is_empty() {
+        return Err(RuntimeError::CallError(
+            "std::fs::glob pattern is empty".to_string(),
+        ));
+    }
+    let (base, segments) = match pattern.strip_prefix('/') {
+        Some(rest) => (PathBuf::from("/"), rest),
+        None => (PathBuf::new(), pattern.as_str()),
+    };
+    let segments: Vec<&str> = segments.split('/').filter(|s| !s.is_empty()).collect();
+    if base == Path::new("/") && segments.first() == Some(&"**") {
+        return Err(RuntimeError::CallError(
+            "std::fs::glob refuses `**` at the filesystem root".to_string(),
+        ));
+    }
+
+    let mut walk = GlobWalk {
+        matches: Vec::new(),
+        visited: 0,
+    };
+    walk.descend(&base, &segments)?;
+    let mut matches = walk.matches;
+    matches.sort();
+    matches.dedup();
+    Ok(Value::Array(
+        matches.into_iter().map(Value::String).collect(),
+    ))
+}
+
+struct GlobWalk {
+    matches: Vec<String>,
+    visited: usize,
+}
+
+impl GlobWalk {
+    /// Match `segments` against the tree under `dir` (`""` is the current
+    /// directory).
+    fn descend(&mut self, dir: &Path, segments: &[&str]) -> Result<()> {
+        let Some((segment, rest)) = segments.split_first() else {
+            return Ok(());
+        };
+        if *segment == "**" {
+            // Zero directories; a trailing `**` matches everything below
+            // `dir` but not `dir` itself.
+            if !rest.is_empty() {
+                self.descend(dir, rest)?;
+            }
+            for (name, path) in self.entries(dir)? {
+                if name.starts_with('.') {
+                    continue;
+                }
+                if rest.is_empty() {
+                    self.push(&path);
+                }
+                if is_real_dir(&path) {
+                    self.descend(&path, segments)?;
+                }
+            }
+            return Ok(());
+        }
+        if !segment.contains(['*', '?']) {
+            let path = dir.join(segment);
+            if rest.is_empty() {
+                if path.exists() {
+                    self.push(&path);
+                }
+            } else if path.is_dir() {
+                self.descend(&path, rest)?;
+            }
+            return Ok(());
+        }
+        for (name, path) in self.entries(dir)? {
+            if name.starts_with('.') && !segment.starts_with('.') {
+                continue;
+            }
+            if !wildcard_match(segment, &name) {
+                continue;
+            }
+            if rest.is_empty() {
+                self.push(&path);
+            } else if path.is_dir() {
+                self.descend(&path, rest)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Entries of `dir` as (name, path); unreadable directories are empty.
+    fn entries(&mut self, dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let read_from = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let Ok(entries) = std::fs::read_dir(read_from) else {
+            return Ok(Vec::new());
+        };
+        let mut out = Vec::new();
+        for entry in entries.flatten() {
+            self.visited += 1;
+            if self.visited > GLOB_MAX_ENTRIES {
+                return Err(RuntimeError::CallError(format!(
+                    "std::fs::glob visited more than {} entries; narrow the pattern",
+                    GLOB_MAX_ENTRIES
+                )));
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            out.push((name.clone(), dir.join(name)));
+        }
+        Ok(out)
+    }
+
+    fn push(&mut self, path: &Path) {
+        self.matches.push(path.to_string_lossy().to_string());
+    }
+}
+
+/// A directory that is not a symlink, so `**` cannot loop.
+fn is_real_dir(path: &Path) -> bool {
+    std::fs::symlink_metadata(path).is_ok_and(|m| m.is_dir())
+}
+
+/// Match one path segment against a pattern with `*` and `?`.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name index it was tried at.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,6 +463,132 @@ mod tests {
         std::fs::remove_file(&path).ok();
     }
 
+    #[test]
+    fn write_json_and_read_json_round_trip() {
+        let path = temp_path("json_round_trip.json");
+        // Keys in sorted order: JSON objects come back key-sorted.
+        let nested = Value::Map(vec![
+            (
+                "inner".into(),
+                Value::Map(vec![("score".into(), Value::Float(0.5))]),
+            ),
+            ("name".into(), Value::String("concerto".into())),
+            (
+                "tags".into(),
+                Value::Array(vec![Value::Int(1), Value::Bool(true), Value::Nil]),
+            ),
+        ]);
+        let written = call(
+            "write_json",
+            vec![
+                Value::String(path.clone()),
+                nested.clone(),
+                Value::Bool(true),
+            ],
+        )
+        .unwrap();
+        assert!(matches!(written, Value::Result { is_ok: true, .. }));
+        assert!(std::fs::read_to_string(&path).unwrap().contains('\n'));
+
+        match call("read_json", vec![Value::String(path.clone())]).unwrap() {
+            Value::Result { is_ok: true, value } => assert_eq!(*value, nested),
+            other => panic!("expected Ok, got {:?}", other),
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn read_json_distinguishes_io_and_parse_errors() {
+        let missing = call(
+            "read_json",
+            vec![Value::String("/tmp/concerto_nonexistent_12345.json".into())],
+        )
+        .unwrap();
+        match missing {
+            Value::Result {
+                is_ok: false,
+                value,
+            } => {
+                assert!(value.to_string().starts_with("io error:"))
+            }
+            _ => panic!("expected Err"),
+        }
+
+        let path = temp_path("json_invalid.json");
+        std::fs::write(&path, "{not json").unwrap();
+        match call("read_json", vec![Value::String(path.clone())]).unwrap() {
+            Value::Result {
+                is_ok: false,
+                value,
+            } => {
+                assert!(value.to_string().starts_with("parse error:"))
+            }
+            _ => panic!("expected Err"),
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn glob_matches_nested_patterns() {
+        let root = temp_path(&format!("glob_{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+        for file in [
+            "a.txt",
+            "b.md",
+            "sub/c.txt",
+            "sub/.f.txt",
+            "sub/deep/d.txt",
+            ".hidden/e.txt",
+        ] {
+            let path = format!("{}/{}", root, file);
+            std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
+            std::fs::write(&path, "x").unwrap();
+        }
+        let glob = |pattern: &str| {
+            call("glob", vec![Value::String(format!("{}/{}", root, pattern))]).unwrap()
+        };
+        let paths = |rel: &[&str]| {
+            Value::Array(
+                rel.iter()
+                    .map(|r| Value::String(format!("{}/{}", root, r)))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(glob("*.txt"), paths(&["a.txt"]));
+        assert_eq!(
+            glob("**/*.txt"),
+            paths(&["a.txt", "sub/c.txt", "sub/deep/d.txt"])
+        );
+        assert_eq!(glob("s?b/*.txt"), paths(&["sub/c.txt"]));
+        assert_eq!(glob("sub/.*"), paths(&["sub/.f.txt"]));
+        assert_eq!(
+            glob("sub/**"),
+            paths(&["sub/c.txt", "sub/deep", "sub/deep/d.txt"])
+        );
+        assert_eq!(glob("sub/deep/d.txt"), paths(&["sub/deep/d.txt"]));
+        assert_eq!(glob("**/*.csv"), Value::Array(vec![]));
+        assert_eq!(glob("missing/*"), Value::Array(vec![]));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn glob_refuses_unbounded_patterns() {
+        assert!(call("glob", vec![Value::String("/**/*.txt".into())]).is_err());
+        assert!(call("glob", vec![Value::String(String::new())]).is_err());
+    }
+
+    #[test]
+    fn wildcard_segments() {
+        assert!(wildcard_match("*.txt", "notes.txt"));
+        assert!(wildcard_match("a*b*c", "aXbYbc"));
+        assert!(wildcard_match("?x", "ax"));
+        assert!(!wildcard_match("?x", "x"));
+        assert!(!wildcard_match("*.txt", "notes.md"));
+        assert!(wildcard_match("*", ""));
+    }
+
     #[test]
     fn unknown_function() {
         assert!(call("nonexistent", vec![]).is_err());
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
+use crate::decorator::{backoff_delay, BackoffStrategy};
 use crate::error::{Result, RuntimeError};
-use crate::value::Value;
+use crate::value::{MapKey, Value};
 
 pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     match name {
@@ -37,7 +38,7 @@ fn extract_headers(args: &[Value], idx: usize) -> Vec<(String, String)> {
     match args.get(idx) {
         Some(Value::Map(pairs)) => pairs
             .iter()
-            .map(|(k, v)| (k.clone(), v.display_string()))
+            .map(|(k, v)| (k.to_string(), v.display_string()))
             .collect(),
         _ => vec![],
     }
@@ -49,12 +50,12 @@ fn response_to_value(
     match result {
         Ok(resp) => {
             let status = resp.status().as_u16() as i64;
-            let headers: Vec<(String, Value)> = resp
+            let headers: Vec<(MapKey, Value)> = resp
                 .headers()
                 .iter()
                 .map(|(k, v)| {
                     (
-                        k.to_string(),
+                        k.to_string().into(),
                         Value::String(v.to_str().unwrap_or("").to_string()),
                     )
                 })
@@ -74,10 +75,99 @@ fn response_to_value(
                 }),
             }
         }
-        Err(e) => Value::Result {
-            is_ok: false,
-            value: Box::new(Value::String(e.to_string())),
-        },
+        Err(e) => http_error(e.to_string()),
+    }
+}
+
+fn http_error(message: String) -> Value {
+    Value::Result {
+        is_ok: false,
+        value: Box::new(Value::String(message)),
+    }
+}
+
+/// Settings from an optional options map:
+/// `{ "retries": n, "backoff_ms": ms, "query": { ... }, "bearer": token }`.
+#[derive(Debug, Clone, PartialEq)]
+struct RequestOptions {
+    retries: u32,
+    backoff_ms: u64,
+    /// Query parameters appended (URL-encoded) to the request URL.
+    query: Vec<(String, String)>,
+    /// Token sent as `Authorization: Bearer <token>`.
+    bearer: Option<String>,
+}
+
+impl Default for RequestOptions {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            backoff_ms: 100,
+            query: Vec::new(),
+            bearer: None,
+        }
+    }
+}
+
+fn extract_request_options(args: &[Value], idx: usize) -> RequestOptions {
+    let mut opts = RequestOptions::default();
+    if let Some(Value::Map(pairs)) = args.get(idx) {
+        for (k, v) in pairs {
+            match (k.as_str().unwrap_or(""), v) {
+                ("retries", Value::Int(n)) => opts.retries = (*n).max(0) as u32,
+                ("backoff_ms", Value::Int(n)) => opts.backoff_ms = (*n).max(0) as u64,
+                ("query", Value::Map(params)) => {
+                    opts.query = params
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.display_string()))
+                        .collect();
+                }
+                ("bearer", Value::String(token)) => opts.bearer = Some(token.clone()),
+                _ => {}
+            }
+        }
+    }
+    opts
+}
+
+/// Append `query` to `url`, percent-encoding names and values. The URL is
+/// returned unchanged when there are no parameters.
+fn url_with_query(url: &str, query: &[(String, String)]) -> std::result::Result<String, String> {
+    if query.is_empty() {
+        return Ok(url.to_string());
+    }
+    let mut parsed =
+        reqwest::Url::parse(url).map_err(|e| format!("invalid URL '{}': {}", url, e))?;
+    parsed.query_pairs_mut().extend_pairs(query);
+    Ok(parsed.into())
+}
+
+/// Send a request, retrying connection errors and 5xx responses with
+/// exponential backoff. 4xx responses are returned immediately. When all
+/// attempts fail, the last outcome is returned.
+fn send_with_retry(
+    build: impl Fn() -> reqwest::blocking::RequestBuilder,
+    opts: &RequestOptions,
+) -> Value {
+    let strategy = BackoffStrategy::Exponential {
+        base_ms: opts.backoff_ms,
+    };
+    let mut attempt = 0;
+    loop {
+        let mut builder = build();
+        if let Some(token) = &opts.bearer {
+            builder = builder.bearer_auth(token);
+        }
+        let result = builder.send();
+        let retryable = match &result {
+            Ok(resp) => resp.status().is_server_error(),
+            Err(_) => true,
+        };
+        if !retryable || attempt >= opts.retries {
+            return response_to_value(result);
+        }
+        std::thread::sleep(backoff_delay(&strategy, attempt));
+        attempt += 1;
     }
 }
 
@@ -95,24 +185,39 @@ fn apply_headers(
 fn stdlib_get(args: Vec<Value>) -> Result<Value> {
     let url = expect_string(&args, 0, "get")?;
     let headers = extract_headers(&args, 1);
+    let opts = extract_request_options(&args, 2);
+    let url = match url_with_query(&url, &opts.query) {
+        Ok(url) => url,
+        Err(msg) => return Ok(http_error(msg)),
+    };
     let client = reqwest::blocking::Client::new();
-    let builder = client.get(&url);
-    let builder = apply_headers(builder, &headers);
-    Ok(response_to_value(builder.send()))
+    Ok(send_with_retry(
+        || apply_headers(client.get(&url), &headers),
+        &opts,
+    ))
 }
 
 fn stdlib_post(args: Vec<Value>) -> Result<Value> {
     let url = expect_string(&args, 0, "post")?;
     let body = args.get(1).cloned().unwrap_or(Value::Nil);
     let headers = extract_headers(&args, 2);
+    let opts = extract_request_options(&args, 3);
+    let url = match url_with_query(&url, &opts.query) {
+        Ok(url) => url,
+        Err(msg) => return Ok(http_error(msg)),
+    };
     let client = reqwest::blocking::Client::new();
-    let mut builder = client.post(&url);
-    if body != Value::Nil {
-        let json_body = body.to_json();
-        builder = builder.json(&json_body);
-    }
-    builder = apply_headers(builder, &headers);
-    Ok(response_to_value(builder.send()))
+    let json_body = (body != Value::Nil).then(|| body.to_json());
+    Ok(send_with_retry(
+        || {
+            let mut builder = client.post(&url);
+            if let Some(ref json_body) = json_body {
+                builder = builder.json(json_body);
+            }
+            apply_headers(builder, &headers)
+        },
+        &opts,
+    ))
 }
 
 fn stdlib_put(args: Vec<Value>) -> Result<Value> {
@@ -171,6 +276,7 @@ fn stdlib_request(args: Vec<Value>) -> Result<Value> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::{serve, serve_once, ReceivedRequest};
 
     #[test]
     fn extract_headers_from_map() {
@@ -205,6 +311,140 @@ mod tests {
         }
     }
 
+    /// Serve one response per status, each with a `status N` body.
+    fn serve_statuses(statuses: Vec<u16>) -> (String, std::sync::mpsc::Receiver<ReceivedRequest>) {
+        serve(
+            statuses
+                .into_iter()
+                .map(|status| (status, format!("status {}", status)))
+                .collect(),
+        )
+    }
+
+    fn retry_opts(retries: i64) -> Value {
+        Value::Map(vec![
+            ("retries".into(), Value::Int(retries)),
+            ("backoff_ms".into(), Value::Int(1)),
+        ])
+    }
+
+    fn response_status(value: &Value) -> i64 {
+        match value {
+            Value::Result { is_ok: true, value } => match value.field_get("status") {
+                Ok(Value::Int(n)) => n,
+                other => panic!("expected status, got {:?}", other),
+            },
+            other => panic!("expected Ok response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn extract_request_options_from_map() {
+        let opts = extract_request_options(&[retry_opts(3)], 0);
+        assert_eq!(
+            opts,
+            RequestOptions {
+                retries: 3,
+                backoff_ms: 1,
+                ..RequestOptions::default()
+            }
+        );
+        assert_eq!(extract_request_options(&[], 0), RequestOptions::default());
+    }
+
+    #[test]
+    fn url_with_query_encodes_params() {
+        let query = vec![
+            ("q".to_string(), "rust & go=fast?".to_string()),
+            ("page".to_string(), "2".to_string()),
+        ];
+        assert_eq!(
+            url_with_query("https://api.example.com/search?lang=en", &query).unwrap(),
+            "https://api.example.com/search?lang=en&q=rust+%26+go%3Dfast%3F&page=2"
+        );
+        assert_eq!(url_with_query("not a url", &[]).unwrap(), "not a url");
+        assert!(url_with_query("not a url", &query).is_err());
+    }
+
+    #[test]
+    fn get_sends_encoded_query_and_bearer_token() {
+        let (url, requests) = serve_once("");
+        let options = Value::Map(vec![
+            (
+                "query".into(),
+                Value::Map(vec![
+                    ("q".into(), Value::String("café & tea".into())),
+                    ("limit".into(), Value::Int(10)),
+                ]),
+            ),
+            ("bearer".into(), Value::String("s3cr3t".into())),
+        ]);
+        let result = call(
+            "get",
+            vec![
+                Value::String(format!("{}/search", url)),
+                Value::Map(vec![]),
+                options,
+            ],
+        )
+        .unwrap();
+        assert_eq!(response_status(&result), 200);
+
+        let request = requests.recv().unwrap().head;
+        let request_line = request.lines().next().unwrap();
+        assert_eq!(
+            request_line,
+            "GET /search?q=caf%C3%A9+%26+tea&limit=10 HTTP/1.1"
+        );
+        assert!(
+            request
+                .lines()
+                .any(|l| l.eq_ignore_ascii_case("authorization: Bearer s3cr3t")),
+            "request: {}",
+            request
+        );
+    }
+
+    #[test]
+    fn get_retries_server_errors_until_success() {
+        let (url, served) = serve_statuses(vec![503, 500, 200]);
+        let result = call(
+            "get",
+            vec![Value::String(url), Value::Map(vec![]), retry_opts(3)],
+        )
+        .unwrap();
+        assert_eq!(response_status(&result), 200);
+        assert_eq!(served.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn post_returns_last_failure_when_retries_exhausted() {
+        let (url, _) = serve_statuses(vec![502, 503]);
+        let result = call(
+            "post",
+            vec![
+                Value::String(url),
+                Value::Nil,
+                Value::Map(vec![]),
+                retry_opts(1),
+            ],
+        )
+        .unwrap();
+        assert_eq!(response_status(&result), 503);
+    }
+
+    #[test]
+    fn get_does_not_retry_client_errors() {
+        let (url, served) = serve_statuses(vec![404, 200]);
+        let result = call(
+            "get",
+            vec![Value::String(url), Value::Map(vec![]), retry_opts(3)],
+        )
+        .unwrap();
+        assert_eq!(response_status(&result), 404);
+        assert_eq!(served.try_iter().count(), 1);
+    }
+
     #[test]
     fn get_missing_url_error() {
         assert!(call("get", vec![]).is_err());
@@ -1,6 +1,6 @@
 use crate::error::{Result, RuntimeError};
 use crate::schema::SchemaValidator;
-use crate::value::Value;
+use crate::value::{MapKey, Value};
 
 pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     match name {
@@ -8,6 +8,7 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
         "stringify" => stdlib_stringify(args),
         "stringify_pretty" => stdlib_stringify_pretty(args),
         "is_valid" => stdlib_is_valid(args),
+        "diff" => stdlib_diff(args),
         _ => Err(RuntimeError::CallError(format!(
             "unknown function: std::json::{}",
             name
@@ -82,6 +83,95 @@ fn stdlib_is_valid(args: Vec<Value>) -> Result<Value> {
     ))
 }
 
+/// `diff(a, b)`: the changes that turn `a` into `b`, one record per
+/// differing leaf: `{path, kind: "added"|"removed"|"changed", old?, new?}`.
+fn stdlib_diff(args: Vec<Value>) -> Result<Value> {
+    let [a, b] = <[Value; 2]>::try_from(args).map_err(|_| {
+        RuntimeError::TypeError("std::json::diff expects 2 arguments (a, b)".to_string())
+    })?;
+    let mut changes = Vec::new();
+    diff_into(&a, &b, "", &mut changes);
+    Ok(Value::Array(changes))
+}
+
+/// Recurse into maps, arrays and same-typed structs; anything else that
+/// differs is `changed` at `path` (dot-separated keys and indexes, `""` for
+/// the root).
+fn diff_into(a: &Value, b: &Value, path: &str, changes: &mut Vec<Value>) {
+    let child = |segment: &dyn std::fmt::Display| {
+        if path.is_empty() {
+            segment.to_string()
+        } else {
+            format!("{}.{}", path, segment)
+        }
+    };
+    match (a, b) {
+        (Value::Map(old), Value::Map(new)) => {
+            for (key, old_val) in old {
+                match new.iter().find(|(k, _)| k == key) {
+                    Some((_, new_val)) => diff_into(old_val, new_val, &child(key), changes),
+                    None => changes.push(change(child(key), "removed", Some(old_val), None)),
+                }
+            }
+            for (key, new_val) in new {
+                if !old.iter().any(|(k, _)| k == key) {
+                    changes.push(change(child(key), "added", None, Some(new_val)));
+                }
+            }
+        }
+        (
+            Value::Struct {
+                type_name: old_type,
+                fields: old,
+            },
+            Value::Struct {
+                type_name: new_type,
+                fields: new,
+            },
+        ) if old_type == new_type => {
+            let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let path = child(name);
+                match (old.get(name), new.get(name)) {
+                    (Some(o), Some(n)) => diff_into(o, n, &path, changes),
+                    (Some(o), None) => changes.push(change(path, "removed", Some(o), None)),
+                    (None, Some(n)) => changes.push(change(path, "added", None, Some(n))),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = child(&i);
+                match (old.get(i), new.get(i)) {
+                    (Some(o), Some(n)) => diff_into(o, n, &path, changes),
+                    (Some(o), None) => changes.push(change(path, "removed", Some(o), None)),
+                    (None, Some(n)) => changes.push(change(path, "added", None, Some(n))),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if a != b => changes.push(change(path.to_string(), "changed", Some(a), Some(b))),
+        _ => {}
+    }
+}
+
+fn change(path: String, kind: &str, old: Option<&Value>, new: Option<&Value>) -> Value {
+    let mut record: Vec<(MapKey, Value)> = vec![
+        ("path".into(), Value::String(path)),
+        ("kind".into(), Value::String(kind.to_string())),
+    ];
+    if let Some(old) = old {
+        record.push(("old".into(), old.clone()));
+    }
+    if let Some(new) = new {
+        record.push(("new".into(), new.clone()));
+    }
+    Value::Map(record)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,6 +228,38 @@ mod tests {
         assert_eq!(result, Value::String(r#"{"a":1}"#.into()));
     }
 
+    #[test]
+    fn map_key_order_survives_round_trip() {
+        let map = Value::Map(vec![
+            ("zeta".into(), Value::Int(1)),
+            ("alpha".into(), Value::String("a".into())),
+            ("mid".into(), Value::Bool(true)),
+            ("beta".into(), Value::Array(vec![Value::Int(2)])),
+            (
+                "omega".into(),
+                Value::Map(vec![("y".into(), Value::Nil), ("x".into(), Value::Nil)]),
+            ),
+        ]);
+        assert_eq!(SchemaValidator::json_to_value(&map.to_json()), map);
+
+        let text = call("stringify", vec![map.clone()]).unwrap();
+        assert_eq!(
+            text,
+            Value::String(
+                r#"{"zeta":1,"alpha":"a","mid":true,"beta":[2],"omega":{"y":null,"x":null}}"#
+                    .into()
+            )
+        );
+        let parsed = call("parse", vec![text]).unwrap();
+        assert_eq!(
+            parsed,
+            Value::Result {
+                is_ok: true,
+                value: Box::new(map)
+            }
+        );
+    }
+
     #[test]
     fn stringify_pretty_format() {
         let result = call("stringify_pretty", vec![Value::Int(42)]).unwrap();
@@ -156,6 +278,73 @@ mod tests {
         );
     }
 
+    fn record(path: &str, kind: &str, old: Option<Value>, new: Option<Value>) -> Value {
+        change(path.to_string(), kind, old.as_ref(), new.as_ref())
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let user = |name: &str, tags: Vec<&str>, extra: Option<(&str, Value)>| {
+            let mut fields: Vec<(MapKey, Value)> = vec![
+                ("name".into(), Value::String(name.into())),
+                (
+                    "tags".into(),
+                    Value::Array(tags.into_iter().map(|t| Value::String(t.into())).collect()),
+                ),
+            ];
+            if let Some((key, val)) = extra {
+                fields.push((key.into(), val));
+            }
+            Value::Map(vec![("user".into(), Value::Map(fields))])
+        };
+        let a = user("ada", vec!["x", "y"], Some(("age", Value::Int(36))));
+        let b = user(
+            "ada",
+            vec!["x", "z", "w"],
+            Some(("email", Value::String("a@b".into()))),
+        );
+
+        let diff = call("diff", vec![a.clone(), b]).unwrap();
+        assert_eq!(
+            diff,
+            Value::Array(vec![
+                record(
+                    "user.tags.1",
+                    "changed",
+                    Some(Value::String("y".into())),
+                    Some(Value::String("z".into())),
+                ),
+                record(
+                    "user.tags.2",
+                    "added",
+                    None,
+                    Some(Value::String("w".into()))
+                ),
+                record("user.age", "removed", Some(Value::Int(36)), None),
+                record(
+                    "user.email",
+                    "added",
+                    None,
+                    Some(Value::String("a@b".into()))
+                ),
+            ])
+        );
+
+        assert_eq!(
+            call("diff", vec![a.clone(), a]).unwrap(),
+            Value::Array(vec![])
+        );
+        assert_eq!(
+            call("diff", vec![Value::Int(1), Value::Int(2)]).unwrap(),
+            Value::Array(vec![record(
+                "",
+                "changed",
+                Some(Value::Int(1)),
+                Some(Value::Int(2))
+            )])
+        );
+    }
+
     #[test]
     fn unknown_function() {
         assert!(call("nonexistent", vec![]).is_err());
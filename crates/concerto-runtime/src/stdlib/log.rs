@@ -1,6 +1,10 @@
 use crate::error::{Result, RuntimeError};
 use crate::value::Value;
 
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     let msg = args.first().map(|v| v.display_string()).unwrap_or_default();
 
@@ -28,6 +32,66 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     }
 }
 
+/// File sink installed by `std::log::set_file(path, max_bytes)`.
+///
+/// Each log call appends one JSON record per line. When appending would push
+/// the file past `max_bytes`, the file is first renamed to `<path>.1`
+/// (replacing any previous rotation) and a fresh file is started.
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl LogFile {
+    /// Create the sink, checking that `path` can be opened for appending.
+    pub fn open(path: &str, max_bytes: u64) -> std::io::Result<Self> {
+        std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)?;
+        Ok(LogFile {
+            path: PathBuf::from(path),
+            max_bytes,
+        })
+    }
+
+    /// Path the current file is renamed to on rotation.
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        PathBuf::from(rotated)
+    }
+
+    /// Append a record for `level`, rotating first if needed.
+    pub fn append(&self, level: &str, message: &str) -> std::io::Result<()> {
+        let line = format!("{}\n", record(level, message));
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            std::fs::rename(&self.path, self.rotated_path())?;
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+}
+
+/// A structured log record: `{"ts": ISO-8601, "level": ..., "message": ...}`.
+pub fn record(level: &str, message: &str) -> String {
+    let ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    serde_json::json!({
+        "ts": crate::stdlib::time::iso8601(ms),
+        "level": level,
+        "message": message,
+    })
+    .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,6 +128,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn log_file_rotates_past_max_bytes() {
+        let path = format!(
+            "{}/concerto_log_rotate_{}.log",
+            std::env::temp_dir().display(),
+            std::process::id()
+        );
+        std::fs::remove_file(&path).ok();
+        let sink = LogFile::open(&path, 100).unwrap();
+        std::fs::remove_file(sink.rotated_path()).ok();
+
+        sink.append("info", "first").unwrap();
+        let first = std::fs::read_to_string(&path).unwrap();
+        let record: serde_json::Value = serde_json::from_str(first.trim()).unwrap();
+        assert_eq!(record["level"], "info");
+        assert_eq!(record["message"], "first");
+        assert!(!sink.rotated_path().exists());
+
+        sink.append("warn", "second").unwrap();
+        assert!(sink.rotated_path().exists());
+        assert_eq!(std::fs::read_to_string(sink.rotated_path()).unwrap(), first);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("\"second\""));
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(sink.rotated_path()).ok();
+    }
+
     #[test]
     fn unknown_function() {
         assert!(call("nonexistent", vec![]).is_err());
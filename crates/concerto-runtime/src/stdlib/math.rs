@@ -6,14 +6,19 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
         "abs" => stdlib_abs(args),
         "min" => stdlib_min(args),
         "max" => stdlib_max(args),
+        "sum" => stdlib_sum(args),
+        "avg" => stdlib_avg(args),
         "clamp" => stdlib_clamp(args),
         "round" => stdlib_round(args),
+        "round_to" => stdlib_round_to(args),
+        "round_half_even" => stdlib_round_half_even(args),
         "floor" => stdlib_floor(args),
         "ceil" => stdlib_ceil(args),
         "pow" => stdlib_pow(args),
         "sqrt" => stdlib_sqrt(args),
         "random" => stdlib_random(),
         "random_int" => stdlib_random_int(args),
+        "parse_number" => stdlib_parse_number(args),
         _ => Err(RuntimeError::CallError(format!(
             "unknown function: std::math::{}",
             name
@@ -63,7 +68,86 @@ fn stdlib_abs(args: Vec<Value>) -> Result<Value> {
     }
 }
 
+/// Extract the numeric elements of an array argument.
+fn expect_numeric_array(args: &[Value], fn_name: &str) -> Result<Vec<Value>> {
+    match args.first() {
+        Some(Value::Array(items)) => {
+            for item in items {
+                if !matches!(item, Value::Int(_) | Value::Float(_)) {
+                    return Err(RuntimeError::TypeError(format!(
+                        "std::math::{} expected array of numbers, found {}",
+                        fn_name,
+                        item.type_name()
+                    )));
+                }
+            }
+            Ok(items.clone())
+        }
+        Some(other) => Err(RuntimeError::TypeError(format!(
+            "std::math::{} expected Array, got {}",
+            fn_name,
+            other.type_name()
+        ))),
+        None => Err(RuntimeError::TypeError(format!(
+            "std::math::{} missing argument 0",
+            fn_name
+        ))),
+    }
+}
+
+fn empty_array_error(fn_name: &str) -> RuntimeError {
+    RuntimeError::CallError(format!("std::math::{} of an empty array", fn_name))
+}
+
+/// Sum of an array: Int if every element is Int, Float otherwise. Empty sums to 0.
+fn stdlib_sum(args: Vec<Value>) -> Result<Value> {
+    let items = expect_numeric_array(&args, "sum")?;
+    if items.iter().all(|v| matches!(v, Value::Int(_))) {
+        let mut total: i64 = 0;
+        for item in &items {
+            total = total.wrapping_add(to_i64(item, "sum")?);
+        }
+        Ok(Value::Int(total))
+    } else {
+        let mut total = 0.0;
+        for item in &items {
+            total += to_f64(item, "sum")?;
+        }
+        Ok(Value::Float(total))
+    }
+}
+
+/// Arithmetic mean of an array, always a Float.
+fn stdlib_avg(args: Vec<Value>) -> Result<Value> {
+    let items = expect_numeric_array(&args, "avg")?;
+    if items.is_empty() {
+        return Err(empty_array_error("avg"));
+    }
+    let mut total = 0.0;
+    for item in &items {
+        total += to_f64(item, "avg")?;
+    }
+    Ok(Value::Float(total / items.len() as f64))
+}
+
+/// Reduce a non-empty numeric array with a two-argument min/max.
+fn fold_array(
+    args: Vec<Value>,
+    fn_name: &str,
+    pick: fn(Vec<Value>) -> Result<Value>,
+) -> Result<Value> {
+    let mut items = expect_numeric_array(&args, fn_name)?.into_iter();
+    let mut acc = items.next().ok_or_else(|| empty_array_error(fn_name))?;
+    for item in items {
+        acc = pick(vec![acc, item])?;
+    }
+    Ok(acc)
+}
+
 fn stdlib_min(args: Vec<Value>) -> Result<Value> {
+    if args.len() == 1 {
+        return fold_array(args, "min", stdlib_min);
+    }
     let a = expect_numeric(&args, 0, "min")?;
     let b = expect_numeric(&args, 1, "min")?;
     match (&a, &b) {
@@ -77,6 +161,9 @@ fn stdlib_min(args: Vec<Value>) -> Result<Value> {
 }
 
 fn stdlib_max(args: Vec<Value>) -> Result<Value> {
+    if args.len() == 1 {
+        return fold_array(args, "max", stdlib_max);
+    }
     let a = expect_numeric(&args, 0, "max")?;
     let b = expect_numeric(&args, 1, "max")?;
     match (&a, &b) {
@@ -89,6 +176,22 @@ fn stdlib_max(args: Vec<Value>) -> Result<Value> {
     }
 }
 
+/// `Array.sum()`, `.avg()`, `.min()` and `.max()`: the array forms of the
+/// aggregations above, with errors naming the method instead of std::math.
+pub(crate) fn array_method(method: &str, items: &[Value]) -> Result<Value> {
+    let relabel = |msg: String| {
+        msg.replace(
+            &format!("std::math::{}", method),
+            &format!("Array.{}()", method),
+        )
+    };
+    call(method, vec![Value::Array(items.to_vec())]).map_err(|e| match e {
+        RuntimeError::TypeError(msg) => RuntimeError::TypeError(relabel(msg)),
+        RuntimeError::CallError(msg) => RuntimeError::CallError(relabel(msg)),
+        other => other,
+    })
+}
+
 fn stdlib_clamp(args: Vec<Value>) -> Result<Value> {
     let x = expect_numeric(&args, 0, "clamp")?;
     let lo = expect_numeric(&args, 1, "clamp")?;
@@ -110,6 +213,54 @@ fn stdlib_round(args: Vec<Value>) -> Result<Value> {
     Ok(Value::Int(f.round() as i64))
 }
 
+/// Round `x` to `decimals` places (negative rounds to tens, hundreds, ...),
+/// ties away from zero. Scaling goes through the shortest decimal text of `x`
+/// (`1.005` -> `100.5`), so values that print as an exact tie round as they
+/// read instead of by their binary approximation. Shared with `std::fmt`.
+pub(crate) fn round_decimal(x: f64, decimals: i64) -> f64 {
+    if !x.is_finite() {
+        return x;
+    }
+    // f64 spans about 10^±308, so scaling beyond ±400 places changes nothing
+    // (and keeps `-decimals` from overflowing).
+    let decimals = decimals.clamp(-400, 400);
+    let scaled: f64 = format!("{}e{}", x, decimals).parse().unwrap_or(f64::NAN);
+    if !scaled.is_finite() {
+        return x;
+    }
+    format!("{}e{}", scaled.round(), -decimals)
+        .parse()
+        .unwrap_or(x)
+}
+
+/// `round_to(x, decimals)`: see [`round_decimal`].
+fn stdlib_round_to(args: Vec<Value>) -> Result<Value> {
+    let v = expect_numeric(&args, 0, "round_to")?;
+    let x = to_f64(&v, "round_to")?;
+    let decimals = match args.get(1) {
+        Some(Value::Int(d)) => *d,
+        Some(other) => {
+            return Err(RuntimeError::TypeError(format!(
+                "round_to() decimals must be Int, got {}",
+                other.type_name()
+            )))
+        }
+        None => {
+            return Err(RuntimeError::TypeError(
+                "round_to() requires 2 arguments".into(),
+            ))
+        }
+    };
+    Ok(Value::Float(round_decimal(x, decimals)))
+}
+
+/// Round to the nearest Int, ties to the even neighbour (banker's rounding).
+fn stdlib_round_half_even(args: Vec<Value>) -> Result<Value> {
+    let v = expect_numeric(&args, 0, "round_half_even")?;
+    let f = to_f64(&v, "round_half_even")?;
+    Ok(Value::Int(f.round_ties_even() as i64))
+}
+
 fn stdlib_floor(args: Vec<Value>) -> Result<Value> {
     let v = expect_numeric(&args, 0, "floor")?;
     let f = to_f64(&v, "floor")?;
@@ -179,6 +330,45 @@ fn stdlib_random_int(args: Vec<Value>) -> Result<Value> {
     Ok(Value::Int(result))
 }
 
+/// Parse a numeric string as `Int`, or as `Float` when it has a decimal
+/// point or exponent. Returns a `Result` value; surrounding whitespace is
+/// ignored.
+fn stdlib_parse_number(args: Vec<Value>) -> Result<Value> {
+    let text = match args.first() {
+        Some(Value::String(s)) => s.trim(),
+        Some(other) => {
+            return Err(RuntimeError::TypeError(format!(
+                "std::math::parse_number expected String, got {}",
+                other.type_name()
+            )))
+        }
+        None => {
+            return Err(RuntimeError::TypeError(
+                "std::math::parse_number missing argument 0".to_string(),
+            ))
+        }
+    };
+    let parsed = if text.contains(['.', 'e', 'E']) {
+        text.parse::<f64>()
+            .map(Value::Float)
+            .map_err(|e| e.to_string())
+    } else {
+        text.parse::<i64>()
+            .map(Value::Int)
+            .map_err(|e| e.to_string())
+    };
+    Ok(match parsed {
+        Ok(value) => Value::Result {
+            is_ok: true,
+            value: Box::new(value),
+        },
+        Err(e) => Value::Result {
+            is_ok: false,
+            value: Box::new(Value::String(format!("invalid number '{}': {}", text, e))),
+        },
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,6 +403,55 @@ mod tests {
         );
     }
 
+    fn ints(values: &[i64]) -> Value {
+        Value::Array(values.iter().map(|n| Value::Int(*n)).collect())
+    }
+
+    #[test]
+    fn sum_ints_and_mixed() {
+        assert_eq!(call("sum", vec![ints(&[1, 2, 3])]).unwrap(), Value::Int(6));
+        let mixed = Value::Array(vec![Value::Int(1), Value::Float(0.5)]);
+        assert_eq!(call("sum", vec![mixed]).unwrap(), Value::Float(1.5));
+    }
+
+    #[test]
+    fn sum_empty_is_zero() {
+        assert_eq!(call("sum", vec![ints(&[])]).unwrap(), Value::Int(0));
+    }
+
+    #[test]
+    fn avg_is_float() {
+        assert_eq!(call("avg", vec![ints(&[1, 2])]).unwrap(), Value::Float(1.5));
+        let mixed = Value::Array(vec![Value::Int(1), Value::Float(2.0)]);
+        assert_eq!(call("avg", vec![mixed]).unwrap(), Value::Float(1.5));
+    }
+
+    #[test]
+    fn min_max_over_arrays() {
+        assert_eq!(call("min", vec![ints(&[4, 2, 9])]).unwrap(), Value::Int(2));
+        assert_eq!(call("max", vec![ints(&[4, 2, 9])]).unwrap(), Value::Int(9));
+        let mixed = Value::Array(vec![Value::Int(3), Value::Float(1.5)]);
+        assert_eq!(call("min", vec![mixed.clone()]).unwrap(), Value::Float(1.5));
+        assert_eq!(call("max", vec![mixed]).unwrap(), Value::Float(3.0));
+    }
+
+    #[test]
+    fn aggregates_reject_empty_arrays() {
+        for name in ["avg", "min", "max"] {
+            let err = call(name, vec![ints(&[])]).unwrap_err().to_string();
+            assert!(err.contains("empty array"), "{}: {}", name, err);
+        }
+    }
+
+    #[test]
+    fn aggregates_reject_non_numeric_elements() {
+        let arr = Value::Array(vec![Value::Int(1), Value::String("x".into())]);
+        for name in ["sum", "avg", "min", "max"] {
+            let err = call(name, vec![arr.clone()]).unwrap_err().to_string();
+            assert!(err.contains("expected array of numbers"), "{}: {}", name, err);
+        }
+    }
+
     #[test]
     fn clamp_in_range() {
         assert_eq!(
@@ -261,6 +500,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn round_half_even_differs_from_round_at_ties() {
+        for (x, half_up, half_even) in [
+            (0.5, 1, 0),
+            (1.5, 2, 2),
+            (2.5, 3, 2),
+            (-2.5, -3, -2),
+            (2.6, 3, 3),
+        ] {
+            assert_eq!(
+                call("round", vec![Value::Float(x)]).unwrap(),
+                Value::Int(half_up)
+            );
+            assert_eq!(
+                call("round_half_even", vec![Value::Float(x)]).unwrap(),
+                Value::Int(half_even)
+            );
+        }
+        assert_eq!(
+            call("round_half_even", vec![Value::Int(7)]).unwrap(),
+            Value::Int(7)
+        );
+    }
+
+    #[test]
+    fn round_to_decimal_places() {
+        let round_to = |x: f64, d: i64| call("round_to", vec![Value::Float(x), Value::Int(d)]);
+        assert_eq!(round_to(1.23456, 2).unwrap(), Value::Float(1.23));
+        assert_eq!(round_to(1.23456, 0).unwrap(), Value::Float(1.0));
+        assert_eq!(round_to(2.675, 2).unwrap(), Value::Float(2.68));
+        assert_eq!(round_to(1.005, 2).unwrap(), Value::Float(1.01));
+        assert_eq!(round_to(-1.005, 2).unwrap(), Value::Float(-1.01));
+        assert_eq!(round_to(0.1 + 0.2, 2).unwrap(), Value::Float(0.3));
+        assert_eq!(round_to(1234.5, -2).unwrap(), Value::Float(1200.0));
+        assert_eq!(round_to(1.5, i64::MIN).unwrap(), Value::Float(0.0));
+        assert_eq!(round_to(1.5, i64::MAX).unwrap(), Value::Float(1.5));
+        assert_eq!(
+            call("round_to", vec![Value::Int(5), Value::Int(1)]).unwrap(),
+            Value::Float(5.0)
+        );
+        assert!(call("round_to", vec![Value::Float(1.0), Value::Float(1.0)]).is_err());
+    }
+
     #[test]
     fn floor_value() {
         assert_eq!(
@@ -315,6 +597,33 @@ mod tests {
         }
     }
 
+    fn parse_number(text: &str) -> Value {
+        call("parse_number", vec![Value::String(text.into())]).unwrap()
+    }
+
+    #[test]
+    fn parse_number_detects_int_and_float() {
+        let ok = |value| Value::Result {
+            is_ok: true,
+            value: Box::new(value),
+        };
+        assert_eq!(parse_number("42"), ok(Value::Int(42)));
+        assert_eq!(parse_number(" -7 "), ok(Value::Int(-7)));
+        assert_eq!(parse_number("42.0"), ok(Value::Float(42.0)));
+        assert_eq!(parse_number("1e3"), ok(Value::Float(1000.0)));
+    }
+
+    #[test]
+    fn parse_number_invalid_is_err() {
+        for text in ["", "abc", "4 2", "1.2.3", "nan"] {
+            assert!(
+                matches!(parse_number(text), Value::Result { is_ok: false, .. }),
+                "{:?} should not parse",
+                text
+            );
+        }
+    }
+
     #[test]
     fn unknown_function() {
         assert!(call("nonexistent", vec![]).is_err());
@@ -14,6 +14,116 @@ pub mod time;
 use crate::error::{Result, RuntimeError};
 use crate::value::Value;
 
+/// The std:: modules, as named in `std::<module>::<function>` paths.
+pub const MODULES: &[&str] = &[
+    "math",
+    "string",
+    "env",
+    "time",
+    "json",
+    "fmt",
+    "log",
+    "fs",
+    "collections",
+    "http",
+    "crypto",
+    "prompt",
+];
+
+/// The functions of a std:: module, including those the VM handles itself
+/// (`env::args`, `json::validate`, `log::set_file`). Used for did-you-mean
+/// hints; empty for an unknown module.
+pub fn function_names(module: &str) -> &'static [&'static str] {
+    match module {
+        "math" => &[
+            "abs",
+            "min",
+            "max",
+            "sum",
+            "avg",
+            "clamp",
+            "round",
+            "round_to",
+            "round_half_even",
+            "floor",
+            "ceil",
+            "pow",
+            "sqrt",
+            "random",
+            "random_int",
+            "parse_number",
+        ],
+        "string" => &[
+            "split",
+            "join",
+            "trim",
+            "trim_start",
+            "trim_end",
+            "replace",
+            "to_upper",
+            "to_lower",
+            "contains",
+            "starts_with",
+            "ends_with",
+            "substring",
+            "len",
+            "char_len",
+            "to_chars",
+            "from_chars",
+            "repeat",
+            "reverse",
+            "parse_int",
+            "parse_float",
+            "regex_match",
+            "regex_replace",
+            "slugify",
+            "truncate",
+            "levenshtein",
+            "similarity",
+        ],
+        "env" => &["get", "require", "all", "has", "args"],
+        "time" => &["now", "now_ms", "from_ms", "sleep", "measure"],
+        "json" => &[
+            "parse",
+            "parse_as",
+            "stringify",
+            "stringify_pretty",
+            "is_valid",
+            "diff",
+            "validate",
+        ],
+        "fmt" => &[
+            "format",
+            "pad_left",
+            "pad_right",
+            "truncate",
+            "indent",
+            "pretty",
+            "json_lines",
+            "currency",
+            "percent",
+        ],
+        "log" => &["info", "warn", "error", "debug", "set_file"],
+        "fs" => &[
+            "read_file",
+            "write_file",
+            "append_file",
+            "exists",
+            "list_dir",
+            "remove_file",
+            "file_size",
+            "read_json",
+            "write_json",
+            "glob",
+        ],
+        "collections" => &["Set::new", "Set::from", "Queue::new", "Stack::new"],
+        "http" => &["get", "post", "put", "delete", "request"],
+        "crypto" => &["sha256", "md5", "uuid", "random_bytes"],
+        "prompt" => &["template", "from_file", "count_tokens"],
+        _ => &[],
+    }
+}
+
 /// Dispatch a std:: library call by full path name.
 /// Called from VM exec_call when function name starts with "std::".
 pub fn call_stdlib(name: &str, args: Vec<Value>) -> Result<Value> {
@@ -56,6 +166,26 @@ mod tests {
         assert!(err.contains("unknown stdlib module"));
     }
 
+    #[test]
+    fn function_names_are_all_dispatched() {
+        // Handled by the VM rather than `call_stdlib`
+        let vm_handled = ["env::args", "json::validate", "log::set_file"];
+        for module in MODULES {
+            assert!(!function_names(module).is_empty(), "std::{}", module);
+            for function in function_names(module) {
+                let path = format!("{}::{}", module, function);
+                if vm_handled.contains(&path.as_str()) {
+                    continue;
+                }
+                // Called without arguments: an argument error is fine, an
+                // unknown-function error means the list is stale.
+                if let Err(e) = call_stdlib(&format!("std::{}", path), vec![]) {
+                    assert!(!e.to_string().contains("unknown function"), "std::{}", path);
+                }
+            }
+        }
+    }
+
     #[test]
     fn invalid_path_error() {
         let result = call_stdlib("std::nofunction", vec![]);
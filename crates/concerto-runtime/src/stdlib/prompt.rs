@@ -1,5 +1,5 @@
 use crate::error::{Result, RuntimeError};
-use crate::value::Value;
+use crate::value::{MapKey, Value};
 
 pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     match name {
@@ -28,7 +28,88 @@ fn expect_string(args: &[Value], idx: usize, fn_name: &str) -> Result<String> {
     }
 }
 
-/// Replace `${name}` placeholders in text with values from a Map.
+/// Render a template: keep or drop `{{#if name}}...{{else}}...{{/if}}`
+/// blocks by the truthiness of `vars[name]` (missing names are falsy), then
+/// replace `${name}` placeholders. Blocks nest; `{{#else}}` is accepted as
+/// a spelling of `{{else}}`. Other `{{...}}` text is left as-is.
+fn render(text: &str, vars: &[(MapKey, Value)]) -> std::result::Result<String, String> {
+    let mut out = expand_conditionals(text, vars)?;
+    for (key, val) in vars {
+        let placeholder = format!("${{{}}}", key);
+        let replacement = match val {
+            Value::String(s) => s.clone(),
+            other => format!("{}", other),
+        };
+        out = out.replace(&placeholder, &replacement);
+    }
+    Ok(out)
+}
+
+/// An open `{{#if}}` block: its variable, its condition, and whether the
+/// `{{else}}` branch has been reached.
+struct IfBlock<'a> {
+    name: &'a str,
+    condition: bool,
+    in_else: bool,
+}
+
+fn expand_conditionals(
+    text: &str,
+    vars: &[(MapKey, Value)],
+) -> std::result::Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut blocks: Vec<IfBlock> = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let emitting = blocks.iter().all(|b| b.condition != b.in_else);
+        if emitting {
+            out.push_str(&rest[..open]);
+        }
+        let Some(close) = rest[open..].find("}}") else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = rest[open + 2..open + close].trim();
+        let after = &rest[open + close + 2..];
+        if let Some(name) = tag.strip_prefix("#if ") {
+            let name = name.trim();
+            let condition = vars
+                .iter()
+                .find(|(k, _)| matches!(k, MapKey::String(s) if s == name))
+                .is_some_and(|(_, v)| v.is_truthy());
+            blocks.push(IfBlock {
+                name,
+                condition,
+                in_else: false,
+            });
+        } else if tag == "else" || tag == "#else" {
+            match blocks.last_mut() {
+                Some(block) if !block.in_else => block.in_else = true,
+                Some(block) => {
+                    return Err(format!(
+                        "duplicate {{{{else}}}} in {{{{#if {}}}}}",
+                        block.name
+                    ))
+                }
+                None => return Err("{{else}} outside an {{#if}} block".to_string()),
+            }
+        } else if tag == "/if" {
+            if blocks.pop().is_none() {
+                return Err("{{/if}} without a matching {{#if}}".to_string());
+            }
+        } else if emitting {
+            out.push_str(&rest[open..open + close + 2]);
+        }
+        rest = after;
+    }
+    if let Some(block) = blocks.last() {
+        return Err(format!("unclosed {{{{#if {}}}}}", block.name));
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Fill a template with values from a Map (see [`render`]).
 fn stdlib_template(args: Vec<Value>) -> Result<Value> {
     let text = expect_string(&args, 0, "template")?;
     let vars = match args.get(1) {
@@ -46,16 +127,9 @@ fn stdlib_template(args: Vec<Value>) -> Result<Value> {
         }
     };
 
-    let mut result = text;
-    for (key, val) in &vars {
-        let placeholder = format!("${{{}}}", key);
-        let replacement = match val {
-            Value::String(s) => s.clone(),
-            other => format!("{}", other),
-        };
-        result = result.replace(&placeholder, &replacement);
-    }
-    Ok(Value::String(result))
+    render(&text, &vars)
+        .map(Value::String)
+        .map_err(|e| RuntimeError::CallError(format!("std::prompt::template: {}", e)))
 }
 
 /// Read a prompt template from a file and optionally apply variable substitution.
@@ -76,18 +150,15 @@ fn stdlib_from_file(args: Vec<Value>) -> Result<Value> {
 
     // If a vars Map is provided, apply template substitution
     if let Some(Value::Map(pairs)) = args.get(1) {
-        let mut result = content;
-        for (key, val) in pairs {
-            let placeholder = format!("${{{}}}", key);
-            let replacement = match val {
-                Value::String(s) => s.clone(),
-                other => format!("{}", other),
-            };
-            result = result.replace(&placeholder, &replacement);
-        }
-        Ok(Value::Result {
-            is_ok: true,
-            value: Box::new(Value::String(result)),
+        Ok(match render(&content, pairs) {
+            Ok(text) => Value::Result {
+                is_ok: true,
+                value: Box::new(Value::String(text)),
+            },
+            Err(e) => Value::Result {
+                is_ok: false,
+                value: Box::new(Value::String(format!("{}: {}", path, e))),
+            },
         })
     } else {
         Ok(Value::Result {
@@ -143,6 +214,56 @@ mod tests {
         assert_eq!(result, Value::String("No placeholders here.".into()));
     }
 
+    fn render_with(text: &str, vars: Vec<(&str, Value)>) -> Result<Value> {
+        let vars = vars.into_iter().map(|(k, v)| (k.into(), v)).collect();
+        call(
+            "template",
+            vec![Value::String(text.into()), Value::Map(vars)],
+        )
+    }
+
+    #[test]
+    fn template_if_true_keeps_block() {
+        let result = render_with(
+            "Hi ${name}.{{#if premium}} Priority support is on.{{/if}}",
+            vec![
+                ("name", Value::String("Ada".into())),
+                ("premium", Value::Bool(true)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::String("Hi Ada. Priority support is on.".into())
+        );
+    }
+
+    #[test]
+    fn template_if_false_or_missing_drops_block() {
+        let text = "Hi.{{#if premium}} Priority support is on.{{/if}}";
+        let off = render_with(text, vec![("premium", Value::Bool(false))]).unwrap();
+        assert_eq!(off, Value::String("Hi.".into()));
+        let missing = render_with(text, vec![]).unwrap();
+        assert_eq!(missing, Value::String("Hi.".into()));
+    }
+
+    #[test]
+    fn template_if_else_picks_branch_and_nests() {
+        let text = "{{#if premium}}Gold{{#if beta}} (beta){{/if}}{{else}}Free {{ tier }}{{/if}}";
+        let gold = render_with(
+            text,
+            vec![("premium", Value::Bool(true)), ("beta", Value::Int(1))],
+        )
+        .unwrap();
+        assert_eq!(gold, Value::String("Gold (beta)".into()));
+        let free = render_with(text, vec![("premium", Value::Bool(false))]).unwrap();
+        assert_eq!(free, Value::String("Free {{ tier }}".into()));
+
+        let err = render_with("{{#if a}}open", vec![]).unwrap_err();
+        assert!(err.to_string().contains("unclosed {{#if a}}"), "{}", err);
+        assert!(render_with("{{/if}}", vec![]).is_err());
+    }
+
     #[test]
     fn from_file_missing() {
         let result = call(
@@ -16,10 +16,19 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
         "ends_with" => stdlib_ends_with(args),
         "substring" => stdlib_substring(args),
         "len" => stdlib_len(args),
+        "char_len" => stdlib_char_len(args),
+        "to_chars" => stdlib_to_chars(args),
+        "from_chars" => stdlib_from_chars(args),
         "repeat" => stdlib_repeat(args),
         "reverse" => stdlib_reverse(args),
         "parse_int" => stdlib_parse_int(args),
         "parse_float" => stdlib_parse_float(args),
+        "regex_match" => stdlib_regex_match(args),
+        "regex_replace" => stdlib_regex_replace(args),
+        "slugify" => stdlib_slugify(args),
+        "truncate" => stdlib_truncate(args),
+        "levenshtein" => stdlib_levenshtein(args),
+        "similarity" => stdlib_similarity(args),
         _ => Err(RuntimeError::CallError(format!(
             "unknown function: std::string::{}",
             name
@@ -150,6 +159,44 @@ fn stdlib_len(args: Vec<Value>) -> Result<Value> {
     Ok(Value::Int(s.chars().count() as i64))
 }
 
+/// Number of Unicode scalar values; the same count as `len`.
+fn stdlib_char_len(args: Vec<Value>) -> Result<Value> {
+    let s = expect_string(&args, 0, "char_len")?;
+    Ok(Value::Int(s.chars().count() as i64))
+}
+
+/// One single-character String per Unicode scalar value.
+fn stdlib_to_chars(args: Vec<Value>) -> Result<Value> {
+    let s = expect_string(&args, 0, "to_chars")?;
+    Ok(Value::Array(
+        s.chars().map(|c| Value::String(c.to_string())).collect(),
+    ))
+}
+
+fn stdlib_from_chars(args: Vec<Value>) -> Result<Value> {
+    let arr = match args.first() {
+        Some(Value::Array(a)) => a,
+        _ => {
+            return Err(RuntimeError::TypeError(
+                "std::string::from_chars expected Array as first argument".to_string(),
+            ))
+        }
+    };
+    let mut out = String::new();
+    for item in arr {
+        match item {
+            Value::String(s) => out.push_str(s),
+            other => {
+                return Err(RuntimeError::TypeError(format!(
+                    "std::string::from_chars expected an Array of String, got {}",
+                    other.type_name()
+                )))
+            }
+        }
+    }
+    Ok(Value::String(out))
+}
+
 fn stdlib_repeat(args: Vec<Value>) -> Result<Value> {
     let s = expect_string(&args, 0, "repeat")?;
     let n = expect_int(&args, 1, "repeat")?;
@@ -189,6 +236,133 @@ fn stdlib_parse_float(args: Vec<Value>) -> Result<Value> {
     }
 }
 
+/// Compile a pattern, returning the compile error message on failure. The
+/// `regex` engine matches in linear time, so patterns applied to model
+/// output cannot backtrack exponentially.
+fn compile_regex(pattern: &str) -> std::result::Result<regex::Regex, String> {
+    regex::Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))
+}
+
+fn stdlib_regex_match(args: Vec<Value>) -> Result<Value> {
+    let s = expect_string(&args, 0, "regex_match")?;
+    let pattern = expect_string(&args, 1, "regex_match")?;
+    let outcome = compile_regex(&pattern).map(|re| {
+        re.captures(&s).map(|caps| {
+            // Full match first, then each group; unmatched groups are "".
+            let groups = caps
+                .iter()
+                .map(|m| Value::String(m.map(|m| m.as_str()).unwrap_or("").to_string()))
+                .collect();
+            Box::new(Value::Array(groups))
+        })
+    });
+    Ok(match outcome {
+        Ok(groups) => Value::Result {
+            is_ok: true,
+            value: Box::new(Value::Option(groups)),
+        },
+        Err(e) => Value::Result {
+            is_ok: false,
+            value: Box::new(Value::String(e)),
+        },
+    })
+}
+
+fn stdlib_regex_replace(args: Vec<Value>) -> Result<Value> {
+    let s = expect_string(&args, 0, "regex_replace")?;
+    let pattern = expect_string(&args, 1, "regex_replace")?;
+    let replacement = expect_string(&args, 2, "regex_replace")?;
+    let outcome =
+        compile_regex(&pattern).map(|re| re.replace_all(&s, replacement.as_str()).into_owned());
+    Ok(match outcome {
+        Ok(replaced) => Value::Result {
+            is_ok: true,
+            value: Box::new(Value::String(replaced)),
+        },
+        Err(e) => Value::Result {
+            is_ok: false,
+            value: Box::new(Value::String(e)),
+        },
+    })
+}
+
+/// Lowercase, keep alphanumerics (any script), and join the words with
+/// single hyphens. Whitespace, `-` and `_` separate words; other characters
+/// are dropped.
+fn stdlib_slugify(args: Vec<Value>) -> Result<Value> {
+    let s = expect_string(&args, 0, "slugify")?;
+    let mut slug = String::with_capacity(s.len());
+    let mut pending_hyphen = false;
+    for c in s.chars() {
+        if c.is_alphanumeric() {
+            if pending_hyphen && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_hyphen = false;
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() || c == '-' || c == '_' {
+            pending_hyphen = true;
+        }
+    }
+    Ok(Value::String(slug))
+}
+
+/// Keep the first `max_chars` characters, appending the optional ellipsis
+/// only when something was cut.
+fn stdlib_truncate(args: Vec<Value>) -> Result<Value> {
+    let s = expect_string(&args, 0, "truncate")?;
+    let max_chars = expect_int(&args, 1, "truncate")?.max(0) as usize;
+    let ellipsis = if args.len() > 2 {
+        expect_string(&args, 2, "truncate")?
+    } else {
+        String::new()
+    };
+    match s.char_indices().nth(max_chars) {
+        Some((cut, _)) => Ok(Value::String(format!("{}{}", &s[..cut], ellipsis))),
+        None => Ok(Value::String(s)),
+    }
+}
+
+/// Edit distance over characters (insertions, deletions, substitutions),
+/// using a single rolling row.
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn stdlib_levenshtein(args: Vec<Value>) -> Result<Value> {
+    let a = expect_string(&args, 0, "levenshtein")?;
+    let b = expect_string(&args, 1, "levenshtein")?;
+    Ok(Value::Int(levenshtein(&a, &b) as i64))
+}
+
+/// `1 - distance / max_len`, so identical strings (including two empty
+/// strings) score 1.0 and completely different ones 0.0.
+fn stdlib_similarity(args: Vec<Value>) -> Result<Value> {
+    let a = expect_string(&args, 0, "similarity")?;
+    let b = expect_string(&args, 1, "similarity")?;
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return Ok(Value::Float(1.0));
+    }
+    let distance = levenshtein(&a, &b);
+    Ok(Value::Float(1.0 - distance as f64 / max_len as f64))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -315,6 +489,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn char_functions_count_scalar_values() {
+        // "e" + combining acute accent is two scalar values; the emoji is one
+        let text = "ne\u{301}e 👋";
+        assert_eq!(
+            call("char_len", vec![Value::String(text.into())]).unwrap(),
+            Value::Int(6)
+        );
+        assert_eq!(
+            call("len", vec![Value::String(text.into())]).unwrap(),
+            Value::Int(6)
+        );
+
+        let chars = call("to_chars", vec![Value::String(text.into())]).unwrap();
+        let expected: Vec<Value> = ["n", "e", "\u{301}", "e", " ", "👋"]
+            .iter()
+            .map(|c| Value::String(c.to_string()))
+            .collect();
+        assert_eq!(chars, Value::Array(expected));
+        assert_eq!(
+            call("from_chars", vec![chars]).unwrap(),
+            Value::String(text.into())
+        );
+        assert!(call("from_chars", vec![Value::Array(vec![Value::Int(1)])]).is_err());
+    }
+
     #[test]
     fn repeat_basic() {
         assert_eq!(
@@ -370,8 +570,195 @@ mod tests {
         );
     }
 
+    #[test]
+    fn slugify_basic() {
+        assert_eq!(
+            call(
+                "slugify",
+                vec![Value::String("  Hello, World! -- Q3_report ".into())]
+            )
+            .unwrap(),
+            Value::String("hello-world-q3-report".into())
+        );
+        assert_eq!(
+            call("slugify", vec![Value::String("Ünïcode Straße 東京".into())]).unwrap(),
+            Value::String("ünïcode-straße-東京".into())
+        );
+    }
+
+    #[test]
+    fn truncate_on_char_boundary() {
+        assert_eq!(
+            call(
+                "truncate",
+                vec![
+                    Value::String("héllo wörld".into()),
+                    Value::Int(7),
+                    Value::String("…".into())
+                ]
+            )
+            .unwrap(),
+            Value::String("héllo w…".into())
+        );
+        assert_eq!(
+            call(
+                "truncate",
+                vec![Value::String("日本語テキスト".into()), Value::Int(3)]
+            )
+            .unwrap(),
+            Value::String("日本語".into())
+        );
+    }
+
+    #[test]
+    fn truncate_short_string_unchanged() {
+        for s in ["short", "exact"] {
+            assert_eq!(
+                call(
+                    "truncate",
+                    vec![
+                        Value::String(s.into()),
+                        Value::Int(5),
+                        Value::String("...".into())
+                    ]
+                )
+                .unwrap(),
+                Value::String(s.into())
+            );
+        }
+    }
+
+    #[test]
+    fn regex_match_captures_groups() {
+        let result = call(
+            "regex_match",
+            vec![
+                Value::String("order #42 shipped".into()),
+                Value::String(r"#(\d+) (\w+)".into()),
+            ],
+        )
+        .unwrap();
+        let expected = Value::Array(vec![
+            Value::String("#42 shipped".into()),
+            Value::String("42".into()),
+            Value::String("shipped".into()),
+        ]);
+        match result {
+            Value::Result { is_ok: true, value } => {
+                assert_eq!(*value, Value::Option(Some(Box::new(expected))))
+            }
+            other => panic!("expected Ok(Some(..)), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn regex_match_no_match_is_none() {
+        let result = call(
+            "regex_match",
+            vec![Value::String("abc".into()), Value::String(r"\d+".into())],
+        )
+        .unwrap();
+        match result {
+            Value::Result { is_ok: true, value } => assert_eq!(*value, Value::Option(None)),
+            other => panic!("expected Ok(None), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn regex_replace_with_backrefs() {
+        let result = call(
+            "regex_replace",
+            vec![
+                Value::String("Doe, John; Roe, Jane".into()),
+                Value::String(r"(\w+), (\w+)".into()),
+                Value::String("$2 $1".into()),
+            ],
+        )
+        .unwrap();
+        match result {
+            Value::Result { is_ok: true, value } => {
+                assert_eq!(*value, Value::String("John Doe; Jane Roe".into()))
+            }
+            other => panic!("expected Ok, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn regex_nested_quantifiers_do_not_backtrack() {
+        // Exponential for a backtracking engine; linear here.
+        let subject = format!("{}!", "a".repeat(10_000));
+        let result = call(
+            "regex_match",
+            vec![Value::String(subject), Value::String(r"^(a+)+$".into())],
+        )
+        .unwrap();
+        match result {
+            Value::Result { is_ok: true, value } => assert_eq!(*value, Value::Option(None)),
+            other => panic!("expected Ok(None), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn regex_invalid_pattern_is_err() {
+        let result = call(
+            "regex_replace",
+            vec![
+                Value::String("abc".into()),
+                Value::String("(unclosed".into()),
+                Value::String("".into()),
+            ],
+        )
+        .unwrap();
+        match result {
+            Value::Result {
+                is_ok: false,
+                value,
+            } => {
+                assert!(value.display_string().contains("invalid regex"))
+            }
+            other => panic!("expected Err, got {:?}", other),
+        }
+    }
+
     #[test]
     fn unknown_function() {
         assert!(call("nonexistent", vec![]).is_err());
     }
+
+    fn edit_distance(a: &str, b: &str) -> Value {
+        call(
+            "levenshtein",
+            vec![Value::String(a.into()), Value::String(b.into())],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn levenshtein_known_distances() {
+        assert_eq!(edit_distance("kitten", "sitting"), Value::Int(3));
+        assert_eq!(edit_distance("flaw", "lawn"), Value::Int(2));
+        assert_eq!(edit_distance("same", "same"), Value::Int(0));
+        assert_eq!(edit_distance("", "abc"), Value::Int(3));
+        assert_eq!(edit_distance("abc", ""), Value::Int(3));
+        assert_eq!(edit_distance("", ""), Value::Int(0));
+        assert_eq!(edit_distance("café", "cafe"), Value::Int(1));
+    }
+
+    #[test]
+    fn similarity_scores() {
+        let sim = |a: &str, b: &str| match call(
+            "similarity",
+            vec![Value::String(a.into()), Value::String(b.into())],
+        )
+        .unwrap()
+        {
+            Value::Float(f) => f,
+            other => panic!("expected Float, got {:?}", other),
+        };
+        assert_eq!(sim("positive", "positive"), 1.0);
+        assert_eq!(sim("", ""), 1.0);
+        assert_eq!(sim("abc", ""), 0.0);
+        assert_eq!(sim("abc", "xyz"), 0.0);
+        assert!((sim("kitten", "sitting") - (1.0 - 3.0 / 7.0)).abs() < 1e-9);
+    }
 }
@@ -7,6 +7,7 @@ pub fn call(name: &str, args: Vec<Value>) -> Result<Value> {
     match name {
         "now" => stdlib_now(),
         "now_ms" => stdlib_now_ms(),
+        "from_ms" => stdlib_from_ms(args),
         "sleep" => stdlib_sleep(args),
         "measure" => Err(RuntimeError::CallError(
             "std::time::measure requires VM context for closure execution. \
@@ -24,8 +25,7 @@ fn stdlib_now() -> Result<Value> {
     let duration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default();
-    let iso = epoch_to_iso8601(duration.as_secs(), duration.subsec_millis());
-    Ok(Value::String(iso))
+    Ok(Value::DateTime(duration.as_millis() as i64))
 }
 
 fn stdlib_now_ms() -> Result<Value> {
@@ -35,6 +35,19 @@ fn stdlib_now_ms() -> Result<Value> {
     Ok(Value::Int(duration.as_millis() as i64))
 }
 
+fn stdlib_from_ms(args: Vec<Value>) -> Result<Value> {
+    match args.first() {
+        Some(Value::Int(ms)) => Ok(Value::DateTime(*ms)),
+        Some(other) => Err(RuntimeError::TypeError(format!(
+            "std::time::from_ms expected Int, got {}",
+            other.type_name()
+        ))),
+        None => Err(RuntimeError::TypeError(
+            "std::time::from_ms missing argument".to_string(),
+        )),
+    }
+}
+
 fn stdlib_sleep(args: Vec<Value>) -> Result<Value> {
     let ms = match args.first() {
         Some(Value::Int(n)) => *n,
@@ -54,21 +67,43 @@ fn stdlib_sleep(args: Vec<Value>) -> Result<Value> {
     Ok(Value::Nil)
 }
 
-/// Convert Unix epoch seconds + millis to ISO 8601 UTC string.
-/// Format: YYYY-MM-DDTHH:MM:SS.mmmZ
-fn epoch_to_iso8601(epoch_secs: u64, millis: u32) -> String {
-    // Days since 1970-01-01
-    let total_secs = epoch_secs;
-    let secs_in_day = 86400u64;
-    let mut days = (total_secs / secs_in_day) as i64;
-    let day_secs = (total_secs % secs_in_day) as u32;
-
-    let hours = day_secs / 3600;
-    let minutes = (day_secs % 3600) / 60;
-    let seconds = day_secs % 60;
-
-    // Civil date from days since epoch (algorithm from Howard Hinnant)
-    days += 719468; // shift to 0000-03-01
+// ============================================================================
+// DateTime
+// ============================================================================
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// Calendar fields of a UTC timestamp.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Civil {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millis: u32,
+}
+
+/// Split epoch milliseconds into UTC calendar fields.
+pub fn civil_from_ms(ms: i64) -> Civil {
+    let days = ms.div_euclid(MS_PER_DAY);
+    let day_ms = ms.rem_euclid(MS_PER_DAY) as u32;
+    let (year, month, day) = civil_from_days(days);
+    Civil {
+        year,
+        month,
+        day,
+        hour: day_ms / 3_600_000,
+        minute: (day_ms / 60_000) % 60,
+        second: (day_ms / 1000) % 60,
+        millis: day_ms % 1000,
+    }
+}
+
+/// Civil date from days since 1970-01-01 (algorithm from Howard Hinnant).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468; // shift to 0000-03-01
     let era = if days >= 0 { days } else { days - 146096 } / 146097;
     let doe = (days - era * 146097) as u32; // day of era [0, 146096]
     let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // year of era [0, 399]
@@ -77,12 +112,88 @@ fn epoch_to_iso8601(epoch_secs: u64, millis: u32) -> String {
     let mp = (5 * doy + 2) / 153; // month [0, 11]
     let d = doy - (153 * mp + 2) / 5 + 1; // day [1, 31]
     let m = if mp < 10 { mp + 3 } else { mp - 9 }; // month [1, 12]
-    let year = if m <= 2 { y + 1 } else { y };
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
 
-    format!(
-        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
-        year, m, d, hours, minutes, seconds, millis
-    )
+/// Format a timestamp with strftime-style specifiers:
+/// `%Y %m %d %H %M %S` (zero-padded), `%f` (milliseconds) and `%%`.
+/// Other characters are copied through.
+pub fn format_datetime(ms: i64, pattern: &str) -> String {
+    let c = civil_from_ms(ms);
+    let mut out = String::with_capacity(pattern.len() + 8);
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&format!("{:04}", c.year)),
+            Some('m') => out.push_str(&format!("{:02}", c.month)),
+            Some('d') => out.push_str(&format!("{:02}", c.day)),
+            Some('H') => out.push_str(&format!("{:02}", c.hour)),
+            Some('M') => out.push_str(&format!("{:02}", c.minute)),
+            Some('S') => out.push_str(&format!("{:02}", c.second)),
+            Some('f') => out.push_str(&format!("{:03}", c.millis)),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// ISO 8601 UTC string: `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+pub fn iso8601(ms: i64) -> String {
+    format_datetime(ms, "%Y-%m-%dT%H:%M:%S.%fZ")
+}
+
+/// Dispatch a method call on a `DateTime` value.
+pub fn call_datetime_method(ms: i64, method: &str, args: Vec<Value>) -> Result<Value> {
+    let c = civil_from_ms(ms);
+    match method {
+        "year" => Ok(Value::Int(c.year)),
+        "month" => Ok(Value::Int(c.month as i64)),
+        "day" => Ok(Value::Int(c.day as i64)),
+        "hour" => Ok(Value::Int(c.hour as i64)),
+        "minute" => Ok(Value::Int(c.minute as i64)),
+        "second" => Ok(Value::Int(c.second as i64)),
+        "to_ms" => Ok(Value::Int(ms)),
+        "add_days" => match args.first() {
+            Some(Value::Int(n)) => n
+                .checked_mul(MS_PER_DAY)
+                .and_then(|delta| ms.checked_add(delta))
+                .map(Value::DateTime)
+                .ok_or_else(|| {
+                    RuntimeError::TypeError(format!("DateTime.add_days({}) overflows", n))
+                }),
+            _ => Err(RuntimeError::TypeError(
+                "DateTime.add_days() requires an Int argument".into(),
+            )),
+        },
+        "diff" => match args.first() {
+            Some(Value::DateTime(other)) => ms
+                .checked_sub(*other)
+                .map(Value::Int)
+                .ok_or_else(|| RuntimeError::TypeError("DateTime.diff() overflows".into())),
+            _ => Err(RuntimeError::TypeError(
+                "DateTime.diff() requires a DateTime argument".into(),
+            )),
+        },
+        "format" => match args.first() {
+            Some(Value::String(pattern)) => Ok(Value::String(format_datetime(ms, pattern))),
+            _ => Err(RuntimeError::TypeError(
+                "DateTime.format() requires a String pattern".into(),
+            )),
+        },
+        _ => Err(RuntimeError::TypeError(format!(
+            "no method '{}' on DateTime",
+            method
+        ))),
+    }
 }
 
 #[cfg(test)]
@@ -90,14 +201,17 @@ mod tests {
     use super::*;
 
     #[test]
-    fn now_returns_string() {
+    fn now_returns_datetime() {
         let result = call("now", vec![]).unwrap();
         match result {
-            Value::String(s) => {
+            Value::DateTime(ms) => {
+                // Should be a reasonable epoch time (after 2020)
+                assert!(ms > 1_577_836_800_000);
+                let s = result.display_string();
                 assert!(s.ends_with('Z'));
                 assert!(s.contains('T'));
             }
-            _ => panic!("expected String"),
+            _ => panic!("expected DateTime"),
         }
     }
 
@@ -120,16 +234,65 @@ mod tests {
     }
 
     #[test]
-    fn epoch_to_iso8601_known() {
+    fn iso8601_known() {
         // 2024-01-01T00:00:00.000Z = 1704067200 seconds
-        let result = epoch_to_iso8601(1704067200, 0);
-        assert_eq!(result, "2024-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(1_704_067_200_000), "2024-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(-1), "1969-12-31T23:59:59.999Z");
+    }
+
+    /// 2024-02-28T13:45:30.250Z
+    const LEAP_EVE: i64 = 1_709_127_930_250;
+
+    #[test]
+    fn datetime_components() {
+        let get = |m: &str| call_datetime_method(LEAP_EVE, m, vec![]).unwrap();
+        assert_eq!(get("year"), Value::Int(2024));
+        assert_eq!(get("month"), Value::Int(2));
+        assert_eq!(get("day"), Value::Int(28));
+        assert_eq!(get("hour"), Value::Int(13));
+        assert_eq!(get("minute"), Value::Int(45));
+        assert_eq!(get("second"), Value::Int(30));
+    }
+
+    #[test]
+    fn datetime_add_days_and_diff() {
+        let next = call_datetime_method(LEAP_EVE, "add_days", vec![Value::Int(2)]).unwrap();
+        assert_eq!(next.display_string(), "2024-03-01T13:45:30.250Z");
+        let back = call_datetime_method(LEAP_EVE, "add_days", vec![Value::Int(-59)]).unwrap();
+        assert_eq!(back.display_string(), "2023-12-31T13:45:30.250Z");
+        let Value::DateTime(next_ms) = next else {
+            panic!("expected DateTime");
+        };
+        assert_eq!(
+            call_datetime_method(next_ms, "diff", vec![Value::DateTime(LEAP_EVE)]).unwrap(),
+            Value::Int(2 * 86_400_000)
+        );
+        assert!(call_datetime_method(LEAP_EVE, "add_days", vec![Value::Int(i64::MAX)]).is_err());
+        assert!(call_datetime_method(i64::MAX, "diff", vec![Value::DateTime(-1)]).is_err());
+    }
+
+    #[test]
+    fn datetime_format() {
+        let formatted = call_datetime_method(
+            LEAP_EVE,
+            "format",
+            vec![Value::String("%d/%m/%Y %H:%M:%S.%f (100%%)".into())],
+        )
+        .unwrap();
+        assert_eq!(
+            formatted,
+            Value::String("28/02/2024 13:45:30.250 (100%)".into())
+        );
     }
 
     #[test]
-    fn epoch_to_iso8601_with_millis() {
-        let result = epoch_to_iso8601(0, 0);
-        assert_eq!(result, "1970-01-01T00:00:00.000Z");
+    fn from_ms_builds_datetime() {
+        assert_eq!(
+            call("from_ms", vec![Value::Int(LEAP_EVE)]).unwrap(),
+            Value::DateTime(LEAP_EVE)
+        );
+        assert!(call("from_ms", vec![Value::String("x".into())]).is_err());
     }
 
     #[test]
@@ -30,6 +30,11 @@ impl ToolRegistry {
         self.tool_state.entry(name.to_string()).or_default();
     }
 
+    /// Register a tool with the given initial state fields.
+    pub fn register_tool_with_state(&mut self, name: &str, state: HashMap<String, Value>) {
+        self.tool_state.insert(name.to_string(), state);
+    }
+
     /// Get a tool's state as a Value::Struct (used as `self` in method calls).
     pub fn get_self_value(&self, tool_name: &str) -> Value {
         let fields = self.tool_state.get(tool_name).cloned().unwrap_or_default();
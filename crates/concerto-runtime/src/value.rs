@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
 
@@ -13,7 +14,7 @@ pub enum Value {
     Nil,
     Array(Vec<Value>),
     /// Ordered key-value pairs (preserves insertion order).
-    Map(Vec<(String, Value)>),
+    Map(Vec<(MapKey, Value)>),
     Struct {
         type_name: String,
         fields: HashMap<String, Value>,
@@ -54,6 +55,8 @@ pub enum Value {
         end: i64,
         inclusive: bool,
     },
+    /// A UTC timestamp in milliseconds since the Unix epoch.
+    DateTime(i64),
     /// Transient builder for Model/Agent execution with chained config.
     /// Created by Model.with_memory(), Model.with_tools(), etc.
     ModelBuilder {
@@ -64,6 +67,15 @@ pub enum Value {
         extra_tools: Vec<String>,
         exclude_default_tools: bool,
         context: Option<Box<Value>>,
+        /// Per-call `with_retry(n)` override of the model's `@retry` attempts.
+        retry: Option<u32>,
+        /// Per-call `with_timeout(secs)` override of the model's `@timeout`.
+        timeout: Option<u64>,
+        /// Per-call `with_seed(n)` override of the model's `seed`.
+        seed: Option<i64>,
+        /// Inline `mock(text)` (Ok) or `mock_error(msg)` (Err): answer this
+        /// call without a provider or agent.
+        mock: Option<std::result::Result<String, String>>,
     },
 }
 
@@ -74,6 +86,110 @@ pub enum BuilderSourceKind {
     Agent,
 }
 
+/// A map key. Keys keep their type, so the Int key `1`, the Bool key
+/// `true`, and the String key `"1"` are distinct entries. Tuples of keys
+/// (arrays at runtime) are keys too. Keys order by variant first (strings,
+/// then ints, bools, tuples), then by value.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapKey {
+    String(String),
+    Int(i64),
+    Bool(bool),
+    Tuple(Vec<MapKey>),
+}
+
+impl MapKey {
+    /// Convert a value to a key. Floats, nil, and compound values other than
+    /// tuples of keys are not hashable.
+    pub fn from_value(value: &Value) -> crate::error::Result<MapKey> {
+        match value {
+            Value::String(s) => Ok(MapKey::String(s.clone())),
+            Value::Int(n) => Ok(MapKey::Int(*n)),
+            Value::Bool(b) => Ok(MapKey::Bool(*b)),
+            Value::Array(items) => items
+                .iter()
+                .map(MapKey::from_value)
+                .collect::<crate::error::Result<_>>()
+                .map(MapKey::Tuple),
+            other => Err(RuntimeError::TypeError(format!(
+                "{} cannot be used as a map key",
+                other.type_name()
+            ))),
+        }
+    }
+
+    /// The key as a runtime value (tuples become arrays).
+    pub fn to_value(&self) -> Value {
+        match self {
+            MapKey::String(s) => Value::String(s.clone()),
+            MapKey::Int(n) => Value::Int(*n),
+            MapKey::Bool(b) => Value::Bool(*b),
+            MapKey::Tuple(items) => Value::Array(items.iter().map(MapKey::to_value).collect()),
+        }
+    }
+
+    /// The key text if this is a String key.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MapKey::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Key as written in map display: strings quoted, others bare.
+    fn literal(&self) -> String {
+        match self {
+            MapKey::String(s) => format!("{:?}", s),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl From<String> for MapKey {
+    fn from(s: String) -> Self {
+        MapKey::String(s)
+    }
+}
+
+impl From<&str> for MapKey {
+    fn from(s: &str) -> Self {
+        MapKey::String(s.to_string())
+    }
+}
+
+impl PartialEq<str> for MapKey {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<String> for MapKey {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other.as_str())
+    }
+}
+
+/// Plain key text (used for JSON object keys and string coercion).
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapKey::String(s) => write!(f, "{}", s),
+            MapKey::Int(n) => write!(f, "{}", n),
+            MapKey::Bool(b) => write!(f, "{}", b),
+            MapKey::Tuple(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item.literal())?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 // ============================================================================
 // Arithmetic operations
 // ============================================================================
@@ -177,6 +293,64 @@ impl Value {
             ))),
         }
     }
+
+    pub fn bit_and(&self, other: &Value) -> crate::error::Result<Value> {
+        let (a, b) = self.int_operands(other, "&")?;
+        Ok(Value::Int(a & b))
+    }
+
+    pub fn bit_or(&self, other: &Value) -> crate::error::Result<Value> {
+        let (a, b) = self.int_operands(other, "|")?;
+        Ok(Value::Int(a | b))
+    }
+
+    pub fn bit_xor(&self, other: &Value) -> crate::error::Result<Value> {
+        let (a, b) = self.int_operands(other, "^")?;
+        Ok(Value::Int(a ^ b))
+    }
+
+    pub fn shl(&self, other: &Value) -> crate::error::Result<Value> {
+        let (a, b) = self.int_operands(other, "<<")?;
+        shift_amount(b).map(|n| Value::Int(a.wrapping_shl(n)))
+    }
+
+    /// Arithmetic (sign-extending) right shift.
+    pub fn shr(&self, other: &Value) -> crate::error::Result<Value> {
+        let (a, b) = self.int_operands(other, ">>")?;
+        shift_amount(b).map(|n| Value::Int(a.wrapping_shr(n)))
+    }
+
+    pub fn bit_not(&self) -> crate::error::Result<Value> {
+        match self {
+            Value::Int(a) => Ok(Value::Int(!a)),
+            _ => Err(RuntimeError::TypeError(format!(
+                "cannot apply ~ to {}",
+                self.type_name()
+            ))),
+        }
+    }
+
+    /// Both operands of a bitwise operator, which must be Int.
+    fn int_operands(&self, other: &Value, op: &str) -> crate::error::Result<(i64, i64)> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Ok((*a, *b)),
+            _ => Err(RuntimeError::TypeError(format!(
+                "cannot apply {} to {} and {}",
+                op,
+                self.type_name(),
+                other.type_name()
+            ))),
+        }
+    }
+}
+
+/// Validate a shift amount: it must be in `0..64`.
+fn shift_amount(n: i64) -> crate::error::Result<u32> {
+    if (0..64).contains(&n) {
+        Ok(n as u32)
+    } else {
+        Err(RuntimeError::ShiftOverflow(n))
+    }
 }
 
 // ============================================================================
@@ -192,64 +366,75 @@ impl Value {
         Value::Bool(self != other)
     }
 
-    pub fn lt(&self, other: &Value) -> crate::error::Result<Value> {
+    /// Order two values: numbers (with Int/Float promotion), strings
+    /// (lexicographic), DateTimes (chronological) and arrays (element-wise, a strict prefix orders
+    /// first). Returns `None` for unordered floats (NaN) and a `TypeError`
+    /// for incompatible operands.
+    pub fn compare(&self, other: &Value) -> crate::error::Result<Option<Ordering>> {
         match (self, other) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a < b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(a < b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Bool((*a as f64) < *b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Bool(*a < *b as f64)),
-            (Value::String(a), Value::String(b)) => Ok(Value::Bool(a < b)),
+            (Value::Int(a), Value::Int(b)) => Ok(Some(a.cmp(b))),
+            (Value::Float(a), Value::Float(b)) => Ok(a.partial_cmp(b)),
+            (Value::Int(a), Value::Float(b)) => Ok((*a as f64).partial_cmp(b)),
+            (Value::Float(a), Value::Int(b)) => Ok(a.partial_cmp(&(*b as f64))),
+            (Value::String(a), Value::String(b)) => Ok(Some(a.cmp(b))),
+            (Value::DateTime(a), Value::DateTime(b)) => Ok(Some(a.cmp(b))),
+            (Value::Array(a), Value::Array(b)) => {
+                for (x, y) in a.iter().zip(b) {
+                    match x.compare(y)? {
+                        Some(Ordering::Equal) => continue,
+                        ord => return Ok(ord),
+                    }
+                }
+                Ok(Some(a.len().cmp(&b.len())))
+            }
             _ => Err(RuntimeError::TypeError(format!(
-                "cannot compare {} < {}",
+                "cannot compare {} with {}",
                 self.type_name(),
                 other.type_name()
             ))),
         }
     }
 
-    pub fn gt(&self, other: &Value) -> crate::error::Result<Value> {
-        match (self, other) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a > b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(a > b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Bool(*a as f64 > *b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Bool(*a > *b as f64)),
-            (Value::String(a), Value::String(b)) => Ok(Value::Bool(a > b)),
-            _ => Err(RuntimeError::TypeError(format!(
-                "cannot compare {} > {}",
+    fn compare_op(
+        &self,
+        other: &Value,
+        op: &str,
+        accept: fn(Ordering) -> bool,
+    ) -> crate::error::Result<Value> {
+        let comparable = matches!(
+            (self, other),
+            (
+                Value::Int(_) | Value::Float(_),
+                Value::Int(_) | Value::Float(_)
+            ) | (Value::String(_), Value::String(_))
+                | (Value::DateTime(_), Value::DateTime(_))
+                | (Value::Array(_), Value::Array(_))
+        );
+        if !comparable {
+            return Err(RuntimeError::TypeError(format!(
+                "cannot compare {} {} {}",
                 self.type_name(),
+                op,
                 other.type_name()
-            ))),
+            )));
         }
+        Ok(Value::Bool(self.compare(other)?.is_some_and(accept)))
+    }
+
+    pub fn lt(&self, other: &Value) -> crate::error::Result<Value> {
+        self.compare_op(other, "<", Ordering::is_lt)
+    }
+
+    pub fn gt(&self, other: &Value) -> crate::error::Result<Value> {
+        self.compare_op(other, ">", Ordering::is_gt)
     }
 
     pub fn lte(&self, other: &Value) -> crate::error::Result<Value> {
-        match (self, other) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a <= b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(a <= b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Bool(*a as f64 <= *b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Bool(*a <= *b as f64)),
-            (Value::String(a), Value::String(b)) => Ok(Value::Bool(a <= b)),
-            _ => Err(RuntimeError::TypeError(format!(
-                "cannot compare {} <= {}",
-                self.type_name(),
-                other.type_name()
-            ))),
-        }
+        self.compare_op(other, "<=", Ordering::is_le)
     }
 
     pub fn gte(&self, other: &Value) -> crate::error::Result<Value> {
-        match (self, other) {
-            (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a >= b)),
-            (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(a >= b)),
-            (Value::Int(a), Value::Float(b)) => Ok(Value::Bool(*a as f64 >= *b)),
-            (Value::Float(a), Value::Int(b)) => Ok(Value::Bool(*a >= *b as f64)),
-            (Value::String(a), Value::String(b)) => Ok(Value::Bool(a >= b)),
-            _ => Err(RuntimeError::TypeError(format!(
-                "cannot compare {} >= {}",
-                self.type_name(),
-                other.type_name()
-            ))),
-        }
+        self.compare_op(other, ">=", Ordering::is_ge)
     }
 
     /// Truthiness for conditionals and logical operations.
@@ -324,12 +509,15 @@ impl Value {
                     len: arr.len(),
                 })
             }
-            // Map[String]
-            (Value::Map(pairs), Value::String(key)) => Ok(pairs
-                .iter()
-                .find(|(k, _)| k == key)
-                .map(|(_, v)| v.clone())
-                .unwrap_or(Value::Nil)),
+            // Map[key]
+            (Value::Map(pairs), key) => {
+                let key = MapKey::from_value(key)?;
+                Ok(pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or(Value::Nil))
+            }
             // Result[0] => inner value (used by compiler for pattern destructuring)
             (Value::Result { value, .. }, Value::Int(0)) => Ok(*value.clone()),
             // Option[0] => inner value or Nil
@@ -345,16 +533,15 @@ impl Value {
                     Ok(Value::Int(start + i))
                 }
             }
-            // Array[Range] => slice
+            // Array[Range] => sub-array
             (Value::Array(arr), Value::Range { start, end, inclusive }) => {
-                let s = (*start).max(0) as usize;
-                let e = if *inclusive { *end + 1 } else { *end };
-                let e = (e as usize).min(arr.len());
-                if s > arr.len() {
-                    Ok(Value::Array(vec![]))
-                } else {
-                    Ok(Value::Array(arr[s..e].to_vec()))
-                }
+                let (s, e) = slice_bounds(*start, *end, *inclusive, arr.len())?;
+                Ok(Value::Array(arr[s..e].to_vec()))
+            }
+            // String[Range] => substring (by character)
+            (Value::String(text), Value::Range { start, end, inclusive }) => {
+                let (s, e) = slice_bounds(*start, *end, *inclusive, text.chars().count())?;
+                Ok(Value::String(text.chars().skip(s).take(e - s).collect()))
             }
             // String[Int] => character at index
             (Value::String(s), Value::Int(i)) => {
@@ -374,6 +561,52 @@ impl Value {
     }
 }
 
+// ============================================================================
+// Extraction for embedders
+// ============================================================================
+
+impl Value {
+    /// The integer if this is an `Int`.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The text if this is a `String`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The elements if this is an `Array`.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The entries, in insertion order, if this is a `Map`.
+    pub fn as_map(&self) -> Option<&[(MapKey, Value)]> {
+        match self {
+            Value::Map(pairs) => Some(pairs),
+            _ => None,
+        }
+    }
+
+    /// The named field if this is a struct that has it.
+    pub fn as_struct_field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct { fields, .. } => fields.get(name),
+            _ => None,
+        }
+    }
+}
+
 // ============================================================================
 // Type introspection
 // ============================================================================
@@ -401,6 +634,7 @@ impl Value {
             Value::AgentRef(_) => "AgentRef",
             Value::Thunk { .. } => "Thunk",
             Value::Range { .. } => "Range",
+            Value::DateTime(_) => "DateTime",
             Value::ModelBuilder { .. } => "ModelBuilder",
         }
     }
@@ -410,6 +644,73 @@ impl Value {
         format!("{}", self)
     }
 
+    /// Multi-line, indented representation for `pprint` and `std::fmt::pretty`.
+    ///
+    /// Nested collections are laid out one element per line using Concerto's
+    /// value syntax. Strings inside collections are quoted; a top-level string
+    /// is returned as-is, like `display_string`.
+    pub fn pretty_string(&self) -> String {
+        match self {
+            Value::String(s) => s.clone(),
+            _ => {
+                let mut out = String::new();
+                self.write_pretty(&mut out, 0);
+                out
+            }
+        }
+    }
+
+    fn write_pretty(&self, out: &mut String, depth: usize) {
+        let pad = "  ".repeat(depth + 1);
+        let close = "  ".repeat(depth);
+        match self {
+            Value::String(s) => out.push_str(&format!("{:?}", s)),
+            Value::Array(items) if !items.is_empty() => {
+                out.push_str("[\n");
+                for item in items {
+                    out.push_str(&pad);
+                    item.write_pretty(out, depth + 1);
+                    out.push_str(",\n");
+                }
+                out.push_str(&close);
+                out.push(']');
+            }
+            Value::Map(pairs) if !pairs.is_empty() => {
+                out.push_str("{\n");
+                for (k, v) in pairs {
+                    out.push_str(&format!("{}{}: ", pad, k.literal()));
+                    v.write_pretty(out, depth + 1);
+                    out.push_str(",\n");
+                }
+                out.push_str(&close);
+                out.push('}');
+            }
+            Value::Struct { type_name, fields } if !fields.is_empty() => {
+                out.push_str(&format!("{} {{\n", type_name));
+                let mut sorted: Vec<_> = fields.iter().collect();
+                sorted.sort_by(|a, b| a.0.cmp(b.0));
+                for (k, v) in sorted {
+                    out.push_str(&format!("{}{}: ", pad, k));
+                    v.write_pretty(out, depth + 1);
+                    out.push_str(",\n");
+                }
+                out.push_str(&close);
+                out.push('}');
+            }
+            Value::Result { is_ok, value } => {
+                out.push_str(if *is_ok { "Ok(" } else { "Err(" });
+                value.write_pretty(out, depth);
+                out.push(')');
+            }
+            Value::Option(Some(value)) => {
+                out.push_str("Some(");
+                value.write_pretty(out, depth);
+                out.push(')');
+            }
+            other => out.push_str(&other.display_string()),
+        }
+    }
+
     /// Convert to a PropagatedValue for the error system.
     pub fn to_propagated(&self) -> PropagatedValue {
         PropagatedValue {
@@ -429,16 +730,20 @@ impl Value {
             Value::Array(arr) => {
                 serde_json::Value::Array(arr.iter().map(|v| v.to_json()).collect())
             }
+            // JSON objects keep insertion order (serde_json `preserve_order`),
+            // so map keys round-trip in order and struct fields are sorted.
             Value::Map(pairs) => {
                 let map: serde_json::Map<String, serde_json::Value> = pairs
                     .iter()
-                    .map(|(k, v)| (k.clone(), v.to_json()))
+                    .map(|(k, v)| (k.to_string(), v.to_json()))
                     .collect();
                 serde_json::Value::Object(map)
             }
             Value::Struct { fields, .. } => {
-                let map: serde_json::Map<String, serde_json::Value> = fields
-                    .iter()
+                let mut sorted: Vec<_> = fields.iter().collect();
+                sorted.sort_by(|a, b| a.0.cmp(b.0));
+                let map: serde_json::Map<String, serde_json::Value> = sorted
+                    .into_iter()
                     .map(|(k, v)| (k.clone(), v.to_json()))
                     .collect();
                 serde_json::Value::Object(map)
@@ -466,6 +771,7 @@ impl Value {
             Value::Range { start, end, inclusive } => {
                 serde_json::json!({"start": start, "end": end, "inclusive": inclusive})
             }
+            Value::DateTime(ms) => serde_json::json!(crate::stdlib::time::iso8601(*ms)),
             Value::ModelBuilder { source_name, .. } => {
                 serde_json::json!(format!("<builder {}>", source_name))
             }
@@ -498,6 +804,8 @@ impl PartialEq for Value {
                     type_name: t2,
                     fields: f2,
                 },
+                // Nominal: the type must match. Fields are a HashMap, so
+                // construction order never matters.
             ) => t1 == t2 && f1 == f2,
             (
                 Value::Result {
@@ -511,6 +819,7 @@ impl PartialEq for Value {
             ) => ok1 == ok2 && v1 == v2,
             (Value::Option(a), Value::Option(b)) => a == b,
             (Value::Function(a), Value::Function(b)) => a == b,
+            (Value::DateTime(a), Value::DateTime(b)) => a == b,
             (
                 Value::Range { start: s1, end: e1, inclusive: i1 },
                 Value::Range { start: s2, end: e2, inclusive: i2 },
@@ -524,6 +833,47 @@ impl PartialEq for Value {
 // Display
 // ============================================================================
 
+/// Resolve a range used as a slice index into `[start, end)` bounds for a
+/// collection of `len` elements. Ends past the collection are clamped;
+/// negative bounds and `start > end` are errors.
+fn slice_bounds(
+    start: i64,
+    end: i64,
+    inclusive: bool,
+    len: usize,
+) -> crate::error::Result<(usize, usize)> {
+    let exclusive_end = if inclusive { end.saturating_add(1) } else { end };
+    let dots = if inclusive { "..=" } else { ".." };
+    if start < 0 || end < 0 {
+        return Err(RuntimeError::TypeError(format!(
+            "slice bounds must be non-negative, got {}{}{}",
+            start, dots, end
+        )));
+    }
+    if start > end {
+        return Err(RuntimeError::TypeError(format!(
+            "slice start is greater than end in {}{}{}",
+            start, dots, end
+        )));
+    }
+    let end = (exclusive_end as usize).min(len);
+    let start = (start as usize).min(end);
+    Ok((start, end))
+}
+
+/// The value wrapped by `Ok`/`Err`/`Some`, displayed with strings quoted so
+/// `Ok("42")` and `Ok(42)` stay distinguishable.
+struct Payload<'a>(&'a Value);
+
+impl fmt::Display for Payload<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::String(s) => write!(f, "{:?}", s),
+            other => write!(f, "{}", other),
+        }
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -548,13 +898,16 @@ impl fmt::Display for Value {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "\"{}\": {}", k, v)?;
+                    write!(f, "{}: {}", k.literal(), v)?;
                 }
                 write!(f, "}}")
             }
             Value::Struct { type_name, fields } => {
                 write!(f, "{} {{", type_name)?;
-                for (i, (k, v)) in fields.iter().enumerate() {
+                // Sort fields so display output is stable across runs.
+                let mut sorted: Vec<_> = fields.iter().collect();
+                sorted.sort_by(|a, b| a.0.cmp(b.0));
+                for (i, (k, v)) in sorted.into_iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
@@ -562,12 +915,12 @@ impl fmt::Display for Value {
                 }
                 write!(f, "}}")
             }
-            Value::Result { is_ok: true, value } => write!(f, "Ok({})", value),
+            Value::Result { is_ok: true, value } => write!(f, "Ok({})", Payload(value)),
             Value::Result {
                 is_ok: false,
                 value,
-            } => write!(f, "Err({})", value),
-            Value::Option(Some(v)) => write!(f, "Some({})", v),
+            } => write!(f, "Err({})", Payload(value)),
+            Value::Option(Some(v)) => write!(f, "Some({})", Payload(v)),
             Value::Option(None) => write!(f, "None"),
             Value::Function(name) => write!(f, "<fn {}>", name),
             Value::ModelRef(name) => write!(f, "<model {}>", name),
@@ -585,6 +938,7 @@ impl fmt::Display for Value {
                 }
             }
             Value::Thunk { function, .. } => write!(f, "<thunk {}>", function),
+            Value::DateTime(ms) => write!(f, "{}", crate::stdlib::time::iso8601(*ms)),
             Value::ModelBuilder { source_name, .. } => write!(f, "<builder {}>", source_name),
         }
     }
@@ -682,6 +1036,61 @@ mod tests {
         assert_eq!(Value::Nil.neq_val(&Value::Int(0)), Value::Bool(true));
     }
 
+    #[test]
+    fn extractors_match_only_their_variant() {
+        assert_eq!(Value::Int(7).as_int(), Some(7));
+        assert_eq!(Value::Float(7.0).as_int(), None);
+
+        assert_eq!(Value::String("hi".into()).as_str(), Some("hi"));
+        assert_eq!(Value::Int(1).as_str(), None);
+
+        let arr = Value::Array(vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(arr.as_array(), Some(&[Value::Int(1), Value::Int(2)][..]));
+        assert_eq!(Value::Nil.as_array(), None);
+
+        let map = Value::Map(vec![("a".into(), Value::Bool(true))]);
+        let entries = map.as_map().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0.as_str(), Some("a"));
+        assert_eq!(entries[0].1, Value::Bool(true));
+        assert_eq!(arr.as_map(), None);
+
+        let mut fields = HashMap::new();
+        fields.insert("text".to_string(), Value::String("ok".into()));
+        let response = Value::Struct {
+            type_name: "Response".into(),
+            fields,
+        };
+        assert_eq!(
+            response.as_struct_field("text").and_then(Value::as_str),
+            Some("ok")
+        );
+        assert_eq!(response.as_struct_field("missing"), None);
+        assert_eq!(map.as_struct_field("a"), None);
+    }
+
+    #[test]
+    fn struct_equality_is_nominal_and_ignores_field_order() {
+        let make = |type_name: &str, order: &[(&str, i64)]| {
+            let mut fields = HashMap::new();
+            for (name, v) in order {
+                fields.insert(name.to_string(), Value::Int(*v));
+            }
+            Value::Struct {
+                type_name: type_name.to_string(),
+                fields,
+            }
+        };
+        let a = make("Point", &[("x", 1), ("y", 2)]);
+        let b = make("Point", &[("y", 2), ("x", 1)]);
+        let c = make("Vec2", &[("x", 1), ("y", 2)]);
+        assert_eq!(a.eq_val(&b), Value::Bool(true));
+        assert_eq!(a.eq_val(&c), Value::Bool(false));
+        assert_eq!(a.neq_val(&c), Value::Bool(true));
+        assert_ne!(a, make("Point", &[("x", 1), ("y", 3)]));
+        assert_ne!(a, make("Point", &[("x", 1)]));
+    }
+
     #[test]
     fn truthiness() {
         assert!(Value::Bool(true).is_truthy());
@@ -709,13 +1118,40 @@ mod tests {
     #[test]
     fn field_access_map() {
         let m = Value::Map(vec![
-            ("a".to_string(), Value::Int(1)),
-            ("b".to_string(), Value::Int(2)),
+            ("a".into(), Value::Int(1)),
+            ("b".into(), Value::Int(2)),
         ]);
         assert_eq!(m.field_get("a").unwrap(), Value::Int(1));
         assert_eq!(m.field_get("c").unwrap(), Value::Nil);
     }
 
+    #[test]
+    fn bitwise_operators() {
+        let a = Value::Int(0b1100);
+        let b = Value::Int(0b1010);
+        assert_eq!(a.bit_and(&b).unwrap(), Value::Int(0b1000));
+        assert_eq!(a.bit_or(&b).unwrap(), Value::Int(0b1110));
+        assert_eq!(a.bit_xor(&b).unwrap(), Value::Int(0b0110));
+        assert_eq!(Value::Int(0).bit_not().unwrap(), Value::Int(-1));
+        assert!(Value::Float(1.0).bit_not().is_err());
+        assert!(a.bit_and(&Value::Float(1.0)).is_err());
+    }
+
+    #[test]
+    fn shifts_reject_out_of_range_amounts() {
+        assert_eq!(Value::Int(1).shl(&Value::Int(4)).unwrap(), Value::Int(16));
+        assert_eq!(Value::Int(1).shl(&Value::Int(63)).unwrap(), Value::Int(i64::MIN));
+        assert_eq!(Value::Int(-16).shr(&Value::Int(2)).unwrap(), Value::Int(-4));
+        assert!(matches!(
+            Value::Int(1).shl(&Value::Int(64)),
+            Err(RuntimeError::ShiftOverflow(64))
+        ));
+        assert!(matches!(
+            Value::Int(1).shr(&Value::Int(-1)),
+            Err(RuntimeError::ShiftOverflow(-1))
+        ));
+    }
+
     #[test]
     fn index_access_array() {
         let arr = Value::Array(vec![Value::Int(10), Value::Int(20), Value::Int(30)]);
@@ -723,6 +1159,103 @@ mod tests {
         assert!(arr.index_get(&Value::Int(5)).is_err());
     }
 
+    #[test]
+    fn slice_array_with_range() {
+        let arr = Value::Array(vec![Value::Int(10), Value::Int(20), Value::Int(30)]);
+        let range = |start, end, inclusive| Value::Range {
+            start,
+            end,
+            inclusive,
+        };
+        assert_eq!(
+            arr.index_get(&range(1, 3, false)).unwrap(),
+            Value::Array(vec![Value::Int(20), Value::Int(30)])
+        );
+        assert_eq!(
+            arr.index_get(&range(0, 1, true)).unwrap(),
+            Value::Array(vec![Value::Int(10), Value::Int(20)])
+        );
+        // Ends past the array are clamped
+        assert_eq!(
+            arr.index_get(&range(2, 10, false)).unwrap(),
+            Value::Array(vec![Value::Int(30)])
+        );
+        assert_eq!(
+            arr.index_get(&range(5, 8, false)).unwrap(),
+            Value::Array(vec![])
+        );
+        assert_eq!(arr.index_get(&range(2, 2, false)).unwrap(), Value::Array(vec![]));
+        assert!(arr.index_get(&range(2, 1, false)).is_err());
+        assert!(arr.index_get(&range(3, 2, true)).is_err());
+        assert_eq!(
+            arr.index_get(&range(2, 2, true)).unwrap(),
+            Value::Array(vec![Value::Int(30)])
+        );
+        assert!(arr.index_get(&range(-1, 2, false)).is_err());
+    }
+
+    #[test]
+    fn slice_string_with_range() {
+        let s = Value::String("héllo".to_string());
+        let range = |start, end, inclusive| Value::Range {
+            start,
+            end,
+            inclusive,
+        };
+        assert_eq!(
+            s.index_get(&range(0, 2, false)).unwrap(),
+            Value::String("hé".to_string())
+        );
+        assert_eq!(
+            s.index_get(&range(1, 3, true)).unwrap(),
+            Value::String("éll".to_string())
+        );
+        assert_eq!(
+            s.index_get(&range(3, 100, false)).unwrap(),
+            Value::String("lo".to_string())
+        );
+        assert!(s.index_get(&range(4, 2, false)).is_err());
+        assert!(s.index_get(&range(5, 4, true)).is_err());
+    }
+
+    #[test]
+    fn pretty_string_indents_nested_struct() {
+        let mut inner = HashMap::new();
+        inner.insert("city".to_string(), Value::String("Oslo".to_string()));
+        let mut fields = HashMap::new();
+        fields.insert("name".to_string(), Value::String("Ada".to_string()));
+        fields.insert(
+            "address".to_string(),
+            Value::Struct {
+                type_name: "Address".to_string(),
+                fields: inner,
+            },
+        );
+        fields.insert(
+            "tags".to_string(),
+            Value::Array(vec![Value::Int(1), Value::Array(vec![])]),
+        );
+        let person = Value::Struct {
+            type_name: "Person".to_string(),
+            fields,
+        };
+        assert_eq!(
+            person.pretty_string(),
+            "Person {\n  address: Address {\n    city: \"Oslo\",\n  },\n  name: \"Ada\",\n  tags: [\n    1,\n    [],\n  ],\n}"
+        );
+    }
+
+    #[test]
+    fn pretty_string_scalars_stay_inline() {
+        assert_eq!(Value::Int(3).pretty_string(), "3");
+        assert_eq!(Value::String("hi".to_string()).pretty_string(), "hi");
+        assert_eq!(
+            Value::Option(Some(Box::new(Value::Map(vec![("k".into(), Value::Nil)]))))
+                .pretty_string(),
+            "Some({\n  \"k\": nil,\n})"
+        );
+    }
+
     #[test]
     fn index_access_result() {
         let ok = Value::Result {
@@ -741,6 +1274,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn compare_strings_lexicographically() {
+        let a = Value::String("apple".into());
+        let b = Value::String("banana".into());
+        assert_eq!(a.lt(&b).unwrap(), Value::Bool(true));
+        assert_eq!(b.gte(&a).unwrap(), Value::Bool(true));
+        assert_eq!(a.lte(&a).unwrap(), Value::Bool(true));
+        assert_eq!(a.gt(&a).unwrap(), Value::Bool(false));
+    }
+
+    #[test]
+    fn compare_arrays_elementwise() {
+        let arr = |xs: &[i64]| Value::Array(xs.iter().map(|n| Value::Int(*n)).collect());
+        assert_eq!(arr(&[1, 2, 3]).lt(&arr(&[1, 3])).unwrap(), Value::Bool(true));
+        assert_eq!(arr(&[2]).gt(&arr(&[1, 9, 9])).unwrap(), Value::Bool(true));
+        // A strict prefix orders first.
+        assert_eq!(arr(&[1, 2]).lt(&arr(&[1, 2, 0])).unwrap(), Value::Bool(true));
+        assert_eq!(arr(&[]).lt(&arr(&[0])).unwrap(), Value::Bool(true));
+        assert_eq!(arr(&[1, 2]).gte(&arr(&[1, 2])).unwrap(), Value::Bool(true));
+        // Elements promote Int/Float like scalars.
+        let mixed = Value::Array(vec![Value::Float(1.5)]);
+        assert_eq!(mixed.gt(&arr(&[1])).unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn compare_incompatible_types_errors() {
+        let err = Value::Int(1).lt(&Value::String("1".into())).unwrap_err();
+        assert_eq!(err.to_string(), "type error: cannot compare Int < String");
+        let nested = Value::Array(vec![Value::Int(1)])
+            .lt(&Value::Array(vec![Value::String("a".into())]))
+            .unwrap_err();
+        assert!(matches!(nested, RuntimeError::TypeError(_)));
+    }
+
+    #[test]
+    fn compare_datetimes_chronologically() {
+        let earlier = Value::DateTime(1_704_067_200_000);
+        let later = Value::DateTime(1_704_153_600_000);
+        assert_eq!(earlier.lt(&later).unwrap(), Value::Bool(true));
+        assert_eq!(later.gt(&earlier).unwrap(), Value::Bool(true));
+        assert_eq!(earlier.gte(&earlier.clone()).unwrap(), Value::Bool(true));
+        assert_eq!(earlier, Value::DateTime(1_704_067_200_000));
+        assert!(earlier.lt(&Value::Int(0)).is_err());
+    }
+
+    #[test]
+    fn display_struct_fields_sorted() {
+        let mut fields = HashMap::new();
+        for (name, n) in [("zeta", 3), ("alpha", 1), ("mid", 2)] {
+            fields.insert(name.to_string(), Value::Int(n));
+        }
+        let s = Value::Struct {
+            type_name: "Point".to_string(),
+            fields,
+        };
+        assert_eq!(format!("{}", s), "Point {alpha: 1, mid: 2, zeta: 3}");
+    }
+
     #[test]
     fn display_values() {
         assert_eq!(format!("{}", Value::Int(42)), "42");
@@ -753,4 +1344,60 @@ mod tests {
             "[1, 2]"
         );
     }
+
+    #[test]
+    fn display_result_and_option() {
+        let ok = |v| Value::Result {
+            is_ok: true,
+            value: Box::new(v),
+        };
+        let err = Value::Result {
+            is_ok: false,
+            value: Box::new(Value::String("msg".into())),
+        };
+        assert_eq!(ok(Value::Int(42)).display_string(), "Ok(42)");
+        assert_eq!(
+            ok(Value::String("42".into())).display_string(),
+            "Ok(\"42\")"
+        );
+        assert_eq!(err.display_string(), "Err(\"msg\")");
+        assert_eq!(
+            Value::Option(Some(Box::new(Value::String("x".into())))).display_string(),
+            "Some(\"x\")"
+        );
+        assert_eq!(
+            Value::Option(Some(Box::new(ok(Value::Nil)))).display_string(),
+            "Some(Ok(nil))"
+        );
+        assert_eq!(Value::Option(None).display_string(), "None");
+    }
+
+    #[test]
+    fn map_keys_keep_their_type() {
+        let int_key = MapKey::from_value(&Value::Int(1)).unwrap();
+        let str_key = MapKey::from_value(&Value::String("1".into())).unwrap();
+        assert_ne!(int_key, str_key);
+
+        let map = Value::Map(vec![
+            (int_key, Value::String("int".into())),
+            (str_key, Value::String("str".into())),
+        ]);
+        assert_eq!(
+            map.index_get(&Value::Int(1)).unwrap(),
+            Value::String("int".into())
+        );
+        assert_eq!(
+            map.index_get(&Value::String("1".into())).unwrap(),
+            Value::String("str".into())
+        );
+        assert_eq!(format!("{}", map), r#"{1: int, "1": str}"#);
+        // JSON keys are text, so the two keys collapse and the later one wins
+        assert_eq!(map.to_json(), serde_json::json!({"1": "str"}));
+
+        let tuple = Value::Array(vec![Value::Int(1), Value::Bool(true)]);
+        let key = MapKey::from_value(&tuple).unwrap();
+        assert_eq!(key.to_value(), tuple);
+        assert!(MapKey::from_value(&Value::Float(1.5)).is_err());
+        assert!(MapKey::from_value(&Value::Nil).is_err());
+    }
 }
@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use concerto_common::ir::IrInstruction;
 use concerto_common::ir_opcodes::Opcode;
@@ -10,12 +14,17 @@ use crate::ir_loader::LoadedModule;
 use crate::ledger::LedgerStore;
 use crate::mcp::McpRegistry;
 use crate::memory::MemoryStore;
+use crate::profile::Profiler;
 use crate::provider::{ChatMessage, ChatRequest, ConnectionManager};
 use crate::schema::SchemaValidator;
 use crate::tool::ToolRegistry;
-use crate::value::Value;
+use crate::value::{MapKey, Value};
 
-const MAX_CALL_DEPTH: usize = 1000;
+/// Default recursion limit; override with [`VM::set_max_call_depth`].
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// How often sampled-out emits are reported on `emit:dropped`.
+const EMIT_DROPPED_INTERVAL: Duration = Duration::from_secs(1);
 
 // ============================================================================
 // Call Frame
@@ -26,6 +35,19 @@ struct CallFrame {
     instructions: Vec<IrInstruction>,
     pc: usize,
     locals: HashMap<String, Value>,
+    /// Operand stack height when the frame was entered. Returning from the
+    /// frame truncates back to it, dropping operands left by an early exit
+    /// from the middle of an expression (a `return` or `?` in a match arm).
+    stack_base: usize,
+    /// Set for tool method frames: stores to `self` are written back to
+    /// this tool's state in the registry.
+    tool: Option<String>,
+    /// Entry time, set only while profiling.
+    entered_at: Option<Instant>,
+    /// Time spent in frames called from this one (for profile self time).
+    child_time: Duration,
+    /// Entry time of a `@trace` function, for its `fn:exit` event.
+    trace_start: Option<Instant>,
 }
 
 // ============================================================================
@@ -51,9 +73,12 @@ pub struct VM {
     module: LoadedModule,
     stack: Vec<Value>,
     call_stack: Vec<CallFrame>,
-    globals: HashMap<String, Value>,
-    /// In-memory hashmaps (hashmap_name -> key -> value).
-    hashmaps: HashMap<String, HashMap<String, Value>>,
+    /// Global bindings, ordered so enumeration is deterministic.
+    globals: BTreeMap<String, Value>,
+    /// In-memory hashmaps (hashmap_name -> key -> value). Keys are typed like
+    /// `Value::Map` keys, and entries are kept sorted by key so queries and
+    /// `keys()`/`values()` are deterministic.
+    hashmaps: HashMap<String, BTreeMap<MapKey, Value>>,
     /// Ledger store (fault-tolerant knowledge stores).
     ledger_store: LedgerStore,
     /// Memory store (conversation history).
@@ -77,6 +102,66 @@ pub struct VM {
     test_emits: Vec<(String, Value)>,
     /// Whether to capture emits for test_emits() built-in.
     test_capture_emits: bool,
+    /// Program arguments returned by `std::env::args()`.
+    program_args: Vec<String>,
+    /// Maximum number of call frames before `StackOverflow`.
+    max_call_depth: usize,
+    /// File sink for `std::log` records, set by `std::log::set_file()`.
+    log_file: Option<crate::stdlib::log::LogFile>,
+    /// Per-function timings, set by `enable_profiling()`.
+    profiler: Option<Profiler>,
+    /// Names of `@trace` functions, which emit `fn:enter`/`fn:exit`.
+    traced: HashSet<String>,
+    /// Answer every model and agent call with a canned mock response,
+    /// set by `set_mock_all()`.
+    mock_all: bool,
+    /// Wall-clock limit from `set_timeout()`, applied to each run.
+    timeout: Option<Duration>,
+    /// Generation of the current run, bumped as each run starts. Starts at 1
+    /// so the initial 0 in `timed_out` matches no run.
+    run_generation: u64,
+    /// Generation whose deadline has passed, stored by that run's watcher
+    /// thread and checked before every instruction.
+    timed_out: Arc<AtomicU64>,
+    /// Dropping it wakes the current run's watcher thread, which then exits
+    /// without cancelling anything.
+    timeout_watch: Option<mpsc::Sender<()>>,
+    /// Per-channel emit sampling, set by `set_emit_every_n()`.
+    emit_sampler: Option<EmitSampler>,
+}
+
+/// Program state captured by `VM::snapshot()` for a later `VM::restore()`.
+///
+/// Covers globals, hashmaps, ledgers and memories. Opaque to embedders.
+#[derive(Clone)]
+pub struct VmSnapshot {
+    globals: BTreeMap<String, Value>,
+    hashmaps: HashMap<String, BTreeMap<MapKey, Value>>,
+    ledger_store: LedgerStore,
+    memory_store: MemoryStore,
+}
+
+/// Forwards every `every_n`-th `emit()` per channel and counts the rest.
+struct EmitSampler {
+    every_n: u64,
+    /// Emits seen per channel since sampling was enabled.
+    seen: HashMap<String, u64>,
+    /// Emits dropped per channel since the last `emit:dropped` summary.
+    dropped: BTreeMap<String, u64>,
+    last_summary: Instant,
+}
+
+/// Per-call settings a `ModelBuilder` layers over a model declaration.
+/// The default is a plain `Model.execute(...)` call.
+#[derive(Default)]
+struct ModelCallOverrides<'a> {
+    memory: Option<&'a str>,
+    extra_tools: &'a [String],
+    exclude_default_tools: bool,
+    context: Option<&'a Value>,
+    retry: Option<u32>,
+    timeout: Option<u64>,
+    seed: Option<i64>,
 }
 
 /// Mock configuration for a model.
@@ -89,7 +174,7 @@ struct MockConfig {
 impl VM {
     /// Create a new VM from a loaded module.
     pub fn new(module: LoadedModule) -> Self {
-        let mut globals = HashMap::new();
+        let mut globals = BTreeMap::new();
 
         // Register models as ModelRef values
         for name in module.models.keys() {
@@ -151,6 +236,10 @@ impl VM {
             "println".to_string(),
             Value::Function("$builtin_println".to_string()),
         );
+        globals.insert(
+            "pprint".to_string(),
+            Value::Function("$builtin_pprint".to_string()),
+        );
         globals.insert(
             "len".to_string(),
             Value::Function("$builtin_len".to_string()),
@@ -188,12 +277,26 @@ impl VM {
             Value::Function("$builtin_tool_error_new".to_string()),
         );
 
+        // A user function with a built-in's name replaces the built-in
+        // (the compiler warns about the shadowing).
+        globals.retain(|name, value| {
+            !(module.functions.contains_key(name)
+                && matches!(value, Value::Function(f) if f.starts_with("$builtin_")))
+        });
+
         // Initialize hashmaps
         let mut hashmaps = HashMap::new();
         for name in module.hashmaps.keys() {
-            hashmaps.insert(name.clone(), HashMap::new());
+            hashmaps.insert(name.clone(), BTreeMap::new());
         }
 
+        let traced = module
+            .functions
+            .values()
+            .filter(|f| f.decorators.iter().any(|d| d.name == "trace"))
+            .map(|f| f.name.clone())
+            .collect();
+
         // Initialize ledger store
         let mut ledger_store = LedgerStore::new();
         for name in module.ledgers.keys() {
@@ -210,8 +313,13 @@ impl VM {
 
         // Initialize tool registry
         let mut tool_registry = ToolRegistry::new();
-        for name in module.tools.keys() {
-            tool_registry.register_tool(name);
+        for (name, ir_tool) in &module.tools {
+            let state = ir_tool
+                .state
+                .iter()
+                .map(|(k, v)| (k.clone(), SchemaValidator::json_to_value(v)))
+                .collect();
+            tool_registry.register_tool_with_state(name, state);
         }
 
         // Initialize connection manager from IR connections
@@ -246,6 +354,17 @@ impl VM {
             mock_models: HashMap::new(),
             test_emits: Vec::new(),
             test_capture_emits: false,
+            program_args: Vec::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            log_file: None,
+            profiler: None,
+            traced,
+            mock_all: false,
+            timeout: None,
+            run_generation: 1,
+            timed_out: Arc::new(AtomicU64::new(0)),
+            timeout_watch: None,
+            emit_sampler: None,
         }
     }
 
@@ -254,6 +373,157 @@ impl VM {
         self.emit_handler = Box::new(handler);
     }
 
+    /// Set the program arguments returned by `std::env::args()`
+    /// (the CLI passes everything after `--`).
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.program_args = args;
+    }
+
+    /// Set the maximum call depth (default [`DEFAULT_MAX_CALL_DEPTH`]).
+    /// Raise it for deep non-tail recursion, lower it for untrusted code.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Stop each run (`execute`, `execute_function`, `run_test`) with
+    /// [`RuntimeError::Timeout`] once `limit` of wall-clock time has passed
+    /// since it started (`concerto run --timeout-secs N`). A watcher thread
+    /// per run marks the deadline; the VM checks it before each instruction
+    /// and during `std::time::sleep`, but cannot interrupt a blocking provider
+    /// or agent call. A run that finishes in time retires its watcher, so a
+    /// reused VM starts every run with a fresh deadline.
+    pub fn set_timeout(&mut self, limit: Duration) {
+        self.timeout = Some(limit);
+    }
+
+    /// Begin a run under the configured timeout: a new generation, so a
+    /// deadline left over from an earlier run cannot fire on this one, and
+    /// a watcher that marks this generation timed out when `limit` passes.
+    fn start_deadline(&mut self) {
+        self.run_generation += 1;
+        self.timeout_watch = None;
+        let Some(limit) = self.timeout else {
+            return;
+        };
+        let (watch, stop) = mpsc::channel::<()>();
+        let timed_out = self.timed_out.clone();
+        let generation = self.run_generation;
+        std::thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = stop.recv_timeout(limit) {
+                timed_out.store(generation, Ordering::Relaxed);
+            }
+        });
+        self.timeout_watch = Some(watch);
+    }
+
+    /// End a run: wake and retire its deadline watcher.
+    fn finish_deadline(&mut self) {
+        self.timeout_watch = None;
+    }
+
+    /// Whether the current run's deadline has passed.
+    fn deadline_passed(&self) -> bool {
+        self.timed_out.load(Ordering::Relaxed) == self.run_generation
+    }
+
+    /// Forward only the 1st, (n+1)th, (2n+1)th... program `emit()` on each
+    /// channel to the emit handler (`concerto run --emit-every-n N`). Dropped
+    /// emits are counted and reported at most once a second, and when the
+    /// run ends, as an `emit:dropped` event whose payload maps each channel
+    /// to its count. Runtime events (`model:log`, `pipeline:*`, ...) are not
+    /// sampled. `n <= 1` turns sampling off.
+    pub fn set_emit_every_n(&mut self, n: u64) {
+        self.emit_sampler = (n > 1).then(|| EmitSampler {
+            every_n: n,
+            seen: HashMap::new(),
+            dropped: BTreeMap::new(),
+            last_summary: Instant::now(),
+        });
+    }
+
+    /// Answer every model and agent call with a canned mock response instead
+    /// of reaching a provider or spawning an agent, so a program with real
+    /// connections can run offline (`concerto run --mock-all`). `mock`
+    /// overrides in tests still take precedence.
+    pub fn set_mock_all(&mut self, enabled: bool) {
+        self.mock_all = enabled;
+        self.connection_manager.set_mock_all(enabled);
+    }
+
+    /// Supply a value for `{key}` placeholders in an agent's manifest `args`
+    /// and `env`, overriding its `[agents.<name>.params]` entry. Applied when
+    /// the agent process is next spawned.
+    pub fn set_agent_param(&mut self, agent: &str, key: &str, value: &str) -> Result<()> {
+        self.agent_registry.set_param(agent, key, value)
+    }
+
+    /// A remediation hint for an error returned by this VM (`concerto run
+    /// --explain-error`), with did-you-mean matching against the program's
+    /// functions and globals. See [`crate::explain::hint`].
+    pub fn explain_error(&self, err: &RuntimeError) -> Option<String> {
+        let names: Vec<&str> = self
+            .module
+            .functions
+            .keys()
+            .chain(self.globals.keys())
+            .map(String::as_str)
+            .collect();
+        crate::explain::hint(err, &names)
+    }
+
+    /// Re-read `Concerto.toml` at `path` and rebuild the LLM connections and
+    /// MCP servers from it, so a long-running embedder picks up config changes
+    /// without recompiling. Manifest connections replace the ones baked into
+    /// the IR; `[mcp.*]` settings are merged over the declared MCP servers.
+    /// Providers installed with `set_provider` are discarded.
+    pub fn reload_connections(&mut self, path: &std::path::Path) -> Result<()> {
+        let manifest = concerto_common::manifest::load_manifest(path).map_err(|e| {
+            RuntimeError::LoadError(format!("cannot reload {}: {}", path.display(), e))
+        })?;
+
+        let mut connections: HashMap<String, concerto_common::ir::IrConnection> = self
+            .module
+            .connections
+            .iter()
+            .filter(|(_, conn)| conn.config.get("type").and_then(|t| t.as_str()) == Some("mcp"))
+            .map(|(name, conn)| (name.clone(), conn.clone()))
+            .collect();
+        for (name, conn) in connections.iter_mut() {
+            if let Some(mcp) = manifest.mcp.get(name) {
+                if let (Some(config), serde_json::Value::Object(overrides)) =
+                    (conn.config.as_object_mut(), mcp.to_ir_config())
+                {
+                    config.extend(overrides);
+                }
+            }
+        }
+        for (name, cfg) in &manifest.connections {
+            connections.insert(
+                name.clone(),
+                concerto_common::ir::IrConnection {
+                    name: name.clone(),
+                    config: cfg.to_ir_config(),
+                },
+            );
+        }
+
+        self.connection_manager = ConnectionManager::from_connections(&connections);
+        self.connection_manager.set_mock_all(self.mock_all);
+        self.mcp_registry = McpRegistry::from_connections(&connections);
+        self.module.connections = connections;
+        Ok(())
+    }
+
+    /// Record call counts and timings for every function call from now on.
+    pub fn enable_profiling(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+
+    /// The profile collected since `enable_profiling()`, if enabled.
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
     /// Get the name of the currently executing function (for error reporting).
     pub fn current_function_name(&self) -> &str {
         self.call_stack
@@ -267,6 +537,28 @@ impl VM {
         self.call_stack.len()
     }
 
+    /// Capture the program state so a speculative step can be rolled back
+    /// with `restore()`.
+    pub fn snapshot(&self) -> VmSnapshot {
+        VmSnapshot {
+            globals: self.globals.clone(),
+            hashmaps: self.hashmaps.clone(),
+            ledger_store: self.ledger_store.clone(),
+            memory_store: self.memory_store.clone(),
+        }
+    }
+
+    /// Roll globals, hashmaps, ledgers and memories back to `snapshot`.
+    ///
+    /// Only that state is restored: emits already sent, tool instances, open
+    /// connections and anything an agent did outside the VM stay as they are.
+    pub fn restore(&mut self, snapshot: VmSnapshot) {
+        self.globals = snapshot.globals;
+        self.hashmaps = snapshot.hashmaps;
+        self.ledger_store = snapshot.ledger_store;
+        self.memory_store = snapshot.memory_store;
+    }
+
     /// Execute the module starting from the entry point.
     pub fn execute(&mut self) -> Result<Value> {
         let entry = self.module.entry_point.clone();
@@ -277,13 +569,63 @@ impl VM {
             .ok_or_else(|| RuntimeError::NameError(entry.clone()))?
             .clone();
 
+        self.start_deadline();
         self.push_frame(
             func.name.clone(),
             func.instructions.clone(),
             vec![],
             &func.params,
         )?;
-        self.run_loop()
+        let result = self.run_loop();
+        self.finish_deadline();
+        self.flush_dropped_emits();
+        result
+    }
+
+    /// Call a module function by name and run it to completion.
+    ///
+    /// For embedders that drive which function runs instead of going through
+    /// the entry point. `args` bind to the function's parameters in order;
+    /// omitted trailing parameters take their defaults, as with `Call`.
+    pub fn execute_function(&mut self, name: &str, args: Vec<Value>) -> Result<Value> {
+        let func = self
+            .module
+            .functions
+            .get(name)
+            .ok_or_else(|| RuntimeError::NameError(name.to_string()))?
+            .clone();
+        let total = func.params.len();
+        let required = func.params.iter().filter(|p| p.default.is_none()).count();
+        if args.len() < required || args.len() > total {
+            let expected = if required == total {
+                total.to_string()
+            } else {
+                format!("{} to {}", required, total)
+            };
+            return Err(RuntimeError::CallError(format!(
+                "{} expects {} argument(s), got {}",
+                name,
+                expected,
+                args.len()
+            )));
+        }
+
+        let stop_depth = self.call_stack.len();
+        self.start_deadline();
+        self.push_frame(
+            func.name.clone(),
+            func.instructions.clone(),
+            args,
+            &func.params,
+        )?;
+        let result = self.run_loop_until(stop_depth);
+        if let Err(ref e) = result {
+            // Leave no half-run frames behind for the next call to resume.
+            self.unwind_to_depth(stop_depth, &Value::String(e.to_string()));
+        }
+        self.finish_deadline();
+        self.flush_dropped_emits();
+        result
     }
 
     /// Execute a single test in the current VM instance.
@@ -297,6 +639,7 @@ impl VM {
         self.test_capture_emits = true;
 
         // Push a call frame for the test
+        self.start_deadline();
         self.push_frame(
             format!("test:{}", test.description),
             test.instructions.clone(),
@@ -304,7 +647,9 @@ impl VM {
             &[],
         )?;
 
-        self.run_loop()
+        let result = self.run_loop();
+        self.finish_deadline();
+        result
     }
 
     // ========================================================================
@@ -334,24 +679,76 @@ impl VM {
         args: Vec<Value>,
         params: &[concerto_common::ir::IrParam],
     ) -> Result<()> {
-        if self.call_stack.len() >= MAX_CALL_DEPTH {
-            return Err(RuntimeError::StackOverflow(MAX_CALL_DEPTH));
+        if self.call_stack.len() >= self.max_call_depth {
+            return Err(RuntimeError::StackOverflow(self.max_call_depth));
         }
 
+        let trace_start = if !self.traced.is_empty() && self.traced.contains(&function_name) {
+            let args = params
+                .iter()
+                .zip(&args)
+                .map(|(param, arg)| (param.name.clone().into(), arg.clone()))
+                .collect();
+            self.emit_event(
+                "fn:enter",
+                Value::Map(vec![
+                    ("function".into(), Value::String(function_name.clone())),
+                    ("args".into(), Value::Map(args)),
+                ]),
+            );
+            Some(Instant::now())
+        } else {
+            None
+        };
+
+        let argc = args.len();
         let mut locals = HashMap::new();
         for (param, arg) in params.iter().zip(args) {
             locals.insert(param.name.clone(), arg);
         }
+        // Trailing parameters the caller omitted take their declared defaults.
+        for param in params.iter().skip(argc) {
+            if let Some(default) = &param.default {
+                locals.insert(param.name.clone(), json_to_value(default)?);
+            }
+        }
 
         self.call_stack.push(CallFrame {
             function_name,
             instructions,
             pc: 0,
             locals,
+            stack_base: self.stack.len(),
+            tool: None,
+            entered_at: self.profiler.as_ref().map(|_| Instant::now()),
+            child_time: Duration::ZERO,
+            trace_start,
         });
         Ok(())
     }
 
+    /// Push a frame for a tool method, passing the tool's current state as
+    /// `self`. Mutations of `self` persist across calls within the run.
+    fn push_tool_frame(
+        &mut self,
+        tool_name: &str,
+        func: &concerto_common::ir::IrFunction,
+        args: Vec<Value>,
+    ) -> Result<()> {
+        let mut full_args = vec![self.tool_registry.get_self_value(tool_name)];
+        full_args.extend(args);
+        self.push_frame(
+            func.name.clone(),
+            func.instructions.clone(),
+            full_args,
+            &func.params,
+        )?;
+        if let Some(frame) = self.call_stack.last_mut() {
+            frame.tool = Some(tool_name.to_string());
+        }
+        Ok(())
+    }
+
     /// Resolve a thunk by calling the named function synchronously.
     fn resolve_thunk(&mut self, function: &str, args: Vec<Value>) -> Result<Value> {
         if let Some(func) = self.module.functions.get(function).cloned() {
@@ -383,6 +780,10 @@ impl VM {
     /// When `stop_depth` is 0, runs until the call stack is empty (top-level).
     fn run_loop_until(&mut self, stop_depth: usize) -> Result<Value> {
         loop {
+            if self.deadline_passed() {
+                return Err(RuntimeError::Timeout(self.timeout.unwrap_or_default()));
+            }
+
             // Check if call stack is empty or returned to caller's depth
             if self.call_stack.is_empty() {
                 return Ok(self.stack.pop().unwrap_or(Value::Nil));
@@ -399,7 +800,7 @@ impl VM {
                 frame.pc >= frame.instructions.len()
             };
             if at_end {
-                self.call_stack.pop();
+                self.exit_frame(&Value::Nil);
                 if self.call_stack.is_empty() || self.call_stack.len() <= stop_depth {
                     return Ok(Value::Nil);
                 }
@@ -479,6 +880,29 @@ impl VM {
                     self.push(a.neg()?);
                 }
 
+                // === Bitwise ===
+                Opcode::BitAnd => {
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    self.push(a.bit_and(&b)?);
+                }
+                Opcode::BitOr => {
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    self.push(a.bit_or(&b)?);
+                }
+                Opcode::BitXor => {
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    self.push(a.bit_xor(&b)?);
+                }
+                Opcode::Shl => self.exec_shift(Value::shl)?,
+                Opcode::Shr => self.exec_shift(Value::shr)?,
+                Opcode::BitNot => {
+                    let a = self.pop()?;
+                    self.push(a.bit_not()?);
+                }
+
                 // === Comparison ===
                 Opcode::Eq => {
                     let b = self.pop()?;
@@ -574,7 +998,7 @@ impl VM {
                 }
                 Opcode::Return => {
                     let return_val = self.pop()?;
-                    self.call_stack.pop();
+                    self.exit_frame(&return_val);
                     if self.call_stack.is_empty() || self.call_stack.len() <= stop_depth {
                         return Ok(return_val);
                     }
@@ -587,15 +1011,24 @@ impl VM {
                 Opcode::CallNative => self.exec_call_native(&inst)?,
 
                 // === Emit ===
-                Opcode::Emit => self.exec_emit()?,
+                Opcode::Emit => self.exec_emit(inst.schema.as_deref())?,
                 Opcode::EmitAwait => {
                     // Phase 3a: treat as fire-and-forget emit, push Nil as response
-                    self.exec_emit()?;
+                    self.exec_emit(None)?;
                     self.push(Value::Nil);
                 }
 
                 // === Error handling ===
-                Opcode::Propagate => self.exec_propagate()?,
+                Opcode::Propagate => {
+                    // `?` on Err/None returns from the current function
+                    if let Some(return_val) = self.exec_propagate()? {
+                        self.exit_frame(&return_val);
+                        if self.call_stack.is_empty() || self.call_stack.len() <= stop_depth {
+                            return Ok(return_val);
+                        }
+                        self.push(return_val);
+                    }
+                }
                 Opcode::Throw => {
                     let val = self.pop()?;
                     self.exec_throw(val)?;
@@ -625,7 +1058,19 @@ impl VM {
                         let error_val = self.peek()?;
                         let actual_type = error_val.type_name().to_string();
                         if actual_type != expected_type {
-                            // Type doesn't match — skip this catch body.
+                            // Type doesn't match — resume at the next clause.
+                            if let Some(next) = inst.offset {
+                                self.call_stack
+                                    .last_mut()
+                                    .ok_or_else(|| {
+                                        RuntimeError::CallError(
+                                            "internal: catch with empty call stack".into(),
+                                        )
+                                    })?
+                                    .pc = next as usize;
+                                continue;
+                            }
+                            // IR without clause offsets: scan for the next clause.
                             let has_more_catches = self.skip_catch_body()?;
                             if !has_more_catches {
                                 // No catch matched — rethrow the error
@@ -765,7 +1210,7 @@ impl VM {
                     let hashmap_name = inst.hashmap_name.as_ref().ok_or_else(|| {
                         RuntimeError::LoadError("HASH_MAP_QUERY missing hashmap_name".into())
                     })?;
-                    let entries: Vec<(String, Value)> = self
+                    let entries: Vec<(MapKey, Value)> = self
                         .hashmaps
                         .get(hashmap_name)
                         .map(|hm| hm.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
@@ -776,7 +1221,7 @@ impl VM {
                         Value::Function(fn_name) => {
                             for (key, value) in entries {
                                 if let Some(func) = self.module.functions.get(fn_name).cloned() {
-                                    let args = vec![Value::String(key.clone()), value.clone()];
+                                    let args = vec![key.to_value(), value.clone()];
                                     let stop_depth = self.call_stack.len();
                                     self.push_frame(
                                         func.name.clone(),
@@ -918,6 +1363,11 @@ impl VM {
             .call_stack
             .last_mut()
             .ok_or_else(|| RuntimeError::CallError("no call frame".into()))?;
+        if name == "self" {
+            if let Some(tool_name) = &frame.tool {
+                self.tool_registry.update_state(tool_name, &value);
+            }
+        }
         frame.locals.insert(name.clone(), value);
         Ok(())
     }
@@ -990,6 +1440,32 @@ impl VM {
                         &func.params,
                     )?;
                     // Execution continues in run_loop reading from new frame
+                } else if name == "std::env::args" {
+                    // Program arguments live on the VM, not in the process env
+                    let args = self.program_args.iter().cloned().map(Value::String);
+                    self.push(Value::Array(args.collect()));
+                } else if name == "std::json::validate" {
+                    // Needs the module's schemas — handle here, not in stdlib
+                    let result = self.json_validate(args)?;
+                    self.push(result);
+                } else if let Some(function) = name.strip_prefix("std::log::") {
+                    // The file sink is VM state — handle here, not in stdlib
+                    let result = self.log_call(function, args)?;
+                    self.push(result);
+                } else if let ("std::time::sleep", Some(Value::Int(ms)), Some(_)) =
+                    (name.as_str(), args.first(), self.timeout)
+                {
+                    // Under a timeout, sleep in slices so the deadline can
+                    // end the run mid-sleep.
+                    let end = Instant::now() + Duration::from_millis((*ms).max(0) as u64);
+                    while !self.deadline_passed() {
+                        let left = end.saturating_duration_since(Instant::now());
+                        if left.is_zero() {
+                            break;
+                        }
+                        std::thread::sleep(left.min(Duration::from_millis(10)));
+                    }
+                    self.push(Value::Nil);
                 } else if name.starts_with("std::") {
                     let result = crate::stdlib::call_stdlib(&name, args)?;
                     self.push(result);
@@ -1012,6 +1488,81 @@ impl VM {
         Ok(())
     }
 
+    /// `std::json::validate(value, SchemaName)`: check an in-program value
+    /// against a declared schema. Returns `Ok(typed struct)` or
+    /// `Err(SchemaError)`, the same shapes `execute_with_schema` produces.
+    fn json_validate(&self, args: Vec<Value>) -> Result<Value> {
+        let mut args = args.into_iter();
+        let (Some(value), Some(schema_arg), None) = (args.next(), args.next(), args.next()) else {
+            return Err(RuntimeError::TypeError(
+                "std::json::validate expects (value, Schema)".into(),
+            ));
+        };
+        let schema_name = match schema_arg {
+            Value::SchemaRef(name) => name,
+            other => {
+                return Err(RuntimeError::TypeError(format!(
+                    "std::json::validate expected a schema, got {}",
+                    other.type_name()
+                )))
+            }
+        };
+        let schema = self
+            .module
+            .schemas
+            .get(&schema_name)
+            .ok_or_else(|| RuntimeError::NameError(schema_name.clone()))?;
+        Ok(
+            match SchemaValidator::validate_value(&value.to_json(), schema) {
+                Ok(validated) => Value::Result {
+                    is_ok: true,
+                    value: Box::new(validated),
+                },
+                Err(e) => Value::Result {
+                    is_ok: false,
+                    value: Box::new(e.to_value(e.to_string())),
+                },
+            },
+        )
+    }
+
+    /// `std::log::*`: print as usual, and append a structured record to the
+    /// file sink when one was installed with `set_file(path, max_bytes)`.
+    fn log_call(&mut self, function: &str, args: Vec<Value>) -> Result<Value> {
+        if function == "set_file" {
+            let (path, max_bytes) = match args.as_slice() {
+                [Value::String(path), Value::Int(max)] if *max > 0 => (path.clone(), *max as u64),
+                _ => {
+                    return Err(RuntimeError::TypeError(
+                        "std::log::set_file expects (path: String, max_bytes: Int > 0)".into(),
+                    ))
+                }
+            };
+            return Ok(match crate::stdlib::log::LogFile::open(&path, max_bytes) {
+                Ok(sink) => {
+                    self.log_file = Some(sink);
+                    Value::Result {
+                        is_ok: true,
+                        value: Box::new(Value::Nil),
+                    }
+                }
+                Err(e) => Value::Result {
+                    is_ok: false,
+                    value: Box::new(Value::String(e.to_string())),
+                },
+            });
+        }
+
+        let message = args.first().map(|v| v.display_string()).unwrap_or_default();
+        let result = crate::stdlib::log::call(function, args)?;
+        if let Some(sink) = &self.log_file {
+            sink.append(function, &message).map_err(|e| {
+                RuntimeError::CallError(format!("std::log: could not write log file: {}", e))
+            })?;
+        }
+        Ok(result)
+    }
+
     fn exec_call_method(&mut self, inst: &IrInstruction) -> Result<()> {
         let method = inst
             .name
@@ -1033,7 +1584,8 @@ impl VM {
         let schema = inst.schema.clone();
         let result = match &object {
             Value::ModelRef(model_name) => match method.as_str() {
-                "with_memory" | "with_tools" | "without_tools" => {
+                "with_memory" | "with_tools" | "without_tools" | "with_context" | "with_retry"
+                | "with_timeout" | "with_seed" | "mock" | "mock_error" => {
                     self.model_ref_to_builder(model_name, &method, args)?
                 }
                 _ => self.call_model_method(model_name, &method, args, schema.as_deref())?,
@@ -1044,9 +1596,8 @@ impl VM {
             Value::LedgerRef(ledger_name) => self.call_ledger_method(ledger_name, &method, args)?,
             Value::MemoryRef(memory_name) => self.call_memory_method(memory_name, &method, args)?,
             Value::AgentRef(agent_name) => match method.as_str() {
-                "with_memory" | "with_tools" | "without_tools" | "with_context" => {
-                    self.agent_ref_to_builder(agent_name, &method, args)?
-                }
+                "with_memory" | "with_tools" | "without_tools" | "with_context" | "mock"
+                | "mock_error" => self.agent_ref_to_builder(agent_name, &method, args)?,
                 "execute" => self.call_agent_execute(agent_name, args, None)?,
                 "execute_with_schema" => {
                     self.call_agent_execute(agent_name, args, schema.as_deref())?
@@ -1067,15 +1618,44 @@ impl VM {
             Value::Result { is_ok, value } => Self::call_result_method(*is_ok, value, &method)?,
             Value::Option(opt) => Self::call_option_method(opt, &method)?,
             Value::String(s) => Self::call_string_method(s, &method, args)?,
-            Value::Array(arr) => Self::call_array_method(arr, &method, args)?,
-            Value::Range { start, end, inclusive } => {
-                Self::call_range_method(*start, *end, *inclusive, &method)?
+            Value::Array(arr) if method == "group_by" => self.array_group_by(arr.clone(), args)?,
+            Value::Array(arr) if method == "partition" => {
+                self.array_partition(arr.clone(), args)?
             }
+            Value::Array(arr) if method == "flat_map" => self.array_flat_map(arr.clone(), args)?,
+            Value::Array(arr) => Self::call_array_method(arr, &method, args)?,
+            Value::DateTime(ms) => crate::stdlib::time::call_datetime_method(*ms, &method, args)?,
+            // `[key, value]` pairs in insertion order (what `for` iterates)
+            Value::Map(pairs) if method == "entries" => Value::Array(
+                pairs
+                    .iter()
+                    .map(|(k, v)| Value::Array(vec![k.to_value(), v.clone()]))
+                    .collect(),
+            ),
+            Value::Range {
+                start,
+                end,
+                inclusive,
+            } => Self::call_range_method(*start, *end, *inclusive, &method)?,
             Value::Struct { ref type_name, .. }
                 if type_name == "Set" || type_name == "Queue" || type_name == "Stack" =>
             {
                 crate::stdlib::collections::call_collection_method(object, &method, args)?
             }
+            Value::Function(tool_name) if self.module.tools.contains_key(tool_name) => {
+                let qualified = format!("{}::{}", tool_name, method);
+                if let Some(func) = self.module.functions.get(&qualified).cloned() {
+                    self.push_tool_frame(tool_name, &func, args)?;
+                    return Ok(());
+                }
+                return Err(RuntimeError::CallError(format!(
+                    "unknown tool method: {}::{}",
+                    tool_name, method
+                )));
+            }
+            Value::Function(fn_name) if method == "arity" || method == "name" => {
+                self.call_function_introspection(fn_name, &method)?
+            }
             _ => {
                 // Try to find a qualified function (Type::method)
                 let type_name = object.type_name().to_string();
@@ -1123,7 +1703,10 @@ impl VM {
         Ok(())
     }
 
-    fn exec_emit(&mut self) -> Result<()> {
+    /// Emit `payload` on the channel below it. With a schema
+    /// (`emit<Schema>(...)`), a payload that does not match is thrown as a
+    /// catchable error instead of being emitted.
+    fn exec_emit(&mut self, schema: Option<&str>) -> Result<()> {
         let payload = self.pop()?;
         let channel = self.pop()?;
 
@@ -1132,42 +1715,120 @@ impl VM {
             _ => channel.display_string(),
         };
 
-        // Capture emits during test execution
-        if self.test_capture_emits {
-            self.test_emits
-                .push((channel_str.clone(), payload.clone()));
+        if let Some(schema_name) = schema {
+            let schema = self.module.schemas.get(schema_name).ok_or_else(|| {
+                RuntimeError::CallError(format!("Schema '{}' not found", schema_name))
+            })?;
+            if let Err(e) = SchemaValidator::validate_value(&payload.to_json(), schema) {
+                let detail = e.to_string();
+                return self.exec_throw(Value::String(format!(
+                    "emit on '{}' does not match schema '{}': {}",
+                    channel_str,
+                    schema_name,
+                    detail
+                        .strip_prefix("schema validation error: ")
+                        .unwrap_or(&detail)
+                )));
+            }
         }
 
-        (self.emit_handler)(&channel_str, &payload);
+        if self.sample_emit(&channel_str) {
+            self.emit_event(&channel_str, payload);
+        }
         Ok(())
     }
 
-    fn exec_propagate(&mut self) -> Result<()> {
+    /// Whether a program emit on `channel` passes `set_emit_every_n()`
+    /// sampling. Also sends the periodic `emit:dropped` summary.
+    fn sample_emit(&mut self, channel: &str) -> bool {
+        let Some(sampler) = self.emit_sampler.as_mut() else {
+            return true;
+        };
+        let seen = sampler.seen.entry(channel.to_string()).or_insert(0);
+        *seen += 1;
+        let keep = (*seen - 1) % sampler.every_n == 0;
+        if !keep {
+            *sampler.dropped.entry(channel.to_string()).or_insert(0) += 1;
+        }
+        if sampler.last_summary.elapsed() >= EMIT_DROPPED_INTERVAL {
+            self.flush_dropped_emits();
+        }
+        keep
+    }
+
+    /// Report emits dropped by sampling since the last summary, if any.
+    fn flush_dropped_emits(&mut self) {
+        let Some(sampler) = self.emit_sampler.as_mut() else {
+            return;
+        };
+        sampler.last_summary = Instant::now();
+        if sampler.dropped.is_empty() {
+            return;
+        }
+        let counts = std::mem::take(&mut sampler.dropped)
+            .into_iter()
+            .map(|(channel, n)| (channel.into(), Value::Int(n as i64)))
+            .collect();
+        self.emit_event("emit:dropped", Value::Map(counts));
+    }
+
+    /// Emit an event generated by the runtime (captured like `emit()` in tests).
+    fn emit_event(&mut self, channel: &str, payload: Value) {
+        if self.test_capture_emits {
+            self.test_emits.push((channel.to_string(), payload.clone()));
+        }
+        (self.emit_handler)(channel, &payload);
+    }
+
+    /// Emit `fn:exit` for a `@trace` frame that returned (`outcome` is
+    /// `"result"`) or was unwound by a throw (`"error"`).
+    fn trace_exit(&mut self, frame: &CallFrame, outcome: &str, value: &Value) {
+        if let Some(start) = frame.trace_start {
+            self.emit_event(
+                "fn:exit",
+                Value::Map(vec![
+                    (
+                        "function".into(),
+                        Value::String(frame.function_name.clone()),
+                    ),
+                    (outcome.into(), value.clone()),
+                    (
+                        "duration_ms".into(),
+                        Value::Int(start.elapsed().as_millis() as i64),
+                    ),
+                ]),
+            );
+        }
+    }
+
+    /// Execute the `?` operator. Ok/Some unwrap in place. Err/None end the
+    /// current function: the returned value is what it returns (`Err(e)` or
+    /// `None`). An Err inside a `try` of the same function goes to its
+    /// `catch` instead, and one reaching the entry function is unhandled.
+    fn exec_propagate(&mut self) -> Result<Option<Value>> {
         let value = self.pop()?;
         match value {
-            Value::Result { is_ok: true, value } => {
-                // Unwrap Ok
+            Value::Result { is_ok: true, value } | Value::Option(Some(value)) => {
                 self.push(*value);
-                Ok(())
+                Ok(None)
             }
             Value::Result {
                 is_ok: false,
                 value,
             } => {
-                // Propagate Err — try to catch it via try/catch first
-                self.exec_throw(*value)
-            }
-            Value::Option(Some(inner)) => {
-                // Unwrap Some
-                self.push(*inner);
-                Ok(())
-            }
-            Value::Option(None) => {
-                // Early return with None — pop current frame, push None for caller
-                self.call_stack.pop();
-                self.push(Value::Option(None));
-                Ok(())
+                let depth = self.call_stack.len();
+                let caught_here = self.try_stack.last().is_some_and(|t| t.call_depth == depth);
+                if caught_here || depth <= 1 {
+                    self.exec_throw(*value)?;
+                    Ok(None)
+                } else {
+                    Ok(Some(Value::Result {
+                        is_ok: false,
+                        value,
+                    }))
+                }
             }
+            Value::Option(None) => Ok(Some(Value::Option(None))),
             other => {
                 // Not a Result or Option — runtime error
                 Err(RuntimeError::TypeError(format!(
@@ -1178,13 +1839,74 @@ impl VM {
         }
     }
 
+    /// Pop the current frame, charging its time to the profiler when enabled.
+    fn pop_frame(&mut self) -> Option<CallFrame> {
+        let frame = self.call_stack.pop()?;
+        if let (Some(profiler), Some(entered_at)) = (self.profiler.as_mut(), frame.entered_at) {
+            let total = entered_at.elapsed();
+            profiler.record(
+                &frame.function_name,
+                total,
+                total.saturating_sub(frame.child_time),
+            );
+            if let Some(caller) = self.call_stack.last_mut() {
+                caller.child_time += total;
+            }
+        }
+        Some(frame)
+    }
+
+    /// Pop the current frame, discarding its leftover operands and any try
+    /// handlers it installed. `return_val` is reported to `@trace`.
+    fn exit_frame(&mut self, return_val: &Value) {
+        let depth = self.call_stack.len();
+        if let Some(frame) = self.pop_frame() {
+            self.stack.truncate(frame.stack_base);
+            self.trace_exit(&frame, "result", return_val);
+        }
+        while self.try_stack.last().is_some_and(|t| t.call_depth >= depth) {
+            self.try_stack.pop();
+        }
+    }
+
+    /// Discard every frame above `depth` after an error escaped a nested
+    /// run loop, restoring the operand stack to where the first of them began.
+    fn unwind_to_depth(&mut self, depth: usize, error_val: &Value) {
+        while self.call_stack.len() > depth {
+            if let Some(frame) = self.pop_frame() {
+                self.stack.truncate(frame.stack_base);
+                self.trace_exit(&frame, "error", error_val);
+            }
+        }
+        while self.try_stack.last().is_some_and(|t| t.call_depth > depth) {
+            self.try_stack.pop();
+        }
+    }
+
+    /// Execute a shift. An out-of-range shift amount is thrown as a
+    /// catchable error rather than aborting the VM.
+    fn exec_shift(&mut self, shift: fn(&Value, &Value) -> Result<Value>) -> Result<()> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        match shift(&a, &b) {
+            Ok(value) => {
+                self.push(value);
+                Ok(())
+            }
+            Err(e @ RuntimeError::ShiftOverflow(_)) => self.exec_throw(Value::String(e.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Handle a throw: unwind to the nearest try/catch handler, or return
     /// an unhandled error if none exists.
     fn exec_throw(&mut self, error_val: Value) -> Result<()> {
         if let Some(try_frame) = self.try_stack.pop() {
             // Unwind call stack to the frame that owns the try block
             while self.call_stack.len() > try_frame.call_depth {
-                self.call_stack.pop();
+                if let Some(frame) = self.pop_frame() {
+                    self.trace_exit(&frame, "error", &error_val);
+                }
             }
             // Restore stack height (discard any values pushed during try body)
             self.stack.truncate(try_frame.stack_height);
@@ -1264,7 +1986,7 @@ impl VM {
                 if let Some(pair) = pairs.iter_mut().find(|(k, _)| k == name) {
                     pair.1 = value;
                 } else {
-                    pairs.push((name.clone(), value));
+                    pairs.push((name.clone().into(), value));
                 }
             }
             _ => {
@@ -1303,11 +2025,12 @@ impl VM {
                     });
                 }
             }
-            (Value::Map(pairs), Value::String(key)) => {
-                if let Some(pair) = pairs.iter_mut().find(|(k, _)| k == key) {
+            (Value::Map(pairs), key) => {
+                let key = MapKey::from_value(key)?;
+                if let Some(pair) = pairs.iter_mut().find(|(k, _)| *k == key) {
                     pair.1 = value;
                 } else {
-                    pairs.push((key.clone(), value));
+                    pairs.push((key, value));
                 }
             }
             _ => {
@@ -1390,11 +2113,7 @@ impl VM {
         for _ in 0..count {
             let value = self.pop()?;
             let key = self.pop()?;
-            let key_str = match key {
-                Value::String(s) => s,
-                _ => key.display_string(),
-            };
-            pairs.push((key_str, value));
+            pairs.push((MapKey::from_value(&key)?, value));
         }
         pairs.reverse();
         self.push(Value::Map(pairs));
@@ -1445,13 +2164,12 @@ impl VM {
             .hashmap_name
             .as_ref()
             .ok_or_else(|| RuntimeError::LoadError("HASH_MAP_GET missing hashmap_name".into()))?;
-        let key = self.pop()?;
-        let key_str = key.display_string();
+        let key = MapKey::from_value(&self.pop()?)?;
 
         let value = self
             .hashmaps
             .get(hashmap_name)
-            .and_then(|hm| hm.get(&key_str))
+            .and_then(|hm| hm.get(&key))
             .cloned();
 
         match value {
@@ -1467,13 +2185,12 @@ impl VM {
             .as_ref()
             .ok_or_else(|| RuntimeError::LoadError("HASH_MAP_SET missing hashmap_name".into()))?;
         let value = self.pop()?;
-        let key = self.pop()?;
-        let key_str = key.display_string();
+        let key = MapKey::from_value(&self.pop()?)?;
 
         self.hashmaps
             .entry(hashmap_name.clone())
             .or_default()
-            .insert(key_str, value);
+            .insert(key, value);
         self.push(Value::Nil);
         Ok(())
     }
@@ -1482,11 +2199,10 @@ impl VM {
         let hashmap_name = inst.hashmap_name.as_ref().ok_or_else(|| {
             RuntimeError::LoadError("HASH_MAP_DELETE missing hashmap_name".into())
         })?;
-        let key = self.pop()?;
-        let key_str = key.display_string();
+        let key = MapKey::from_value(&self.pop()?)?;
 
         if let Some(hm) = self.hashmaps.get_mut(hashmap_name) {
-            hm.remove(&key_str);
+            hm.remove(&key);
         }
         self.push(Value::Nil);
         Ok(())
@@ -1497,13 +2213,12 @@ impl VM {
             .hashmap_name
             .as_ref()
             .ok_or_else(|| RuntimeError::LoadError("HASH_MAP_HAS missing hashmap_name".into()))?;
-        let key = self.pop()?;
-        let key_str = key.display_string();
+        let key = MapKey::from_value(&self.pop()?)?;
 
         let exists = self
             .hashmaps
             .get(hashmap_name)
-            .map(|hm| hm.contains_key(&key_str))
+            .map(|hm| hm.contains_key(&key))
             .unwrap_or(false);
 
         self.push(Value::Bool(exists));
@@ -1514,6 +2229,64 @@ impl VM {
     // Model method dispatch (provider-based)
     // ========================================================================
 
+    /// Resolve the retry/timeout policy for a model call from its `@retry` and
+    /// `@timeout` decorators. Per-call builder overrides (`with_retry`,
+    /// `with_timeout`) take precedence; an overridden retry keeps the declared
+    /// backoff strategy.
+    fn model_call_policy(
+        model_def: &concerto_common::ir::IrModel,
+        retry_override: Option<u32>,
+        timeout_override: Option<u64>,
+    ) -> (
+        Option<crate::decorator::RetryConfig>,
+        Option<crate::decorator::TimeoutConfig>,
+    ) {
+        let mut retry_config = crate::decorator::find_decorator(&model_def.decorators, "retry")
+            .map(crate::decorator::parse_retry);
+        if let Some(max_attempts) = retry_override {
+            let backoff = retry_config
+                .map(|rc| rc.backoff)
+                .unwrap_or(crate::decorator::BackoffStrategy::Exponential { base_ms: 1000 });
+            retry_config = Some(crate::decorator::RetryConfig {
+                max_attempts,
+                backoff,
+            });
+        }
+        let timeout_config = match timeout_override {
+            Some(seconds) => Some(crate::decorator::TimeoutConfig { seconds }),
+            None => crate::decorator::find_decorator(&model_def.decorators, "timeout")
+                .map(crate::decorator::parse_timeout),
+        };
+        (retry_config, timeout_config)
+    }
+
+    /// Emit `model:request` just before a provider call on an `@log` model,
+    /// pairing with the `model:log` event emitted once the response arrives.
+    fn emit_model_request(
+        &self,
+        model_name: &str,
+        method: &str,
+        attempt: u32,
+        request: &ChatRequest,
+    ) {
+        (self.emit_handler)(
+            "model:request",
+            &Value::Map(vec![
+                ("model".into(), Value::String(model_name.to_string())),
+                ("method".into(), Value::String(method.to_string())),
+                ("attempt".into(), Value::Int((attempt + 1) as i64)),
+                (
+                    "message_count".into(),
+                    Value::Int(request.messages.len() as i64),
+                ),
+                (
+                    "temperature".into(),
+                    request.temperature.map(Value::Float).unwrap_or(Value::Nil),
+                ),
+            ]),
+        );
+    }
+
     fn call_model_method(
         &self,
         model_name: &str,
@@ -1526,90 +2299,85 @@ impl VM {
             return self.call_mock_model(model_name, method, mock.clone(), schema_name);
         }
 
+        let prompt = args.into_iter().next().unwrap_or(Value::Nil);
+        let prompt = prompt.display_string();
+        let overrides = ModelCallOverrides::default();
+        let (result, _) =
+            self.run_model_call(model_name, method, &prompt, schema_name, &overrides)?;
+        Ok(result)
+    }
+
+    /// Run a provider-backed model call: `@retry`/`@timeout` attempts and,
+    /// for `execute_with_schema`, schema self-correction retries. Plain
+    /// calls and `ModelBuilder` calls both come through here, so builder
+    /// overrides only change the request and the policy. Returns the
+    /// `Result` value plus the text of the last response received.
+    fn run_model_call(
+        &self,
+        model_name: &str,
+        method: &str,
+        prompt_str: &str,
+        schema_name: Option<&str>,
+        overrides: &ModelCallOverrides<'_>,
+    ) -> Result<(Value, Option<String>)> {
         let model_def = self
             .module
             .models
             .get(model_name)
             .ok_or_else(|| RuntimeError::NameError(format!("unknown model: {}", model_name)))?;
 
-        // Parse decorator configs from model definition
-        let retry_config = crate::decorator::find_decorator(&model_def.decorators, "retry")
-            .map(crate::decorator::parse_retry);
-        let timeout_config = crate::decorator::find_decorator(&model_def.decorators, "timeout")
-            .map(crate::decorator::parse_timeout);
+        // Decorator configs from the model definition, under builder overrides
+        let (retry_config, timeout_config) =
+            Self::model_call_policy(model_def, overrides.retry, overrides.timeout);
+        let build_request = |prompt: &str, response_format| {
+            self.build_chat_request_full(model_def, prompt, response_format, overrides)
+        };
         let has_log = crate::decorator::find_decorator(&model_def.decorators, "log").is_some();
 
         let max_attempts = retry_config.as_ref().map(|r| r.max_attempts).unwrap_or(1);
+        // `@timeout` bounds each attempt; the provider call is abandoned
+        // once it runs past the limit.
+        let call_limit = timeout_config
+            .as_ref()
+            .map(|tc| Duration::from_secs(tc.seconds));
 
         match method {
             "execute" => {
-                let prompt = args.into_iter().next().unwrap_or(Value::Nil);
-                let prompt_str = prompt.display_string();
                 let mut last_error = String::new();
 
                 for attempt in 0..max_attempts {
                     let start = std::time::Instant::now();
-                    let request = self.build_chat_request(model_def, &prompt_str, None);
+                    let request = build_request(prompt_str, None);
                     let provider = self.connection_manager.get_provider(&model_def.connection);
 
-                    match provider.chat_completion(request) {
+                    if has_log {
+                        self.emit_model_request(model_name, "execute", attempt, &request);
+                    }
+                    match crate::provider::chat_completion_within(provider, request, call_limit) {
                         Ok(chat_response) => {
-                            // Check timeout
-                            if let Some(ref tc) = timeout_config {
-                                let elapsed = start.elapsed();
-                                if elapsed > std::time::Duration::from_secs(tc.seconds) {
-                                    last_error = format!(
-                                        "timeout exceeded ({}s > {}s)",
-                                        elapsed.as_secs(),
-                                        tc.seconds
-                                    );
-                                    if attempt + 1 < max_attempts {
-                                        if let Some(ref rc) = retry_config {
-                                            std::thread::sleep(crate::decorator::backoff_delay(
-                                                &rc.backoff,
-                                                attempt,
-                                            ));
-                                        }
-                                        continue;
-                                    }
-                                    return Ok(Value::Result {
-                                        is_ok: false,
-                                        value: Box::new(Value::String(last_error)),
-                                    });
-                                }
-                            }
+                            let latency_ms = start.elapsed().as_millis() as i64;
 
                             // @log decorator
                             if has_log {
                                 (self.emit_handler)(
                                     "model:log",
                                     &Value::Map(vec![
-                                        (
-                                            "model".to_string(),
-                                            Value::String(model_name.to_string()),
-                                        ),
-                                        (
-                                            "method".to_string(),
-                                            Value::String("execute".to_string()),
-                                        ),
-                                        ("attempt".to_string(), Value::Int((attempt + 1) as i64)),
-                                        (
-                                            "tokens_in".to_string(),
-                                            Value::Int(chat_response.tokens_in),
-                                        ),
-                                        (
-                                            "tokens_out".to_string(),
-                                            Value::Int(chat_response.tokens_out),
-                                        ),
+                                        ("model".into(), Value::String(model_name.to_string())),
+                                        ("method".into(), Value::String("execute".to_string())),
+                                        ("attempt".into(), Value::Int((attempt + 1) as i64)),
+                                        ("tokens_in".into(), Value::Int(chat_response.tokens_in)),
+                                        ("tokens_out".into(), Value::Int(chat_response.tokens_out)),
+                                        ("latency_ms".into(), Value::Int(latency_ms)),
                                     ]),
                                 );
                             }
 
-                            let response = Self::chat_response_to_value(&chat_response);
-                            return Ok(Value::Result {
+                            let response = Self::chat_response_to_value(&chat_response, latency_ms);
+                            let result = Value::Result {
                                 is_ok: true,
                                 value: Box::new(response),
-                            });
+                            };
+                            return Ok((result, Some(chat_response.text)));
                         }
                         Err(e) => {
                             last_error = e.to_string();
@@ -1626,18 +2394,16 @@ impl VM {
                 }
 
                 // All retries exhausted
-                Ok(Value::Result {
+                let result = Value::Result {
                     is_ok: false,
                     value: Box::new(Value::String(format!(
                         "model '{}' failed after {} attempts: {}",
                         model_name, max_attempts, last_error
                     ))),
-                })
+                };
+                Ok((result, None))
             }
             "execute_with_schema" => {
-                let prompt = args.into_iter().next().unwrap_or(Value::Nil);
-                let prompt_str = prompt.display_string();
-
                 // Look up schema for structured output
                 let schema = schema_name.and_then(|n| self.module.schemas.get(n));
 
@@ -1647,73 +2413,96 @@ impl VM {
                         json_schema: Some(schema.json_schema.clone()),
                     });
 
+                    // `schema_retries: N` on the model caps self-correction
+                    // attempts per outer attempt (at least one request).
+                    let schema_attempts = model_def
+                        .config
+                        .schema_retries
+                        .map_or(SchemaValidator::max_retries(), |n| (n as usize).max(1));
+
                     let mut last_error = String::new();
+                    // Set when the most recent failure was a validation error,
+                    // so exhaustion can report the offending field.
+                    let mut last_schema_error = None;
+                    let mut last_text = None;
 
                     // Outer retry loop: @retry decorator (catches provider errors)
                     for attempt in 0..max_attempts {
                         let start = std::time::Instant::now();
                         let provider = self.connection_manager.get_provider(&model_def.connection);
-                        let mut current_prompt = prompt_str.clone();
+                        let mut current_prompt = prompt_str.to_string();
 
                         // Inner retry loop: schema validation retry
                         let mut schema_result = None;
-                        for schema_attempt in 0..SchemaValidator::max_retries() {
+                        for schema_attempt in 0..schema_attempts {
                             let rf = if schema_attempt == 0 {
                                 response_format.clone()
                             } else {
                                 current_prompt =
-                                    SchemaValidator::retry_prompt(&prompt_str, &last_error, schema);
+                                    SchemaValidator::retry_prompt(prompt_str, &last_error, schema);
                                 response_format.clone()
                             };
 
-                            let request = self.build_chat_request(model_def, &current_prompt, rf);
-                            match provider.chat_completion(request) {
+                            let request = build_request(&current_prompt, rf);
+                            if has_log {
+                                self.emit_model_request(
+                                    model_name,
+                                    "execute_with_schema",
+                                    attempt,
+                                    &request,
+                                );
+                            }
+                            let call_start = std::time::Instant::now();
+                            // Schema retries share the attempt's time budget.
+                            let remaining =
+                                call_limit.map(|limit| limit.saturating_sub(start.elapsed()));
+                            match crate::provider::chat_completion_within(
+                                Arc::clone(&provider),
+                                request,
+                                remaining,
+                            ) {
                                 Ok(chat_response) => {
-                                    // Check timeout
-                                    if let Some(ref tc) = timeout_config {
-                                        let elapsed = start.elapsed();
-                                        if elapsed > std::time::Duration::from_secs(tc.seconds) {
-                                            last_error = format!(
-                                                "timeout exceeded ({}s > {}s)",
-                                                elapsed.as_secs(),
-                                                tc.seconds
-                                            );
-                                            break;
-                                        }
-                                    }
+                                    let latency_ms = call_start.elapsed().as_millis() as i64;
+                                    let validation =
+                                        SchemaValidator::validate(&chat_response.text, schema);
+                                    last_text = Some(chat_response.text.clone());
 
-                                    match SchemaValidator::validate(&chat_response.text, schema) {
+                                    match validation {
                                         Ok(validated) => {
                                             if has_log {
                                                 (self.emit_handler)(
                                                     "model:log",
                                                     &Value::Map(vec![
                                                         (
-                                                            "model".to_string(),
+                                                            "model".into(),
                                                             Value::String(model_name.to_string()),
                                                         ),
                                                         (
-                                                            "method".to_string(),
+                                                            "method".into(),
                                                             Value::String(
                                                                 "execute_with_schema".to_string(),
                                                             ),
                                                         ),
                                                         (
-                                                            "attempt".to_string(),
+                                                            "attempt".into(),
                                                             Value::Int((attempt + 1) as i64),
                                                         ),
                                                         (
-                                                            "schema_attempt".to_string(),
+                                                            "schema_attempt".into(),
                                                             Value::Int((schema_attempt + 1) as i64),
                                                         ),
                                                         (
-                                                            "tokens_in".to_string(),
+                                                            "tokens_in".into(),
                                                             Value::Int(chat_response.tokens_in),
                                                         ),
                                                         (
-                                                            "tokens_out".to_string(),
+                                                            "tokens_out".into(),
                                                             Value::Int(chat_response.tokens_out),
                                                         ),
+                                                        (
+                                                            "latency_ms".into(),
+                                                            Value::Int(latency_ms),
+                                                        ),
                                                     ]),
                                                 );
                                             }
@@ -1722,21 +2511,24 @@ impl VM {
                                         }
                                         Err(e) => {
                                             last_error = e.to_string();
+                                            last_schema_error = Some(e);
                                         }
                                     }
                                 }
                                 Err(e) => {
                                     last_error = e.to_string();
+                                    last_schema_error = None;
                                     break; // Provider error — exit inner loop, let outer retry handle it
                                 }
                             }
                         }
 
                         if let Some(validated) = schema_result {
-                            return Ok(Value::Result {
+                            let result = Value::Result {
                                 is_ok: true,
                                 value: Box::new(validated),
-                            });
+                            };
+                            return Ok((result, last_text));
                         }
 
                         // Inner loop didn't succeed — try outer retry
@@ -1751,24 +2543,34 @@ impl VM {
                     }
 
                     // All retries exhausted
-                    Ok(Value::Result {
+                    let message = format!(
+                        "schema validation failed after {} attempts: {}",
+                        max_attempts * schema_attempts as u32,
+                        last_error
+                    );
+                    let value = match last_schema_error {
+                        Some(e) => e.to_value(message),
+                        None => Value::String(message),
+                    };
+                    let result = Value::Result {
                         is_ok: false,
-                        value: Box::new(Value::String(format!(
-                            "schema validation failed after {} attempts: {}",
-                            max_attempts * SchemaValidator::max_retries() as u32,
-                            last_error
-                        ))),
-                    })
+                        value: Box::new(value),
+                    };
+                    Ok((result, last_text))
                 } else {
                     // No schema found — just do a regular execute
-                    let request = self.build_chat_request(model_def, &prompt_str, None);
+                    let request = build_request(prompt_str, None);
                     let provider = self.connection_manager.get_provider(&model_def.connection);
-                    let chat_response = provider.chat_completion(request)?;
-                    let response = Self::chat_response_to_value(&chat_response);
-                    Ok(Value::Result {
+                    let start = std::time::Instant::now();
+                    let chat_response =
+                        crate::provider::chat_completion_within(provider, request, call_limit)?;
+                    let latency_ms = start.elapsed().as_millis() as i64;
+                    let response = Self::chat_response_to_value(&chat_response, latency_ms);
+                    let result = Value::Result {
                         is_ok: true,
                         value: Box::new(response),
-                    })
+                    };
+                    Ok((result, Some(chat_response.text)))
                 }
             }
             _ => Err(RuntimeError::CallError(format!(
@@ -1810,12 +2612,10 @@ impl VM {
                                     });
                                 }
                                 Err(e) => {
+                                    let message = format!("mock schema validation failed: {}", e);
                                     return Ok(Value::Result {
                                         is_ok: false,
-                                        value: Box::new(Value::String(format!(
-                                            "mock schema validation failed: {}",
-                                            e
-                                        ))),
+                                        value: Box::new(e.to_value(message)),
                                     });
                                 }
                             }
@@ -1829,6 +2629,7 @@ impl VM {
                 fields.insert("model".to_string(), Value::String("mock".to_string()));
                 fields.insert("tokens_in".to_string(), Value::Int(0));
                 fields.insert("tokens_out".to_string(), Value::Int(0));
+                fields.insert("latency_ms".to_string(), Value::Int(0));
                 Ok(Value::Result {
                     is_ok: true,
                     value: Box::new(Value::Struct {
@@ -1845,33 +2646,26 @@ impl VM {
     }
 
     /// Build a ChatRequest from model config and prompt.
+    #[cfg(test)]
     fn build_chat_request(
         &self,
         model_def: &concerto_common::ir::IrModel,
         prompt: &str,
         response_format: Option<crate::provider::ResponseFormat>,
     ) -> ChatRequest {
-        self.build_chat_request_with_memory(model_def, prompt, response_format, None)
-    }
-
-    fn build_chat_request_with_memory(
-        &self,
-        model_def: &concerto_common::ir::IrModel,
-        prompt: &str,
-        response_format: Option<crate::provider::ResponseFormat>,
-        memory_name: Option<&str>,
-    ) -> ChatRequest {
-        self.build_chat_request_full(model_def, prompt, response_format, memory_name, &[], false)
+        let overrides = ModelCallOverrides::default();
+        self.build_chat_request_full(model_def, prompt, response_format, &overrides)
     }
 
+    /// Build a ChatRequest with builder overrides. A `with_context` value is
+    /// sent as a system message (`Context:` + JSON) after the model's system
+    /// prompt and before any memory history.
     fn build_chat_request_full(
         &self,
         model_def: &concerto_common::ir::IrModel,
         prompt: &str,
         response_format: Option<crate::provider::ResponseFormat>,
-        memory_name: Option<&str>,
-        extra_tools: &[String],
-        exclude_default_tools: bool,
+        overrides: &ModelCallOverrides<'_>,
     ) -> ChatRequest {
         let mut messages = Vec::new();
 
@@ -1884,8 +2678,17 @@ impl VM {
             });
         }
 
+        // Builder context (with_context)
+        if let Some(ctx) = overrides.context {
+            messages.push(ChatMessage {
+                role: "system".to_string(),
+                content: format!("Context:\n{}", ctx.to_json()),
+                tool_call_id: None,
+            });
+        }
+
         // Inject memory messages (between system prompt and user prompt)
-        if let Some(mem_name) = memory_name {
+        if let Some(mem_name) = overrides.memory {
             if let Ok(mem_msgs) = self.memory_store.messages(mem_name) {
                 messages.extend(mem_msgs);
             }
@@ -1903,7 +2706,7 @@ impl VM {
         let mut seen_names = std::collections::HashSet::new();
 
         // Static tools from model definition (unless excluded)
-        if !exclude_default_tools {
+        if !overrides.exclude_default_tools {
             for tool_ref in &model_def.tools {
                 if self.mcp_registry.has_server(tool_ref) {
                     for schema in self.mcp_registry.get_tool_schemas(tool_ref) {
@@ -1916,7 +2719,7 @@ impl VM {
         }
 
         // Dynamic tools from extra_tools
-        for tool_name in extra_tools {
+        for tool_name in overrides.extra_tools {
             // Check if it's a Concerto tool with schemas
             if let Some(ir_tool) = self.module.tools.get(tool_name) {
                 for entry in &ir_tool.tool_schemas {
@@ -1950,22 +2753,35 @@ impl VM {
                 .config
                 .base
                 .clone()
+                .or_else(|| {
+                    self.connection_manager
+                        .default_model(&model_def.connection)
+                        .map(str::to_string)
+                })
                 .unwrap_or_else(|| "gpt-4".to_string()),
             messages,
             temperature: model_def.config.temperature,
             max_tokens: model_def.config.max_tokens,
             tools,
             response_format,
+            headers: self
+                .connection_manager
+                .headers(&model_def.connection)
+                .to_vec(),
+            base_url: model_def.config.base_url.clone(),
+            seed: overrides.seed.or(model_def.config.seed),
         }
     }
 
     /// Convert a ChatResponse into a Value::Struct { type_name: "Response", ... }.
-    fn chat_response_to_value(response: &crate::provider::ChatResponse) -> Value {
+    /// `latency_ms` is the wall-clock duration of the provider call.
+    fn chat_response_to_value(response: &crate::provider::ChatResponse, latency_ms: i64) -> Value {
         let mut fields = HashMap::new();
         fields.insert("text".to_string(), Value::String(response.text.clone()));
         fields.insert("tokens_in".to_string(), Value::Int(response.tokens_in));
         fields.insert("tokens_out".to_string(), Value::Int(response.tokens_out));
         fields.insert("model".to_string(), Value::String(response.model.clone()));
+        fields.insert("latency_ms".to_string(), Value::Int(latency_ms));
         Value::Struct {
             type_name: "Response".to_string(),
             fields,
@@ -1997,20 +2813,7 @@ impl VM {
         // Look up the qualified function "Tool::method"
         let qualified = format!("{}::{}", tool_name, method_name);
         if let Some(func) = self.module.functions.get(&qualified).cloned() {
-            // Get tool self value
-            let self_val = self.tool_registry.get_self_value(tool_name);
-
-            // Prepend self to args
-            let mut full_args = vec![self_val];
-            full_args.extend(args);
-
-            // Push frame and execute
-            self.push_frame(
-                func.name.clone(),
-                func.instructions.clone(),
-                full_args,
-                &func.params,
-            )?;
+            self.push_tool_frame(tool_name, &func, args)?;
         } else {
             return Err(RuntimeError::CallError(format!(
                 "unknown tool method: {}::{}",
@@ -2033,7 +2836,7 @@ impl VM {
         match method {
             "set" => {
                 if args.len() >= 2 {
-                    let key = args[0].display_string();
+                    let key = MapKey::from_value(&args[0])?;
                     let value = args[1].clone();
                     self.hashmaps
                         .entry(hashmap_name.to_string())
@@ -2043,11 +2846,7 @@ impl VM {
                 Ok(Value::Nil)
             }
             "get" => {
-                let key = args
-                    .into_iter()
-                    .next()
-                    .map(|v| v.display_string())
-                    .unwrap_or_default();
+                let key = MapKey::from_value(args.first().unwrap_or(&Value::Nil))?;
                 let value = self
                     .hashmaps
                     .get(hashmap_name)
@@ -2059,11 +2858,7 @@ impl VM {
                 }
             }
             "has" => {
-                let key = args
-                    .into_iter()
-                    .next()
-                    .map(|v| v.display_string())
-                    .unwrap_or_default();
+                let key = MapKey::from_value(args.first().unwrap_or(&Value::Nil))?;
                 let exists = self
                     .hashmaps
                     .get(hashmap_name)
@@ -2072,16 +2867,28 @@ impl VM {
                 Ok(Value::Bool(exists))
             }
             "delete" => {
-                let key = args
-                    .into_iter()
-                    .next()
-                    .map(|v| v.display_string())
-                    .unwrap_or_default();
+                let key = MapKey::from_value(args.first().unwrap_or(&Value::Nil))?;
                 if let Some(hm) = self.hashmaps.get_mut(hashmap_name) {
                     hm.remove(&key);
                 }
                 Ok(Value::Nil)
             }
+            "keys" => {
+                let keys = self
+                    .hashmaps
+                    .get(hashmap_name)
+                    .map(|hm| hm.keys().map(MapKey::to_value).collect())
+                    .unwrap_or_default();
+                Ok(Value::Array(keys))
+            }
+            "values" => {
+                let values = self
+                    .hashmaps
+                    .get(hashmap_name)
+                    .map(|hm| hm.values().cloned().collect())
+                    .unwrap_or_default();
+                Ok(Value::Array(values))
+            }
             _ => Err(RuntimeError::CallError(format!(
                 "unknown hashmap method: {}.{}",
                 hashmap_name, method
@@ -2114,6 +2921,25 @@ impl VM {
                 }
                 Ok(Value::Nil)
             }
+            "insert_unique" => {
+                // insert_unique(identifier: String, keys: Array<String>, value: String) -> Bool
+                if args.len() >= 3 {
+                    let identifier = args[0].display_string();
+                    let keys = match &args[1] {
+                        Value::Array(arr) => arr.iter().map(|v| v.display_string()).collect(),
+                        _ => vec![args[1].display_string()],
+                    };
+                    let value = args[2].display_string();
+                    Ok(Value::Bool(self.ledger_store.insert_unique(
+                        ledger_name,
+                        identifier,
+                        keys,
+                        value,
+                    )))
+                } else {
+                    Ok(Value::Bool(false))
+                }
+            }
             "delete" => {
                 // delete(identifier: String) -> Bool
                 let identifier = args
@@ -2275,121 +3101,121 @@ impl VM {
     ) -> Result<Value> {
         match method {
             "run" => {
-                let pipeline = self
-                    .module
-                    .pipelines
-                    .get(pipeline_name)
-                    .ok_or_else(|| {
-                        RuntimeError::NameError(format!("unknown pipeline: {}", pipeline_name))
-                    })?
-                    .clone();
-
-                let mut input = args.into_iter().next().unwrap_or(Value::Nil);
-                let pipeline_start = std::time::Instant::now();
-
-                // Emit pipeline:start
-                (self.emit_handler)(
-                    "pipeline:start",
-                    &Value::Map(vec![
-                        ("name".to_string(), Value::String(pipeline_name.to_string())),
-                        (
-                            "stages".to_string(),
-                            Value::Int(pipeline.stages.len() as i64),
-                        ),
-                    ]),
-                );
+                let input = args.into_iter().next().unwrap_or(Value::Nil);
+                self.run_pipeline(pipeline_name, input, pipeline_name)
+            }
+            _ => Err(RuntimeError::CallError(format!(
+                "unknown pipeline method: {}.{}",
+                pipeline_name, method
+            ))),
+        }
+    }
 
-                for stage in &pipeline.stages {
-                    let stage_start = std::time::Instant::now();
+    /// Run a pipeline's stages over `input`. `path` names this run in the
+    /// `pipeline:*` events: the pipeline name at top level, extended with
+    /// `/stage` for each `stage name = Other;` that nests it.
+    fn run_pipeline(&mut self, pipeline_name: &str, mut input: Value, path: &str) -> Result<Value> {
+        let pipeline = self
+            .module
+            .pipelines
+            .get(pipeline_name)
+            .ok_or_else(|| RuntimeError::NameError(format!("unknown pipeline: {}", pipeline_name)))?
+            .clone();
 
-                    // Emit pipeline:stage_start
-                    (self.emit_handler)(
-                        "pipeline:stage_start",
-                        &Value::Map(vec![
-                            (
-                                "pipeline".to_string(),
-                                Value::String(pipeline_name.to_string()),
-                            ),
-                            ("stage".to_string(), Value::String(stage.name.clone())),
-                        ]),
-                    );
+        let pipeline_start = std::time::Instant::now();
 
-                    // Parse stage decorators
-                    let retry_config = crate::decorator::find_decorator(&stage.decorators, "retry")
-                        .map(crate::decorator::parse_retry);
-                    let timeout_config =
-                        crate::decorator::find_decorator(&stage.decorators, "timeout")
-                            .map(crate::decorator::parse_timeout);
+        // Emit pipeline:start
+        (self.emit_handler)(
+            "pipeline:start",
+            &Value::Map(vec![
+                ("name".into(), Value::String(pipeline_name.to_string())),
+                ("path".into(), Value::String(path.to_string())),
+                ("stages".into(), Value::Int(pipeline.stages.len() as i64)),
+            ]),
+        );
 
-                    let max_attempts = retry_config.as_ref().map(|r| r.max_attempts).unwrap_or(1);
-                    let mut last_error = String::new();
-                    let mut stage_result = None;
+        for stage in &pipeline.stages {
+            let stage_start = std::time::Instant::now();
+
+            // Emit pipeline:stage_start
+            (self.emit_handler)(
+                "pipeline:stage_start",
+                &Value::Map(vec![
+                    ("pipeline".into(), Value::String(pipeline_name.to_string())),
+                    ("path".into(), Value::String(path.to_string())),
+                    ("stage".into(), Value::String(stage.name.clone())),
+                ]),
+            );
+
+            // Parse stage decorators
+            let retry_config = crate::decorator::find_decorator(&stage.decorators, "retry")
+                .map(crate::decorator::parse_retry);
+            let timeout_config = crate::decorator::find_decorator(&stage.decorators, "timeout")
+                .map(crate::decorator::parse_timeout);
+
+            let max_attempts = retry_config.as_ref().map(|r| r.max_attempts).unwrap_or(1);
+            let mut last_error = String::new();
+            let mut stage_result = None;
+
+            for attempt in 0..max_attempts {
+                let run_result = if let Some(ref sub) = stage.pipeline {
+                    // The sub-pipeline's Result is handled like a stage's
+                    let sub_path = format!("{}/{}", path, stage.name);
+                    self.run_pipeline(sub, input.clone(), &sub_path)
+                } else {
+                    let stop_depth = self.call_stack.len();
+                    self.push_frame(
+                        format!("{}::{}", pipeline_name, stage.name),
+                        stage.instructions.clone(),
+                        vec![input.clone()],
+                        &stage.params,
+                    )?;
 
-                    for attempt in 0..max_attempts {
-                        let stop_depth = self.call_stack.len();
-                        self.push_frame(
-                            format!("{}::{}", pipeline_name, stage.name),
-                            stage.instructions.clone(),
-                            vec![input.clone()],
-                            &stage.params,
-                        )?;
+                    // A throw inside the stage must not unwind into a
+                    // try/catch of the caller: hide the outer handlers so
+                    // it surfaces here, where retry and failure apply.
+                    let outer_try_stack = std::mem::take(&mut self.try_stack);
+                    let run_result = self.run_loop_until(stop_depth);
+                    if let Err(ref e) = run_result {
+                        self.unwind_to_depth(stop_depth, &Value::String(e.to_string()));
+                    }
+                    self.try_stack = outer_try_stack;
+                    run_result
+                };
 
-                        match self.run_loop_until(stop_depth) {
-                            Ok(val) => {
-                                // Check timeout
-                                if let Some(ref tc) = timeout_config {
-                                    let elapsed = stage_start.elapsed();
-                                    if elapsed > std::time::Duration::from_secs(tc.seconds) {
-                                        last_error = format!(
-                                            "stage '{}' timed out ({}ms > {}s)",
-                                            stage.name,
-                                            elapsed.as_millis(),
-                                            tc.seconds
-                                        );
-                                        if attempt + 1 < max_attempts {
-                                            if let Some(ref rc) = retry_config {
-                                                std::thread::sleep(
-                                                    crate::decorator::backoff_delay(
-                                                        &rc.backoff,
-                                                        attempt,
-                                                    ),
-                                                );
-                                            }
-                                            continue;
-                                        }
-                                        break;
+                match run_result {
+                    Ok(val) => {
+                        // Check timeout
+                        if let Some(ref tc) = timeout_config {
+                            let elapsed = stage_start.elapsed();
+                            if elapsed > std::time::Duration::from_secs(tc.seconds) {
+                                last_error = format!(
+                                    "stage '{}' timed out ({}ms > {}s)",
+                                    stage.name,
+                                    elapsed.as_millis(),
+                                    tc.seconds
+                                );
+                                if attempt + 1 < max_attempts {
+                                    if let Some(ref rc) = retry_config {
+                                        std::thread::sleep(crate::decorator::backoff_delay(
+                                            &rc.backoff,
+                                            attempt,
+                                        ));
                                     }
+                                    continue;
                                 }
-
-                                // Unwrap Result if stage returned one
-                                let output = match val {
-                                    Value::Result { is_ok: true, value } => *value,
-                                    Value::Result {
-                                        is_ok: false,
-                                        value,
-                                    } => {
-                                        last_error = value.display_string();
-                                        if attempt + 1 < max_attempts {
-                                            if let Some(ref rc) = retry_config {
-                                                std::thread::sleep(
-                                                    crate::decorator::backoff_delay(
-                                                        &rc.backoff,
-                                                        attempt,
-                                                    ),
-                                                );
-                                            }
-                                            continue;
-                                        }
-                                        break;
-                                    }
-                                    other => other,
-                                };
-
-                                stage_result = Some(output);
                                 break;
                             }
-                            Err(e) => {
-                                last_error = e.to_string();
+                        }
+
+                        // Unwrap Result if stage returned one
+                        let output = match val {
+                            Value::Result { is_ok: true, value } => *value,
+                            Value::Result {
+                                is_ok: false,
+                                value,
+                            } => {
+                                last_error = value.display_string();
                                 if attempt + 1 < max_attempts {
                                     if let Some(ref rc) = retry_config {
                                         std::thread::sleep(crate::decorator::backoff_delay(
@@ -2397,72 +3223,89 @@ impl VM {
                                             attempt,
                                         ));
                                     }
+                                    continue;
                                 }
+                                break;
                             }
-                        }
-                    }
+                            other => other,
+                        };
 
-                    match stage_result {
-                        Some(output) => {
-                            let stage_duration = stage_start.elapsed().as_millis() as i64;
-                            // Emit pipeline:stage_complete
-                            (self.emit_handler)(
-                                "pipeline:stage_complete",
-                                &Value::Map(vec![
-                                    (
-                                        "pipeline".to_string(),
-                                        Value::String(pipeline_name.to_string()),
-                                    ),
-                                    ("stage".to_string(), Value::String(stage.name.clone())),
-                                    ("duration_ms".to_string(), Value::Int(stage_duration)),
-                                ]),
-                            );
-                            input = output;
-                        }
-                        None => {
-                            // Emit pipeline:error
-                            (self.emit_handler)(
-                                "pipeline:error",
-                                &Value::Map(vec![
-                                    (
-                                        "pipeline".to_string(),
-                                        Value::String(pipeline_name.to_string()),
-                                    ),
-                                    ("stage".to_string(), Value::String(stage.name.clone())),
-                                    ("error".to_string(), Value::String(last_error.clone())),
-                                ]),
-                            );
-                            return Ok(Value::Result {
-                                is_ok: false,
-                                value: Box::new(Value::String(format!(
-                                    "pipeline '{}' failed at stage '{}': {}",
-                                    pipeline_name, stage.name, last_error
-                                ))),
-                            });
+                        stage_result = Some(output);
+                        break;
+                    }
+                    // Running out of time ends the program, not just the stage
+                    Err(e @ RuntimeError::Timeout(_)) => return Err(e),
+                    Err(e) => {
+                        // A stage that throws fails like one that
+                        // returns Err: report the thrown value itself.
+                        last_error = match e {
+                            RuntimeError::UnhandledThrow(msg) => msg,
+                            other => other.to_string(),
+                        };
+                        if attempt + 1 < max_attempts {
+                            if let Some(ref rc) = retry_config {
+                                std::thread::sleep(crate::decorator::backoff_delay(
+                                    &rc.backoff,
+                                    attempt,
+                                ));
+                            }
                         }
                     }
                 }
+            }
 
-                let total_duration = pipeline_start.elapsed().as_millis() as i64;
-                // Emit pipeline:complete
-                (self.emit_handler)(
-                    "pipeline:complete",
-                    &Value::Map(vec![
-                        ("name".to_string(), Value::String(pipeline_name.to_string())),
-                        ("duration_ms".to_string(), Value::Int(total_duration)),
-                    ]),
-                );
-
-                Ok(Value::Result {
-                    is_ok: true,
-                    value: Box::new(input),
-                })
+            match stage_result {
+                Some(output) => {
+                    let stage_duration = stage_start.elapsed().as_millis() as i64;
+                    // Emit pipeline:stage_complete
+                    (self.emit_handler)(
+                        "pipeline:stage_complete",
+                        &Value::Map(vec![
+                            ("pipeline".into(), Value::String(pipeline_name.to_string())),
+                            ("path".into(), Value::String(path.to_string())),
+                            ("stage".into(), Value::String(stage.name.clone())),
+                            ("duration_ms".into(), Value::Int(stage_duration)),
+                        ]),
+                    );
+                    input = output;
+                }
+                None => {
+                    // Emit pipeline:error
+                    (self.emit_handler)(
+                        "pipeline:error",
+                        &Value::Map(vec![
+                            ("pipeline".into(), Value::String(pipeline_name.to_string())),
+                            ("path".into(), Value::String(path.to_string())),
+                            ("stage".into(), Value::String(stage.name.clone())),
+                            ("error".into(), Value::String(last_error.clone())),
+                        ]),
+                    );
+                    return Ok(Value::Result {
+                        is_ok: false,
+                        value: Box::new(Value::String(format!(
+                            "pipeline '{}' failed at stage '{}': {}",
+                            pipeline_name, stage.name, last_error
+                        ))),
+                    });
+                }
             }
-            _ => Err(RuntimeError::CallError(format!(
-                "unknown pipeline method: {}.{}",
-                pipeline_name, method
-            ))),
         }
+
+        let total_duration = pipeline_start.elapsed().as_millis() as i64;
+        // Emit pipeline:complete
+        (self.emit_handler)(
+            "pipeline:complete",
+            &Value::Map(vec![
+                ("name".into(), Value::String(pipeline_name.to_string())),
+                ("path".into(), Value::String(path.to_string())),
+                ("duration_ms".into(), Value::Int(total_duration)),
+            ]),
+        );
+
+        Ok(Value::Result {
+            is_ok: true,
+            value: Box::new(input),
+        })
     }
 
     // ========================================================================
@@ -2528,12 +3371,51 @@ impl VM {
             extra_tools: Vec::new(),
             exclude_default_tools: false,
             context: None,
+            retry: None,
+            timeout: None,
+            seed: None,
+            mock: None,
         };
         self.apply_builder_method(&mut builder, method, args)?;
         Ok(builder)
     }
 
-    /// Execute a prompt on an agent directly (without builder).
+    /// Run an agent's `execute`, forwarding intermediate token/progress
+    /// messages to the emit handler on `agent:token` / `agent:progress`.
+    fn execute_agent(
+        &mut self,
+        agent_name: &str,
+        prompt: &str,
+        context: Option<&Value>,
+    ) -> Result<String> {
+        let emit_handler = &self.emit_handler;
+        self.agent_registry
+            .execute_with_events(agent_name, prompt, context, &mut |event| {
+                let mut payload = event.clone();
+                let kind = payload
+                    .as_object_mut()
+                    .and_then(|fields| fields.shift_remove("type"))
+                    .and_then(|t| t.as_str().map(String::from))
+                    .unwrap_or_default();
+                payload["agent"] = serde_json::Value::String(agent_name.to_string());
+                emit_handler(
+                    &format!("agent:{}", kind),
+                    &SchemaValidator::json_to_value(&payload),
+                );
+            })
+    }
+
+    /// Canned agent reply for mock-all mode, shaped like `schema_name` when
+    /// the call asks for a schema.
+    fn mock_agent_response(&self, prompt: &str, schema_name: Option<&str>) -> String {
+        let schema = schema_name
+            .and_then(|name| self.module.schemas.get(name))
+            .map(|s| &s.json_schema);
+        crate::provider::mock_response_text(prompt, schema)
+    }
+
+    /// Execute a prompt on an agent directly (without builder). With a
+    /// `schema_name`, the reply is validated into that schema's struct.
     fn call_agent_execute(
         &mut self,
         agent_name: &str,
@@ -2543,7 +3425,11 @@ impl VM {
         let prompt = args.into_iter().next().unwrap_or(Value::Nil);
         let prompt_str = prompt.display_string();
 
-        let result_text = self.agent_registry.execute(agent_name, &prompt_str, None)?;
+        let result_text = if self.mock_all {
+            self.mock_agent_response(&prompt_str, schema_name)
+        } else {
+            self.execute_agent(agent_name, &prompt_str, None)?
+        };
 
         // If schema validation requested
         if let Some(sname) = schema_name {
@@ -2618,6 +3504,16 @@ impl VM {
             })?
             .clone();
 
+        if self.mock_all {
+            self.push(Value::Result {
+                is_ok: true,
+                value: Box::new(Value::String(crate::provider::mock_response_text(
+                    &prompt, None,
+                ))),
+            });
+            return Ok(());
+        }
+
         // Send prompt to agent
         self.agent_registry
             .get_client_mut(agent_name)?
@@ -2627,8 +3523,8 @@ impl VM {
         (self.emit_handler)(
             "listen:start",
             &Value::Map(vec![
-                ("agent".to_string(), Value::String(agent_name.to_string())),
-                ("listen".to_string(), Value::String(listen_name.to_string())),
+                ("agent".into(), Value::String(agent_name.to_string())),
+                ("listen".into(), Value::String(listen_name.to_string())),
             ]),
         );
 
@@ -2640,17 +3536,18 @@ impl VM {
             Ok(_) => {
                 (self.emit_handler)(
                     "listen:complete",
-                    &Value::Map(vec![
-                        ("agent".to_string(), Value::String(agent_name.to_string())),
-                    ]),
+                    &Value::Map(vec![(
+                        "agent".into(),
+                        Value::String(agent_name.to_string()),
+                    )]),
                 );
             }
             Err(e) => {
                 (self.emit_handler)(
                     "listen:error",
                     &Value::Map(vec![
-                        ("agent".to_string(), Value::String(agent_name.to_string())),
-                        ("error".to_string(), Value::String(e.to_string())),
+                        ("agent".into(), Value::String(agent_name.to_string())),
+                        ("error".into(), Value::String(e.to_string())),
                     ]),
                 );
             }
@@ -2703,7 +3600,7 @@ impl VM {
                     // Terminal: convert remaining fields to Value and return
                     let mut result_obj = msg.clone();
                     if let Some(obj) = result_obj.as_object_mut() {
-                        obj.remove("type");
+                        obj.shift_remove("type");
                     }
                     return Ok(SchemaValidator::json_to_value(&result_obj));
                 }
@@ -2728,7 +3625,7 @@ impl VM {
                         // Remove "type" field, convert rest to Value
                         let mut param_obj = msg.clone();
                         if let Some(obj) = param_obj.as_object_mut() {
-                            obj.remove("type");
+                            obj.shift_remove("type");
                         }
                         let param_value = SchemaValidator::json_to_value(&param_obj);
 
@@ -2746,10 +3643,32 @@ impl VM {
 
                         // If handler returned a non-Nil value, send response back to agent
                         if handler_result != Value::Nil {
+                            let value_json = handler_result.to_json();
+                            if let Some(schema_name) = &handler.response_schema {
+                                let schema =
+                                    self.module.schemas.get(schema_name).ok_or_else(|| {
+                                        RuntimeError::CallError(format!(
+                                            "Schema '{}' not found",
+                                            schema_name
+                                        ))
+                                    })?;
+                                if let Err(e) = SchemaValidator::validate_value(&value_json, schema)
+                                {
+                                    let detail = e.to_string();
+                                    return Err(RuntimeError::SchemaError(format!(
+                                        "listen handler '{}' response does not match schema '{}': {}",
+                                        handler.message_type,
+                                        schema_name,
+                                        detail
+                                            .strip_prefix("schema validation error: ")
+                                            .unwrap_or(&detail)
+                                    )));
+                                }
+                            }
                             let response_json = serde_json::json!({
                                 "type": "response",
                                 "in_reply_to": msg_type,
-                                "value": handler_result.to_json()
+                                "value": value_json
                             });
                             self.agent_registry
                                 .get_client_mut(agent_name)?
@@ -2787,6 +3706,10 @@ impl VM {
             extra_tools: Vec::new(),
             exclude_default_tools: false,
             context: None,
+            retry: None,
+            timeout: None,
+            seed: None,
+            mock: None,
         };
         self.apply_builder_method(&mut builder, method, args)?;
         Ok(builder)
@@ -2805,6 +3728,11 @@ impl VM {
             ref mut extra_tools,
             ref mut exclude_default_tools,
             ref mut context,
+            ref mut retry,
+            ref mut timeout,
+            ref mut seed,
+            ref mut mock,
+            ref source_kind,
             ..
         } = builder
         {
@@ -2859,6 +3787,48 @@ impl VM {
                         *context = Some(Box::new(val));
                     }
                 }
+                "with_retry" | "with_timeout" | "with_seed"
+                    if *source_kind == crate::value::BuilderSourceKind::Agent =>
+                {
+                    return Err(RuntimeError::TypeError(format!(
+                        "{}() is only supported on models",
+                        method
+                    )));
+                }
+                "with_retry" => match args.first() {
+                    Some(Value::Int(n)) if *n >= 1 => *retry = Some(*n as u32),
+                    _ => {
+                        return Err(RuntimeError::TypeError(
+                            "with_retry() requires a positive Int argument".into(),
+                        ))
+                    }
+                },
+                "with_timeout" => match args.first() {
+                    Some(Value::Int(n)) if *n >= 0 => *timeout = Some(*n as u64),
+                    _ => {
+                        return Err(RuntimeError::TypeError(
+                            "with_timeout() requires a non-negative Int argument".into(),
+                        ))
+                    }
+                },
+                "with_seed" => match args.first() {
+                    Some(Value::Int(n)) => *seed = Some(*n),
+                    _ => {
+                        return Err(RuntimeError::TypeError(
+                            "with_seed() requires an Int argument".into(),
+                        ))
+                    }
+                },
+                "mock" | "mock_error" => match args.first() {
+                    Some(Value::String(text)) if method == "mock" => *mock = Some(Ok(text.clone())),
+                    Some(Value::String(msg)) => *mock = Some(Err(msg.clone())),
+                    _ => {
+                        return Err(RuntimeError::TypeError(format!(
+                            "{}() requires a String argument",
+                            method
+                        )))
+                    }
+                },
                 _ => {
                     return Err(RuntimeError::TypeError(format!(
                         "unknown builder method '{}'",
@@ -2879,7 +3849,8 @@ impl VM {
         schema_name: Option<&str>,
     ) -> Result<Value> {
         match method {
-            "with_memory" | "with_tools" | "without_tools" | "with_context" => {
+            "with_memory" | "with_tools" | "without_tools" | "with_context" | "with_retry"
+            | "with_timeout" | "with_seed" | "mock" | "mock_error" => {
                 let mut new_builder = builder;
                 self.apply_builder_method(&mut new_builder, method, args)?;
                 Ok(new_builder)
@@ -2908,6 +3879,10 @@ impl VM {
             extra_tools,
             exclude_default_tools,
             context,
+            retry,
+            timeout,
+            seed,
+            mock,
         } = &builder
         {
             let source_name = source_name.clone();
@@ -2917,10 +3892,38 @@ impl VM {
             let extra_tools = extra_tools.clone();
             let exclude_default_tools = *exclude_default_tools;
             let context = context.clone();
+            let retry = *retry;
+            let timeout = *timeout;
+            let seed = *seed;
 
             let prompt = args.into_iter().next().unwrap_or(Value::Nil);
             let prompt_str = prompt.display_string();
 
+            // An inline mock answers this call only, ahead of any `mock`
+            // block. A mocked agent reply continues below like a real one.
+            let mock = mock.clone();
+            match (&mock, &source_kind) {
+                (Some(Err(msg)), _) => {
+                    return Ok(Value::Result {
+                        is_ok: false,
+                        value: Box::new(Value::String(msg.clone())),
+                    })
+                }
+                (Some(Ok(text)), crate::value::BuilderSourceKind::Model) => {
+                    let config = MockConfig {
+                        response: Some(text.clone()),
+                        error: None,
+                    };
+                    let method = if schema_name.is_some() {
+                        "execute_with_schema"
+                    } else {
+                        "execute"
+                    };
+                    return self.call_mock_model(&source_name, method, config, schema_name);
+                }
+                _ => {}
+            }
+
             // Check for mock override on model builders
             if matches!(source_kind, crate::value::BuilderSourceKind::Model) {
                 if let Some(mock) = self.mock_models.get(&source_name).cloned() {
@@ -2934,55 +3937,46 @@ impl VM {
             }
 
             // Dispatch based on source kind
-            let (result_text, response_meta) = match source_kind {
+            let result_text = match source_kind {
                 crate::value::BuilderSourceKind::Model => {
-                    let model_def = self
-                        .module
-                        .models
-                        .get(&source_name)
-                        .ok_or_else(|| {
-                            RuntimeError::NameError(format!("unknown model: {}", source_name))
-                        })?
-                        .clone();
-
-                    let response_format = schema_name.map(|schema| {
-                        let json_schema = self
-                            .module
-                            .schemas
-                            .get(schema)
-                            .map(|s| s.json_schema.clone())
-                            .unwrap_or(serde_json::json!({}));
-                        crate::provider::ResponseFormat {
-                            format_type: "json_schema".to_string(),
-                            json_schema: Some(json_schema),
-                        }
-                    });
-
-                    let request = self.build_chat_request_full(
-                        &model_def,
-                        &prompt_str,
-                        response_format,
-                        memory.as_deref(),
-                        &extra_tools,
+                    let method = if schema_name.is_some() {
+                        "execute_with_schema"
+                    } else {
+                        "execute"
+                    };
+                    let overrides = ModelCallOverrides {
+                        memory: memory.as_deref(),
+                        extra_tools: &extra_tools,
                         exclude_default_tools,
-                    );
-
-                    let provider = self.connection_manager.get_provider(&model_def.connection);
-                    let chat_response = provider.chat_completion(request)?;
-                    (
-                        chat_response.text,
-                        Some((
-                            chat_response.model,
-                            chat_response.tokens_in,
-                            chat_response.tokens_out,
-                        )),
-                    )
+                        context: context.as_deref(),
+                        retry,
+                        timeout,
+                        seed,
+                    };
+                    let (result, text) = self.run_model_call(
+                        &source_name,
+                        method,
+                        &prompt_str,
+                        schema_name,
+                        &overrides,
+                    )?;
+                    if memory_auto_append {
+                        if let (Some(mem_name), Some(text)) = (&memory, &text) {
+                            self.memory_store.append(mem_name, "user", &prompt_str)?;
+                            self.memory_store.append(mem_name, "assistant", text)?;
+                        }
+                    }
+                    return Ok(result);
+                }
+                crate::value::BuilderSourceKind::Agent if mock.is_some() => {
+                    mock.and_then(|m| m.ok()).unwrap_or_default()
+                }
+                crate::value::BuilderSourceKind::Agent if self.mock_all => {
+                    self.mock_agent_response(&prompt_str, schema_name)
+                }
+                crate::value::BuilderSourceKind::Agent => {
+                    self.execute_agent(&source_name, &prompt_str, context.as_deref())?
                 }
-                crate::value::BuilderSourceKind::Agent => (
-                    self.agent_registry
-                        .execute(&source_name, &prompt_str, context.as_deref())?,
-                    None,
-                ),
             };
 
             // Auto-append to memory if enabled
@@ -3014,28 +4008,12 @@ impl VM {
                         value: Box::new(Value::String(format!("unknown schema: {}", sname))),
                     })
                 }
-            } else if matches!(source_kind, crate::value::BuilderSourceKind::Agent) {
+            } else {
                 // Agents return Result<String>
                 Ok(Value::Result {
                     is_ok: true,
                     value: Box::new(Value::String(result_text)),
                 })
-            } else {
-                // Models return Response struct
-                let (model, tokens_in, tokens_out) =
-                    response_meta.unwrap_or_else(|| (String::new(), 0, 0));
-                let mut fields = std::collections::HashMap::new();
-                fields.insert("text".to_string(), Value::String(result_text));
-                fields.insert("model".to_string(), Value::String(model));
-                fields.insert("tokens_in".to_string(), Value::Int(tokens_in));
-                fields.insert("tokens_out".to_string(), Value::Int(tokens_out));
-                Ok(Value::Result {
-                    is_ok: true,
-                    value: Box::new(Value::Struct {
-                        type_name: "Response".to_string(),
-                        fields,
-                    }),
-                })
             }
         } else {
             Err(RuntimeError::TypeError("expected ModelBuilder".into()))
@@ -3086,7 +4064,9 @@ impl VM {
 
     fn call_string_method(s: &str, method: &str, args: Vec<Value>) -> Result<Value> {
         match method {
-            "len" => Ok(Value::Int(s.len() as i64)),
+            // Lengths count chars (Unicode scalar values), matching indexing
+            "len" => Ok(Value::Int(s.chars().count() as i64)),
+            "byte_len" => Ok(Value::Int(s.len() as i64)),
             "contains" => {
                 let substr = args
                     .into_iter()
@@ -3105,22 +4085,144 @@ impl VM {
         }
     }
 
+    /// Call a function value (named function or closure) synchronously and
+    /// return its result.
+    fn call_function_value(&mut self, callee: &Value, args: Vec<Value>) -> Result<Value> {
+        match callee {
+            Value::Function(name) if self.module.functions.contains_key(name) => {
+                self.resolve_thunk(name, args)
+            }
+            other => Err(RuntimeError::TypeError(format!(
+                "expected a function, got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    /// `arr.group_by(fn)`: bucket elements by the key `fn` computes for each.
+    /// Buckets appear in first-seen key order and keep input order inside.
+    fn array_group_by(&mut self, arr: Vec<Value>, args: Vec<Value>) -> Result<Value> {
+        let key_fn = match args.as_slice() {
+            [f] => f.clone(),
+            _ => {
+                return Err(RuntimeError::TypeError(
+                    "Array.group_by() requires a function argument".into(),
+                ))
+            }
+        };
+        let mut groups: Vec<(MapKey, Value)> = Vec::new();
+        for item in arr {
+            let key = MapKey::from_value(&self.call_function_value(&key_fn, vec![item.clone()])?)?;
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, Value::Array(bucket))) => bucket.push(item),
+                _ => groups.push((key, Value::Array(vec![item]))),
+            }
+        }
+        Ok(Value::Map(groups))
+    }
+
+    /// `f.arity()` (declared parameters, defaults included) and `f.name()`.
+    /// Closures are named `<closure>`; arity needs an IR function, so
+    /// built-ins report a CallError.
+    fn call_function_introspection(&self, fn_name: &str, method: &str) -> Result<Value> {
+        if method == "name" {
+            let name = if fn_name.starts_with("$closure_") {
+                "<closure>"
+            } else {
+                fn_name.strip_prefix("$builtin_").unwrap_or(fn_name)
+            };
+            return Ok(Value::String(name.to_string()));
+        }
+        match self.module.functions.get(fn_name) {
+            Some(func) => Ok(Value::Int(func.params.len() as i64)),
+            None => Err(RuntimeError::CallError(format!(
+                "cannot get the arity of built-in function '{}'",
+                fn_name.strip_prefix("$builtin_").unwrap_or(fn_name)
+            ))),
+        }
+    }
+
+    /// `[matching, non_matching]`, calling the predicate once per element
+    /// and keeping input order in both halves.
+    fn array_partition(&mut self, arr: Vec<Value>, args: Vec<Value>) -> Result<Value> {
+        let predicate = match args.as_slice() {
+            [f] => f.clone(),
+            _ => {
+                return Err(RuntimeError::TypeError(
+                    "Array.partition() requires a function argument".into(),
+                ))
+            }
+        };
+        let (mut matching, mut rest) = (Vec::new(), Vec::new());
+        for item in arr {
+            if self
+                .call_function_value(&predicate, vec![item.clone()])?
+                .is_truthy()
+            {
+                matching.push(item);
+            } else {
+                rest.push(item);
+            }
+        }
+        Ok(Value::Array(vec![
+            Value::Array(matching),
+            Value::Array(rest),
+        ]))
+    }
+
+    /// Call `fn` on each element and concatenate the arrays it returns.
+    fn array_flat_map(&mut self, arr: Vec<Value>, args: Vec<Value>) -> Result<Value> {
+        let mapper = match args.as_slice() {
+            [f] => f.clone(),
+            _ => {
+                return Err(RuntimeError::TypeError(
+                    "Array.flat_map() requires a function argument".into(),
+                ))
+            }
+        };
+        let mut flat = Vec::new();
+        for item in arr {
+            match self.call_function_value(&mapper, vec![item])? {
+                Value::Array(items) => flat.extend(items),
+                other => {
+                    return Err(RuntimeError::TypeError(format!(
+                        "Array.flat_map() callback must return an Array, got {}",
+                        other.type_name()
+                    )))
+                }
+            }
+        }
+        Ok(Value::Array(flat))
+    }
+
     fn call_array_method(arr: &[Value], method: &str, args: Vec<Value>) -> Result<Value> {
         match method {
             "len" => Ok(Value::Int(arr.len() as i64)),
             "is_empty" => Ok(Value::Bool(arr.is_empty())),
-            "get" => {
+            "first" => Ok(Value::Option(arr.first().cloned().map(Box::new))),
+            "last" => Ok(Value::Option(arr.last().cloned().map(Box::new))),
+            "enumerate" => Ok(Value::Array(
+                arr.iter()
+                    .enumerate()
+                    .map(|(i, v)| Value::Array(vec![Value::Int(i as i64), v.clone()]))
+                    .collect(),
+            )),
+            "get" | "nth" => {
                 let idx = match args.first() {
-                    Some(Value::Int(i)) => *i as usize,
+                    Some(Value::Int(i)) => *i,
                     _ => {
-                        return Err(RuntimeError::TypeError(
-                            "Array.get() requires an Int argument".into(),
-                        ))
+                        return Err(RuntimeError::TypeError(format!(
+                            "Array.{}() requires an Int argument",
+                            method
+                        )))
                     }
                 };
-                Ok(arr.get(idx).cloned().map_or(Value::Option(None), |v| {
-                    Value::Option(Some(Box::new(v)))
-                }))
+                // Negative indexes are out of range, not wrapped.
+                let item = usize::try_from(idx).ok().and_then(|i| arr.get(i));
+                Ok(Value::Option(item.cloned().map(Box::new)))
+            }
+            "sum" | "avg" | "min" | "max" if args.is_empty() => {
+                crate::stdlib::math::array_method(method, arr)
             }
             _ => Err(RuntimeError::TypeError(format!(
                 "no method '{}' on Array",
@@ -3195,6 +4297,7 @@ mod tests {
                 params: vec![],
                 return_type: serde_json::json!("nil"),
                 is_async: false,
+                decorators: vec![],
                 locals: vec![],
                 instructions,
             }],
@@ -3289,6 +4392,64 @@ mod tests {
         assert_eq!(result, Value::Int(8));
     }
 
+    #[test]
+    fn timeout_stops_a_run_that_never_ends() {
+        let module = make_module(vec![IrInstruction {
+            offset: Some(0),
+            ..inst(Opcode::Jump)
+        }]);
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        vm.set_timeout(Duration::from_millis(50));
+
+        let err = vm.execute().unwrap_err();
+        assert!(matches!(err, RuntimeError::Timeout(d) if d == Duration::from_millis(50)));
+        assert_eq!(err.to_string(), "timeout: run exceeded 50ms");
+    }
+
+    #[test]
+    fn timeout_applies_per_run_on_a_reused_vm() {
+        let mut module = make_module(vec![IrInstruction {
+            offset: Some(0),
+            ..inst(Opcode::Jump)
+        }]);
+        module.functions.push(IrFunction {
+            name: "quick".to_string(),
+            module: "test".to_string(),
+            visibility: "private".to_string(),
+            params: vec![],
+            return_type: serde_json::json!("Int"),
+            is_async: false,
+            locals: vec![],
+            instructions: vec![
+                IrInstruction {
+                    arg: Some(serde_json::json!(0)),
+                    ..inst(Opcode::LoadConst)
+                },
+                inst(Opcode::Return),
+            ],
+            decorators: vec![],
+        });
+        module.constants = vec![IrConstant {
+            index: 0,
+            const_type: "int".to_string(),
+            value: serde_json::json!(7),
+        }];
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        vm.set_timeout(Duration::from_millis(50));
+
+        // The timed-out run does not poison the next one.
+        assert!(matches!(vm.execute(), Err(RuntimeError::Timeout(_))));
+        assert_eq!(vm.execute_function("quick", vec![]).unwrap(), Value::Int(7));
+
+        // A deadline from an earlier, finished run never fires later.
+        vm.set_timeout(Duration::from_millis(30));
+        assert_eq!(vm.execute_function("quick", vec![]).unwrap(), Value::Int(7));
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(vm.execute_function("quick", vec![]).unwrap(), Value::Int(7));
+    }
+
     #[test]
     fn store_and_load_local() {
         let mut module = make_module(vec![
@@ -3423,6 +4584,7 @@ mod tests {
                     params: vec![],
                     return_type: serde_json::json!("nil"),
                     is_async: false,
+                    decorators: vec![],
                     locals: vec![],
                     instructions: vec![
                         inst_const(0),    // push 3
@@ -3444,14 +4606,17 @@ mod tests {
                         IrParam {
                             name: "a".to_string(),
                             param_type: serde_json::json!("Int"),
+                            default: None,
                         },
                         IrParam {
                             name: "b".to_string(),
                             param_type: serde_json::json!("Int"),
+                            default: None,
                         },
                     ],
                     return_type: serde_json::json!("Int"),
                     is_async: false,
+                    decorators: vec![],
                     locals: vec!["a".to_string(), "b".to_string()],
                     instructions: vec![
                         inst_load("a"),
@@ -3611,8 +4776,8 @@ mod tests {
         match result {
             Value::Map(pairs) => {
                 assert_eq!(pairs.len(), 2);
-                assert_eq!(pairs[0], ("a".to_string(), Value::Int(1)));
-                assert_eq!(pairs[1], ("b".to_string(), Value::Int(2)));
+                assert_eq!(pairs[0], ("a".into(), Value::Int(1)));
+                assert_eq!(pairs[1], ("b".into(), Value::Int(2)));
             }
             _ => panic!("expected Map, got {:?}", result),
         }
@@ -3938,6 +5103,7 @@ mod tests {
                     params: vec![],
                     return_type: serde_json::json!("nil"),
                     is_async: false,
+                    decorators: vec![],
                     locals: vec![],
                     instructions: vec![
                         // 0: TRY_BEGIN offset=5
@@ -3977,6 +5143,7 @@ mod tests {
                     params: vec![],
                     return_type: serde_json::json!("nil"),
                     is_async: false,
+                    decorators: vec![],
                     locals: vec![],
                     instructions: vec![
                         inst_const(0),       // 0: push "boom"
@@ -4119,7 +5286,7 @@ mod tests {
         match result {
             Value::Map(pairs) => {
                 assert_eq!(pairs.len(), 1);
-                assert_eq!(pairs[0], ("a".to_string(), Value::Int(99)));
+                assert_eq!(pairs[0], ("a".into(), Value::Int(99)));
             }
             _ => panic!("expected Map"),
         }
@@ -4261,6 +5428,9 @@ mod tests {
                 max_tokens: Some(256),
                 system_prompt: Some("System prompt".to_string()),
                 timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
             },
             tools: vec![],
             memory: None,
@@ -4283,8 +5453,11 @@ mod tests {
             .unwrap();
 
         let model_def = vm.module.models.get("Assistant").unwrap().clone();
-        let request =
-            vm.build_chat_request_full(&model_def, "Current question", None, Some("conv"), &[], false);
+        let overrides = ModelCallOverrides {
+            memory: Some("conv"),
+            ..Default::default()
+        };
+        let request = vm.build_chat_request_full(&model_def, "Current question", None, &overrides);
 
         assert_eq!(request.messages.len(), 4);
         assert_eq!(request.messages[0].role, "system");
@@ -4297,6 +5470,70 @@ mod tests {
         assert_eq!(request.messages[3].content, "Current question");
     }
 
+    #[test]
+    fn reload_connections_picks_up_new_default_model() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Drafter".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: None,
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![],
+            methods: vec![],
+        }];
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let model_def = vm.module.models.get("Drafter").unwrap().clone();
+        assert_eq!(vm.build_chat_request(&model_def, "hi", None).model, "gpt-4");
+
+        let dir = std::env::temp_dir().join(format!("concerto-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Concerto.toml");
+        let write_manifest = |model: &str| {
+            let toml = format!(
+                "[project]\nname = \"reload\"\nversion = \"0.1.0\"\nentry = \"main.conc\"\n\n\
+                 [connections.local]\nprovider = \"ollama\"\ndefault_model = \"{}\"\n",
+                model
+            );
+            std::fs::write(&path, toml).unwrap();
+        };
+
+        write_manifest("llama3.1");
+        vm.reload_connections(&path).unwrap();
+        assert_eq!(
+            vm.build_chat_request(&model_def, "hi", None).model,
+            "llama3.1"
+        );
+
+        write_manifest("qwen2.5");
+        vm.reload_connections(&path).unwrap();
+        assert_eq!(
+            vm.build_chat_request(&model_def, "hi", None).model,
+            "qwen2.5"
+        );
+
+        std::fs::write(&path, "not = [valid").unwrap();
+        let err = vm.reload_connections(&path).unwrap_err();
+        assert!(matches!(err, RuntimeError::LoadError(_)));
+        assert_eq!(
+            vm.build_chat_request(&model_def, "hi", None).model,
+            "qwen2.5"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn build_chat_request_merges_and_deduplicates_static_and_dynamic_tools() {
         let mut module = make_module(vec![inst(Opcode::Return)]);
@@ -4310,6 +5547,9 @@ mod tests {
                 max_tokens: None,
                 system_prompt: None,
                 timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
             },
             tools: vec!["Calculator".to_string()],
             memory: None,
@@ -4320,6 +5560,7 @@ mod tests {
             IrTool {
                 name: "Calculator".to_string(),
                 module: "test".to_string(),
+                state: Default::default(),
                 methods: vec![],
                 tool_schemas: vec![ToolSchemaEntry {
                     method_name: "Calculator::add".to_string(),
@@ -4337,6 +5578,7 @@ mod tests {
             IrTool {
                 name: "Formatter".to_string(),
                 module: "test".to_string(),
+                state: Default::default(),
                 methods: vec![],
                 tool_schemas: vec![ToolSchemaEntry {
                     method_name: "Formatter::up".to_string(),
@@ -4357,7 +5599,11 @@ mod tests {
 
         let model_def = vm.module.models.get("Worker").unwrap().clone();
         let extra_tools = vec!["Calculator".to_string(), "Formatter".to_string()];
-        let request = vm.build_chat_request_full(&model_def, "prompt", None, None, &extra_tools, false);
+        let overrides = ModelCallOverrides {
+            extra_tools: &extra_tools,
+            ..Default::default()
+        };
+        let request = vm.build_chat_request_full(&model_def, "prompt", None, &overrides);
 
         let mut tool_names: Vec<String> = request
             .tools
@@ -4383,6 +5629,9 @@ mod tests {
                 max_tokens: None,
                 system_prompt: None,
                 timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
             },
             tools: vec!["Calculator".to_string()],
             memory: None,
@@ -4393,6 +5642,7 @@ mod tests {
             IrTool {
                 name: "Calculator".to_string(),
                 module: "test".to_string(),
+                state: Default::default(),
                 methods: vec![],
                 tool_schemas: vec![ToolSchemaEntry {
                     method_name: "Calculator::add".to_string(),
@@ -4403,6 +5653,7 @@ mod tests {
             IrTool {
                 name: "Formatter".to_string(),
                 module: "test".to_string(),
+                state: Default::default(),
                 methods: vec![],
                 tool_schemas: vec![ToolSchemaEntry {
                     method_name: "Formatter::up".to_string(),
@@ -4417,7 +5668,12 @@ mod tests {
 
         let model_def = vm.module.models.get("Worker").unwrap().clone();
         let extra_tools = vec!["Formatter".to_string()];
-        let request = vm.build_chat_request_full(&model_def, "prompt", None, None, &extra_tools, true);
+        let overrides = ModelCallOverrides {
+            extra_tools: &extra_tools,
+            exclude_default_tools: true,
+            ..Default::default()
+        };
+        let request = vm.build_chat_request_full(&model_def, "prompt", None, &overrides);
 
         let tool_names: Vec<String> = request
             .tools
@@ -4429,12 +5685,688 @@ mod tests {
         assert_eq!(tool_names, vec!["Formatter::up"]);
     }
 
+    #[test]
+    fn log_decorator_emits_request_before_completion() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Assistant".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: Some("llama3".to_string()),
+                temperature: Some(0.3),
+                max_tokens: None,
+                system_prompt: Some("Be brief.".to_string()),
+                timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![IrDecorator {
+                name: "log".to_string(),
+                args: None,
+            }],
+            methods: vec![],
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        vm.set_emit_handler(move |channel, payload| {
+            events_clone
+                .lock()
+                .unwrap()
+                .push((channel.to_string(), payload.clone()));
+        });
+
+        vm.call_model_method(
+            "Assistant",
+            "execute",
+            vec![Value::String("hi".to_string())],
+            None,
+        )
+        .unwrap();
+
+        let events = events.lock().unwrap();
+        let channels: Vec<&str> = events.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(channels, vec!["model:request", "model:log"]);
+        let Value::Map(fields) = &events[0].1 else {
+            panic!("expected map payload");
+        };
+        let field = |name: &str| fields.iter().find(|(k, _)| k == name).map(|(_, v)| v);
+        assert_eq!(
+            field("model"),
+            Some(&Value::String("Assistant".to_string()))
+        );
+        assert_eq!(field("message_count"), Some(&Value::Int(2)));
+        assert_eq!(field("temperature"), Some(&Value::Float(0.3)));
+    }
+
+    /// Provider that never returns valid JSON and counts its calls.
+    struct InvalidJsonProvider {
+        calls: Arc<Mutex<usize>>,
+    }
+
+    impl crate::provider::LlmProvider for InvalidJsonProvider {
+        fn chat_completion(&self, _request: ChatRequest) -> Result<crate::provider::ChatResponse> {
+            *self.calls.lock().unwrap() += 1;
+            Ok(crate::provider::ChatResponse {
+                text: "not json".to_string(),
+                tokens_in: 0,
+                tokens_out: 0,
+                model: "mock".to_string(),
+                tool_calls: vec![],
+            })
+        }
+    }
+
+    #[test]
+    fn schema_retries_caps_self_correction_attempts() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Cheap".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: None,
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: Some(2),
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![],
+            methods: vec![],
+        }];
+        module.schemas = vec![concerto_common::ir::IrSchema {
+            name: "Answer".to_string(),
+            json_schema: serde_json::json!({
+                "type": "object",
+                "properties": { "text": { "type": "String" } },
+                "required": ["text"],
+            }),
+            validation_mode: "strict".to_string(),
+            aliases: Default::default(),
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let calls = Arc::new(Mutex::new(0));
+        vm.connection_manager.set_provider(
+            "local",
+            Box::new(InvalidJsonProvider {
+                calls: calls.clone(),
+            }),
+        );
+
+        let result = vm
+            .call_model_method(
+                "Cheap",
+                "execute_with_schema",
+                vec![Value::String("hi".to_string())],
+                Some("Answer"),
+            )
+            .unwrap();
+
+        assert!(matches!(result, Value::Result { is_ok: false, .. }));
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn model_builder_execute_with_schema_shares_self_correction() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Cheap".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: None,
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: Some(2),
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![IrDecorator {
+                name: "retry".to_string(),
+                args: Some(serde_json::json!([{"max": 1, "backoff": "none"}])),
+            }],
+            methods: vec![],
+        }];
+        module.schemas = vec![concerto_common::ir::IrSchema {
+            name: "Answer".to_string(),
+            json_schema: serde_json::json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"],
+            }),
+            validation_mode: "strict".to_string(),
+            aliases: Default::default(),
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let calls = Arc::new(Mutex::new(0));
+        vm.connection_manager.set_provider(
+            "local",
+            Box::new(InvalidJsonProvider {
+                calls: calls.clone(),
+            }),
+        );
+
+        let plain = vm
+            .call_model_method(
+                "Cheap",
+                "execute_with_schema",
+                vec![Value::String("hi".to_string())],
+                Some("Answer"),
+            )
+            .unwrap();
+        assert_eq!(*calls.lock().unwrap(), 2);
+
+        // with_retry(2) runs two outer attempts of `schema_retries` requests each
+        let builder = vm
+            .model_ref_to_builder("Cheap", "with_retry", vec![Value::Int(2)])
+            .unwrap();
+        let built = vm
+            .call_model_builder_method(
+                builder,
+                "execute_with_schema",
+                vec![Value::String("hi".to_string())],
+                Some("Answer"),
+            )
+            .unwrap();
+        assert_eq!(*calls.lock().unwrap(), 6);
+
+        for result in [plain, built] {
+            let Value::Result {
+                is_ok: false,
+                value,
+            } = result
+            else {
+                panic!("expected Err result, got {:?}", result);
+            };
+            assert!(
+                matches!(&*value, Value::Struct { type_name, .. } if type_name == "SchemaError"),
+                "expected SchemaError, got {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn mock_all_bypasses_providers_and_agents() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Cheap".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: None,
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![],
+            methods: vec![],
+        }];
+        module.agents = vec![IrAgent {
+            name: "Worker".to_string(),
+            connector: "test".to_string(),
+            input_format: "text".to_string(),
+            output_format: "json".to_string(),
+            timeout: Some(5),
+            decorators: vec![],
+            command: Some("concerto-no-such-agent".to_string()),
+            args: None,
+            env: None,
+            working_dir: None,
+            params: None,
+        }];
+        module.schemas = vec![concerto_common::ir::IrSchema {
+            name: "Answer".to_string(),
+            json_schema: serde_json::json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"],
+            }),
+            validation_mode: "strict".to_string(),
+            aliases: Default::default(),
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let calls = Arc::new(Mutex::new(0));
+        vm.connection_manager.set_provider(
+            "local",
+            Box::new(InvalidJsonProvider {
+                calls: calls.clone(),
+            }),
+        );
+        vm.set_mock_all(true);
+
+        let result = vm
+            .call_model_method(
+                "Cheap",
+                "execute",
+                vec![Value::String("hi".to_string())],
+                None,
+            )
+            .unwrap();
+        let Value::Result { is_ok: true, value } = result else {
+            panic!("expected Ok, got {:?}", result);
+        };
+        assert_eq!(
+            value.as_struct_field("text"),
+            Some(&Value::String("[mock response to: hi]".to_string()))
+        );
+        assert_eq!(*calls.lock().unwrap(), 0);
+
+        // The agent command does not exist; the schema still shapes the reply.
+        let result = vm
+            .call_agent_execute(
+                "Worker",
+                vec![Value::String("go".to_string())],
+                Some("Answer"),
+            )
+            .unwrap();
+        let Value::Result { is_ok: true, value } = result else {
+            panic!("expected Ok, got {:?}", result);
+        };
+        assert_eq!(
+            value.as_struct_field("text"),
+            Some(&Value::String("[mock text]".to_string()))
+        );
+    }
+
+    #[test]
+    fn model_seed_and_with_seed_reach_the_request() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        let mut model = test_model("Grader");
+        model.config.seed = Some(42);
+        module.models = vec![model];
+        let (mut vm, requests) = recording_vm(module, "local");
+
+        vm.call_model_method("Grader", "execute", vec![Value::String("a".into())], None)
+            .unwrap();
+        let builder = vm
+            .model_ref_to_builder("Grader", "with_seed", vec![Value::Int(7)])
+            .unwrap();
+        vm.call_model_builder_method(builder, "execute", vec![Value::String("b".into())], None)
+            .unwrap();
+
+        let seeds: Vec<Option<i64>> = requests.lock().unwrap().iter().map(|r| r.seed).collect();
+        assert_eq!(seeds, vec![Some(42), Some(7)]);
+    }
+
+    #[test]
+    fn model_builder_context_is_sent_as_system_message() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        let mut model = test_model("Support");
+        model.config.system_prompt = Some("Be helpful".to_string());
+        module.models = vec![model];
+        let (mut vm, requests) = recording_vm(module, "local");
+
+        let context = Value::Map(vec![
+            ("user".into(), Value::String("ada".into())),
+            ("tier".into(), Value::Int(2)),
+        ]);
+        let builder = vm
+            .model_ref_to_builder("Support", "with_context", vec![context])
+            .unwrap();
+        vm.call_model_builder_method(builder, "execute", vec![Value::String("hi".into())], None)
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let sent = &requests[0].messages;
+        let roles: Vec<&str> = sent.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "system", "user"]);
+        assert_eq!(sent[0].content, "Be helpful");
+        assert_eq!(
+            sent[1].content,
+            r#"Context:
+{"user":"ada","tier":2}"#
+        );
+        assert_eq!(sent[2].content, "hi");
+    }
+
+    /// Provider that takes a measurable amount of time to answer.
+    struct SlowProvider;
+
+    impl crate::provider::LlmProvider for SlowProvider {
+        fn chat_completion(&self, request: ChatRequest) -> Result<crate::provider::ChatResponse> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            crate::provider::MockProvider.chat_completion(request)
+        }
+    }
+
+    #[test]
+    fn model_calls_report_latency() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Assistant".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: Some("llama3".to_string()),
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![IrDecorator {
+                name: "log".to_string(),
+                args: None,
+            }],
+            methods: vec![],
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        vm.connection_manager
+            .set_provider("local", Box::new(SlowProvider));
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let logged_clone = logged.clone();
+        vm.set_emit_handler(move |channel, payload| {
+            if channel == "model:log" {
+                logged_clone.lock().unwrap().push(payload.clone());
+            }
+        });
+
+        let result = vm
+            .call_model_method(
+                "Assistant",
+                "execute",
+                vec![Value::String("hi".to_string())],
+                None,
+            )
+            .unwrap();
+
+        let Value::Result { is_ok: true, value } = result else {
+            panic!("expected Ok response");
+        };
+        let Value::Struct { fields, .. } = *value else {
+            panic!("expected Response struct");
+        };
+        let Some(Value::Int(response_latency)) = fields.get("latency_ms") else {
+            panic!("Response missing latency_ms");
+        };
+        assert!(*response_latency > 0);
+
+        let logged = logged.lock().unwrap();
+        let Value::Map(log_fields) = &logged[0] else {
+            panic!("expected map payload");
+        };
+        let log_latency = log_fields
+            .iter()
+            .find(|(k, _)| k == "latency_ms")
+            .map(|(_, v)| v);
+        assert_eq!(log_latency, Some(&Value::Int(*response_latency)));
+    }
+
+    /// Provider that never answers within any reasonable deadline.
+    struct HangingProvider;
+
+    impl crate::provider::LlmProvider for HangingProvider {
+        fn chat_completion(&self, request: ChatRequest) -> Result<crate::provider::ChatResponse> {
+            std::thread::sleep(std::time::Duration::from_secs(30));
+            crate::provider::MockProvider.chat_completion(request)
+        }
+    }
+
+    #[test]
+    fn model_timeout_interrupts_a_hung_provider() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.models = vec![IrModel {
+            name: "Assistant".to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: Some("llama3".to_string()),
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![IrDecorator {
+                name: "timeout".to_string(),
+                args: Some(serde_json::json!([{"seconds": 1}])),
+            }],
+            methods: vec![],
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        vm.connection_manager
+            .set_provider("local", Box::new(HangingProvider));
+
+        let started = std::time::Instant::now();
+        let result = vm
+            .call_model_method(
+                "Assistant",
+                "execute",
+                vec![Value::String("hi".to_string())],
+                None,
+            )
+            .unwrap();
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let Value::Result {
+            is_ok: false,
+            value,
+        } = result
+        else {
+            panic!("expected Err result, got {:?}", result);
+        };
+        assert!(
+            value.to_string().contains("timeout exceeded"),
+            "unexpected error: {}",
+            value
+        );
+    }
+
+    #[test]
+    fn agent_execute_forwards_progress_to_emit_handler() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.agents = vec![IrAgent {
+            name: "Worker".to_string(),
+            connector: "test".to_string(),
+            input_format: "text".to_string(),
+            output_format: "json".to_string(),
+            timeout: Some(5),
+            decorators: vec![],
+            command: Some("bash".to_string()),
+            args: Some(vec![
+                "-c".to_string(),
+                r#"read prompt; echo '{"type":"progress","message":"step 1"}'; echo '{"type":"progress","message":"step 2"}'; echo '{"type":"result","text":"finished"}'"#
+                    .to_string(),
+            ]),
+            env: None,
+            working_dir: None,
+            params: None,
+        }];
+
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        vm.set_emit_handler(move |channel, payload| {
+            events_clone
+                .lock()
+                .unwrap()
+                .push((channel.to_string(), payload.clone()));
+        });
+
+        let result = vm
+            .call_agent_execute("Worker", vec![Value::String("go".to_string())], None)
+            .unwrap();
+        assert_eq!(
+            result,
+            Value::Result {
+                is_ok: true,
+                value: Box::new(Value::String("finished".to_string())),
+            }
+        );
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        for ((channel, payload), step) in events.iter().zip(["step 1", "step 2"]) {
+            assert_eq!(channel, "agent:progress");
+            let Value::Map(fields) = payload else {
+                panic!("expected map payload");
+            };
+            assert!(fields.contains(&("agent".into(), Value::String("Worker".to_string()))));
+            assert!(fields.contains(&("message".into(), Value::String(step.to_string()))));
+        }
+    }
+
+    /// Provider that records every request it receives and answers like
+    /// `MockProvider`.
+    struct RecordingProvider {
+        requests: Arc<Mutex<Vec<ChatRequest>>>,
+    }
+
+    impl crate::provider::LlmProvider for RecordingProvider {
+        fn chat_completion(&self, request: ChatRequest) -> Result<crate::provider::ChatResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            crate::provider::MockProvider.chat_completion(request)
+        }
+    }
+
+    /// A model on the `local` connection with an empty config.
+    fn test_model(name: &str) -> IrModel {
+        IrModel {
+            name: name.to_string(),
+            module: "test".to_string(),
+            connection: "local".to_string(),
+            config: IrModelConfig {
+                base: None,
+                temperature: None,
+                max_tokens: None,
+                system_prompt: None,
+                timeout: None,
+                schema_retries: None,
+                base_url: None,
+                seed: None,
+            },
+            tools: vec![],
+            memory: None,
+            decorators: vec![],
+            methods: vec![],
+        }
+    }
+
+    /// Loads `module` into a VM whose `connection` is answered by a
+    /// `RecordingProvider`, returning the VM and the recorded requests.
+    fn recording_vm(module: IrModule, connection: &str) -> (VM, Arc<Mutex<Vec<ChatRequest>>>) {
+        let loaded = LoadedModule::from_ir(module).unwrap();
+        let mut vm = VM::new(loaded);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        vm.connection_manager.set_provider(
+            connection,
+            Box::new(RecordingProvider {
+                requests: requests.clone(),
+            }),
+        );
+        (vm, requests)
+    }
+
+    #[test]
+    fn model_base_url_is_sent_with_its_requests() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        let mut routed = test_model("Routed");
+        routed.config.base_url = Some("http://127.0.0.1:8000".to_string());
+        module.models = vec![routed, test_model("Plain")];
+        let (vm, requests) = recording_vm(module, "local");
+
+        for name in ["Routed", "Plain"] {
+            let result = vm
+                .call_model_method(name, "execute", vec![Value::String("hi".into())], None)
+                .unwrap();
+            assert!(matches!(result, Value::Result { is_ok: true, .. }));
+        }
+        let base_urls: Vec<Option<String>> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.base_url.clone())
+            .collect();
+        assert_eq!(
+            base_urls,
+            vec![Some("http://127.0.0.1:8000".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn connection_headers_are_sent_with_model_requests() {
+        let mut module = make_module(vec![inst(Opcode::Return)]);
+        module.connections = vec![IrConnection {
+            name: "gateway".to_string(),
+            config: serde_json::json!({
+                "provider": "openai",
+                "organization": "org-123",
+                "headers": { "X-Gateway-Key": "abc" },
+            }),
+        }];
+        let mut model = test_model("Assistant");
+        model.connection = "gateway".to_string();
+        module.models = vec![model];
+        let (vm, requests) = recording_vm(module, "gateway");
+
+        let result = vm
+            .call_model_method(
+                "Assistant",
+                "execute",
+                vec![Value::String("hi".to_string())],
+                None,
+            )
+            .unwrap();
+        assert!(matches!(result, Value::Result { is_ok: true, .. }));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[0].headers,
+            vec![
+                ("OpenAI-Organization".to_string(), "org-123".to_string()),
+                ("X-Gateway-Key".to_string(), "abc".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn vm_registers_tool_refs_for_with_tools_arrays() {
         let mut module = make_module(vec![inst(Opcode::Return)]);
         module.tools = vec![IrTool {
             name: "Calculator".to_string(),
             module: "test".to_string(),
+            state: Default::default(),
             methods: vec![],
             tool_schemas: vec![],
         }];
@@ -4464,6 +6396,7 @@ mod tests {
             params: vec![],
             return_type: serde_json::json!("Int"),
             is_async: false,
+            decorators: vec![],
             locals: vec![],
             instructions: vec![inst_const(1), inst(Opcode::Return)],
         });
@@ -4513,6 +6446,7 @@ mod tests {
             params: vec![],
             return_type: serde_json::json!("Int"),
             is_async: false,
+            decorators: vec![],
             locals: vec![],
             instructions: vec![inst_const(0), inst(Opcode::Return)],
         });
@@ -4570,6 +6504,7 @@ mod tests {
             params: vec![],
             return_type: serde_json::json!("Int"),
             is_async: false,
+            decorators: vec![],
             locals: vec![],
             instructions: vec![inst_const(0), inst(Opcode::Return)],
         });
@@ -114,6 +114,300 @@ fn e2e_string_ops() {
     assert_eq!(emits[0].1, "Hello, world!");
 }
 
+#[test]
+fn e2e_bitwise_operators() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let flags = 12;
+            emit("and", flags & 10);
+            emit("or", flags | 3);
+            emit("xor", flags ^ 10);
+            emit("not", ~flags);
+            emit("shl", 1 << 4);
+            emit("shr", -16 >> 2);
+            emit("mixed", 1 | 2 ^ 6 & 3 << 1);
+            let amount = 64;
+            try {
+                emit("overflow", 1 << amount);
+            } catch {
+                emit("overflow", "caught");
+            }
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "8");
+    assert_eq!(emits[1].1, "15");
+    assert_eq!(emits[2].1, "6");
+    assert_eq!(emits[3].1, "-13");
+    assert_eq!(emits[4].1, "16");
+    assert_eq!(emits[5].1, "-4");
+    // 1 | (2 ^ (6 & (3 << 1))) = 1 | (2 ^ 6) = 5
+    assert_eq!(emits[6].1, "5");
+    assert_eq!(emits[7].1, "caught");
+}
+
+#[test]
+fn e2e_array_group_by() {
+    let (_, emits) = run_program(
+        r#"
+        fn word_length(word: String) -> Int {
+            return word.len();
+        }
+
+        fn main() {
+            let nums = [1, 2, 3, 4, 5, 6];
+            let parity = nums.group_by(|n: Int| if n % 2 == 0 { "even" } else { "odd" });
+            emit("parity", parity);
+            emit("buckets", len(parity));
+
+            let words = ["kiwi", "apple", "pear", "mango"];
+            emit("lengths", words.group_by(word_length));
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, r#"{"odd": [1, 3, 5], "even": [2, 4, 6]}"#);
+    assert_eq!(emits[1].1, "2");
+    // Non-string keys keep their type
+    assert_eq!(emits[2].1, "{4: [kiwi, pear], 5: [apple, mango]}");
+}
+
+#[test]
+fn e2e_array_partition() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let nums = [5, 2, 8, 1, 4, 7];
+            let halves = nums.partition(|n: Int| n % 2 == 0);
+            emit("evens", halves[0]);
+            emit("odds", halves[1]);
+
+            let none = [1, 3].partition(|n: Int| n > 10);
+            emit("none", none);
+            emit("empty", [].partition(|n: Int| true));
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "[2, 8, 4]");
+    assert_eq!(emits[1].1, "[5, 1, 7]");
+    assert_eq!(emits[2].1, "[[], [1, 3]]");
+    assert_eq!(emits[3].1, "[[], []]");
+}
+
+#[test]
+fn e2e_match_binding_patterns() {
+    let (_, emits) = run_program(
+        r#"
+        fn classify(n: Int) -> String {
+            match n {
+                x @ 1..=10 => "small ${x * 10}",
+                y @ (20 | 30) => "round ${y}",
+                z @ 11..100 => "medium ${z}",
+                _ => "other",
+            }
+        }
+
+        fn main() {
+            for n in [1, 10, 30, 99, 100, -3] {
+                emit("class", classify(n));
+            }
+            match Some(4) {
+                Some(v @ 1..=5) => emit("inner", v),
+                whole @ Some(_) => emit("inner", whole),
+                None => emit("inner", "none"),
+            }
+        }
+        "#,
+    );
+    let got: Vec<&str> = emits.iter().map(|(_, v)| v.as_str()).collect();
+    assert_eq!(
+        got,
+        vec![
+            "small 10",
+            "small 100",
+            "round 30",
+            "medium 99",
+            "other",
+            "other",
+            "4"
+        ]
+    );
+}
+
+#[test]
+fn e2e_array_numeric_aggregates() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let scores = [7, 3, 9, 5];
+            emit("sum", scores.sum());
+            emit("avg", scores.avg());
+            emit("min", scores.min());
+            emit("max", scores.max());
+            emit("mixed", [1, 2.5].sum());
+            emit("chained", [1, 2, 3].flat_map(|n: Int| [n, n * 10]).max());
+            emit("empty_sum", [].sum());
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "24");
+    assert_eq!(emits[1].1, "6");
+    assert_eq!(emits[2].1, "3");
+    assert_eq!(emits[3].1, "9");
+    assert_eq!(emits[4].1, "3.5");
+    assert_eq!(emits[5].1, "30");
+    assert_eq!(emits[6].1, "0");
+
+    for method in ["min", "max", "avg"] {
+        let err = run_program_err(&format!("fn main() {{ [].{}(); }}", method));
+        assert!(
+            err.contains(&format!("Array.{}() of an empty array", method)),
+            "unexpected error: {}",
+            err
+        );
+    }
+    let err = run_program_err(r#"fn main() { [1, "two"].sum(); }"#);
+    assert!(
+        err.contains("Array.sum() expected array of numbers, found String"),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+fn e2e_array_flat_map() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            emit("doubled", [1, 2, 3].flat_map(|n: Int| [n, n]));
+            emit("words", ["a b", "c"].flat_map(|s: String| std::string::split(s, " ")));
+            emit("empty", [].flat_map(|n: Int| [n]));
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "[1, 1, 2, 2, 3, 3]");
+    assert_eq!(emits[1].1, "[a, b, c]");
+    assert_eq!(emits[2].1, "[]");
+
+    let err = run_program_err(
+        r#"
+        fn main() {
+            [1, 2].flat_map(|n: Int| n * 2);
+        }
+        "#,
+    );
+    assert!(
+        err.contains("flat_map() callback must return an Array, got Int"),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+fn e2e_function_arity_and_name() {
+    let (_, emits) = run_program(
+        r#"
+        fn add(a: Int, b: Int) -> Int {
+            return a + b;
+        }
+
+        fn greet(name: String, punct: String = "!") -> String {
+            return name + punct;
+        }
+
+        fn main() {
+            let f = add;
+            emit("arity", f.arity());
+            emit("name", f.name());
+            emit("defaults", greet.arity());
+            let inc = |x: Int| x + 1;
+            emit("closure", inc.arity());
+            emit("closure_name", inc.name());
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "2");
+    assert_eq!(emits[1].1, "add");
+    assert_eq!(emits[2].1, "2");
+    assert_eq!(emits[3].1, "1");
+    assert_eq!(emits[4].1, "<closure>");
+}
+
+#[test]
+fn e2e_array_first_last_nth() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let nums = [10, 20, 30];
+            let empty: Array<Int> = [];
+            emit("first", nums.first());
+            emit("last", nums.last());
+            emit("nth", nums.nth(1));
+            emit("nth_out", nums.nth(3));
+            emit("nth_neg", nums.nth(-1));
+            emit("empty_first", empty.first());
+            emit("empty_last", empty.last());
+            emit("empty_nth", empty.nth(0));
+            let head = match nums.first() {
+                Some(n) => n,
+                None => 0,
+            };
+            emit("head", head);
+            emit("default", empty.last() ?? -1);
+        }
+        "#,
+    );
+    let values: Vec<&str> = emits.iter().map(|(_, v)| v.as_str()).collect();
+    assert_eq!(
+        values,
+        vec![
+            "Some(10)", "Some(30)", "Some(20)", "None", "None", "None", "None", "None", "10", "-1"
+        ]
+    );
+}
+
+#[test]
+fn e2e_map_non_string_keys() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let mut m = {1: "int", "1": "str", true: "bool"};
+            m[(1, true)] = "tuple";
+            m[1] = "int again";
+            emit("len", len(m));
+            emit("int", m[1]);
+            emit("str", m["1"]);
+            emit("bool", m[true]);
+            emit("tuple", m[(1, true)]);
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "4");
+    assert_eq!(emits[1].1, "int again");
+    assert_eq!(emits[2].1, "str");
+    assert_eq!(emits[3].1, "bool");
+    assert_eq!(emits[4].1, "tuple");
+}
+
+#[test]
+fn e2e_const_folding() {
+    let (_, emits) = run_program(
+        r#"
+        const MAX_RETRIES: Int = BASE_RETRIES * 2;
+        const BASE_RETRIES: Int = 3;
+        const GREETING: String = PREFIX + "retries";
+        const PREFIX: String = "max ";
+
+        fn main() {
+            emit("max", MAX_RETRIES);
+            emit("greeting", GREETING);
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "6");
+    assert_eq!(emits[1].1, "max retries");
+}
+
 #[test]
 fn e2e_if_else() {
     let (_, emits) = run_program(
@@ -162,6 +456,82 @@ fn e2e_for_loop() {
     assert_eq!(emits[0].1, "15");
 }
 
+#[test]
+fn e2e_for_loop_with_index() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let mut weighted = 0;
+            for (i, x) in [3, 5, 7] {
+                weighted = weighted + i * x;
+            }
+            emit("weighted", weighted);
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "19");
+}
+
+#[test]
+fn e2e_for_loop_with_index_over_nested_and_mixed_arrays() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            for (i, row) in [[10, 20, 30], [40, 50, 60]] {
+                emit("row", "${i}:${row.len()}:${row[2]}");
+            }
+            for (i, x) in [5, [6, 7]] {
+                emit("mixed", "${i}:${x}");
+            }
+            let pairs = [(1, 2), (3, 4)];
+            for (i, pair) in pairs {
+                emit("pair", "${i}:${pair[1]}");
+            }
+        }
+        "#,
+    );
+    let rows: Vec<&str> = emits.iter().map(|(_, v)| v.as_str()).collect();
+    assert_eq!(
+        rows,
+        vec!["0:3:30", "1:3:60", "0:5", "1:[6, 7]", "0:2", "1:4"]
+    );
+}
+
+#[test]
+fn e2e_for_loop_destructures_map_entries_and_enumerate() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            for (key, value) in {"a": 1, "b": 2} {
+                emit("entry", "${key}=${value}");
+            }
+            for (i, row) in [[10, 20, 30], [40, 50, 60]].enumerate() {
+                emit("row", "${i}:${row.len()}");
+            }
+        }
+        "#,
+    );
+    let rows: Vec<&str> = emits.iter().map(|(_, v)| v.as_str()).collect();
+    assert_eq!(rows, vec!["a=1", "b=2", "0:3", "1:3"]);
+}
+
+#[test]
+fn e2e_for_loop_destructure_arity_mismatch_is_error() {
+    let err = run_program_err(
+        r#"
+        fn main() {
+            for (a, b, c) in [[1, 2, 3], [4, 5, 6, 7]] {
+                emit("t", a + b + c);
+            }
+        }
+        "#,
+    );
+    assert!(
+        err.contains("for loop pattern expects a tuple of 3 elements"),
+        "{err}"
+    );
+}
+
 #[test]
 fn e2e_while_loop() {
     let (_, emits) = run_program(
@@ -178,6 +548,133 @@ fn e2e_while_loop() {
     assert_eq!(emits[0].1, "5");
 }
 
+#[test]
+fn e2e_loop_max_iters_throws_past_cap() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let mut n = 0;
+            let done = @max_iters(5) loop {
+                n = n + 1;
+                if n == 5 { break n; }
+            };
+            emit("within", done);
+
+            let mut runs = 0;
+            try {
+                @max_iters(3) loop {
+                    runs = runs + 1;
+                }
+            } catch String(e) {
+                emit("error", e);
+            }
+            emit("runs", runs);
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "5");
+    assert_eq!(emits[1].1, "loop exceeded @max_iters(3)");
+    assert_eq!(emits[2].1, "3");
+}
+
+#[test]
+fn e2e_for_and_while_yield_break_value() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let items = [3, 8, 12];
+            let found = for item in items {
+                if item > 5 { break item; }
+            };
+            emit("found", found);
+
+            let missing = for item in items {
+                if item > 50 { break item; }
+            };
+            emit("missing", missing);
+
+            let mut i = 0;
+            let hit = while i < 10 {
+                i = i + 1;
+                if i == 4 { break i * 10; }
+            };
+            emit("hit", hit);
+
+            let done = while i < 10 {
+                i = i + 1;
+            };
+            emit("done", done);
+
+            // Each run of an inner loop starts from nil again
+            for row in [[1, 9], [2, 3]] {
+                let big = for n in row {
+                    if n > 5 { break n; }
+                };
+                emit("big", big);
+            }
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "8");
+    assert_eq!(emits[1].1, "nil");
+    assert_eq!(emits[2].1, "40");
+    assert_eq!(emits[3].1, "nil");
+    assert_eq!(emits[4].1, "9");
+    assert_eq!(emits[5].1, "nil");
+}
+
+#[test]
+fn e2e_trace_decorator_emits_enter_and_exit() {
+    let (_, emits) = run_program(
+        r#"
+        @trace
+        fn add(a: Int, b: Int) -> Int {
+            return a + b;
+        }
+
+        @trace
+        fn fail(code: Int) -> Result<Int, String> {
+            throw "failed with ${code}";
+        }
+
+        fn untraced() -> Int {
+            return 1;
+        }
+
+        fn main() {
+            let sum = add(2, untraced());
+            emit("sum", sum);
+            try {
+                fail(7);
+            } catch {
+                emit("caught", true);
+            }
+        }
+        "#,
+    );
+    let channels: Vec<&str> = emits.iter().map(|(c, _)| c.as_str()).collect();
+    assert_eq!(
+        channels,
+        vec!["fn:enter", "fn:exit", "sum", "fn:enter", "fn:exit", "caught"]
+    );
+    assert_eq!(emits[0].1, r#"{"function": add, "args": {"a": 2, "b": 1}}"#);
+    assert!(
+        emits[1]
+            .1
+            .starts_with(r#"{"function": add, "result": 3, "duration_ms": "#),
+        "{}",
+        emits[1].1
+    );
+    assert_eq!(emits[3].1, r#"{"function": fail, "args": {"code": 7}}"#);
+    assert!(
+        emits[4]
+            .1
+            .starts_with(r#"{"function": fail, "error": failed with 7, "#),
+        "{}",
+        emits[4].1
+    );
+}
+
 #[test]
 fn e2e_functions() {
     let (_, emits) = run_program(
@@ -233,6 +730,72 @@ fn e2e_structs() {
     assert_eq!(emits[1].1, "20");
 }
 
+#[test]
+fn e2e_field_assignment_writes_back() {
+    let (_, emits) = run_program(
+        r#"
+        struct Inner { c: Int }
+        struct Outer { inner: Inner }
+
+        fn main() {
+            let mut o = Outer { inner: Inner { c: 1 } };
+            o.inner.c = 7;
+            o.inner.c += 1;
+            let mut arr = [1, 2, 3];
+            arr[0] = 9;
+            emit("c", o.inner.c);
+            emit("arr", arr);
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "8");
+    assert_eq!(emits[1].1, "[9, 2, 3]");
+}
+
+#[test]
+fn e2e_tool_state_persists_across_calls() {
+    let (_, emits) = run_program(
+        r#"
+        tool Counter {
+            description: "Counts invocations",
+            count: 0,
+
+            @describe("Increment the counter and return the new value")
+            pub fn increment(mut self) -> Int {
+                self.count += 1;
+                self.count
+            }
+        }
+
+        fn main() {
+            Counter.increment();
+            Counter.increment();
+            emit("count", Counter.increment());
+        }
+        "#,
+    );
+    assert_eq!(emits, vec![("count".to_string(), "3".to_string())]);
+}
+
+#[test]
+fn e2e_single_arg_emit_uses_default_channel() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            emit("done");
+            emit("named", 1);
+        }
+        "#,
+    );
+    assert_eq!(
+        emits,
+        vec![
+            ("default".to_string(), "done".to_string()),
+            ("named".to_string(), "1".to_string()),
+        ]
+    );
+}
+
 #[test]
 fn e2e_try_catch() {
     let (_, emits) = run_program(
@@ -291,9 +854,276 @@ fn e2e_match_result_err_selects_err_arm() {
         }
         "#,
     );
-    assert_eq!(emits.len(), 1);
-    assert_eq!(emits[0].0, "branch");
-    assert_eq!(emits[0].1, "err");
+    assert_eq!(emits.len(), 1);
+    assert_eq!(emits[0].0, "branch");
+    assert_eq!(emits[0].1, "err");
+}
+
+#[test]
+fn e2e_pipeline_stage_runs_nested_pipeline() {
+    let (_, emits) = run_program(
+        r#"
+        pipeline Normalize(text: String) -> String {
+            stage trim(s: String) -> String {
+                s.trim()
+            }
+            stage lower(s: String) -> String {
+                s.to_lowercase()
+            }
+        }
+
+        pipeline Publish(raw: String) -> String {
+            stage tag(s: String) -> String {
+                "draft: ${s}"
+            }
+            stage clean = Normalize;
+            stage stamp(s: String) -> String {
+                "${s} [ok]"
+            }
+        }
+
+        fn main() {
+            match Publish.run("  Hello World ") {
+                Ok(v) => emit("out", v),
+                Err(e) => emit("err", e),
+            }
+        }
+        "#,
+    );
+    let out: Vec<&str> = emits
+        .iter()
+        .filter(|(ch, _)| ch == "out")
+        .map(|(_, v)| v.as_str())
+        .collect();
+    assert_eq!(out, vec!["draft:   hello world [ok]"]);
+
+    let starts: Vec<&str> = emits
+        .iter()
+        .filter(|(ch, _)| ch == "pipeline:start")
+        .map(|(_, v)| v.as_str())
+        .collect();
+    assert_eq!(starts.len(), 2);
+    assert!(starts[0].contains(r#""path": Publish,"#), "{}", starts[0]);
+    assert!(
+        starts[1].contains(r#""name": Normalize,"#)
+            && starts[1].contains(r#""path": Publish/clean,"#),
+        "{}",
+        starts[1]
+    );
+}
+
+#[test]
+fn e2e_throwing_pipeline_stage_is_retried() {
+    let (_, emits) = run_program(
+        r#"
+        pipeline Flaky {
+            @retry(max: 3, backoff: "none")
+            stage fetch(input: String) -> String {
+                emit("attempt", input);
+                throw "upstream down";
+                input
+            }
+        }
+
+        fn fetch_all() -> Result<String, String> {
+            try {
+                let out = Flaky.run("doc")?;
+                emit("out", out);
+            } catch String(e) {
+                emit("caught", e);
+            }
+            Ok("done")
+        }
+
+        fn main() {
+            match fetch_all() {
+                Ok(v) => emit("after", v),
+                Err(e) => emit("err", e),
+            }
+        }
+        "#,
+    );
+    let user: Vec<(&str, &str)> = emits
+        .iter()
+        .filter(|(ch, _)| !ch.starts_with("pipeline:"))
+        .map(|(ch, v)| (ch.as_str(), v.as_str()))
+        .collect();
+    assert_eq!(
+        user,
+        vec![
+            ("attempt", "doc"),
+            ("attempt", "doc"),
+            ("attempt", "doc"),
+            (
+                "caught",
+                "pipeline 'Flaky' failed at stage 'fetch': upstream down"
+            ),
+            ("after", "done"),
+        ]
+    );
+}
+
+#[test]
+fn e2e_throwing_pipeline_stage_caught_by_outer_try() {
+    let (_, emits) = run_program(
+        r#"
+        pipeline Checked {
+            stage validate(input: Int) -> Int {
+                if input < 0 {
+                    throw "negative input";
+                }
+                input
+            }
+
+            stage double(n: Int) -> Int {
+                n * 2
+            }
+        }
+
+        fn run_both() -> Result<Int, String> {
+            try {
+                emit("ok", Checked.run(4)?);
+                Checked.run(-5)?;
+                emit("unreachable", "");
+            } catch String(e) {
+                emit("caught", e);
+            }
+            Ok(0)
+        }
+
+        fn main() {
+            match Checked.run(-1) {
+                Ok(v) => emit("ok", v),
+                Err(e) => emit("err", e),
+            }
+            run_both();
+        }
+        "#,
+    );
+    let user: Vec<(&str, &str)> = emits
+        .iter()
+        .filter(|(ch, _)| !ch.starts_with("pipeline:"))
+        .map(|(ch, v)| (ch.as_str(), v.as_str()))
+        .collect();
+    assert_eq!(
+        user,
+        vec![
+            (
+                "err",
+                "pipeline 'Checked' failed at stage 'validate': negative input"
+            ),
+            ("ok", "8"),
+            (
+                "caught",
+                "pipeline 'Checked' failed at stage 'validate': negative input"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn e2e_string_length_counts_chars() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let s = "né👋";
+            emit("len", s.len());
+            emit("builtin_len", len(s));
+            emit("bytes", s.byte_len());
+            let mut seen = 0;
+            for c in s {
+                seen = seen + 1;
+            }
+            emit("iterated", seen);
+            emit("last", s[s.len() - 1]);
+            emit("roundtrip", std::string::from_chars(std::string::to_chars(s)));
+        }
+        "#,
+    );
+    let values: Vec<&str> = emits.iter().map(|(_, v)| v.as_str()).collect();
+    assert_eq!(values, vec!["3", "3", "7", "3", "👋", "né👋"]);
+}
+
+#[test]
+fn e2e_struct_equality() {
+    let (_, emits) = run_program(
+        r#"
+        struct Point { x: Int, y: Int }
+        struct Vec2 { x: Int, y: Int }
+
+        fn main() {
+            let a = Point { x: 1, y: 2 };
+            let b = Point { y: 2, x: 1 };
+            assert_eq(a, b);
+            emit("same", a == b);
+            emit("other_type", a == Vec2 { x: 1, y: 2 });
+            emit("other_value", a != Point { x: 1, y: 3 });
+        }
+        "#,
+    );
+    let values: Vec<&str> = emits.iter().map(|(_, v)| v.as_str()).collect();
+    assert_eq!(values, vec!["true", "false", "true"]);
+}
+
+#[test]
+fn e2e_match_binds_result_and_option_payloads() {
+    let (_, emits) = run_program(
+        r#"
+        fn describe(r: Result<Option<Int>, String>) -> String {
+            return match r {
+                Ok(Some(n)) => "ok some ${n * 2}",
+                Ok(None) => "ok none",
+                Err("timeout") => "timed out",
+                Err(e) => "err ${e}",
+            };
+        }
+
+        fn main() {
+            emit("a", describe(Ok(Some(21))));
+            emit("b", describe(Ok(None)));
+            emit("c", describe(Err("timeout")));
+            emit("d", describe(Err("refused")));
+
+            let ok: Result<Int, String> = Ok(5);
+            match ok {
+                Ok(v) => emit("ok", v + 1),
+                Err(e) => emit("ok", e),
+            }
+            let err: Result<Int, String> = Err("bad input");
+            match err {
+                Ok(v) => emit("err", v),
+                Err(e) => emit("err", e),
+            }
+            let some = Some("x");
+            match some {
+                Some(x) => emit("some", x),
+                None => emit("some", "none"),
+            }
+            let none: Option<String> = None;
+            match none {
+                Some(x) => emit("none", x),
+                None => emit("none", "empty"),
+            }
+        }
+        "#,
+    );
+    let values: Vec<(&str, &str)> = emits
+        .iter()
+        .map(|(c, v)| (c.as_str(), v.as_str()))
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            ("a", "ok some 42"),
+            ("b", "ok none"),
+            ("c", "timed out"),
+            ("d", "err refused"),
+            ("ok", "6"),
+            ("err", "bad input"),
+            ("some", "x"),
+            ("none", "empty"),
+        ]
+    );
 }
 
 #[test]
@@ -318,6 +1148,56 @@ fn e2e_database() {
     assert_eq!(emits[2].1, "false");
 }
 
+#[test]
+fn e2e_hashmap_enumeration_is_sorted() {
+    let source = r#"
+        hashmap store: HashMap<String, Int> = HashMap::new();
+
+        fn main() {
+            store.set("pear", 3);
+            store.set("apple", 1);
+            store.set("zucchini", 26);
+            store.set("mango", 13);
+            emit("keys", store.keys());
+            emit("values", store.values());
+        }
+        "#;
+    // Enumeration must not depend on hash seeds: repeated runs agree.
+    for _ in 0..5 {
+        let (_, emits) = run_program(source);
+        assert_eq!(emits[0].1, "[apple, mango, pear, zucchini]");
+        assert_eq!(emits[1].1, "[1, 13, 3, 26]");
+    }
+}
+
+#[test]
+fn e2e_hashmap_non_string_keys() {
+    let (_, emits) = run_program(
+        r#"
+        hashmap store: HashMap<Any, String> = HashMap::new();
+
+        fn main() {
+            store.set(1, "int");
+            store.set("1", "str");
+            store.set(10, "ten");
+            store.set(2, "two");
+            emit("int", store.get(1));
+            emit("str", store.get("1"));
+            store.delete("1");
+            emit("has_int", store.has(1));
+            emit("has_str", store.has("1"));
+            emit("keys", store.keys());
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, r#"Some("int")"#);
+    assert_eq!(emits[1].1, r#"Some("str")"#);
+    assert_eq!(emits[2].1, "true");
+    assert_eq!(emits[3].1, "false");
+    // Int keys keep their type and sort numerically
+    assert_eq!(emits[4].1, "[1, 2, 10]");
+}
+
 #[test]
 fn e2e_stdlib_math() {
     let (_, emits) = run_program(
@@ -353,6 +1233,57 @@ fn e2e_stdlib_string() {
     assert_eq!(emits[1].1, "hi");
 }
 
+#[test]
+fn e2e_datetime_values() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let start = std::time::from_ms(1709127930250);
+            let due = start.add_days(2);
+            emit("parts", [due.year(), due.month(), due.day()]);
+            emit("due", due);
+            emit("label", due.format("%d/%m/%Y"));
+            emit("diff_days", due.diff(start) / 86400000);
+            emit("ordered", start < due && due > start);
+            emit("now_is_later", std::time::now() > start);
+        }
+        "#,
+    );
+    assert_eq!(
+        emits,
+        vec![
+            ("parts".to_string(), "[2024, 3, 1]".to_string()),
+            ("due".to_string(), "2024-03-01T13:45:30.250Z".to_string()),
+            ("label".to_string(), "01/03/2024".to_string()),
+            ("diff_days".to_string(), "2".to_string()),
+            ("ordered".to_string(), "true".to_string()),
+            ("now_is_later".to_string(), "true".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn e2e_range_slicing() {
+    let (_, emits) = run_program(
+        r#"
+        fn main() {
+            let items = [10, 20, 30, 40, 50];
+            emit("mid", items[1..4]);
+            emit("clamped", items[3..100]);
+            emit("open", items[..2]);
+            let word = "concerto";
+            emit("sub", word[0..=3]);
+            emit("tail", word[5..99]);
+        }
+        "#,
+    );
+    assert_eq!(emits[0].1, "[20, 30, 40]");
+    assert_eq!(emits[1].1, "[40, 50]");
+    assert_eq!(emits[2].1, "[10, 20]");
+    assert_eq!(emits[3].1, "conc");
+    assert_eq!(emits[4].1, "rto");
+}
+
 #[test]
 fn e2e_nested_calls() {
     let (_, emits) = run_program(
@@ -476,6 +1407,76 @@ fn e2e_agent_with_memory_builder_auto_and_manual_modes() {
     assert_eq!(emits[4].1, "3");
 }
 
+#[test]
+fn e2e_builder_timeout_overrides_declared_timeout() {
+    // The mock provider always takes a non-zero amount of time, so a zero
+    // second timeout is guaranteed to trip.
+    let (_, emits) = run_program(
+        r#"
+        const openai: Int = 0;
+
+        @retry(max: 1, backoff: "none")
+        @timeout(seconds: 60)
+        model Patient {
+            provider: openai,
+            base: "gpt-4o-mini",
+        }
+
+        @timeout(seconds: 0)
+        model Hasty {
+            provider: openai,
+            base: "gpt-4o-mini",
+        }
+
+        fn main() {
+            emit("declared", Patient.execute("hi").is_ok());
+            emit("tightened", Patient.with_timeout(0).execute("hi").is_ok());
+            emit("hasty", Hasty.execute("hi").is_ok());
+            emit("relaxed", Hasty.with_timeout(60).with_retry(2).execute("hi").is_ok());
+            match Patient.with_retry(2).with_timeout(0).execute("hi") {
+                Ok(_) => emit("error", "none"),
+                Err(e) => emit("error", e),
+            }
+        }
+        "#,
+    );
+    assert_eq!(emits[0], ("declared".to_string(), "true".to_string()));
+    assert_eq!(emits[1], ("tightened".to_string(), "false".to_string()));
+    assert_eq!(emits[2], ("hasty".to_string(), "false".to_string()));
+    assert_eq!(emits[3], ("relaxed".to_string(), "true".to_string()));
+    assert!(
+        emits[4].1.contains("failed after 2 attempts: timeout exceeded"),
+        "unexpected error: {}",
+        emits[4].1
+    );
+}
+
+#[test]
+fn e2e_model_with_context_from_source() {
+    let (_, emits) = run_program(
+        r#"
+        const openai: Int = 0;
+
+        model Support {
+            provider: openai,
+            base: "gpt-4o-mini",
+            system_prompt: "Be helpful",
+        }
+
+        fn main() {
+            match Support.with_context({"user": "ada", "tier": 2}).execute("hi") {
+                Ok(r) => emit("reply", r.text),
+                Err(e) => emit("error", e),
+            }
+        }
+        "#,
+    );
+    assert_eq!(
+        emits,
+        vec![("reply".to_string(), "[mock response to: hi]".to_string())]
+    );
+}
+
 #[test]
 fn e2e_dynamic_tool_binding_builder_paths() {
     let (_, emits) = run_program(
@@ -775,6 +1776,77 @@ fn e2e_listen_bidirectional() {
     assert!(channels.contains(&"final"), "missing final, got: {:?}", channels);
 }
 
+#[test]
+fn e2e_listen_handler_response_schema_mismatch() {
+    // The handler declares `-> Answer` but returns a String for a Bool field,
+    // so the response is rejected before it reaches the agent.
+    let source = r#"
+        agent BidiAgent {
+            connector: "bidi",
+        }
+        schema Answer {
+            approved: Bool,
+        }
+        fn main() {
+            let result = listen BidiAgent.execute("start") {
+                "question" => |q| -> Answer {
+                    { "approved": "yes" }
+                },
+            };
+            emit("final", result);
+        }
+    "#;
+
+    let (tokens, _) = Lexer::new(source, "test.conc").tokenize();
+    let (program, parse_diags) = parser::Parser::new(tokens).parse();
+    assert!(!parse_diags.has_errors());
+    let sem_diags = concerto_compiler::semantic::analyze(&program);
+    assert!(
+        !sem_diags.has_errors(),
+        "semantic errors: {:?}",
+        sem_diags.diagnostics()
+    );
+
+    let ir = CodeGenerator::new("test", "test.conc").generate(&program);
+    assert_eq!(
+        ir.listens[0].handlers[0].response_schema.as_deref(),
+        Some("Answer")
+    );
+    let json = serde_json::to_string(&ir).expect("IR serialization failed");
+    let mut ir_module: concerto_common::ir::IrModule =
+        serde_json::from_str(&json).expect("IR deserialization failed");
+    ir_module.agents[0].command = Some("bash".to_string());
+    ir_module.agents[0].args = Some(vec![
+        "-c".to_string(),
+        r#"read prompt; echo '{"type":"question","question":"Approve?"}'; read response; echo '{"type":"result","text":"completed"}';"#.to_string(),
+    ]);
+
+    let module = LoadedModule::from_ir(ir_module).expect("IR loading failed");
+    let emits: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(Vec::new()));
+    let emits_clone = emits.clone();
+    let mut vm = VM::new(module);
+    vm.set_emit_handler(move |channel, payload| {
+        emits_clone
+            .lock()
+            .unwrap()
+            .push((channel.to_string(), payload.display_string()));
+    });
+
+    let err = vm.execute().expect_err("invalid response should fail");
+    let msg = err.to_string();
+    assert!(
+        msg.contains("listen handler 'question' response does not match schema 'Answer'"),
+        "unexpected error: {}",
+        msg
+    );
+    assert!(msg.contains("approved"), "unexpected error: {}", msg);
+
+    let collected = emits.lock().unwrap().clone();
+    let channels: Vec<&str> = collected.iter().map(|(c, _)| c.as_str()).collect();
+    assert!(channels.contains(&"listen:error"), "got: {:?}", channels);
+    assert!(!channels.contains(&"final"), "got: {:?}", channels);
+}
+
 // =========================================================================
 // Direct run tests (compile .conc in-memory → execute)
 // =========================================================================
@@ -825,6 +1897,44 @@ fn e2e_direct_run_basic_program() {
     assert_eq!(collected[0].1, "30");
 }
 
+#[test]
+fn e2e_program_args_via_env_args() {
+    let source = r#"
+        fn main() {
+            let args = std::env::args();
+            emit("count", len(args));
+            for arg in args {
+                emit("arg", arg);
+            }
+        }
+    "#;
+
+    let module = compile_and_load(source);
+    let emits: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(Vec::new()));
+    let emits_clone = emits.clone();
+    let mut vm = VM::new(module);
+    vm.set_args(vec!["input.txt".to_string(), "--verbose".to_string()]);
+    vm.set_emit_handler(move |channel, payload| {
+        emits_clone
+            .lock()
+            .unwrap()
+            .push((channel.to_string(), payload.display_string()));
+    });
+
+    let result = vm.execute();
+    assert!(result.is_ok(), "execution failed: {:?}", result.err());
+
+    let collected = emits.lock().unwrap().clone();
+    assert_eq!(
+        collected,
+        vec![
+            ("count".to_string(), "2".to_string()),
+            ("arg".to_string(), "input.txt".to_string()),
+            ("arg".to_string(), "--verbose".to_string()),
+        ]
+    );
+}
+
 #[test]
 fn e2e_direct_run_with_stdlib() {
     // Verify stdlib calls work through direct run path
@@ -986,32 +2096,135 @@ fn e2e_test_assert_failing() {
     let mut vm = VM::new(module.clone());
     vm.set_emit_handler(|_, _| {});
     let result = vm.run_test(&module.tests[0]);
-    assert!(result.is_err());
-    let err_msg = result.unwrap_err().to_string();
-    assert!(
-        err_msg.contains("1 != 2"),
-        "error should show values: {}",
-        err_msg
-    );
+    assert!(result.is_err());
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("1 != 2"),
+        "error should show values: {}",
+        err_msg
+    );
+}
+
+#[test]
+fn e2e_test_emit_capture() {
+    let module = compile_for_tests(
+        r#"
+        @test
+        fn emit_capture() {
+            emit("greeting", "hello");
+            emit("farewell", "goodbye");
+
+            let emits = test_emits();
+            assert_eq(len(emits), 2);
+            assert_eq(emits[0].channel, "greeting");
+            assert_eq(emits[0].payload, "hello");
+            assert_eq(emits[1].channel, "farewell");
+            assert_eq(emits[1].payload, "goodbye");
+        }
+        "#,
+    );
+
+    let mut vm = VM::new(module.clone());
+    vm.set_emit_handler(|_, _| {});
+    let result = vm.run_test(&module.tests[0]);
+    assert!(result.is_ok(), "test should pass: {:?}", result.err());
+}
+
+#[test]
+fn e2e_test_mock_agent() {
+    let module = compile_for_tests_with_connections(
+        r#"
+        model Greeter {
+            provider: openai,
+            base: "gpt-4o",
+            system_prompt: "You greet people.",
+        }
+
+        @test
+        fn mock_agent_execute() {
+            mock Greeter {
+                response: "Hello from mock!",
+            }
+
+            let result = Greeter.execute("Hi");
+            assert(result.is_ok());
+        }
+        "#,
+        &["openai"],
+    );
+
+    assert_eq!(module.tests.len(), 1);
+    let mut vm = VM::new(module.clone());
+    vm.set_emit_handler(|_, _| {});
+    let result = vm.run_test(&module.tests[0]);
+    assert!(result.is_ok(), "test should pass: {:?}", result.err());
+}
+
+#[test]
+fn e2e_test_mock_agent_error() {
+    let module = compile_for_tests_with_connections(
+        r#"
+        model MyAgent {
+            provider: openai,
+            base: "gpt-4o",
+            system_prompt: "test",
+        }
+
+        @test
+        fn mock_agent_error() {
+            mock MyAgent {
+                error: "API rate limit exceeded",
+            }
+
+            let result = MyAgent.execute("do something");
+            assert(result.is_err());
+        }
+        "#,
+        &["openai"],
+    );
+
+    let mut vm = VM::new(module.clone());
+    vm.set_emit_handler(|_, _| {});
+    let result = vm.run_test(&module.tests[0]);
+    assert!(result.is_ok(), "test should pass: {:?}", result.err());
 }
 
 #[test]
-fn e2e_test_emit_capture() {
-    let module = compile_for_tests(
+fn e2e_test_inline_builder_mock() {
+    let module = compile_for_tests_with_connections(
         r#"
+        model Writer {
+            provider: openai,
+            base: "gpt-4o",
+        }
+
+        model Editor {
+            provider: openai,
+            base: "gpt-4o",
+        }
+
         @test
-        fn emit_capture() {
-            emit("greeting", "hello");
-            emit("farewell", "goodbye");
+        fn mixes_inline_and_declared_mocks() {
+            mock Editor {
+                response: "declared edit",
+            }
 
-            let emits = test_emits();
-            assert_eq(len(emits), 2);
-            assert_eq(emits[0].channel, "greeting");
-            assert_eq(emits[0].payload, "hello");
-            assert_eq(emits[1].channel, "farewell");
-            assert_eq(emits[1].payload, "goodbye");
+            let draft = Writer.mock("inline draft").execute("write");
+            assert_eq(draft.unwrap().text, "inline draft");
+
+            // The inline mock wins over the declared one, for that call only
+            let override = Editor.mock("inline edit").execute("edit");
+            assert_eq(override.unwrap().text, "inline edit");
+            let edit = Editor.execute("edit");
+            assert_eq(edit.unwrap().text, "declared edit");
+
+            match Writer.mock_error("rate limited").execute("write") {
+                Ok(_) => assert(false),
+                Err(e) => assert_eq(e, "rate limited"),
+            }
         }
         "#,
+        &["openai"],
     );
 
     let mut vm = VM::new(module.clone());
@@ -1021,9 +2234,14 @@ fn e2e_test_emit_capture() {
 }
 
 #[test]
-fn e2e_test_mock_agent() {
+fn e2e_schema_error_identifies_offending_field() {
     let module = compile_for_tests_with_connections(
         r#"
+        schema Greeting {
+            message: String,
+            count: Int,
+        }
+
         model Greeter {
             provider: openai,
             base: "gpt-4o",
@@ -1031,19 +2249,25 @@ fn e2e_test_mock_agent() {
         }
 
         @test
-        fn mock_agent_execute() {
+        fn schema_error_is_structured() {
             mock Greeter {
-                response: "Hello from mock!",
+                response: "{\"message\": \"hi\"}",
             }
 
-            let result = Greeter.execute("Hi");
-            assert(result.is_ok());
+            let result = Greeter.execute_with_schema<Greeting>("Hi");
+            match result {
+                Ok(_) => assert(false),
+                Err(e) => {
+                    assert_eq(e.field, "count");
+                    assert_eq(e.reason, "missing");
+                    assert_eq(len(e.violations), 1);
+                },
+            }
         }
         "#,
         &["openai"],
     );
 
-    assert_eq!(module.tests.len(), 1);
     let mut vm = VM::new(module.clone());
     vm.set_emit_handler(|_, _| {});
     let result = vm.run_test(&module.tests[0]);
@@ -1051,26 +2275,36 @@ fn e2e_test_mock_agent() {
 }
 
 #[test]
-fn e2e_test_mock_agent_error() {
-    let module = compile_for_tests_with_connections(
+fn e2e_json_validate_against_schema() {
+    let module = compile_for_tests(
         r#"
-        model MyAgent {
-            provider: openai,
-            base: "gpt-4o",
-            system_prompt: "test",
+        schema Greeting {
+            message: String,
+            count: Int,
         }
 
         @test
-        fn mock_agent_error() {
-            mock MyAgent {
-                error: "API rate limit exceeded",
+        fn validate_good_and_bad_maps() {
+            let good = std::json::validate({"message": "hi", "count": 2}, Greeting);
+            match good {
+                Ok(g) => {
+                    assert_eq(g.message, "hi");
+                    assert_eq(g.count, 2);
+                },
+                Err(_) => assert(false),
             }
 
-            let result = MyAgent.execute("do something");
-            assert(result.is_err());
+            let bad = std::json::validate({"message": "hi", "count": "two"}, Greeting);
+            match bad {
+                Ok(_) => assert(false),
+                Err(e) => {
+                    assert_eq(e.field, "count");
+                    assert_eq(e.reason, "wrong_type");
+                    assert_eq(len(e.violations), 1);
+                },
+            }
         }
         "#,
-        &["openai"],
     );
 
     let mut vm = VM::new(module.clone());
@@ -1479,6 +2713,87 @@ fn bugfix_propagate_option_none() {
     assert_eq!(emits[0], ("result".into(), "None".into()));
 }
 
+#[test]
+fn bugfix_propagate_err_in_match_arm_returns_from_function() {
+    // Bug: ? on Err threw instead of returning Err, so a `?` in a match arm
+    // (or anywhere without a local try) aborted the whole program
+    let (_, emits) = run_program(
+        r#"
+        fn parse(s: String) -> Result<Int, String> {
+            if s == "bad" { return Err("bad input"); }
+            Ok(1)
+        }
+
+        fn pick(tag: String) -> Result<Int, String> {
+            let n = match tag {
+                "a" => parse("ok")? + 10,
+                _ => [5, 6, parse("bad")?][0],
+            };
+            emit("after_match", n);
+            Ok(n)
+        }
+
+        fn local_try() -> Result<String, String> {
+            let r = try {
+                parse("bad")?;
+                "no error"
+            } catch {
+                "caught locally"
+            };
+            Ok(r)
+        }
+
+        fn main() {
+            emit("a", pick("a"));
+            // Operands left mid-expression by the early return must not leak
+            emit("z", [100, pick("z")]);
+            emit("try", local_try());
+        }
+        "#,
+    );
+    assert_eq!(emits[0], ("after_match".into(), "11".into()));
+    assert_eq!(emits[1], ("a".into(), "Ok(11)".into()));
+    assert_eq!(emits[2], ("z".into(), "[100, Err(\"bad input\")]".into()));
+    assert_eq!(emits[3], ("try".into(), "Ok(\"caught locally\")".into()));
+}
+
+#[test]
+fn bugfix_propagate_in_closure_returns_from_closure() {
+    // ? inside a closure returns from the closure, not the enclosing function
+    let (_, emits) = run_program(
+        r#"
+        fn parse(s: String) -> Result<Int, String> {
+            if s == "bad" { return Err("bad input"); }
+            Ok(1)
+        }
+
+        fn run(s: String) -> Result<Int, String> {
+            let f = |x: String| -> Result<Int, String> {
+                let v = parse(x)?;
+                Ok(v + 100)
+            };
+            let r = f(s);
+            emit("closure", r);
+            Ok(5)
+        }
+
+        fn main() {
+            emit("ok", run("fine"));
+            emit("bad", run("bad"));
+        }
+        "#,
+    );
+    assert_eq!(
+        emits,
+        vec![
+            ("closure".into(), "Ok(101)".into()),
+            ("ok".into(), "Ok(5)".into()),
+            ("closure".into(), "Err(\"bad input\")".into()),
+            ("bad".into(), "Ok(5)".into()),
+        ]
+    );
+}
+
 #[test]
 fn bugfix_cast_valid_conversions() {
     let (_, emits) = run_program(
@@ -1673,6 +2988,45 @@ fn bugfix_try_catch_typed_mismatch_rethrows() {
     );
 }
 
+#[test]
+fn bugfix_try_catch_unmatched_clauses_with_branches_rethrow() {
+    // Bug: a jump inside a typed catch body was mistaken for the exit jump,
+    // so later clauses were skipped. Unmatched errors still reach the outer try.
+    let (_, emits) = run_program(
+        r#"
+        fn classify(n: Int) -> Result<String, String> {
+            let out = try {
+                try {
+                    if n > 0 { throw "text"; }
+                    throw false;
+                } catch Int(e) {
+                    if e > 0 { "int-pos" } else { "int-neg" }
+                } catch String(e) {
+                    "inner " + e
+                } catch Float(_f) {
+                    "float"
+                }
+            } catch Bool(b) {
+                if b { "outer true" } else { "outer false" }
+            };
+            Ok(out)
+        }
+        fn main() {
+            match classify(1) {
+                Ok(v) => emit("pos", v),
+                Err(e) => emit("err", e),
+            }
+            match classify(0) {
+                Ok(v) => emit("zero", v),
+                Err(e) => emit("err", e),
+            }
+        }
+        "#,
+    );
+    assert_eq!(emits[0], ("pos".into(), "inner text".into()));
+    assert_eq!(emits[1], ("zero".into(), "outer false".into()));
+}
+
 #[test]
 fn bugfix_string_indexing() {
     let (_, emits) = run_program(
@@ -1725,7 +3079,9 @@ fn bugfix_builder_with_tools_rejects_non_refs() {
 
 #[test]
 fn bugfix_builder_without_tools_rejects_args() {
-    // without_tools(123) should error — takes no arguments
+    // without_tools(123) should error — takes no arguments.
+    // Direct `M.without_tools(123)` is rejected at compile time, so go
+    // through a local binding to exercise the runtime check.
     let err = run_program_err(
         r#"
         model M {
@@ -1733,7 +3089,8 @@ fn bugfix_builder_without_tools_rejects_args() {
             base: "gpt-4o-mini",
         }
         fn main() {
-            let r = M.without_tools(123).execute("hi");
+            let m = M;
+            let r = m.without_tools(123).execute("hi");
         }
         "#,
     );
@@ -1781,3 +3138,333 @@ fn bugfix_use_import_with_alias() {
     assert_eq!(emits[0].0, "result");
     assert_eq!(emits[0].1, "HELLO");
 }
+
+#[test]
+fn e2e_use_aliases_a_user_declaration() {
+    let (_, emits) = run_program(
+        r#"
+        use greet as hello;
+        use Point as P;
+
+        struct Point { x: Int, y: Int }
+
+        fn main() {
+            emit("greeting", hello("bob"));
+            let p = P { x: 1, y: 2 };
+            emit("point", p.x + p.y);
+        }
+
+        fn greet(name: String) -> String {
+            return "hi " + name;
+        }
+        "#,
+    );
+    assert_eq!(emits[0], ("greeting".to_string(), "hi bob".to_string()));
+    assert_eq!(emits[1], ("point".to_string(), "3".to_string()));
+}
+
+#[test]
+fn e2e_max_call_depth_is_configurable() {
+    let source = r#"
+        fn depth(n: Int) -> Int {
+            if n == 0 {
+                return 0;
+            }
+            return 1 + depth(n - 1);
+        }
+
+        fn main() {
+            emit("depth", depth(1500));
+        }
+    "#;
+
+    // Lowered limit: 1500 nested calls overflow a 100-frame stack.
+    let mut vm = VM::new(compile_and_load(source));
+    vm.set_max_call_depth(100);
+    vm.set_emit_handler(|_, _| {});
+    let err = vm.execute().expect_err("expected stack overflow");
+    assert_eq!(err.to_string(), "max call depth exceeded (100)");
+
+    // Raised limit: the same program exceeds the default but fits in 2000.
+    let depth = Arc::new(Mutex::new(None));
+    let depth_clone = depth.clone();
+    let mut vm = VM::new(compile_and_load(source));
+    vm.set_max_call_depth(2000);
+    vm.set_emit_handler(move |_, payload| {
+        *depth_clone.lock().unwrap() = Some(payload.display_string());
+    });
+    let result = vm.execute();
+    assert!(result.is_ok(), "execution failed: {:?}", result.err());
+    assert_eq!(depth.lock().unwrap().as_deref(), Some("1500"));
+}
+
+#[test]
+fn e2e_emit_every_n_samples_rapid_emits() {
+    let source = r#"
+        fn main() {
+            for i in 0..100 {
+                emit("tick", i);
+                emit("tock", i);
+            }
+            emit("done", true);
+        }
+    "#;
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+    let mut vm = VM::new(compile_and_load(source));
+    vm.set_emit_every_n(10);
+    vm.set_emit_handler(move |channel, payload| {
+        seen_clone
+            .lock()
+            .unwrap()
+            .push((channel.to_string(), payload.display_string()));
+    });
+    let result = vm.execute();
+    assert!(result.is_ok(), "execution failed: {:?}", result.err());
+
+    let seen = seen.lock().unwrap();
+    let ticks: Vec<&str> = seen
+        .iter()
+        .filter(|(c, _)| c == "tick")
+        .map(|(_, p)| p.as_str())
+        .collect();
+    assert_eq!(
+        ticks,
+        ["0", "10", "20", "30", "40", "50", "60", "70", "80", "90"]
+    );
+    assert_eq!(seen.iter().filter(|(c, _)| c == "tock").count(), 10);
+    assert!(seen.iter().any(|(c, _)| c == "done"));
+    assert_eq!(
+        seen.last().unwrap(),
+        &(
+            "emit:dropped".to_string(),
+            r#"{"tick": 90, "tock": 90}"#.to_string()
+        )
+    );
+}
+
+#[test]
+fn e2e_log_file_sink_rotates() {
+    let path = std::env::temp_dir().join(format!("concerto_e2e_log_{}.log", std::process::id()));
+    let rotated = format!("{}.1", path.display());
+    std::fs::remove_file(&path).ok();
+    std::fs::remove_file(&rotated).ok();
+
+    let source = format!(
+        r#"
+        fn main() {{
+            let opened = std::log::set_file("{}", 200);
+            if opened.is_err() {{ emit("error", "could not open log file"); }}
+            std::log::info("service started");
+            std::log::warn("disk almost full");
+            std::log::error("request failed");
+        }}
+        "#,
+        path.display()
+    );
+    let (_, emits) = run_program(&source);
+    assert!(emits.is_empty());
+
+    // Three ~80-byte records exceed 200 bytes: the first two rotate out.
+    let current = std::fs::read_to_string(&path).expect("log file written");
+    let records: Vec<serde_json::Value> = current
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["level"], "error");
+    assert_eq!(records[0]["message"], "request failed");
+
+    let old = std::fs::read_to_string(&rotated).expect("rotated log file");
+    assert_eq!(old.lines().count(), 2);
+    assert!(old.contains("\"service started\""));
+    assert!(old.contains("\"disk almost full\""));
+
+    std::fs::remove_file(&path).ok();
+    std::fs::remove_file(&rotated).ok();
+}
+
+#[test]
+fn e2e_execute_function_calls_named_function_with_args() {
+    let source = r#"
+        fn greet(name: String, times: Int) -> String {
+            let mut out = "";
+            for i in 0..times {
+                out = out + "hi " + name + ";";
+            }
+            return out;
+        }
+
+        fn main() {
+            emit("unused", "main should not run");
+        }
+    "#;
+    let (tokens, _) = Lexer::new(source, "test.conc").tokenize();
+    let (program, parse_diags) = parser::Parser::new(tokens).parse();
+    assert!(!parse_diags.has_errors());
+    let ir = CodeGenerator::new("test", "test.conc").generate(&program);
+    let module = LoadedModule::from_ir(ir).expect("IR loading failed");
+    let mut vm = VM::new(module);
+
+    let result = vm
+        .execute_function(
+            "greet",
+            vec![Value::String("Ada".to_string()), Value::Int(2)],
+        )
+        .expect("execute_function failed");
+    assert_eq!(result, Value::String("hi Ada;hi Ada;".to_string()));
+    assert_eq!(vm.call_stack_depth(), 0);
+
+    let err = vm.execute_function("greet", vec![]).unwrap_err();
+    assert!(err.to_string().contains("expects 2 argument(s)"), "{err}");
+    assert!(vm.execute_function("missing", vec![]).is_err());
+}
+
+#[test]
+fn e2e_execute_function_fills_omitted_defaults() {
+    let source = r#"
+        const SEP: String = "-";
+
+        fn label(name: String, times: Int = 2, sep: String = SEP + SEP) -> String {
+            let mut out = name;
+            for i in 0..times {
+                out = out + sep + "x";
+            }
+            return out;
+        }
+
+        fn main() {
+            emit("call", label("a"));
+            emit("call", label("b", 1));
+            emit("call", label("c", 1, "+"));
+        }
+    "#;
+    let (_, emits) = run_program(source);
+    let calls: Vec<&str> = emits.iter().map(|(_, payload)| payload.as_str()).collect();
+    assert_eq!(calls, vec!["a--x--x", "b--x", "c+x"]);
+
+    let (tokens, _) = Lexer::new(source, "test.conc").tokenize();
+    let (program, parse_diags) = parser::Parser::new(tokens).parse();
+    assert!(!parse_diags.has_errors());
+    let ir = CodeGenerator::new("test", "test.conc").generate(&program);
+    let module = LoadedModule::from_ir(ir).expect("IR loading failed");
+    let mut vm = VM::new(module);
+
+    let result = vm
+        .execute_function("label", vec![Value::String("d".to_string())])
+        .expect("execute_function failed");
+    assert_eq!(result, Value::String("d--x--x".to_string()));
+    let result = vm
+        .execute_function("label", vec![Value::String("e".to_string()), Value::Int(0)])
+        .expect("execute_function failed");
+    assert_eq!(result, Value::String("e".to_string()));
+
+    let err = vm.execute_function("label", vec![]).unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("expects 1 to 3 argument(s), got 0"), "{err}");
+}
+
+#[test]
+fn e2e_execute_function_failure_leaves_vm_reusable() {
+    let source = r#"
+        fn inner() -> Int {
+            throw "boom";
+            return 1;
+        }
+
+        fn outer() -> Int {
+            return inner() + 1;
+        }
+
+        fn main() -> Int {
+            return 42;
+        }
+    "#;
+    let (tokens, _) = Lexer::new(source, "test.conc").tokenize();
+    let (program, parse_diags) = parser::Parser::new(tokens).parse();
+    assert!(!parse_diags.has_errors());
+    let ir = CodeGenerator::new("test", "test.conc").generate(&program);
+    let module = LoadedModule::from_ir(ir).expect("IR loading failed");
+    let mut vm = VM::new(module);
+
+    let err = vm.execute_function("outer", vec![]).unwrap_err();
+    assert!(err.to_string().contains("boom"), "{err}");
+    assert_eq!(vm.call_stack_depth(), 0);
+
+    assert_eq!(vm.execute().expect("execute failed"), Value::Int(42));
+}
+
+#[test]
+fn e2e_snapshot_restore_rolls_back_hashmaps_ledgers_and_memories() {
+    let source = r#"
+        hashmap store: HashMap<String, Int> = HashMap::new();
+        ledger facts: Ledger = Ledger::new();
+        memory chat: Memory = Memory::new(10);
+
+        fn record(key: String) {
+            store.set(key, 1);
+            facts.insert(key, [key], "noted " + key);
+            chat.append("user", key);
+        }
+
+        fn state() -> String {
+            let keys = std::string::join(store.keys(), ",");
+            return "${keys}|${facts.len()}|${chat.len()}";
+        }
+
+        fn main() {}
+    "#;
+    let mut vm = VM::new(compile_and_load(source));
+
+    vm.execute_function("record", vec![Value::String("a".into())])
+        .unwrap();
+    let before = vm.snapshot();
+    let state = |vm: &mut VM| vm.execute_function("state", vec![]).unwrap();
+    assert_eq!(state(&mut vm), Value::String("a|1|1".into()));
+
+    vm.execute_function("record", vec![Value::String("b".into())])
+        .unwrap();
+    assert_eq!(state(&mut vm), Value::String("a,b|2|2".into()));
+
+    vm.restore(before.clone());
+    assert_eq!(state(&mut vm), Value::String("a|1|1".into()));
+
+    // The handle stays valid after a restore.
+    vm.execute_function("record", vec![Value::String("c".into())])
+        .unwrap();
+    vm.restore(before);
+    assert_eq!(state(&mut vm), Value::String("a|1|1".into()));
+}
+
+#[test]
+fn e2e_schema_checked_emit() {
+    let source = r#"
+        schema Score {
+            label: String,
+            confidence: Float,
+        }
+
+        fn main() {
+            emit<Score>("result", { "label": "positive", "confidence": 0.9 });
+            try {
+                emit<Score>("result", { "label": "negative" });
+            } catch {
+                emit("error", "rejected");
+            }
+            emit<Score>("unchecked", { "label": 3 });
+        }
+    "#;
+    let err = run_program_err(source);
+    assert!(
+        err.contains("emit on 'unchecked' does not match schema 'Score'"),
+        "{err}"
+    );
+
+    let (_, emits) =
+        run_program(&source.replace(r#"emit<Score>("unchecked", { "label": 3 });"#, ""));
+    assert_eq!(emits.len(), 2, "{emits:?}");
+    assert_eq!(emits[0].0, "result");
+    assert!(emits[0].1.contains("positive"));
+    assert_eq!(emits[1], ("error".to_string(), "rejected".to_string()));
+}
@@ -1,9 +1,33 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::time::{Duration, Instant};
 
 use clap::Parser;
-use concerto_runtime::{LoadedModule, VM};
+use concerto_runtime::vm::DEFAULT_MAX_CALL_DEPTH;
+use concerto_runtime::{LoadedModule, RuntimeError, VM};
+
+/// How long `--timeout-secs` waits past the deadline for the VM to stop on
+/// its own before exiting the process.
+const TIMEOUT_GRACE: Duration = Duration::from_secs(2);
+
+/// `concerto run` exit codes for runtime errors, so scripts can tell
+/// failure kinds apart. Load and compile errors also exit with
+/// `EXIT_RUNTIME_ERROR`.
+const EXIT_RUNTIME_ERROR: i32 = 1;
+const EXIT_UNHANDLED_THROW: i32 = 2;
+const EXIT_TIMEOUT: i32 = 3;
+const EXIT_STACK_OVERFLOW: i32 = 4;
+
+/// The exit code for a run that failed with `err`.
+fn exit_code(err: &RuntimeError) -> i32 {
+    match err {
+        RuntimeError::UnhandledThrow(_) | RuntimeError::Propagated(_) => EXIT_UNHANDLED_THROW,
+        RuntimeError::Timeout(_) | RuntimeError::ProviderTimeout(_) => EXIT_TIMEOUT,
+        RuntimeError::StackOverflow(_) => EXIT_STACK_OVERFLOW,
+        _ => EXIT_RUNTIME_ERROR,
+    }
+}
 
 /// Concerto language runtime — executes .conc source files or compiled .conc-ir files.
 #[derive(Parser)]
@@ -11,7 +35,7 @@ use concerto_runtime::{LoadedModule, VM};
     name = "concerto",
     version,
     about,
-    long_about = "Concerto language runtime.\n\nRuns Concerto programs from source (.conc) or compiled IR (.conc-ir) files.\nWhen given a .conc file, it compiles in-memory and executes directly.\n\nExamples:\n  concerto run src/main.conc            Compile and run in one step\n  concerto run hello.conc-ir            Run a pre-compiled program\n  concerto run src/main.conc --debug    Run with debug output\n  concerto run src/main.conc --quiet    Run without emit output\n  concerto test src/main.conc           Run tests in a source file\n  concerto test src/main.conc --filter \"auth\"  Run matching tests\n  concerto init my-project              Create a new Concerto project"
+    long_about = "Concerto language runtime.\n\nRuns Concerto programs from source (.conc) or compiled IR (.conc-ir) files.\nWhen given a .conc file, it compiles in-memory and executes directly.\nWhen given a project directory (or no path), it runs the Concerto.toml entry.\n\nExamples:\n  concerto run src/main.conc            Compile and run in one step\n  concerto run                          Run the current project's entry file\n  concerto run my-project               Run a project directory's entry file\n  concerto run hello.conc-ir            Run a pre-compiled program\n  concerto run src/main.conc --debug    Run with debug output\n  concerto run src/main.conc --quiet    Run without emit output\n  concerto run src/main.conc -- a b     Pass arguments to std::env::args()\n  concerto run src/main.conc --max-depth 5000  Allow deeper recursion\n  concerto run src/main.conc --timeout-secs 300  Stop runs longer than 5 minutes\n  concerto run src/main.conc --emit-every-n 100  Print every 100th emit per channel\n  concerto run src/main.conc --env-file .env.staging  Load API keys from a file\n  concerto run src/main.conc --explain-error  Suggest fixes for runtime errors\n  gen | concerto run -                  Compile and run source read from stdin\n  concerto test src/main.conc           Run tests in a source file\n  concerto test src/main.conc --filter \"auth\"  Run matching tests\n  concerto test src/main.conc --json     Print a JSON test report\n  concerto test src/main.conc --junit report.xml  Also write a JUnit XML report\n  concerto test src/main.conc --list     List tests without running them\n  concerto test src/main.conc --shuffle  Run tests in a random, replayable order\n  concerto doc src/main.conc            Print a markdown API reference\n  concerto init my-project              Create a new Concerto project"
 )]
 struct Cli {
     #[command(subcommand)]
@@ -20,10 +44,11 @@ struct Cli {
 
 #[derive(clap::Subcommand)]
 enum Command {
-    /// Execute a .conc source file or compiled .conc-ir file
+    /// Execute a .conc source file, compiled .conc-ir file, or project
     Run {
-        /// Path to the .conc or .conc-ir file
-        input: PathBuf,
+        /// Path to the .conc or .conc-ir file, a project directory, or '-' to
+        /// read source from stdin (default: the current project's entry)
+        input: Option<PathBuf>,
 
         /// Enable debug output (show stack trace on error)
         #[arg(long)]
@@ -32,6 +57,47 @@ enum Command {
         /// Suppress emit output
         #[arg(short, long)]
         quiet: bool,
+
+        /// How emits are printed: `text` (`[emit:channel] payload`) or
+        /// `ndjson` (one `{"channel": ..., "payload": ...}` object per line)
+        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = ["text", "ndjson"])]
+        emit_format: String,
+
+        /// Maximum call depth before a stack overflow error
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_CALL_DEPTH)]
+        max_depth: usize,
+
+        /// Print per-function call counts and timings to stderr on exit
+        #[arg(long)]
+        profile: bool,
+
+        /// Answer every model and agent call with a canned mock response
+        /// instead of calling providers (also enabled by CONCERTO_MOCK_ALL=1)
+        #[arg(long)]
+        mock_all: bool,
+
+        /// Stop the run with a timeout error after N seconds of wall-clock time
+        #[arg(long, value_name = "N")]
+        timeout_secs: Option<u64>,
+
+        /// Print only every Nth emit per channel; the rest are counted and
+        /// reported on the `emit:dropped` channel
+        #[arg(long, value_name = "N")]
+        emit_every_n: Option<u64>,
+
+        /// Load KEY=VALUE lines from this file into the environment before
+        /// connections resolve their API keys (values override the shell's)
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<PathBuf>,
+
+        /// On a runtime error, also print a hint on how to fix it
+        /// (did-you-mean for misspelled names, advice for common failures)
+        #[arg(long)]
+        explain_error: bool,
+
+        /// Arguments passed to the program, readable via std::env::args()
+        #[arg(last = true, value_name = "ARGS")]
+        args: Vec<String>,
     },
 
     /// Run tests in a .conc source file
@@ -50,6 +116,39 @@ enum Command {
         /// Show only summary
         #[arg(short, long)]
         quiet: bool,
+
+        /// Print a machine-readable JSON report instead of the human output
+        #[arg(long)]
+        json: bool,
+
+        /// Also write a JUnit XML report to this path
+        #[arg(long, value_name = "PATH")]
+        junit: Option<PathBuf>,
+
+        /// List matching tests (one description per line) without running them
+        #[arg(long)]
+        list: bool,
+
+        /// Run tests in a shuffled order; the seed (random when omitted) is
+        /// printed so the same order can be replayed with `--shuffle=SEED`
+        #[arg(long, value_name = "SEED", num_args = 0..=1, default_missing_value = "random")]
+        shuffle: Option<String>,
+
+        /// Load KEY=VALUE lines from this file into the environment before
+        /// connections resolve their API keys (values override the shell's)
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<PathBuf>,
+    },
+
+    /// Generate a markdown reference from declarations and `///` doc comments
+    Doc {
+        /// Path to the .conc file or a project directory (default: the
+        /// current project's entry)
+        input: Option<PathBuf>,
+
+        /// Write the markdown to this path instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
     },
 
     /// Create a new Concerto project
@@ -71,10 +170,39 @@ fn main() {
             input,
             debug,
             quiet,
+            emit_format,
+            max_depth,
+            profile,
+            mock_all,
+            timeout_secs,
+            emit_every_n,
+            env_file,
+            explain_error,
+            args,
         } => {
+            if let Err(msg) = env_file.as_deref().map_or(Ok(()), load_env_file) {
+                eprintln!("{}", msg);
+                process::exit(1);
+            }
+            let input = match resolve_input(input) {
+                Ok(path) => path,
+                Err(msg) => {
+                    eprintln!("{}", msg);
+                    process::exit(1);
+                }
+            };
             let path_str = input.to_string_lossy().to_string();
 
-            let module = if is_source_file(&input) {
+            let module = if is_stdin(&input) {
+                // Piped program: read source from stdin, compile in-memory
+                match compile_stdin(quiet) {
+                    Ok(m) => m,
+                    Err(msg) => {
+                        eprintln!("{}", msg);
+                        process::exit(1);
+                    }
+                }
+            } else if is_source_file(&input) {
                 // Direct run: compile .conc in-memory, then execute
                 match compile_source(&input, quiet) {
                     Ok(m) => m,
@@ -95,19 +223,56 @@ fn main() {
             };
 
             let mut vm = VM::new(module);
+            vm.set_args(args);
+            vm.set_max_call_depth(max_depth);
+            if profile {
+                vm.enable_profiling();
+            }
+            if mock_all || mock_all_from_env() {
+                vm.set_mock_all(true);
+            }
+            if let Some(n) = emit_every_n {
+                vm.set_emit_every_n(n);
+            }
+            if let Some(secs) = timeout_secs {
+                let limit = Duration::from_secs(secs);
+                vm.set_timeout(limit);
+                // The VM only notices the deadline between instructions; a
+                // run stuck in a provider or agent call is ended from here.
+                std::thread::spawn(move || {
+                    std::thread::sleep(limit + TIMEOUT_GRACE);
+                    let err = RuntimeError::Timeout(limit);
+                    eprintln!("runtime error: {}", err);
+                    process::exit(exit_code(&err));
+                });
+            }
 
             if quiet {
                 vm.set_emit_handler(|_channel, _payload| {});
+            } else if emit_format == "ndjson" {
+                vm.set_emit_handler(|channel, payload| {
+                    println!(
+                        "{}",
+                        serde_json::json!({ "channel": channel, "payload": payload.to_json() })
+                    );
+                });
             }
 
-            match vm.execute() {
+            let result = vm.execute();
+            if let Some(profiler) = vm.profiler() {
+                eprint!("{}", profiler.report());
+            }
+            match result {
                 Ok(_) => {}
                 Err(e) => {
                     eprintln!("runtime error: {}", e);
                     if debug {
                         eprintln!("  in function: {}", vm.current_function_name());
                     }
-                    process::exit(1);
+                    if let Some(hint) = vm.explain_error(&e).filter(|_| explain_error) {
+                        eprintln!("  = help: {}", hint);
+                    }
+                    process::exit(exit_code(&e));
                 }
             }
         }
@@ -117,12 +282,44 @@ fn main() {
             filter,
             debug,
             quiet,
+            json,
+            junit,
+            list,
+            shuffle,
+            env_file,
         } => {
-            if let Err(code) = run_tests(&input, filter.as_deref(), debug, quiet) {
+            if let Err(msg) = env_file.as_deref().map_or(Ok(()), load_env_file) {
+                eprintln!("{}", msg);
+                process::exit(1);
+            }
+            let shuffle = match shuffle.as_deref().map(parse_shuffle_seed).transpose() {
+                Ok(seed) => seed,
+                Err(msg) => {
+                    eprintln!("{}", msg);
+                    process::exit(1);
+                }
+            };
+            let options = TestOptions {
+                filter: filter.as_deref(),
+                debug,
+                quiet,
+                json,
+                junit: junit.as_deref(),
+                list,
+                shuffle,
+            };
+            if let Err(code) = run_tests(&input, &options) {
                 process::exit(code);
             }
         }
 
+        Command::Doc { input, output } => {
+            if let Err(msg) = resolve_input(input).and_then(|p| run_doc(&p, output.as_deref())) {
+                eprintln!("{}", msg);
+                process::exit(1);
+            }
+        }
+
         Command::Init { name, provider } => {
             if let Err(msg) = run_init(&name, &provider) {
                 eprintln!("{}", msg);
@@ -136,6 +333,96 @@ fn main() {
 // Direct .conc compilation
 // ============================================================================
 
+/// Diagnostic label for programs read from stdin.
+const STDIN_FILE_NAME: &str = "<stdin>";
+
+/// Whether `CONCERTO_MOCK_ALL` asks for mock-all mode (any value but empty,
+/// `0` or `false`).
+fn mock_all_from_env() -> bool {
+    std::env::var("CONCERTO_MOCK_ALL")
+        .map(|v| !matches!(v.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+/// Load an env file into the process environment. Runs before the VM is
+/// built, so connections see its values when resolving `api_key_env`.
+fn load_env_file(path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("error: cannot read env file '{}': {}", path.display(), e))?;
+    let vars = parse_env_file(&content).map_err(|e| format!("error: {}: {}", path.display(), e))?;
+    for (key, value) in vars {
+        std::env::set_var(key, value);
+    }
+    Ok(())
+}
+
+/// Parse `KEY=VALUE` lines. Blank lines and `#` comments are skipped, an
+/// `export ` prefix is allowed, and matching single or double quotes around
+/// the value are stripped.
+fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    for (idx, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=VALUE", idx + 1));
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("line {}: invalid variable name '{}'", idx + 1, key));
+        }
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+        vars.push((key.to_string(), value.to_string()));
+    }
+    Ok(vars)
+}
+
+/// Check if the input is the `-` stdin sentinel.
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Resolve a command's input path: a file is used as-is, while a directory
+/// (or no path) resolves to the project entry.
+fn resolve_input(input: Option<PathBuf>) -> Result<PathBuf, String> {
+    match input {
+        Some(path) if !path.is_dir() => Ok(path),
+        dir => resolve_project_entry(dir.as_deref().unwrap_or(Path::new("."))),
+    }
+}
+
+/// Resolve the entry file of the project containing `dir`, from the
+/// `[project].entry` of the nearest Concerto.toml at or above it.
+fn resolve_project_entry(dir: &Path) -> Result<PathBuf, String> {
+    use concerto_common::manifest;
+
+    let abs_dir = fs::canonicalize(dir)
+        .map_err(|e| format!("error: could not read '{}': {}", dir.display(), e))?;
+    let manifest_path = manifest::find_manifest(&abs_dir).ok_or_else(|| {
+        format!(
+            "error: no Concerto.toml found in '{}' or its parents; pass a .conc file to run",
+            dir.display()
+        )
+    })?;
+    let manifest = manifest::load_manifest(&manifest_path).map_err(|e| format!("error: {}", e))?;
+    let entry = manifest.entry_path();
+    if !entry.is_file() {
+        return Err(format!(
+            "error: project entry '{}' not found (from {})",
+            entry.display(),
+            manifest_path.display()
+        ));
+    }
+    Ok(entry)
+}
+
 /// Check if the input file is a .conc source file (not .conc-ir).
 fn is_source_file(path: &Path) -> bool {
     let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -144,13 +431,6 @@ fn is_source_file(path: &Path) -> bool {
 
 /// Compile a .conc source file in-memory and return a LoadedModule.
 fn compile_source(path: &Path, quiet: bool) -> Result<LoadedModule, String> {
-    use concerto_common::ir::IrConnection;
-    use concerto_common::manifest;
-    use concerto_compiler::codegen::CodeGenerator;
-    use concerto_compiler::lexer::Lexer;
-    use concerto_compiler::parser;
-
-    // Read source
     let source = fs::read_to_string(path)
         .map_err(|e| format!("error: could not read '{}': {}", path.display(), e))?;
 
@@ -160,10 +440,44 @@ fn compile_source(path: &Path, quiet: bool) -> Result<LoadedModule, String> {
         .to_string_lossy()
         .to_string();
 
-    // Find and load Concerto.toml
     let abs_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    compile_source_str(&source, &file_name, &abs_path, quiet)
+}
+
+/// Compile a program read from stdin (`concerto run -`).
+///
+/// Concerto.toml is searched for starting from the current directory.
+fn compile_stdin(quiet: bool) -> Result<LoadedModule, String> {
+    use std::io::Read;
+
+    let mut source = String::new();
+    std::io::stdin()
+        .read_to_string(&mut source)
+        .map_err(|e| format!("error: could not read stdin: {}", e))?;
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    compile_source_str(&source, STDIN_FILE_NAME, &cwd.join(STDIN_FILE_NAME), quiet)
+}
+
+/// Compile source text in-memory and return a LoadedModule.
+///
+/// `file_name` labels diagnostics and names the module; `manifest_anchor` is
+/// the path whose parent directory starts the Concerto.toml search.
+fn compile_source_str(
+    source: &str,
+    file_name: &str,
+    manifest_anchor: &Path,
+    quiet: bool,
+) -> Result<LoadedModule, String> {
+    use concerto_common::ir::IrConnection;
+    use concerto_common::manifest;
+    use concerto_compiler::codegen::CodeGenerator;
+    use concerto_compiler::lexer::Lexer;
+    use concerto_compiler::parser;
+
+    // Find and load Concerto.toml
     let (connection_names, ir_connections, manifest_agents) =
-        match manifest::find_and_load_manifest(&abs_path) {
+        match manifest::find_and_load_manifest(manifest_anchor) {
             Ok(m) => {
                 let names: Vec<String> = m.connections.keys().cloned().collect();
                 let ir_conns: Vec<IrConnection> = m
@@ -184,11 +498,11 @@ fn compile_source(path: &Path, quiet: bool) -> Result<LoadedModule, String> {
         };
 
     // Lex
-    let (tokens, lex_diags) = Lexer::new(&source, &file_name).tokenize();
+    let (tokens, lex_diags) = Lexer::new(source, file_name).tokenize();
     if lex_diags.has_errors() {
         let mut msg = String::new();
         for diag in lex_diags.diagnostics() {
-            msg.push_str(&format_diagnostic(diag, &source, &file_name));
+            msg.push_str(&format_diagnostic(diag, source, file_name));
         }
         return Err(msg);
     }
@@ -198,14 +512,14 @@ fn compile_source(path: &Path, quiet: bool) -> Result<LoadedModule, String> {
     if parse_diags.has_errors() {
         let mut msg = String::new();
         for diag in parse_diags.diagnostics() {
-            msg.push_str(&format_diagnostic(diag, &source, &file_name));
+            msg.push_str(&format_diagnostic(diag, source, file_name));
         }
         return Err(msg);
     }
     if !quiet {
         for diag in parse_diags.diagnostics() {
             if !diag.is_error() {
-                eprint!("{}", format_diagnostic(diag, &source, &file_name));
+                eprint!("{}", format_diagnostic(diag, source, file_name));
             }
         }
     }
@@ -216,26 +530,26 @@ fn compile_source(path: &Path, quiet: bool) -> Result<LoadedModule, String> {
     if sem_diags.has_errors() {
         let mut msg = String::new();
         for diag in sem_diags.diagnostics() {
-            msg.push_str(&format_diagnostic(diag, &source, &file_name));
+            msg.push_str(&format_diagnostic(diag, source, file_name));
         }
         return Err(msg);
     }
     if !quiet {
         for diag in sem_diags.diagnostics() {
             if !diag.is_error() {
-                eprint!("{}", format_diagnostic(diag, &source, &file_name));
+                eprint!("{}", format_diagnostic(diag, source, file_name));
             }
         }
     }
 
     // Codegen
-    let module_name = path
+    let module_name = Path::new(file_name)
         .file_stem()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
 
-    let mut codegen = CodeGenerator::new(&module_name, &file_name);
+    let mut codegen = CodeGenerator::new(&module_name, file_name);
     codegen.add_manifest_connections(ir_connections);
     let mut ir = codegen.generate(&program);
 
@@ -281,9 +595,66 @@ fn format_diagnostic(
 // concerto test
 // ============================================================================
 
-fn run_tests(input: &Path, filter: Option<&str>, debug: bool, quiet: bool) -> Result<(), i32> {
+/// Command-line options for `concerto test`.
+struct TestOptions<'a> {
+    filter: Option<&'a str>,
+    debug: bool,
+    quiet: bool,
+    json: bool,
+    junit: Option<&'a Path>,
+    list: bool,
+    /// Seed for `--shuffle`; `None` keeps declaration order.
+    shuffle: Option<u64>,
+}
+
+/// Parse a `--shuffle` value: a `u64` seed, or `random` (the value used when
+/// the flag is given bare) for a seed taken from the clock.
+fn parse_shuffle_seed(value: &str) -> Result<u64, String> {
+    if value == "random" {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        return Ok(nanos as u64 ^ u64::from(process::id()));
+    }
+    value.parse().map_err(|_| {
+        format!(
+            "error: invalid --shuffle seed '{}' (expected an unsigned integer)",
+            value
+        )
+    })
+}
+
+/// Fisher-Yates shuffle driven by splitmix64, so a seed always yields the
+/// same order on every platform.
+fn shuffle_seeded<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+fn run_tests(input: &Path, options: &TestOptions) -> Result<(), i32> {
+    let TestOptions {
+        filter,
+        debug,
+        quiet,
+        json,
+        junit,
+        list,
+        shuffle,
+    } = *options;
+
     // Compile source for tests (permissive — no entry point required)
-    let module = match compile_source_for_tests(input, quiet) {
+    let module = match compile_source_for_tests(input, quiet || json || list) {
         Ok(m) => m,
         Err(msg) => {
             eprintln!("{}", msg);
@@ -291,8 +662,8 @@ fn run_tests(input: &Path, filter: Option<&str>, debug: bool, quiet: bool) -> Re
         }
     };
 
-    // Filter tests by description
-    let tests: Vec<_> = module
+    // Filter tests by description; the order is declaration order unless shuffled
+    let mut tests: Vec<_> = module
         .tests
         .iter()
         .filter(|t| {
@@ -303,6 +674,40 @@ fn run_tests(input: &Path, filter: Option<&str>, debug: bool, quiet: bool) -> Re
             }
         })
         .collect();
+    if let Some(seed) = shuffle {
+        shuffle_seeded(&mut tests, seed);
+        // Keep stdout clean for --list and --json consumers
+        if json || list || quiet {
+            eprintln!("shuffling tests with --shuffle={}", seed);
+        } else {
+            println!("shuffling tests with --shuffle={}", seed);
+        }
+    }
+
+    // Listing never runs tests, and an empty list is not an error
+    if list {
+        if json {
+            let entries: Vec<serde_json::Value> = tests
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "description": t.description,
+                        "expect_fail": t.expect_fail,
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "tests": entries }))
+                    .unwrap_or_default()
+            );
+        } else {
+            for test in &tests {
+                println!("{}", test.description);
+            }
+        }
+        return Ok(());
+    }
 
     if tests.is_empty() {
         if filter.is_some() {
@@ -313,89 +718,60 @@ fn run_tests(input: &Path, filter: Option<&str>, debug: bool, quiet: bool) -> Re
         return Err(1);
     }
 
-    if !quiet {
+    // Human output is replaced entirely by the JSON report
+    let human = !json && !quiet;
+
+    if human {
         println!("running {} tests\n", tests.len());
     }
 
-    let mut passed = 0;
-    let mut failed = 0;
-    let mut failures: Vec<(String, String)> = Vec::new();
-
+    let mut outcomes = Vec::with_capacity(tests.len());
     for test in &tests {
-        // Each test gets a fresh VM instance
-        let mut vm = VM::new(module.clone());
-
-        // Suppress emit output during tests unless debug mode
-        if !debug {
-            vm.set_emit_handler(|_channel, _payload| {});
+        // Emits would interleave with the JSON report, so only show them in debug text mode
+        let outcome = run_test_case(&module, test, debug && !json);
+        if human {
+            if outcome.passed {
+                let note = if test.expect_fail {
+                    " (expected failure)"
+                } else {
+                    ""
+                };
+                println!("  \x1b[32mPASS\x1b[0m  {}{}", outcome.description, note);
+            } else {
+                println!("  \x1b[31mFAIL\x1b[0m  {}", outcome.description);
+            }
         }
+        outcomes.push(outcome);
+    }
 
-        let result = vm.run_test(test);
+    let failed = outcomes.iter().filter(|o| !o.passed).count();
 
-        if test.expect_fail {
-            match result {
-                Ok(_) => {
-                    // Expected failure but test passed
-                    failed += 1;
-                    if !quiet {
-                        println!("  \x1b[31mFAIL\x1b[0m  {}", test.description);
-                    }
-                    failures.push((
-                        test.description.clone(),
-                        "expected failure but test passed".to_string(),
-                    ));
-                }
-                Err(e) => {
-                    let err_msg = e.to_string();
-                    if let Some(ref expected_msg) = test.expect_fail_message {
-                        if err_msg.contains(expected_msg.as_str()) {
-                            passed += 1;
-                            if !quiet {
-                                println!("  \x1b[32mPASS\x1b[0m  {} (expected failure)", test.description);
-                            }
-                        } else {
-                            failed += 1;
-                            if !quiet {
-                                println!("  \x1b[31mFAIL\x1b[0m  {}", test.description);
-                            }
-                            failures.push((
-                                test.description.clone(),
-                                format!(
-                                    "expected error containing '{}', got: {}",
-                                    expected_msg, err_msg
-                                ),
-                            ));
-                        }
-                    } else {
-                        // Any failure is acceptable
-                        passed += 1;
-                        if !quiet {
-                            println!("  \x1b[32mPASS\x1b[0m  {} (expected failure)", test.description);
-                        }
-                    }
-                }
-            }
-        } else {
-            match result {
-                Ok(_) => {
-                    passed += 1;
-                    if !quiet {
-                        println!("  \x1b[32mPASS\x1b[0m  {}", test.description);
-                    }
-                }
-                Err(e) => {
-                    failed += 1;
-                    let err_msg = e.to_string();
-                    if !quiet {
-                        println!("  \x1b[31mFAIL\x1b[0m  {}", test.description);
-                    }
-                    failures.push((test.description.clone(), err_msg));
-                }
-            }
+    if let Some(path) = junit {
+        let suite = input
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if let Err(e) = fs::write(path, junit_report(&suite, &outcomes)) {
+            eprintln!(
+                "error: failed to write JUnit report '{}': {}",
+                path.display(),
+                e
+            );
+            return Err(1);
         }
     }
 
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json_report(&outcomes)).unwrap_or_default()
+        );
+        return if failed == 0 { Ok(()) } else { Err(1) };
+    }
+
     // Print summary
+    let passed = outcomes.len() - failed;
     println!();
     if failed == 0 {
         println!(
@@ -409,13 +785,102 @@ fn run_tests(input: &Path, filter: Option<&str>, debug: bool, quiet: bool) -> Re
             passed, failed
         );
         println!("\nfailures:");
-        for (desc, err) in &failures {
-            println!("  {} -- {}", desc, err);
+        for outcome in outcomes.iter().filter(|o| !o.passed) {
+            println!(
+                "  {} -- {}",
+                outcome.description,
+                outcome.error.as_deref().unwrap_or_default()
+            );
         }
         Err(1)
     }
 }
 
+/// Result of running a single `@test` function.
+struct TestOutcome {
+    description: String,
+    passed: bool,
+    duration_ms: u128,
+    /// Failure reason; `None` when the test passed.
+    error: Option<String>,
+}
+
+/// Run one test in a fresh VM, applying `@expect_fail` semantics.
+fn run_test_case(
+    module: &LoadedModule,
+    test: &concerto_common::ir::IrTest,
+    show_emits: bool,
+) -> TestOutcome {
+    // Each test gets a fresh VM instance
+    let mut vm = VM::new(module.clone());
+
+    // Suppress emit output during tests unless debug mode
+    if !show_emits {
+        vm.set_emit_handler(|_channel, _payload| {});
+    }
+
+    let start = Instant::now();
+    let result = vm.run_test(test);
+    let duration_ms = start.elapsed().as_millis();
+
+    let error = match (test.expect_fail, result) {
+        (false, Ok(_)) => None,
+        (false, Err(e)) => Some(e.to_string()),
+        // Expected failure but test passed
+        (true, Ok(_)) => Some("expected failure but test passed".to_string()),
+        (true, Err(e)) => {
+            let err_msg = e.to_string();
+            match test.expect_fail_message {
+                Some(ref expected_msg) if !err_msg.contains(expected_msg.as_str()) => Some(
+                    format!(
+                        "expected error containing '{}', got: {}",
+                        expected_msg, err_msg
+                    ),
+                ),
+                // Any failure is acceptable
+                _ => None,
+            }
+        }
+    };
+
+    TestOutcome {
+        description: test.description.clone(),
+        passed: error.is_none(),
+        duration_ms,
+        error,
+    }
+}
+
+/// Build the `--json` report: one entry per test plus a summary object.
+fn json_report(outcomes: &[TestOutcome]) -> serde_json::Value {
+    let tests: Vec<serde_json::Value> = outcomes
+        .iter()
+        .map(|o| {
+            let mut entry = serde_json::json!({
+                "description": o.description,
+                "status": if o.passed { "pass" } else { "fail" },
+                "duration_ms": o.duration_ms as u64,
+            });
+            if let Some(ref error) = o.error {
+                entry["error"] = serde_json::json!(error);
+            }
+            entry
+        })
+        .collect();
+
+    let passed = outcomes.iter().filter(|o| o.passed).count();
+    let duration_ms: u128 = outcomes.iter().map(|o| o.duration_ms).sum();
+    serde_json::json!({
+        "tests": tests,
+        "summary": {
+            "total": outcomes.len(),
+            "passed": passed,
+            "failed": outcomes.len() - passed,
+            "duration_ms": duration_ms as u64,
+        },
+    })
+}
+
 /// Compile a .conc source file for test execution (permissive loading).
 fn compile_source_for_tests(path: &Path, quiet: bool) -> Result<LoadedModule, String> {
     use concerto_common::ir::IrConnection;
@@ -521,6 +986,55 @@ fn compile_source_for_tests(path: &Path, quiet: bool) -> Result<LoadedModule, St
         .map_err(|e| format!("error: failed to load IR module: {}", e))
 }
 
+// ============================================================================
+// concerto doc
+// ============================================================================
+
+/// Parse `input` and write its markdown reference to `output` (or stdout).
+fn run_doc(input: &Path, output: Option<&Path>) -> Result<(), String> {
+    use concerto_compiler::docgen;
+    use concerto_compiler::lexer::Lexer;
+    use concerto_compiler::parser;
+
+    let source = fs::read_to_string(input)
+        .map_err(|e| format!("error: could not read '{}': {}", input.display(), e))?;
+    let file_name = input
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    let (tokens, lex_diags) = Lexer::new(&source, &file_name).tokenize();
+    let (program, parse_diags) = parser::Parser::new(tokens).parse();
+    if lex_diags.has_errors() || parse_diags.has_errors() {
+        let mut msg = String::new();
+        for diag in lex_diags
+            .diagnostics()
+            .iter()
+            .chain(parse_diags.diagnostics())
+        {
+            if diag.is_error() {
+                msg.push_str(&format_diagnostic(diag, &source, &file_name));
+            }
+        }
+        return Err(msg);
+    }
+
+    let title = input
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let markdown = docgen::render_markdown(&program, &title);
+    match output {
+        Some(path) => write_file(path, &markdown),
+        None => {
+            print!("{}", markdown);
+            Ok(())
+        }
+    }
+}
+
 // ============================================================================
 // concerto init
 // ============================================================================
@@ -684,3 +1198,238 @@ fn main() {{
         default_model = default_model,
     )
 }
+
+/// Build a JUnit XML report (`<testsuites>` with one `<testsuite>` per file).
+fn junit_report(suite: &str, outcomes: &[TestOutcome]) -> String {
+    let failed = outcomes.iter().filter(|o| !o.passed).count();
+    let total_ms: u128 = outcomes.iter().map(|o| o.duration_ms).sum();
+    let seconds = |ms: u128| format!("{:.3}", ms as f64 / 1000.0);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"concerto\" tests=\"{}\" failures=\"{}\" time=\"{}\">\n",
+        outcomes.len(),
+        failed,
+        seconds(total_ms)
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{}\">\n",
+        xml_escape(suite),
+        outcomes.len(),
+        failed,
+        seconds(total_ms)
+    ));
+    for o in outcomes {
+        let open = format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+            xml_escape(&o.description),
+            xml_escape(suite),
+            seconds(o.duration_ms)
+        );
+        match o.error {
+            Some(ref error) => {
+                let error = xml_escape(error);
+                xml.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    open, error, error
+                ));
+            }
+            None => xml.push_str(&format!("{}/>\n", open)),
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Escape text for use in XML attributes and element content.
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_errors_map_to_exit_codes() {
+        let cases = [
+            (RuntimeError::UnhandledThrow("boom".into()), 2),
+            (RuntimeError::Timeout(Duration::from_secs(1)), 3),
+            (RuntimeError::ProviderTimeout(Duration::from_secs(30)), 3),
+            (RuntimeError::StackOverflow(1000), 4),
+            (RuntimeError::DivisionByZero, 1),
+            (RuntimeError::CallError("provider failed".into()), 1),
+        ];
+        for (err, code) in cases {
+            assert_eq!(exit_code(&err), code, "{}", err);
+        }
+    }
+
+    #[test]
+    fn shuffle_is_a_seeded_permutation() {
+        let shuffled = |seed| {
+            let mut items: Vec<u32> = (0..20).collect();
+            shuffle_seeded(&mut items, seed);
+            items
+        };
+        assert_eq!(shuffled(42), shuffled(42));
+        assert_ne!(shuffled(42), shuffled(43));
+        let mut sorted = shuffled(42);
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+
+        assert_eq!(parse_shuffle_seed("7"), Ok(7));
+        assert!(parse_shuffle_seed("-1").is_err());
+    }
+
+    #[test]
+    fn env_file_lines_parse_to_pairs() {
+        let vars = parse_env_file(
+            "# keys\nOPENAI_API_KEY=sk-test\n\nexport REGION = \"eu west\"\nEMPTY=\nTOKEN='a=b'\n",
+        )
+        .unwrap();
+        let expected = [
+            ("OPENAI_API_KEY", "sk-test"),
+            ("REGION", "eu west"),
+            ("EMPTY", ""),
+            ("TOKEN", "a=b"),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(vars, expected);
+
+        let err = parse_env_file("OK=1\nnot a pair\n").unwrap_err();
+        assert_eq!(err, "line 2: expected KEY=VALUE");
+        assert!(parse_env_file("BAD KEY=1").is_err());
+    }
+
+    /// Compile and run a file with one passing and one failing test.
+    fn run_fixture(tag: &str) -> Vec<TestOutcome> {
+        let dir = std::env::temp_dir().join(format!("concerto-{}-{}", tag, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("report.conc");
+        fs::write(
+            &path,
+            r#"
+            @test("adds numbers")
+            fn adds() {
+                assert_eq(1 + 1, 2);
+            }
+
+            @test("fails on purpose")
+            fn fails() {
+                assert_eq(1, 2);
+            }
+            "#,
+        )
+        .unwrap();
+
+        let module = compile_source_for_tests(&path, true).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        module
+            .tests
+            .iter()
+            .map(|t| run_test_case(&module, t, false))
+            .collect()
+    }
+
+    #[test]
+    fn json_report_reflects_pass_fail_counts() {
+        let outcomes = run_fixture("json-report");
+        let text = serde_json::to_string(&json_report(&outcomes)).unwrap();
+
+        let report: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(report["summary"]["total"], 2);
+        assert_eq!(report["summary"]["passed"], 1);
+        assert_eq!(report["summary"]["failed"], 1);
+
+        let tests = report["tests"].as_array().unwrap();
+        assert_eq!(tests[0]["description"], "adds numbers");
+        assert_eq!(tests[0]["status"], "pass");
+        assert!(tests[0].get("error").is_none());
+        assert!(tests[0]["duration_ms"].is_u64());
+        assert_eq!(tests[1]["status"], "fail");
+        assert!(tests[1]["error"].as_str().unwrap().contains("assert"));
+    }
+
+    #[test]
+    fn junit_report_structure_and_failure_node() {
+        let outcomes = run_fixture("junit-report");
+        let xml = junit_report("report", &outcomes);
+
+        assert!(xml.starts_with("<?xml version=\"1.0\""));
+        assert!(xml.contains("<testsuites name=\"concerto\" tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testsuite name=\"report\" tests=\"2\" failures=\"1\""));
+        assert!(xml.trim_end().ends_with("</testsuites>"));
+        assert_eq!(xml.matches("<testcase ").count(), 2);
+
+        // The passing test is a self-closing testcase; the failing one wraps a <failure>
+        assert!(xml.contains("<testcase name=\"adds numbers\" classname=\"report\""));
+        let failing = xml.find("<testcase name=\"fails on purpose\"").unwrap();
+        let failure = &xml[failing..][xml[failing..].find("<failure message=").unwrap()..];
+        assert!(failure.contains("assertion failed: 1 != 2</failure>"));
+        assert_eq!(xml.matches("<failure ").count(), 1);
+    }
+
+    #[test]
+    fn run_collects_program_args_after_double_dash() {
+        let cli = Cli::try_parse_from([
+            "concerto",
+            "run",
+            "main.conc",
+            "--quiet",
+            "--",
+            "input.txt",
+            "--verbose",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Run { quiet, args, .. } => {
+                assert!(quiet);
+                assert_eq!(args, vec!["input.txt", "--verbose"]);
+            }
+            _ => panic!("expected run command"),
+        }
+    }
+
+    #[test]
+    fn run_max_depth_defaults_and_overrides() {
+        let cli = Cli::try_parse_from(["concerto", "run", "main.conc"]).unwrap();
+        match cli.command {
+            Command::Run { max_depth, .. } => assert_eq!(max_depth, DEFAULT_MAX_CALL_DEPTH),
+            _ => panic!("expected run command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["concerto", "run", "main.conc", "--max-depth", "50"]).unwrap();
+        match cli.command {
+            Command::Run { max_depth, .. } => assert_eq!(max_depth, 50),
+            _ => panic!("expected run command"),
+        }
+    }
+
+    #[test]
+    fn xml_escape_special_characters() {
+        assert_eq!(
+            xml_escape(r#"a < b && "c" > 'd'"#),
+            "a &lt; b &amp;&amp; &quot;c&quot; &gt; &apos;d&apos;"
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! End-to-end tests that drive the `concerto` binary.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn run_reads_program_from_stdin() {
+    let (code, stdout, stderr) = run_stdin_exit(
+        "fn main() {\n    let n = 40 + 2;\n    println(\"answer: ${n}\");\n}\n",
+        &[],
+    );
+    assert_eq!(code, Some(0), "stderr: {}", stderr);
+    assert_eq!(stdout, "answer: 42\n");
+}
+
+#[test]
+fn run_stdin_reports_diagnostics_against_stdin() {
+    let (code, _, stderr) = run_stdin_exit("fn main() {\n    print(missing);\n}\n", &[]);
+    assert_ne!(code, Some(0));
+    assert!(stderr.contains("<stdin>:2:"), "stderr: {}", stderr);
+}
+
+#[test]
+fn run_project_directory_executes_manifest_entry() {
+    let project = std::env::temp_dir().join(format!("concerto_cli_project_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&project);
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    std::fs::write(
+        project.join("Concerto.toml"),
+        "[project]\nname = \"demo\"\nversion = \"0.1.0\"\nentry = \"src/app.conc\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.join("src/app.conc"),
+        "fn main() {\n    println(\"entry ran\");\n}\n",
+    )
+    .unwrap();
+
+    // By directory path
+    let by_dir = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("run")
+        .arg(&project)
+        .output()
+        .expect("spawn concerto");
+    // With no path, from a subdirectory of the project
+    let by_cwd = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("run")
+        .current_dir(project.join("src"))
+        .output()
+        .expect("spawn concerto");
+    std::fs::remove_dir_all(&project).unwrap();
+
+    for output in [by_dir, by_cwd] {
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "entry ran\n");
+    }
+}
+
+#[test]
+fn doc_includes_documented_function_signature() {
+    let file = std::env::temp_dir().join(format!("concerto_cli_doc_{}.conc", std::process::id()));
+    std::fs::write(
+        &file,
+        "/// Greets someone by name.\npub fn greet(name: String, times: Int = 1) -> String {\n    return name;\n}\n\nfn main() {\n    println(greet(\"Ada\"));\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("doc")
+        .arg(&file)
+        .output()
+        .expect("spawn concerto");
+    let _ = std::fs::remove_file(&file);
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let markdown = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        markdown.contains(
+            "### `greet`\n\n```concerto\npub fn greet(name: String, times: Int = 1) -> String\n```\n\nGreets someone by name.\n"
+        ),
+        "markdown: {}",
+        markdown
+    );
+}
+
+#[test]
+fn run_profile_reports_call_counts() {
+    let (code, stdout, stderr) = run_stdin_exit(
+        "fn square(n: Int) -> Int {\n    return n * n;\n}\n\nfn main() {\n    let mut total = 0;\n    for i in 0..3 {\n        total = total + square(i);\n    }\n    println(\"${total}\");\n}\n",
+        &["--profile"],
+    );
+    assert_eq!(code, Some(0), "stderr: {}", stderr);
+    assert_eq!(stdout, "5\n");
+    assert!(stderr.starts_with("function"), "stderr: {}", stderr);
+    let calls = |name: &str| -> u64 {
+        let row = stderr
+            .lines()
+            .find(|l| l.split_whitespace().next() == Some(name))
+            .unwrap_or_else(|| panic!("no row for {} in: {}", name, stderr));
+        row.split_whitespace().nth(1).unwrap().parse().unwrap()
+    };
+    assert_eq!(calls("main"), 1);
+    assert_eq!(calls("square"), 3);
+}
+
+#[test]
+fn run_emit_format_ndjson_prints_one_compact_object_per_line() {
+    let (code, stdout, stderr) = run_stdin_exit(
+        "fn main() {\n    emit(\"step\", {\"n\": 1, \"tags\": [\"a\", \"b\"]});\n    emit(\"done\", true);\n}\n",
+        &["--emit-format", "ndjson"],
+    );
+    assert_eq!(code, Some(0), "stderr: {}", stderr);
+    assert_eq!(
+        stdout,
+        "{\"channel\":\"step\",\"payload\":{\"n\":1,\"tags\":[\"a\",\"b\"]}}\n{\"channel\":\"done\",\"payload\":true}\n"
+    );
+}
+
+#[test]
+fn test_list_prints_descriptions_without_running() {
+    let file = std::env::temp_dir().join(format!("concerto_cli_list_{}.conc", std::process::id()));
+    std::fs::write(
+        &file,
+        "@test(\"auth: accepts valid token\")\nfn a() {\n    assert(true);\n}\n\n@test(\"auth: rejects expired token\")\nfn b() {\n    assert(false);\n}\n\n@test(\"billing: totals invoice\")\nfn c() {\n    panic(\"must not run\");\n}\n",
+    )
+    .unwrap();
+
+    let list = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_concerto"))
+            .arg("test")
+            .arg(&file)
+            .arg("--list")
+            .args(extra)
+            .output()
+            .expect("spawn concerto");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let all = list(&[]);
+    let filtered = list(&["--filter", "auth"]);
+    let _ = std::fs::remove_file(&file);
+
+    assert_eq!(
+        all,
+        "auth: accepts valid token\nauth: rejects expired token\nbilling: totals invoice\n"
+    );
+    assert_eq!(
+        filtered,
+        "auth: accepts valid token\nauth: rejects expired token\n"
+    );
+}
+
+#[test]
+fn run_mock_all_answers_models_and_agents_offline() {
+    let project =
+        std::env::temp_dir().join(format!("concerto_cli_mock_all_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&project);
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    // Nothing listens on port 9 and the agent command does not exist, so
+    // any real call would fail.
+    std::fs::write(
+        project.join("Concerto.toml"),
+        "[project]\nname = \"offline\"\nversion = \"0.1.0\"\nentry = \"src/main.conc\"\n\n\
+         [connections.local]\nprovider = \"ollama\"\nbase_url = \"http://127.0.0.1:9/v1\"\n\n\
+         [agents.worker]\ntransport = \"stdio\"\ncommand = \"concerto-no-such-agent\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.join("src/main.conc"),
+        "model Writer {\n    provider: local,\n    base: \"llama3\",\n}\n\n\
+         agent Worker {\n    connector: \"worker\",\n}\n\n\
+         fn main() {\n\
+         \x20   match Writer.execute(\"draft a haiku\") {\n\
+         \x20       Ok(r) => println(r.text),\n\
+         \x20       Err(e) => println(\"model failed: ${e}\"),\n\
+         \x20   }\n\
+         \x20   match Worker.execute(\"review it\") {\n\
+         \x20       Ok(text) => println(text),\n\
+         \x20       Err(e) => println(\"agent failed: ${e}\"),\n\
+         \x20   }\n\
+         }\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("run")
+        .arg(&project)
+        .env("CONCERTO_MOCK_ALL", "1")
+        .output()
+        .expect("spawn concerto");
+    std::fs::remove_dir_all(&project).unwrap();
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "[mock response to: draft a haiku]\n[mock response to: review it]\n"
+    );
+}
+
+#[test]
+fn run_timeout_secs_stops_a_sleeping_program() {
+    let file =
+        std::env::temp_dir().join(format!("concerto_cli_timeout_{}.conc", std::process::id()));
+    std::fs::write(
+        &file,
+        "fn main() {\n    println(\"started\");\n    std::time::sleep(30000);\n    println(\"finished\");\n}\n",
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .args(["run", "--timeout-secs", "1"])
+        .arg(&file)
+        .output()
+        .expect("spawn concerto");
+    let elapsed = started.elapsed();
+    std::fs::remove_file(&file).unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(
+        elapsed < std::time::Duration::from_secs(10),
+        "took {:?}",
+        elapsed
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "started\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("runtime error: timeout: run exceeded 1s"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+/// Run a program from stdin and return its exit code, stdout and stderr.
+fn run_stdin_exit(source: &str, extra: &[&str]) -> (Option<i32>, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .args(["run", "-"])
+        .args(extra)
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn concerto");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+#[test]
+fn run_exit_codes_distinguish_error_kinds() {
+    let (code, _, stderr) = run_stdin_exit("fn main() {\n    panic(\"bad state\");\n}\n", &[]);
+    assert_eq!(code, Some(2), "stderr: {}", stderr);
+
+    let (code, _, stderr) = run_stdin_exit(
+        "fn main() {\n    let r: Result<Int, String> = Err(\"boom\");\n    println(r.unwrap());\n}\n",
+        &[],
+    );
+    assert_eq!(code, Some(2), "stderr: {}", stderr);
+
+    let (code, _, stderr) = run_stdin_exit(
+        "fn down(n: Int) -> Int {\n    return down(n + 1);\n}\nfn main() {\n    down(0);\n}\n",
+        &["--max-depth", "50"],
+    );
+    assert_eq!(code, Some(4), "stderr: {}", stderr);
+
+    let (code, _, stderr) = run_stdin_exit(
+        "fn main() {\n    let z = 0;\n    println(10 / z);\n}\n",
+        &[],
+    );
+    assert_eq!(code, Some(1), "stderr: {}", stderr);
+}
+
+#[test]
+fn run_env_file_supplies_connection_keys() {
+    let project =
+        std::env::temp_dir().join(format!("concerto_cli_env_file_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&project);
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    let key_var = format!("CONCERTO_CLI_ENV_FILE_KEY_{}", std::process::id());
+    std::fs::write(
+        project.join("Concerto.toml"),
+        format!(
+            "[project]\nname = \"keyed\"\nversion = \"0.1.0\"\nentry = \"src/main.conc\"\n\n\
+             [connections.openai]\nprovider = \"openai\"\napi_key_env = \"{}\"\n",
+            key_var
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        project.join("src/main.conc"),
+        format!("fn main() {{\n    println(env(\"{}\"));\n}}\n", key_var),
+    )
+    .unwrap();
+    let env_path = project.join("test.env");
+    std::fs::write(
+        &env_path,
+        format!("# local keys\n{}=\"sk-from-file\"\n", key_var),
+    )
+    .unwrap();
+
+    let with_file = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("run")
+        .arg(&project)
+        .arg("--env-file")
+        .arg(&env_path)
+        .env_remove(&key_var)
+        .output()
+        .expect("spawn concerto");
+    let without_file = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("run")
+        .arg(&project)
+        .env_remove(&key_var)
+        .output()
+        .expect("spawn concerto");
+    let missing_file = Command::new(env!("CARGO_BIN_EXE_concerto"))
+        .arg("run")
+        .arg(&project)
+        .arg("--env-file")
+        .arg(project.join("missing.env"))
+        .output()
+        .expect("spawn concerto");
+    std::fs::remove_dir_all(&project).unwrap();
+
+    let stderr = String::from_utf8_lossy(&with_file.stderr);
+    assert!(with_file.status.success(), "stderr: {}", stderr);
+    assert!(
+        !stderr.contains("using mock provider"),
+        "stderr: {}",
+        stderr
+    );
+    assert_eq!(String::from_utf8_lossy(&with_file.stdout), "sk-from-file\n");
+
+    let stderr = String::from_utf8_lossy(&without_file.stderr);
+    assert!(
+        stderr.contains("connection 'openai': using mock provider"),
+        "stderr: {}",
+        stderr
+    );
+
+    assert_eq!(missing_file.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&missing_file.stderr).contains("cannot read env file"));
+}
+
+#[test]
+fn test_shuffle_seed_replays_the_same_order() {
+    let file =
+        std::env::temp_dir().join(format!("concerto_cli_shuffle_{}.conc", std::process::id()));
+    let source: String = (0..8)
+        .map(|i| {
+            format!(
+                "@test(\"case {}\")\nfn t{}() {{\n    assert(true);\n}}\n\n",
+                i, i
+            )
+        })
+        .collect();
+    std::fs::write(&file, source).unwrap();
+
+    let list = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_concerto"))
+            .arg("test")
+            .arg(&file)
+            .arg("--list")
+            .args(extra)
+            .output()
+            .expect("spawn concerto");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        (
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+    let declared = list(&[]);
+    let first = list(&["--shuffle", "1234"]);
+    let second = list(&["--shuffle=1234"]);
+    let random = list(&["--shuffle"]);
+    let _ = std::fs::remove_file(&file);
+
+    let in_order: String = (0..8).map(|i| format!("case {}\n", i)).collect();
+    assert_eq!(declared.0, in_order);
+    assert_eq!(first.0, second.0);
+    assert_ne!(first.0, in_order);
+    assert!(first.1.contains("--shuffle=1234"), "stderr: {}", first.1);
+
+    // A bare --shuffle picks a seed and reports it for replay
+    let seed = random.1.trim().rsplit('=').next().unwrap().to_string();
+    assert!(seed.parse::<u64>().is_ok(), "stderr: {}", random.1);
+}
+
+#[test]
+fn run_explain_error_suggests_misspelled_function() {
+    let source = "fn main() {\n    println(std::string::to_uppr(\"quiet\"));\n}\n";
+    let (code, _, plain) = run_stdin_exit(source, &[]);
+    let (explained_code, _, explained) = run_stdin_exit(source, &["--explain-error"]);
+
+    assert_eq!(code, Some(1));
+    assert_eq!(explained_code, Some(1));
+    assert!(!plain.contains("= help:"), "stderr: {}", plain);
+    assert!(
+        explained.contains("runtime error: call error: unknown function: std::string::to_uppr\n  = help: did you mean `std::string::to_upper`?"),
+        "stderr: {}",
+        explained
+    );
+}
@@ -1,12 +1,12 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use ariadne::{Color, Label, Report, ReportKind, Source};
 use clap::Parser;
 
-use concerto_common::ir::IrConnection;
-use concerto_common::manifest;
+use concerto_common::ir::{IrConnection, IrSchema};
+use concerto_common::{json_schema, manifest};
 use concerto_compiler::codegen::CodeGenerator;
 use concerto_compiler::lexer::Lexer;
 use concerto_compiler::parser;
@@ -19,7 +19,7 @@ use concerto_compiler::parser;
     name = "concertoc",
     version,
     about,
-    long_about = "Concerto language compiler.\n\nCompiles .conc source files into .conc-ir (JSON intermediate representation)\nfor execution by the Concerto runtime (concerto run).\n\nExamples:\n  concertoc hello.conc              Compile to hello.conc-ir\n  concertoc hello.conc -o out.ir    Compile to custom output path\n  concertoc hello.conc --check      Check for errors only\n  concertoc hello.conc --emit-ir    Print IR JSON to stdout"
+    long_about = "Concerto language compiler.\n\nCompiles .conc source files into .conc-ir (JSON intermediate representation)\nfor execution by the Concerto runtime (concerto run).\n\nExamples:\n  concertoc hello.conc              Compile to hello.conc-ir\n  concertoc hello.conc -o out.ir    Compile to custom output path\n  concertoc hello.conc --check      Check for errors only\n  concertoc hello.conc --emit-ir    Print IR JSON to stdout\n  concertoc hello.conc --emit-schemas          Print a JSON Schema bundle\n  concertoc hello.conc --emit-schemas=schemas  Write schemas/<Name>.schema.json"
 )]
 struct Cli {
     /// Input .conc source file.
@@ -41,6 +41,18 @@ struct Cli {
     #[arg(long = "emit-ir")]
     emit_ir: bool,
 
+    /// Export declared schemas as JSON Schema documents. Without a value,
+    /// prints a combined bundle to stdout; with DIR, writes one
+    /// `<Name>.schema.json` file per schema.
+    #[arg(
+        long = "emit-schemas",
+        value_name = "DIR",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-"
+    )]
+    emit_schemas: Option<PathBuf>,
+
     /// Emit token stream to stdout (debug).
     #[arg(long = "emit-tokens")]
     emit_tokens: bool,
@@ -186,6 +198,15 @@ fn main() {
         CodeGenerator::embed_manifest_agents(&mut ir, &manifest_agents);
     }
 
+    // --emit-schemas: export schemas instead of writing IR
+    if let Some(ref target) = cli.emit_schemas {
+        if let Err(e) = emit_schemas(&ir.schemas, target) {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
     let json = match serde_json::to_string_pretty(&ir) {
         Ok(j) => j,
         Err(e) => {
@@ -223,6 +244,61 @@ fn main() {
     }
 }
 
+/// JSON Schema dialect declared by exported schema documents.
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Builds a standalone JSON Schema document for a schema: its IR
+/// `json_schema` with Concerto type names normalized to JSON Schema ones,
+/// plus `$schema` and `title`. `required` is kept as the runtime validator
+/// enforces it, so `Option` fields stay required but accept `null`.
+fn schema_document(schema: &IrSchema) -> serde_json::Value {
+    let mut doc = serde_json::Map::new();
+    doc.insert("$schema".to_string(), JSON_SCHEMA_DIALECT.into());
+    doc.insert("title".to_string(), schema.name.clone().into());
+    if let serde_json::Value::Object(fields) = json_schema::normalize_schema(&schema.json_schema) {
+        doc.extend(fields);
+    }
+    serde_json::Value::Object(doc)
+}
+
+/// Builds a single document holding every schema under `$defs`.
+fn schema_bundle(schemas: &[IrSchema]) -> serde_json::Value {
+    let defs: serde_json::Map<String, serde_json::Value> = schemas
+        .iter()
+        .map(|s| {
+            let mut doc = schema_document(s);
+            if let serde_json::Value::Object(fields) = &mut doc {
+                fields.shift_remove("$schema");
+            }
+            (s.name.clone(), doc)
+        })
+        .collect();
+    serde_json::json!({ "$schema": JSON_SCHEMA_DIALECT, "$defs": defs })
+}
+
+/// Writes schemas to `target`: `-` prints the bundle to stdout, anything
+/// else is a directory that receives one `<Name>.schema.json` per schema.
+fn emit_schemas(schemas: &[IrSchema], target: &Path) -> Result<(), String> {
+    let to_json = |v: &serde_json::Value| {
+        serde_json::to_string_pretty(v).map_err(|e| format!("failed to serialize schema: {}", e))
+    };
+
+    if target == Path::new("-") {
+        println!("{}", to_json(&schema_bundle(schemas))?);
+        return Ok(());
+    }
+
+    fs::create_dir_all(target)
+        .map_err(|e| format!("could not create '{}': {}", target.display(), e))?;
+    for schema in schemas {
+        let path = target.join(format!("{}.schema.json", schema.name));
+        fs::write(&path, to_json(&schema_document(schema))?)
+            .map_err(|e| format!("could not write '{}': {}", path.display(), e))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
 fn print_diagnostic(diag: &concerto_common::Diagnostic, source: &str, file_name: &str) {
     let kind = if diag.is_error() {
         ReportKind::Error
@@ -275,3 +351,65 @@ fn print_diagnostic(diag: &concerto_common::Diagnostic, source: &str, file_name:
         eprintln!();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile_schemas(source: &str) -> Vec<IrSchema> {
+        let (tokens, _) = Lexer::new(source, "test.conc").tokenize();
+        let (program, diags) = parser::Parser::new(tokens).parse();
+        assert!(
+            !diags.has_errors(),
+            "parse errors: {:?}",
+            diags.diagnostics()
+        );
+        CodeGenerator::new("test", "test.conc")
+            .generate(&program)
+            .schemas
+    }
+
+    #[test]
+    fn schema_document_is_standard_json_schema() {
+        let schemas = compile_schemas(
+            r#"
+            schema Review {
+                score: Int,
+                tags: Array<String>,
+                note: Option<String>,
+            }
+            fn main() {}
+            "#,
+        );
+        let doc = schema_document(&schemas[0]);
+
+        assert_eq!(
+            doc,
+            serde_json::json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "title": "Review",
+                "type": "object",
+                "properties": {
+                    "score": { "type": "integer" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                },
+                "required": ["score", "tags", "note"],
+            })
+        );
+    }
+
+    #[test]
+    fn schema_bundle_collects_definitions() {
+        let schemas = compile_schemas("schema A { x: Int }\nschema B { y: String }\nfn main() {}");
+        let bundle = schema_bundle(&schemas);
+
+        assert_eq!(bundle["$schema"], JSON_SCHEMA_DIALECT);
+        assert_eq!(bundle["$defs"]["A"]["title"], "A");
+        assert_eq!(
+            bundle["$defs"]["B"]["properties"],
+            serde_json::json!({ "y": { "type": "string" } })
+        );
+        assert!(bundle["$defs"]["A"].get("$schema").is_none());
+    }
+}